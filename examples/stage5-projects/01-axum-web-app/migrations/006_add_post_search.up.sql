@@ -0,0 +1,10 @@
+-- Full-text search over posts. The 'simple' configuration lowercases words
+-- without stemming or stop words, matching the tokenizer of the built-in
+-- search index used by the other backends.
+ALTER TABLE posts ADD COLUMN IF NOT EXISTS search_vector tsvector
+    GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', title), 'A') ||
+        setweight(to_tsvector('simple', content), 'B')
+    ) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_posts_search_vector ON posts USING GIN (search_vector);
@@ -0,0 +1,30 @@
+-- Publishing workflow: posts are drafts, published or archived, and only
+-- published posts are public. Existing posts were public, so they start out
+-- published.
+ALTER TABLE posts ADD COLUMN status VARCHAR(20) NOT NULL DEFAULT 'published'
+    CHECK (status IN ('draft', 'published', 'archived'));
+ALTER TABLE posts ADD COLUMN published_at TIMESTAMP WITH TIME ZONE;
+UPDATE posts SET published_at = created_at;
+
+CREATE INDEX IF NOT EXISTS idx_posts_status_created_at ON posts(status, created_at DESC);
+
+-- The title and content after every edit, numbered from 1 per post
+CREATE TABLE IF NOT EXISTS post_revisions (
+    id UUID PRIMARY KEY,
+    post_id UUID NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    revision INTEGER NOT NULL,
+    title VARCHAR(200) NOT NULL,
+    content TEXT NOT NULL,
+    editor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    UNIQUE (post_id, revision)
+);
+
+INSERT INTO post_revisions (id, post_id, revision, title, content, editor_id, created_at)
+SELECT gen_random_uuid(), id, 1, title, content, author_id, updated_at FROM posts;
+
+-- updated_at is set by the application, as on SQLite, so that it is the
+-- same clock and the same value everywhere
+DROP TRIGGER IF EXISTS update_posts_updated_at ON posts;
+DROP TRIGGER IF EXISTS update_users_updated_at ON users;
+DROP TRIGGER IF EXISTS update_comments_updated_at ON comments;
@@ -0,0 +1,4 @@
+-- Optimistic concurrency: every update bumps the version, and an update only
+-- applies to the version it was based on, so concurrent edits cannot
+-- silently overwrite each other
+ALTER TABLE posts ADD COLUMN version BIGINT NOT NULL DEFAULT 1;
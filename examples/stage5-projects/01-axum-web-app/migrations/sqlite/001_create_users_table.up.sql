@@ -0,0 +1,17 @@
+-- Create users table
+-- SQLite version of ../001_create_users_table.sql: UUIDs are stored as 16-byte
+-- BLOBs and timestamps as RFC 3339 TEXT, both written by the application
+CREATE TABLE IF NOT EXISTS users (
+    id BLOB PRIMARY KEY NOT NULL,
+    username TEXT UNIQUE NOT NULL,
+    email TEXT UNIQUE NOT NULL,
+    password_hash TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+-- Create index on username
+CREATE INDEX IF NOT EXISTS idx_users_username ON users(username);
+
+-- Create index on email
+CREATE INDEX IF NOT EXISTS idx_users_email ON users(email);
@@ -0,0 +1,16 @@
+-- Create posts table
+-- updated_at is set by the application instead of a trigger
+CREATE TABLE IF NOT EXISTS posts (
+    id BLOB PRIMARY KEY NOT NULL,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    author_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+-- Create index on author_id
+CREATE INDEX IF NOT EXISTS idx_posts_author_id ON posts(author_id);
+
+-- Create index on created_at for ordering
+CREATE INDEX IF NOT EXISTS idx_posts_created_at ON posts(created_at DESC);
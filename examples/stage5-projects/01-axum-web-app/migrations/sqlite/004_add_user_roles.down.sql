@@ -0,0 +1,3 @@
+-- DROP COLUMN needs SQLite 3.35 or later
+ALTER TABLE users DROP COLUMN banned_at;
+ALTER TABLE users DROP COLUMN role;
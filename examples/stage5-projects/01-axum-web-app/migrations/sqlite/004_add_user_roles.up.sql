@@ -0,0 +1,4 @@
+-- Add role and ban status to users
+ALTER TABLE users ADD COLUMN role TEXT NOT NULL DEFAULT 'user'
+    CHECK (role IN ('user', 'moderator', 'admin'));
+ALTER TABLE users ADD COLUMN banned_at TEXT;
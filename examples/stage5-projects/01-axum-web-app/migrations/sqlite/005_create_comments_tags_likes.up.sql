@@ -0,0 +1,45 @@
+-- Create comments table; replies point at their parent comment
+CREATE TABLE IF NOT EXISTS comments (
+    id BLOB PRIMARY KEY NOT NULL,
+    post_id BLOB NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    author_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    parent_id BLOB REFERENCES comments(id) ON DELETE CASCADE,
+    content TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+-- Create index on post_id for loading a post's thread
+CREATE INDEX IF NOT EXISTS idx_comments_post_id ON comments(post_id, created_at);
+
+-- Create index on parent_id for cascading deletes
+CREATE INDEX IF NOT EXISTS idx_comments_parent_id ON comments(parent_id);
+
+-- Create index on author_id for cascading deletes
+CREATE INDEX IF NOT EXISTS idx_comments_author_id ON comments(author_id);
+
+-- Create tags table and the post <-> tag association
+CREATE TABLE IF NOT EXISTS tags (
+    id BLOB PRIMARY KEY NOT NULL,
+    name TEXT UNIQUE NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS post_tags (
+    post_id BLOB NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    tag_id BLOB NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+    PRIMARY KEY (post_id, tag_id)
+);
+
+-- Create index on tag_id for listing a tag's posts
+CREATE INDEX IF NOT EXISTS idx_post_tags_tag_id ON post_tags(tag_id);
+
+-- Create likes table; a user likes a post at most once
+CREATE TABLE IF NOT EXISTS post_likes (
+    post_id BLOB NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    user_id BLOB NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (post_id, user_id)
+);
+
+-- Create index on user_id for cascading deletes
+CREATE INDEX IF NOT EXISTS idx_post_likes_user_id ON post_likes(user_id);
@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS post_revisions;
+DROP INDEX IF EXISTS idx_posts_status_created_at;
+ALTER TABLE posts DROP COLUMN published_at;
+ALTER TABLE posts DROP COLUMN status;
@@ -0,0 +1,23 @@
+-- SQLite version of ../007_add_post_status_and_revisions.up.sql. Existing
+-- posts were public, so they start out published.
+ALTER TABLE posts ADD COLUMN status TEXT NOT NULL DEFAULT 'published'
+    CHECK (status IN ('draft', 'published', 'archived'));
+ALTER TABLE posts ADD COLUMN published_at TEXT;
+UPDATE posts SET published_at = created_at;
+
+CREATE INDEX IF NOT EXISTS idx_posts_status_created_at ON posts(status, created_at DESC);
+
+-- The title and content after every edit, numbered from 1 per post
+CREATE TABLE IF NOT EXISTS post_revisions (
+    id BLOB PRIMARY KEY NOT NULL,
+    post_id BLOB NOT NULL REFERENCES posts(id) ON DELETE CASCADE,
+    revision INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    editor_id BLOB REFERENCES users(id) ON DELETE SET NULL,
+    created_at TEXT NOT NULL,
+    UNIQUE (post_id, revision)
+);
+
+INSERT INTO post_revisions (id, post_id, revision, title, content, editor_id, created_at)
+SELECT randomblob(16), id, 1, title, content, author_id, updated_at FROM posts;
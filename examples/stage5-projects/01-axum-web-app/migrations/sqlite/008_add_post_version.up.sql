@@ -0,0 +1,2 @@
+-- SQLite version of ../008_add_post_version.up.sql
+ALTER TABLE posts ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,43 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::request::Parts,
+};
+use uuid::Uuid;
+
+use crate::error::AppError;
+use crate::models::user::{Claims, Role};
+
+/// The authenticated user, taken from the claims that `auth_middleware`
+/// stores in the request extensions.
+///
+/// Only usable on routes layered with `auth_middleware`; anywhere else it
+/// rejects the request with 401. Routes layered with
+/// `optional_auth_middleware` take `Option<AuthUser>` instead.
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub id: Uuid,
+    pub role: Role,
+    pub claims: Claims,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthUser
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let claims = parts
+            .extensions
+            .get::<Claims>()
+            .cloned()
+            .ok_or_else(|| AppError::Authentication("Missing authentication".to_string()))?;
+
+        let id = Uuid::parse_str(&claims.sub)
+            .map_err(|_| AppError::Authentication("Invalid user ID in token".to_string()))?;
+
+        Ok(Self { id, role: claims.role, claims })
+    }
+}
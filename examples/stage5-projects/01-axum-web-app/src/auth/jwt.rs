@@ -1,20 +1,41 @@
 use chrono::{Duration, Utc};
 use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
-use crate::models::user::Claims;
+use uuid::Uuid;
+use crate::models::user::{Claims, Role};
 use crate::error::{AppError, Result};
 
 #[derive(Clone)]
 pub struct JwtService {
     secret: String,
+    access_token_ttl: Duration,
+    refresh_token_ttl: Duration,
 }
 
 impl JwtService {
+    // Access tokens last 15 minutes and refresh tokens 7 days unless
+    // configured with `with_expiry`
     pub fn new(secret: &str) -> Self {
         Self {
             secret: secret.to_string(),
+            access_token_ttl: Duration::minutes(15),
+            refresh_token_ttl: Duration::days(7),
         }
     }
 
+    pub fn with_expiry(mut self, access_token_ttl: Duration, refresh_token_ttl: Duration) -> Self {
+        self.access_token_ttl = access_token_ttl;
+        self.refresh_token_ttl = refresh_token_ttl;
+        self
+    }
+
+    pub fn access_token_ttl(&self) -> Duration {
+        self.access_token_ttl
+    }
+
+    pub fn refresh_token_ttl(&self) -> Duration {
+        self.refresh_token_ttl
+    }
+
     fn encoding_key(&self) -> EncodingKey {
         EncodingKey::from_secret(self.secret.as_ref())
     }
@@ -23,15 +44,17 @@ impl JwtService {
         DecodingKey::from_secret(self.secret.as_ref())
     }
 
-    pub fn generate_token(&self, user_id: &str, username: &str) -> Result<String> {
+    pub fn generate_token(&self, user_id: &str, username: &str, role: Role) -> Result<String> {
         let now = Utc::now();
-        let exp = now + Duration::hours(24); // 24 hours expiration
+        let exp = now + self.access_token_ttl;
 
         let claims = Claims {
             sub: user_id.to_string(),
             username: username.to_string(),
+            role,
             exp: exp.timestamp() as usize,
             iat: now.timestamp() as usize,
+            jti: Uuid::new_v4().to_string(),
         };
 
         encode(&Header::default(), &claims, &self.encoding_key())
@@ -63,13 +86,14 @@ mod tests {
         let username = "testuser";
 
         // Generate token
-        let token = jwt_service.generate_token(user_id, username).unwrap();
+        let token = jwt_service.generate_token(user_id, username, Role::Moderator).unwrap();
         assert!(!token.is_empty());
 
         // Validate token
         let claims = jwt_service.validate_token(&token).unwrap();
         assert_eq!(claims.sub, user_id);
         assert_eq!(claims.username, username);
+        assert_eq!(claims.role, Role::Moderator);
     }
 
     #[test]
@@ -81,4 +105,16 @@ mod tests {
         let result = jwt_service.validate_token(invalid_token);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_token_expiry_and_unique_id() {
+        let jwt_service = JwtService::new("test_secret_key")
+            .with_expiry(Duration::minutes(5), Duration::days(1));
+
+        let first = jwt_service.validate_token(&jwt_service.generate_token("id", "user", Role::User).unwrap()).unwrap();
+        let second = jwt_service.validate_token(&jwt_service.generate_token("id", "user", Role::User).unwrap()).unwrap();
+
+        assert_eq!(first.exp - first.iat, 300);
+        assert_ne!(first.jti, second.jti);
+    }
 }
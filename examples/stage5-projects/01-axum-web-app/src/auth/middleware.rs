@@ -1,38 +1,32 @@
 use axum::{
     extract::{Request, State},
-    http::{header, StatusCode},
+    http::header,
     middleware::Next,
     response::Response,
 };
 
-use crate::auth::JwtService;
+use crate::error::AppError;
 use crate::models::user::Claims;
-use std::sync::Arc;
+use crate::repositories::Repository;
+use crate::state::AppState;
 
-pub async fn auth_middleware(
-    State(jwt_service): State<JwtService>,
-    mut request: Request,
-    next: Next,
-) -> Result<Response, StatusCode> {
-    let auth_header = request
+fn bearer_token(request: &Request) -> Option<&str> {
+    request
         .headers()
         .get(header::AUTHORIZATION)
         .and_then(|header| header.to_str().ok())
-        .and_then(|header| {
-            if header.starts_with("Bearer ") {
-                Some(&header[7..])
-            } else {
-                None
-            }
-        });
-
-    let token = match auth_header {
-        Some(token) => token,
-        None => return Err(StatusCode::UNAUTHORIZED),
-    };
-
-    let claims = jwt_service.validate_token(token)
-        .map_err(|_| StatusCode::UNAUTHORIZED)?;
+        .and_then(|header| header.strip_prefix("Bearer "))
+}
+
+pub async fn auth_middleware<R: Repository>(
+    State(state): State<AppState<R>>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let token = bearer_token(&request).ok_or_else(|| AppError::Authentication("Missing bearer token".to_string()))?;
+
+    // Checks the signature, expiry and the revocation list
+    let claims = state.auth_service.authenticate(token).await?;
 
     // Add user claims to request extensions
     request.extensions_mut().insert(claims);
@@ -40,6 +34,22 @@ pub async fn auth_middleware(
     Ok(next.run(request).await)
 }
 
+// For public routes whose response depends on who asks: requests without a
+// bearer token pass through anonymously, while a token that is sent must be
+// valid. Handlers take `Option<AuthUser>`.
+pub async fn optional_auth_middleware<R: Repository>(
+    State(state): State<AppState<R>>,
+    mut request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    if let Some(token) = bearer_token(&request) {
+        let claims = state.auth_service.authenticate(token).await?;
+        request.extensions_mut().insert(claims);
+    }
+
+    Ok(next.run(request).await)
+}
+
 // Extension trait for extracting user from request
 pub trait UserExt {
     fn user_id(&self) -> Option<String>;
@@ -1,5 +1,8 @@
+pub mod extractor;
 pub mod jwt;
 pub mod middleware;
+pub mod policy;
 
+pub use extractor::*;
 pub use jwt::*;
 pub use middleware::*;
@@ -0,0 +1,145 @@
+use uuid::Uuid;
+
+use crate::auth::AuthUser;
+use crate::error::{AppError, Result};
+use crate::models::post::PostStatus;
+use crate::models::user::Role;
+
+/// Something a signed-in user wants to do, with the facts the decision needs.
+#[derive(Debug, Clone, Copy)]
+pub enum Action {
+    UpdatePost { author_id: Uuid },
+    // Drafts, archived posts and the revisions of any post
+    ViewUnpublished { author_id: Uuid },
+    DeletePost { author_id: Uuid },
+    ModeratePosts,
+    DeleteComment { author_id: Uuid },
+    ListUsers,
+    BanUser { user_id: Uuid, role: Role },
+}
+
+/// Decide whether `actor` may perform `action`.
+///
+/// | Action          | user      | moderator | admin                  |
+/// |-----------------|-----------|-----------|------------------------|
+/// | UpdatePost      | own posts | own posts | own posts              |
+/// | ViewUnpublished | own posts | any post  | any post               |
+/// | DeletePost      | own posts | any post  | any post               |
+/// | ModeratePosts   | no        | yes       | yes                    |
+/// | DeleteComment   | own       | any       | any                    |
+/// | ListUsers       | no        | yes       | yes                    |
+/// | BanUser         | no        | no        | anyone but admins/self |
+///
+/// Denials are `AppError::Authorization` (403). Whether the target exists
+/// (404) is checked by the caller before asking.
+pub fn authorize(actor: &AuthUser, action: Action) -> Result<()> {
+    let allowed = match action {
+        Action::UpdatePost { author_id } => actor.id == author_id,
+        Action::ViewUnpublished { author_id }
+        | Action::DeletePost { author_id }
+        | Action::DeleteComment { author_id } => {
+            actor.id == author_id || actor.role != Role::User
+        }
+        Action::ModeratePosts | Action::ListUsers => actor.role != Role::User,
+        Action::BanUser { user_id, role } => {
+            actor.role == Role::Admin && user_id != actor.id && role != Role::Admin
+        }
+    };
+
+    if allowed {
+        Ok(())
+    } else {
+        Err(AppError::Authorization(denial_message(action).to_string()))
+    }
+}
+
+/// Whether `viewer`, signed in or not, may see a post. Published posts are
+/// public; anything else needs `Action::ViewUnpublished`.
+pub fn can_view_post(viewer: Option<&AuthUser>, author_id: Uuid, status: PostStatus) -> bool {
+    status == PostStatus::Published
+        || viewer.is_some_and(|viewer| authorize(viewer, Action::ViewUnpublished { author_id }).is_ok())
+}
+
+fn denial_message(action: Action) -> &'static str {
+    match action {
+        Action::UpdatePost { .. } => "You can only modify your own posts",
+        Action::ViewUnpublished { .. } => "You can only view your own unpublished posts",
+        Action::DeletePost { .. } => "You can only delete your own posts",
+        Action::ModeratePosts => "Only moderators and admins can moderate posts",
+        Action::DeleteComment { .. } => "You can only delete your own comments",
+        Action::ListUsers => "Only moderators and admins can list users",
+        Action::BanUser { .. } => "Only admins can ban users, and admins cannot be banned",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::user::Claims;
+
+    fn actor(role: Role) -> AuthUser {
+        let id = Uuid::new_v4();
+        AuthUser {
+            id,
+            role,
+            claims: Claims {
+                sub: id.to_string(),
+                username: "actor".to_string(),
+                role,
+                exp: 0,
+                iat: 0,
+                jti: String::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_post_permissions() {
+        let user = actor(Role::User);
+        let moderator = actor(Role::Moderator);
+        let someone_else = Uuid::new_v4();
+
+        assert!(authorize(&user, Action::UpdatePost { author_id: user.id }).is_ok());
+        assert!(authorize(&user, Action::DeletePost { author_id: user.id }).is_ok());
+        assert!(authorize(&user, Action::DeletePost { author_id: someone_else }).is_err());
+        assert!(authorize(&moderator, Action::DeletePost { author_id: someone_else }).is_ok());
+        assert!(authorize(&user, Action::ModeratePosts).is_err());
+        assert!(authorize(&moderator, Action::ModeratePosts).is_ok());
+        assert!(authorize(&user, Action::DeleteComment { author_id: someone_else }).is_err());
+        assert!(authorize(&moderator, Action::DeleteComment { author_id: someone_else }).is_ok());
+        // Moderation removes posts, it does not rewrite them
+        assert!(authorize(&moderator, Action::UpdatePost { author_id: someone_else }).is_err());
+    }
+
+    #[test]
+    fn test_unpublished_posts_are_visible_to_author_and_moderators() {
+        let user = actor(Role::User);
+        let moderator = actor(Role::Moderator);
+        let someone_else = Uuid::new_v4();
+
+        assert!(can_view_post(None, someone_else, PostStatus::Published));
+        assert!(!can_view_post(None, someone_else, PostStatus::Draft));
+        assert!(can_view_post(Some(&user), user.id, PostStatus::Draft));
+        assert!(!can_view_post(Some(&user), someone_else, PostStatus::Archived));
+        assert!(can_view_post(Some(&moderator), someone_else, PostStatus::Archived));
+    }
+
+    #[test]
+    fn test_user_administration_permissions() {
+        let user = actor(Role::User);
+        let moderator = actor(Role::Moderator);
+        let admin = actor(Role::Admin);
+        let target = Uuid::new_v4();
+
+        assert!(authorize(&user, Action::ListUsers).is_err());
+        assert!(authorize(&moderator, Action::ListUsers).is_ok());
+
+        assert!(authorize(&moderator, Action::BanUser { user_id: target, role: Role::User }).is_err());
+        assert!(authorize(&admin, Action::BanUser { user_id: target, role: Role::Moderator }).is_ok());
+        assert!(authorize(&admin, Action::BanUser { user_id: target, role: Role::Admin }).is_err());
+        assert!(matches!(
+            authorize(&admin, Action::BanUser { user_id: admin.id, role: Role::Admin }),
+            Err(AppError::Authorization(_))
+        ));
+    }
+}
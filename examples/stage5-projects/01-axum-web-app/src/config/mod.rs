@@ -1,4 +1,23 @@
 use std::env;
+use std::path::{Path, PathBuf};
+
+use axum::http::Uri;
+use chrono::Duration;
+use thiserror::Error;
+
+pub mod sources;
+
+pub use sources::{Overrides, Sources};
+
+// Read when neither --config nor CONFIG_FILE names a file, if it exists
+pub const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+const MIN_JWT_SECRET_LENGTH: usize = 32;
+
+// Pieces of the example secrets in `.env.example` and `config.example.toml`
+const PLACEHOLDER_SECRETS: &[&str] = &["your_super_secret", "change_me", "changeme", "replace_me"];
+
+const LOG_LEVELS: &[&str] = &["trace", "debug", "info", "warn", "error"];
 
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -6,69 +25,238 @@ pub struct Config {
     pub jwt: JwtConfig,
     pub server: ServerConfig,
     pub app: AppConfig,
+    pub cors: CorsConfig,
+    pub rate_limit: RateLimitConfig,
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("cannot read config file {}: {source}", .path.display())]
+    Read { path: PathBuf, source: std::io::Error },
+
+    #[error("invalid config file {}: {source}", .path.display())]
+    Parse { path: PathBuf, source: toml::de::Error },
+
+    #[error("invalid configuration:{}", .problems.iter().map(|problem| format!("\n  - {}", problem)).collect::<String>())]
+    Invalid { problems: Vec<String> },
 }
 
 #[derive(Debug, Clone)]
 pub struct DatabaseConfig {
-    pub url: String,
+    // None runs the app on the in-memory backend
+    pub url: Option<String>,
+    pub backend: DatabaseBackend,
+    // Apply pending migrations when the server starts (DATABASE_AUTO_MIGRATE);
+    // otherwise `serve` refuses to start until `migrate up` has been run
+    pub auto_migrate: bool,
+}
+
+// Storage backend, chosen from the scheme of DATABASE_URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatabaseBackend {
+    Postgres,
+    Sqlite,
+    InMemory,
+}
+
+impl DatabaseBackend {
+    pub fn from_url(url: Option<&str>) -> Result<Self, String> {
+        let Some(url) = url else {
+            return Ok(DatabaseBackend::InMemory);
+        };
+
+        if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+            Ok(DatabaseBackend::Postgres)
+        } else if url.starts_with("sqlite:") {
+            Ok(DatabaseBackend::Sqlite)
+        } else {
+            Err(format!(
+                "unsupported DATABASE_URL scheme in `{}`; expected postgres://, postgresql:// or sqlite:",
+                url.split("://").next().unwrap_or(url)
+            ))
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct JwtConfig {
+    // At least 32 characters, and not one of the examples
     pub secret: String,
-    pub expires_in: String,
+    // Lifetime of access tokens (JWT_EXPIRES_IN)
+    pub expires_in: Duration,
+    // Lifetime of refresh tokens (JWT_REFRESH_EXPIRES_IN)
+    pub refresh_expires_in: Duration,
 }
 
 #[derive(Debug, Clone)]
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
+    // How long in-flight requests may take to finish after SIGTERM or
+    // Ctrl-C before the server exits anyway (SHUTDOWN_TIMEOUT)
+    pub shutdown_timeout: Duration,
+}
+
+// Which browser origins may call the API (CORS_ORIGINS, CORS_MAX_AGE)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorsConfig {
+    // Exact origins such as `https://blog.example.com`, or just `*` for any.
+    // Empty allows no cross-origin requests.
+    pub allowed_origins: Vec<String>,
+    // How long browsers may cache a preflight response
+    pub max_age: Duration,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self { allowed_origins: Vec::new(), max_age: Duration::hours(1) }
+    }
+}
+
+impl CorsConfig {
+    pub fn allows_any_origin(&self) -> bool {
+        self.allowed_origins.iter().any(|origin| origin == "*")
+    }
+}
+
+// A token bucket: holds up to `burst` tokens and regains `per_minute` tokens
+// a minute; every request takes one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BucketConfig {
+    pub burst: u32,
+    pub per_minute: u32,
+}
+
+// Limits on the auth endpoints (RATE_LIMIT_*, LOGIN_LOCKOUT_*)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    // Requests to login, register and refresh per client IP
+    pub per_ip: BucketConfig,
+    // Login and register attempts per username, whichever IP they come from
+    pub per_account: BucketConfig,
+    // Failed logins in a row before an account is locked
+    pub lockout_threshold: u32,
+    // The first lockout; each further failure doubles it, up to `lockout_max`
+    pub lockout_base: Duration,
+    pub lockout_max: Duration,
+    // Take the client IP from X-Forwarded-For; only safe behind a proxy that sets it
+    pub trust_forwarded_for: bool,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            per_ip: BucketConfig { burst: 20, per_minute: 20 },
+            per_account: BucketConfig { burst: 10, per_minute: 10 },
+            lockout_threshold: 5,
+            lockout_base: Duration::seconds(30),
+            lockout_max: Duration::hours(1),
+            trust_forwarded_for: false,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     pub name: String,
     pub version: String,
+    // The default filter for this crate and tower_http; RUST_LOG overrides it
     pub log_level: String,
 }
 
 impl Config {
-    pub fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
+    // Defaults, then the config file, then environment variables (including
+    // those in `.env`), then command-line flags
+    pub fn load(overrides: &Overrides) -> Result<Self, ConfigError> {
         dotenv::dotenv().ok();
 
+        let path = overrides.config_file.clone().or_else(|| env::var_os("CONFIG_FILE").map(PathBuf::from));
+        let file = read_config_file(path)?;
+        let env = |name: &str| env::var(name).ok();
+        Self::from_sources(Sources::new(file, &env, overrides))
+    }
+
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::load(&Overrides::default())
+    }
+
+    pub fn from_sources(sources: Sources) -> Result<Self, ConfigError> {
+        let database_url: Option<String> = sources.get_optional("database.url", "DATABASE_URL");
+        let backend = DatabaseBackend::from_url(database_url.as_deref()).unwrap_or_else(|message| {
+            sources.error(format!("`database.url`: {}", message));
+            DatabaseBackend::InMemory
+        });
+
+        let secret: Option<String> = sources.get_optional("jwt.secret", "JWT_SECRET");
+        match &secret {
+            None => sources.error("`jwt.secret`: required; set JWT_SECRET or `secret` under [jwt]".to_string()),
+            Some(secret) => {
+                if let Err(message) = check_jwt_secret(secret) {
+                    sources.error(format!("`jwt.secret`: {}", message));
+                }
+            }
+        }
+        let jwt = JwtConfig {
+            secret: secret.unwrap_or_default(),
+            expires_in: sources.get("jwt.expires_in", "JWT_EXPIRES_IN", Duration::minutes(15)),
+            refresh_expires_in: sources.get("jwt.refresh_expires_in", "JWT_REFRESH_EXPIRES_IN", Duration::days(7)),
+        };
+        if jwt.expires_in >= jwt.refresh_expires_in {
+            sources.error("`jwt.expires_in`: must be shorter than `jwt.refresh_expires_in`".to_string());
+        }
+
+        let app = AppConfig {
+            name: sources.get("app.name", "APP_NAME", "Axum Web App".to_string()),
+            version: sources.get("app.version", "APP_VERSION", env!("CARGO_PKG_VERSION").to_string()),
+            log_level: sources.get("app.log_level", "LOG_LEVEL", "info".to_string()).to_lowercase(),
+        };
+        if !LOG_LEVELS.contains(&app.log_level.as_str()) {
+            sources.error(format!("`app.log_level`: expected one of {}, found `{}`", LOG_LEVELS.join(", "), app.log_level));
+        }
+
+        let defaults = CorsConfig::default();
+        let cors = CorsConfig {
+            allowed_origins: sources.get("cors.allowed_origins", "CORS_ORIGINS", defaults.allowed_origins),
+            max_age: sources.get("cors.max_age", "CORS_MAX_AGE", defaults.max_age),
+        };
+        for origin in &cors.allowed_origins {
+            if let Err(message) = check_origin(origin) {
+                sources.error(format!("`cors.allowed_origins`: `{}` {}", origin, message));
+            }
+        }
+        if cors.allows_any_origin() && cors.allowed_origins.len() > 1 {
+            sources.error("`cors.allowed_origins`: `*` cannot be combined with other origins".to_string());
+        }
+
         let config = Config {
             database: DatabaseConfig {
-                url: env::var("DATABASE_URL")
-                    .expect("DATABASE_URL must be set"),
-            },
-            jwt: JwtConfig {
-                secret: env::var("JWT_SECRET")
-                    .expect("JWT_SECRET must be set"),
-                expires_in: env::var("JWT_EXPIRES_IN")
-                    .unwrap_or_else(|_| "24h".to_string()),
+                url: database_url,
+                backend,
+                auto_migrate: sources.get("database.auto_migrate", "DATABASE_AUTO_MIGRATE", false),
             },
+            jwt,
             server: ServerConfig {
-                host: env::var("HOST")
-                    .unwrap_or_else(|_| "127.0.0.1".to_string()),
-                port: env::var("PORT")
-                    .unwrap_or_else(|_| "3001".to_string())
-                    .parse()?,
-            },
-            app: AppConfig {
-                name: env::var("APP_NAME")
-                    .unwrap_or_else(|_| "Axum Web App".to_string()),
-                version: env::var("APP_VERSION")
-                    .unwrap_or_else(|_| "0.1.0".to_string()),
-                log_level: env::var("LOG_LEVEL")
-                    .unwrap_or_else(|_| "info".to_string()),
+                host: sources.get("server.host", "HOST", "127.0.0.1".to_string()),
+                port: sources.get("server.port", "PORT", 3001),
+                shutdown_timeout: sources.get("server.shutdown_timeout", "SHUTDOWN_TIMEOUT", Duration::seconds(30)),
             },
+            app,
+            cors,
+            rate_limit: rate_limit_config(&sources),
         };
 
+        sources.finish().map_err(|problems| ConfigError::Invalid { problems })?;
         Ok(config)
     }
 
-    pub fn database_url(&self) -> &str {
-        &self.database.url
+    pub fn database_url(&self) -> Option<&str> {
+        self.database.url.as_deref()
+    }
+
+    pub fn database_backend(&self) -> DatabaseBackend {
+        self.database.backend
     }
 
     pub fn jwt_secret(&self) -> &str {
@@ -79,3 +267,263 @@ impl Config {
         format!("{}:{}", self.server.host, self.server.port)
     }
 }
+
+// The file at `path`, which must exist, or ./config.toml if there is one
+fn read_config_file(path: Option<PathBuf>) -> Result<Option<(PathBuf, toml::Table)>, ConfigError> {
+    let path = match path {
+        Some(path) => path,
+        None if Path::new(DEFAULT_CONFIG_FILE).exists() => PathBuf::from(DEFAULT_CONFIG_FILE),
+        None => return Ok(None),
+    };
+
+    let text = std::fs::read_to_string(&path).map_err(|source| ConfigError::Read { path: path.clone(), source })?;
+    let table = toml::from_str(&text).map_err(|source| ConfigError::Parse { path: path.clone(), source })?;
+    Ok(Some((path, table)))
+}
+
+fn rate_limit_config(sources: &Sources) -> RateLimitConfig {
+    let defaults = RateLimitConfig::default();
+    let bucket = |key: &str, env: &str, default: BucketConfig| BucketConfig {
+        burst: sources.get(&format!("rate_limit.{}.burst", key), &format!("{}_BURST", env), default.burst),
+        per_minute: sources.get(
+            &format!("rate_limit.{}.per_minute", key),
+            &format!("{}_PER_MINUTE", env),
+            default.per_minute,
+        ),
+    };
+
+    let config = RateLimitConfig {
+        enabled: sources.get("rate_limit.enabled", "RATE_LIMIT_ENABLED", defaults.enabled),
+        per_ip: bucket("per_ip", "RATE_LIMIT_IP", defaults.per_ip),
+        per_account: bucket("per_account", "RATE_LIMIT_ACCOUNT", defaults.per_account),
+        lockout_threshold: sources.get(
+            "rate_limit.lockout_threshold",
+            "LOGIN_LOCKOUT_THRESHOLD",
+            defaults.lockout_threshold,
+        ),
+        lockout_base: sources.get("rate_limit.lockout_base", "LOGIN_LOCKOUT_BASE", defaults.lockout_base),
+        lockout_max: sources.get("rate_limit.lockout_max", "LOGIN_LOCKOUT_MAX", defaults.lockout_max),
+        trust_forwarded_for: sources.get(
+            "rate_limit.trust_forwarded_for",
+            "RATE_LIMIT_TRUST_FORWARDED_FOR",
+            defaults.trust_forwarded_for,
+        ),
+    };
+
+    for (key, bucket) in [("per_ip", config.per_ip), ("per_account", config.per_account)] {
+        if bucket.burst == 0 || bucket.per_minute == 0 {
+            sources.error(format!("`rate_limit.{}`: burst and per_minute must be positive", key));
+        }
+    }
+    if config.lockout_threshold == 0 {
+        sources.error("`rate_limit.lockout_threshold`: must be positive".to_string());
+    }
+    if config.lockout_base > config.lockout_max {
+        sources.error("`rate_limit.lockout_base`: must not be longer than `rate_limit.lockout_max`".to_string());
+    }
+
+    config
+}
+
+// Anyone who learns the secret can sign tokens for any user, so it must be
+// long and must not be one of the examples
+fn check_jwt_secret(secret: &str) -> Result<(), String> {
+    let lowercase = secret.to_lowercase();
+    if PLACEHOLDER_SECRETS.iter().any(|placeholder| lowercase.contains(placeholder)) {
+        return Err("is an example value; generate one with `openssl rand -base64 48`".to_string());
+    }
+    if secret.chars().count() < MIN_JWT_SECRET_LENGTH {
+        return Err(format!("must be at least {} characters", MIN_JWT_SECRET_LENGTH));
+    }
+
+    let mut distinct: Vec<char> = secret.chars().collect();
+    distinct.sort_unstable();
+    distinct.dedup();
+    if distinct.len() < 8 {
+        return Err("is too repetitive".to_string());
+    }
+
+    Ok(())
+}
+
+// Browsers send `Origin: scheme://host[:port]` and CORS compares it exactly,
+// so anything with a path, a trailing slash or credentials would never match
+fn check_origin(origin: &str) -> Result<(), String> {
+    if origin == "*" {
+        return Ok(());
+    }
+
+    let uri: Uri = origin.parse().map_err(|_| "is not a valid origin".to_string())?;
+    let (Some(scheme), Some(authority)) = (uri.scheme_str(), uri.authority()) else {
+        return Err("must include the scheme, e.g. https://example.com".to_string());
+    };
+    if scheme != "http" && scheme != "https" {
+        return Err("must use http or https".to_string());
+    }
+    if authority.as_str().contains('@') || origin != format!("{}://{}", scheme, authority) {
+        return Err("must be only scheme, host and port, without a path or trailing slash".to_string());
+    }
+
+    Ok(())
+}
+
+// Parse durations such as `900`, `30s`, `15m`, `24h` or `7d`; a bare number is seconds
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .map_err(|_| format!("invalid duration `{}`", value))?;
+
+    let duration = match unit {
+        "" | "s" => Duration::try_seconds(amount),
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        _ => return Err(format!("invalid duration unit in `{}`; expected s, m, h or d", value)),
+    }
+    .ok_or_else(|| format!("duration `{}` is too long", value))?;
+
+    if duration <= Duration::zero() {
+        return Err(format!("duration `{}` must be positive", value));
+    }
+
+    Ok(duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "kR9vQ2xL7pW4mZ8nB3cT6yH1jF5gD0sA";
+
+    fn load(file: &str, env: &[(&str, &str)]) -> Result<Config, ConfigError> {
+        let file = (!file.is_empty()).then(|| (PathBuf::from("config.toml"), toml::from_str(file).unwrap()));
+        let env = |name: &str| env.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string());
+        Config::from_sources(Sources::new(file, &env, &Overrides::default()))
+    }
+
+    fn problems(result: Result<Config, ConfigError>) -> Vec<String> {
+        match result {
+            Err(ConfigError::Invalid { problems }) => problems,
+            other => panic!("expected invalid configuration, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_defaults_file_and_env() {
+        let config = load("", &[("JWT_SECRET", SECRET)]).unwrap();
+        assert_eq!(config.database_backend(), DatabaseBackend::InMemory);
+        assert_eq!(config.server_address(), "127.0.0.1:3001");
+        assert_eq!(config.jwt.expires_in, Duration::minutes(15));
+        assert_eq!(config.server.shutdown_timeout, Duration::seconds(30));
+        assert!(!config.database.auto_migrate);
+        assert_eq!(config.cors, CorsConfig::default());
+        assert_eq!(config.rate_limit, RateLimitConfig::default());
+
+        let file = format!(
+            r#"
+            [database]
+            url = "sqlite::memory:"
+            auto_migrate = true
+
+            [jwt]
+            secret = "{}"
+            expires_in = "5m"
+
+            [cors]
+            allowed_origins = ["https://blog.example.com", "http://localhost:3000"]
+
+            [rate_limit.per_ip]
+            burst = 3
+            "#,
+            SECRET
+        );
+        let config = load(&file, &[("PORT", "8080"), ("JWT_EXPIRES_IN", "10m")]).unwrap();
+        assert_eq!(config.database_backend(), DatabaseBackend::Sqlite);
+        assert!(config.database.auto_migrate);
+        assert_eq!(config.server.port, 8080);
+        assert_eq!(config.jwt.expires_in, Duration::minutes(10));
+        assert_eq!(config.cors.allowed_origins.len(), 2);
+        assert_eq!(config.rate_limit.per_ip, BucketConfig { burst: 3, per_minute: 20 });
+    }
+
+    #[test]
+    fn test_every_problem_is_reported() {
+        let problems = problems(load(
+            "[server]\nport = \"http\"\n[jwt]\nexpires_in = \"30d\"",
+            &[
+                ("DATABASE_URL", "mysql://localhost/blog"),
+                ("LOG_LEVEL", "loud"),
+                ("CORS_ORIGINS", "https://ok.example.com,https://bad.example.com/,*"),
+                ("RATE_LIMIT_IP_BURST", "0"),
+            ],
+        ));
+
+        let expected = [
+            "`database.url`: unsupported DATABASE_URL scheme",
+            "`jwt.secret`: required",
+            "`jwt.expires_in`: must be shorter",
+            "`app.log_level`: expected one of",
+            "`cors.allowed_origins`: `https://bad.example.com/` must be only scheme",
+            "`cors.allowed_origins`: `*` cannot be combined",
+            "`server.port` (config.toml): expected a whole number",
+            "`rate_limit.per_ip`: burst and per_minute must be positive",
+        ];
+        assert_eq!(problems.len(), expected.len(), "{:#?}", problems);
+        for prefix in expected {
+            assert!(problems.iter().any(|problem| problem.starts_with(prefix)), "missing {}", prefix);
+        }
+
+        let error = ConfigError::Invalid { problems: vec!["a".to_string(), "b".to_string()] };
+        assert_eq!(error.to_string(), "invalid configuration:\n  - a\n  - b");
+    }
+
+    #[test]
+    fn test_weak_jwt_secrets() {
+        assert!(check_jwt_secret(SECRET).is_ok());
+        assert!(check_jwt_secret("short").unwrap_err().contains("32"));
+        assert!(check_jwt_secret("your_super_secret_jwt_key_here_make_it_long_and_random").unwrap_err().contains("example"));
+        assert!(check_jwt_secret(&"ab".repeat(20)).unwrap_err().contains("repetitive"));
+
+        // The secret itself never appears in errors
+        let problems = problems(load("", &[("JWT_SECRET", "hunter2")]));
+        assert!(!problems[0].contains("hunter2"));
+    }
+
+    #[test]
+    fn test_origins() {
+        assert!(check_origin("*").is_ok());
+        assert!(check_origin("https://blog.example.com").is_ok());
+        assert!(check_origin("http://localhost:3000").is_ok());
+        assert!(check_origin("blog.example.com").is_err());
+        assert!(check_origin("ftp://example.com").is_err());
+        assert!(check_origin("https://example.com/app").is_err());
+        assert!(check_origin("https://user@example.com").is_err());
+        assert!(check_origin("https://exa mple.com").is_err());
+    }
+
+    #[test]
+    fn test_backend_from_url_scheme() {
+        assert_eq!(DatabaseBackend::from_url(None), Ok(DatabaseBackend::InMemory));
+        assert_eq!(DatabaseBackend::from_url(Some("postgres://localhost/blog")), Ok(DatabaseBackend::Postgres));
+        assert_eq!(DatabaseBackend::from_url(Some("postgresql://localhost/blog")), Ok(DatabaseBackend::Postgres));
+        assert_eq!(DatabaseBackend::from_url(Some("sqlite://data/blog.db")), Ok(DatabaseBackend::Sqlite));
+        assert_eq!(DatabaseBackend::from_url(Some("sqlite::memory:")), Ok(DatabaseBackend::Sqlite));
+        assert!(DatabaseBackend::from_url(Some("mysql://localhost/blog")).unwrap_err().contains("mysql"));
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("900"), Ok(Duration::seconds(900)));
+        assert_eq!(parse_duration("30s"), Ok(Duration::seconds(30)));
+        assert_eq!(parse_duration("15m"), Ok(Duration::minutes(15)));
+        assert_eq!(parse_duration("24h"), Ok(Duration::hours(24)));
+        assert_eq!(parse_duration("7d"), Ok(Duration::days(7)));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("0m").is_err());
+        assert!(parse_duration("2w").is_err());
+        assert!(parse_duration("h").is_err());
+    }
+}
@@ -0,0 +1,311 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use chrono::Duration;
+use toml::{Table, Value};
+
+use crate::config::parse_duration;
+
+// Settings that can be given on the command line, as (flag, key)
+pub const FLAGS: &[(&str, &str)] = &[
+    ("--host", "server.host"),
+    ("--port", "server.port"),
+    ("--database-url", "database.url"),
+    ("--log-level", "app.log_level"),
+];
+
+// Picks the config file instead of CONFIG_FILE or ./config.toml
+pub const CONFIG_FLAG: &str = "--config";
+
+// Settings from command-line flags, the highest layer
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Overrides {
+    pub config_file: Option<PathBuf>,
+    // Values by key, e.g. `server.port`
+    pub values: BTreeMap<String, String>,
+}
+
+impl Overrides {
+    // Takes the flags above out of `args`, written `--flag value` or
+    // `--flag=value`, and returns them with the remaining arguments
+    pub fn from_args(args: &[String]) -> Result<(Self, Vec<String>), String> {
+        let mut overrides = Self::default();
+        let mut rest = Vec::new();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            let key = FLAGS.iter().find(|(name, _)| *name == flag).map(|(_, key)| *key);
+            if key.is_none() && flag != CONFIG_FLAG {
+                rest.push(arg.clone());
+                continue;
+            }
+
+            let value = match inline_value {
+                Some(value) => value,
+                None => args.next().cloned().ok_or_else(|| format!("{} needs a value", flag))?,
+            };
+            match key {
+                Some(key) => {
+                    overrides.values.insert(key.to_string(), value);
+                }
+                None => overrides.config_file = Some(PathBuf::from(value)),
+            }
+        }
+
+        Ok((overrides, rest))
+    }
+}
+
+// The layers settings are read from. `get` takes a setting from the highest
+// layer that has it: a command-line flag, then the environment variable,
+// then the config file. Invalid values are collected rather than returned, so
+// that `finish` can report every problem at once.
+pub struct Sources<'a> {
+    file: Option<(PathBuf, Table)>,
+    env: &'a dyn Fn(&str) -> Option<String>,
+    overrides: &'a Overrides,
+    // Keys asked for so far; anything else in the file is a typo
+    known: RefCell<BTreeSet<String>>,
+    errors: RefCell<Vec<String>>,
+}
+
+impl<'a> Sources<'a> {
+    pub fn new(
+        file: Option<(PathBuf, Table)>,
+        env: &'a dyn Fn(&str) -> Option<String>,
+        overrides: &'a Overrides,
+    ) -> Self {
+        Self { file, env, overrides, known: RefCell::default(), errors: RefCell::default() }
+    }
+
+    // `key` is the dotted path in the config file, `env` the variable name
+    pub fn get<T: Setting>(&self, key: &str, env: &str, default: T) -> T {
+        self.get_optional(key, env).unwrap_or(default)
+    }
+
+    pub fn get_optional<T: Setting>(&self, key: &str, env: &str) -> Option<T> {
+        self.known.borrow_mut().insert(key.to_string());
+
+        let (origin, result) = if let Some(value) = self.overrides.values.get(key) {
+            let flag = FLAGS.iter().find(|(_, k)| *k == key).map_or(key, |(flag, _)| *flag);
+            (flag.to_string(), T::parse(value))
+        } else if let Some(value) = (self.env)(env).filter(|value| !value.is_empty()) {
+            (format!("{}={}", env, value), T::parse(&value))
+        } else if let Some((path, value)) = self.file_value(key) {
+            (path.display().to_string(), T::from_toml(value))
+        } else {
+            return None;
+        };
+
+        result
+            .map_err(|message| self.error(format!("`{}` ({}): {}", key, origin, message)))
+            .ok()
+    }
+
+    // Records a problem that is not about the format of a single value
+    pub fn error(&self, message: String) {
+        self.errors.borrow_mut().push(message);
+    }
+
+    // Every problem found, including settings in the file that nothing reads
+    pub fn finish(self) -> Result<(), Vec<String>> {
+        let mut errors = self.errors.into_inner();
+        if let Some((path, table)) = &self.file {
+            let known = self.known.borrow();
+            let mut keys = Vec::new();
+            flatten_keys("", table, &mut keys);
+            for key in keys.into_iter().filter(|key| !known.contains(key)) {
+                errors.push(format!("`{}` ({}): unknown setting", key, path.display()));
+            }
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    fn file_value(&self, key: &str) -> Option<(&PathBuf, &Value)> {
+        let (path, table) = self.file.as_ref()?;
+        let (parents, name) = key.rsplit_once('.').map_or(("", key), |(parents, name)| (parents, name));
+        let mut table = table;
+        for part in parents.split('.').filter(|part| !part.is_empty()) {
+            table = table.get(part)?.as_table()?;
+        }
+        table.get(name).map(|value| (path, value))
+    }
+}
+
+fn flatten_keys(prefix: &str, table: &Table, keys: &mut Vec<String>) {
+    for (name, value) in table {
+        let key = if prefix.is_empty() { name.clone() } else { format!("{}.{}", prefix, name) };
+        match value {
+            Value::Table(table) => flatten_keys(&key, table, keys),
+            _ => keys.push(key),
+        }
+    }
+}
+
+// A type a setting can have. Environment variables and flags are text;
+// config file values may also be native TOML values.
+pub trait Setting: Sized {
+    fn parse(text: &str) -> Result<Self, String>;
+
+    fn from_toml(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::String(text) => Self::parse(text),
+            other => Err(format!("unexpected {}", other.type_str())),
+        }
+    }
+}
+
+impl Setting for String {
+    fn parse(text: &str) -> Result<Self, String> {
+        Ok(text.to_string())
+    }
+}
+
+impl Setting for bool {
+    fn parse(text: &str) -> Result<Self, String> {
+        match text.trim().to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" => Ok(true),
+            "false" | "0" | "no" => Ok(false),
+            _ => Err(format!("expected true or false, found `{}`", text)),
+        }
+    }
+
+    fn from_toml(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Boolean(value) => Ok(*value),
+            other => Err(format!("expected true or false, found {}", other.type_str())),
+        }
+    }
+}
+
+macro_rules! integer_setting {
+    ($($ty:ty),*) => {$(
+        impl Setting for $ty {
+            fn parse(text: &str) -> Result<Self, String> {
+                text.trim().parse().map_err(|_| {
+                    format!("expected a whole number from {} to {}, found `{}`", <$ty>::MIN, <$ty>::MAX, text)
+                })
+            }
+
+            fn from_toml(value: &Value) -> Result<Self, String> {
+                match value {
+                    Value::Integer(value) => <$ty>::try_from(*value).map_err(|_| {
+                        format!("expected a whole number from {} to {}, found {}", <$ty>::MIN, <$ty>::MAX, value)
+                    }),
+                    Value::String(text) => Self::parse(text),
+                    other => Err(format!("expected a whole number, found {}", other.type_str())),
+                }
+            }
+        }
+    )*};
+}
+
+integer_setting!(u16, u32);
+
+// `15m`, `24h` and so on; a bare number, in the file or not, is seconds
+impl Setting for Duration {
+    fn parse(text: &str) -> Result<Self, String> {
+        parse_duration(text)
+    }
+
+    fn from_toml(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Integer(seconds) => parse_duration(&seconds.to_string()),
+            Value::String(text) => parse_duration(text),
+            other => Err(format!("expected a duration such as \"15m\", found {}", other.type_str())),
+        }
+    }
+}
+
+// A comma-separated list, or an array in the file
+impl Setting for Vec<String> {
+    fn parse(text: &str) -> Result<Self, String> {
+        Ok(text.split(',').map(str::trim).filter(|item| !item.is_empty()).map(str::to_string).collect())
+    }
+
+    fn from_toml(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Array(items) => items
+                .iter()
+                .map(|item| item.as_str().map(str::to_string).ok_or("expected a list of strings".to_string()))
+                .collect(),
+            Value::String(text) => Self::parse(text),
+            other => Err(format!("expected a list of strings, found {}", other.type_str())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_overrides_from_args() {
+        let (overrides, rest) =
+            Overrides::from_args(&args(&["--port", "8080", "create-admin", "--config=app.toml", "root"])).unwrap();
+        assert_eq!(overrides.values["server.port"], "8080");
+        assert_eq!(overrides.config_file, Some(PathBuf::from("app.toml")));
+        assert_eq!(rest, ["create-admin", "root"]);
+
+        assert!(Overrides::from_args(&args(&["--host"])).unwrap_err().contains("--host"));
+        assert_eq!(Overrides::from_args(&args(&["--verbose"])).unwrap().1, ["--verbose"]);
+    }
+
+    #[test]
+    fn test_layers_and_errors() {
+        let file: Table = toml::from_str(
+            r#"
+            [server]
+            host = "0.0.0.0"
+            port = 70000
+
+            [jwt]
+            expires_in = 600
+            secrt = "typo"
+            "#,
+        )
+        .unwrap();
+        let env = |name: &str| match name {
+            "HOST" => Some("10.0.0.1".to_string()),
+            "TIMEOUT" => Some("soon".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        };
+        let (overrides, _) = Overrides::from_args(&args(&["--host", "192.168.0.1"])).unwrap();
+        let sources = Sources::new(Some((PathBuf::from("app.toml"), file)), &env, &overrides);
+
+        // Flag over environment over file over default
+        assert_eq!(sources.get("server.host", "HOST", String::new()), "192.168.0.1");
+        assert_eq!(sources.get("jwt.expires_in", "JWT_EXPIRES_IN", Duration::zero()), Duration::minutes(10));
+        assert_eq!(sources.get("app.name", "EMPTY", "default".to_string()), "default");
+        assert_eq!(sources.get_optional::<String>("database.url", "DATABASE_URL"), None);
+
+        // Bad values fall back to the default and are all reported
+        assert_eq!(sources.get("server.port", "PORT", 3001u16), 3001);
+        assert_eq!(sources.get("jwt.refresh_expires_in", "TIMEOUT", Duration::days(7)), Duration::days(7));
+        let errors = sources.finish().unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[0], "`server.port` (app.toml): expected a whole number from 0 to 65535, found 70000");
+        assert!(errors[1].starts_with("`jwt.refresh_expires_in` (TIMEOUT=soon): invalid duration"));
+        assert_eq!(errors[2], "`jwt.secrt` (app.toml): unknown setting");
+    }
+
+    #[test]
+    fn test_setting_types() {
+        assert_eq!(bool::parse("Yes"), Ok(true));
+        assert!(bool::from_toml(&Value::String("true".to_string())).is_err());
+        assert_eq!(Vec::<String>::parse(" a, b ,,c"), Ok(vec!["a".to_string(), "b".to_string(), "c".to_string()]));
+        assert!(Vec::<String>::from_toml(&Value::Array(vec![Value::Integer(1)])).is_err());
+        assert_eq!(u32::from_toml(&Value::String("12".to_string())), Ok(12));
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::BTreeSet;
+use std::ops::Deref;
+
+use sqlx::Acquire;
+use sqlx::migrate::{Migrate, MigrateError, Migrator};
+
+use crate::database::{Database, SqliteDatabase};
+use crate::error::{AppError, Result};
+
+// Every migration is a `.up.sql` file with a `.down.sql` file that reverts it
+pub static POSTGRES: Migrator = sqlx::migrate!("./migrations");
+pub static SQLITE: Migrator = sqlx::migrate!("./migrations/sqlite");
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub state: MigrationState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    // Applied to the database but not part of this build, e.g. after
+    // running a newer version of the app against it
+    Unknown,
+}
+
+// The migrations of one database, for the `migrate` command and for `serve`
+pub enum Migrations<'a> {
+    Postgres(&'a Database),
+    Sqlite(&'a SqliteDatabase),
+}
+
+impl Migrations<'_> {
+    // Every migration of this build and any unknown ones, by version
+    pub async fn status(&self) -> Result<Vec<MigrationStatus>> {
+        match self {
+            Migrations::Postgres(pool) => status(&POSTGRES, *pool).await,
+            Migrations::Sqlite(pool) => status(&SQLITE, *pool).await,
+        }
+    }
+
+    pub async fn pending(&self) -> Result<Vec<MigrationStatus>> {
+        let mut migrations = self.status().await?;
+        migrations.retain(|migration| migration.state == MigrationState::Pending);
+        Ok(migrations)
+    }
+
+    // Applies every pending migration and returns them
+    pub async fn up(&self) -> Result<Vec<MigrationStatus>> {
+        let pending = self.pending().await?;
+        match self {
+            Migrations::Postgres(pool) => POSTGRES.run(*pool).await,
+            Migrations::Sqlite(pool) => SQLITE.run(*pool).await,
+        }
+        .map_err(migrate_error)?;
+
+        Ok(pending)
+    }
+
+    // Reverts the migrations after `target`, newest first, and returns them.
+    // Without a target only the latest applied migration is reverted; `0`
+    // reverts all of them.
+    pub async fn down(&self, target: Option<i64>) -> Result<Vec<MigrationStatus>> {
+        let mut applied = self.status().await?;
+        applied.retain(|migration| migration.state == MigrationState::Applied);
+
+        let target = match target {
+            Some(target) => target,
+            None => applied.iter().rev().nth(1).map_or(0, |migration| migration.version),
+        };
+        applied.retain(|migration| migration.version > target);
+        applied.reverse();
+
+        match self {
+            Migrations::Postgres(pool) => POSTGRES.undo(*pool, target).await,
+            Migrations::Sqlite(pool) => SQLITE.undo(*pool, target).await,
+        }
+        .map_err(migrate_error)?;
+
+        Ok(applied)
+    }
+}
+
+async fn status<'a, A>(migrator: &Migrator, pool: A) -> Result<Vec<MigrationStatus>>
+where
+    A: Acquire<'a>,
+    <A::Connection as Deref>::Target: Migrate,
+{
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await.map_err(migrate_error)?;
+    let mut applied: BTreeSet<i64> = conn
+        .list_applied_migrations()
+        .await
+        .map_err(migrate_error)?
+        .into_iter()
+        .map(|migration| migration.version)
+        .collect();
+
+    let mut migrations: Vec<MigrationStatus> = migrator
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .map(|migration| MigrationStatus {
+            version: migration.version,
+            description: migration.description.to_string(),
+            state: if applied.remove(&migration.version) {
+                MigrationState::Applied
+            } else {
+                MigrationState::Pending
+            },
+        })
+        .collect();
+    migrations.extend(applied.into_iter().map(|version| MigrationStatus {
+        version,
+        description: String::new(),
+        state: MigrationState::Unknown,
+    }));
+    migrations.sort_by_key(|migration| migration.version);
+
+    Ok(migrations)
+}
+
+fn migrate_error(error: MigrateError) -> AppError {
+    AppError::Database(error.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::connect_sqlite;
+
+    fn versions(migrations: &[MigrationStatus]) -> Vec<i64> {
+        migrations.iter().map(|migration| migration.version).collect()
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_up_down_and_status() {
+        // In-memory databases are migrated on connect
+        let pool = connect_sqlite("sqlite::memory:").await.unwrap();
+        let migrations = Migrations::Sqlite(&pool);
+        let status = migrations.status().await.unwrap();
+        assert_eq!(versions(&status), [1, 2, 3, 4, 5, 7, 8]);
+        assert!(status.iter().all(|migration| migration.state == MigrationState::Applied));
+        assert_eq!(status[0].description, "create users table");
+
+        // Down reverts the latest migration, or everything after a target
+        assert_eq!(versions(&migrations.down(None).await.unwrap()), [8]);
+        assert_eq!(versions(&migrations.down(Some(2)).await.unwrap()), [7, 5, 4, 3]);
+        assert_eq!(versions(&migrations.pending().await.unwrap()), [3, 4, 5, 7, 8]);
+        assert!(sqlx::query("SELECT role FROM users").fetch_all(&pool).await.is_err());
+
+        // Up reapplies them, and the down migrations left nothing behind
+        assert_eq!(versions(&migrations.up().await.unwrap()), [3, 4, 5, 7, 8]);
+        assert!(migrations.pending().await.unwrap().is_empty());
+
+        assert_eq!(versions(&migrations.down(Some(0)).await.unwrap()), [8, 7, 5, 4, 3, 2, 1]);
+        let tables: Vec<(String,)> =
+            sqlx::query_as("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE '\\_%' ESCAPE '\\'")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert!(tables.iter().all(|(name,)| name.starts_with("sqlite_")), "{:?}", tables);
+    }
+}
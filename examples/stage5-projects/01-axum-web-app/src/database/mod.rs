@@ -1,8 +1,19 @@
-use sqlx::{PgPool, postgres::PgPoolOptions};
+use std::str::FromStr;
+
+use sqlx::{
+    PgPool, SqlitePool,
+    postgres::PgPoolOptions,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+};
 use crate::config::Config;
 use crate::error::{AppError, Result};
 
+pub mod migrations;
+
+pub use migrations::Migrations;
+
 pub type Database = PgPool;
+pub type SqliteDatabase = SqlitePool;
 
 pub struct DatabaseService {
     pool: Database,
@@ -10,9 +21,11 @@ pub struct DatabaseService {
 
 impl DatabaseService {
     pub async fn new(config: &Config) -> Result<Self> {
+        let url = config.database_url()
+            .ok_or_else(|| AppError::Internal("DATABASE_URL is not set".to_string()))?;
         let pool = PgPoolOptions::new()
             .max_connections(10)
-            .connect(config.database_url())
+            .connect(url)
             .await?;
 
         Ok(Self { pool })
@@ -22,14 +35,8 @@ impl DatabaseService {
         &self.pool
     }
 
-    // Run database migrations
-    pub async fn migrate(&self) -> Result<()> {
-        sqlx::migrate!("./migrations")
-            .run(&self.pool)
-            .await
-            .map_err(AppError::Database)?;
-
-        Ok(())
+    pub fn migrations(&self) -> Migrations<'_> {
+        Migrations::Postgres(&self.pool)
     }
 
     // Test database connection
@@ -43,5 +50,34 @@ impl DatabaseService {
     }
 }
 
+// Open a SQLite database. The file is created if it does not exist and is
+// migrated like a Postgres database; `sqlite::memory:` gives a private
+// database that lives as long as the pool, and is always migrated.
+pub async fn connect_sqlite(url: &str) -> Result<SqliteDatabase> {
+    let options = SqliteConnectOptions::from_str(url)?
+        .create_if_missing(true)
+        .foreign_keys(true);
+
+    // Every connection to `:memory:` opens a separate empty database, so keep
+    // exactly one connection alive for the lifetime of the pool
+    let in_memory = url.contains(":memory:") || url.contains("mode=memory");
+    let pool_options = if in_memory {
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .min_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+    } else {
+        SqlitePoolOptions::new().max_connections(5)
+    };
+    let pool = pool_options.connect_with(options).await?;
+
+    if in_memory {
+        Migrations::Sqlite(&pool).up().await?;
+    }
+
+    Ok(pool)
+}
+
 // Re-export for convenience
 pub use sqlx;
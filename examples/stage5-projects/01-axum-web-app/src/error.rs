@@ -1,15 +1,24 @@
+use std::collections::BTreeMap;
+
 use axum::{
-    http::StatusCode,
+    extract::rejection::{JsonRejection, PathRejection, QueryRejection},
+    http::{header, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
-use serde_json::json;
+use serde::Serialize;
 use thiserror::Error;
+use utoipa::ToSchema;
+use validator::{ValidationError, ValidationErrors, ValidationErrorsKind};
+
+use crate::request_id;
+
+pub const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
 
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("Database error: {0}")]
-    Database(#[from] sqlx::Error),
+    Database(sqlx::Error),
 
     #[error("Authentication error: {0}")]
     Authentication(String),
@@ -20,12 +29,34 @@ pub enum AppError {
     #[error("Validation error: {0}")]
     Validation(String),
 
+    // A request body that failed its `Validate` rules, reported per field
+    #[error("Validation error: {0}")]
+    InvalidFields(#[from] ValidationErrors),
+
+    // A request the extractors could not parse: malformed JSON, a wrong
+    // content type, bad query or path parameters
+    #[error("Invalid request: {message}")]
+    Rejected { status: StatusCode, message: String },
+
     #[error("Not found: {0}")]
     NotFound(String),
 
     #[error("Conflict: {0}")]
     Conflict(String),
 
+    // An `If-Match` precondition that no longer holds: the resource changed
+    // since the client read it
+    #[error("Precondition failed: {0}")]
+    PreconditionFailed(String),
+
+    // A write that must be conditional was sent without `If-Match`
+    #[error("Precondition required: {0}")]
+    PreconditionRequired(String),
+
+    // Too many requests; the client may retry once `retry_after` has passed
+    #[error("Rate limited: {message}")]
+    RateLimited { message: String, retry_after: chrono::Duration },
+
     #[error("Internal server error: {0}")]
     Internal(String),
 
@@ -42,68 +73,285 @@ pub enum AppError {
     SerdeJson(#[from] serde_json::Error),
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let (status, error_message) = match self {
-            AppError::Database(ref err) => {
+// Unique constraints back up the service-level checks, which can race; a
+// violation means the same as when the check catches it
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        match err.as_database_error() {
+            Some(db_err) if db_err.is_unique_violation() => {
+                tracing::debug!("Unique violation: {}", db_err);
+                AppError::Conflict("A record with the same unique value already exists".to_string())
+            }
+            _ => AppError::Database(err),
+        }
+    }
+}
+
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        AppError::Rejected { status: rejection.status(), message: rejection.body_text() }
+    }
+}
+
+impl From<QueryRejection> for AppError {
+    fn from(rejection: QueryRejection) -> Self {
+        AppError::Rejected { status: rejection.status(), message: rejection.body_text() }
+    }
+}
+
+impl From<PathRejection> for AppError {
+    fn from(rejection: PathRejection) -> Self {
+        AppError::Rejected { status: rejection.status(), message: rejection.body_text() }
+    }
+}
+
+// The body of every error response, as described by RFC 7807 and sent as
+// `application/problem+json`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProblemDetails {
+    // Identifies the kind of problem; see the README for the list
+    #[serde(rename = "type")]
+    #[schema(example = "/problems/not-found")]
+    pub problem_type: String,
+    #[schema(example = "Resource not found")]
+    pub title: String,
+    #[schema(example = 404)]
+    pub status: u16,
+    #[schema(example = "Post not found")]
+    pub detail: String,
+    // The request path
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    // Also sent as the `x-request-id` response header
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    // Invalid fields, by name; nested fields are joined with `.`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub errors: BTreeMap<String, Vec<FieldError>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FieldError {
+    #[schema(example = "length")]
+    pub code: String,
+    #[schema(example = "must be between 3 and 50 characters")]
+    pub message: String,
+}
+
+impl AppError {
+    // (status, problem type slug, title, detail). Internal failures are
+    // logged here and hidden from the client.
+    fn problem(&self) -> (StatusCode, &'static str, &'static str, String) {
+        let internal = || {
+            let message = "Internal server error";
+            (StatusCode::INTERNAL_SERVER_ERROR, "internal-error", message, message.to_string())
+        };
+
+        match self {
+            AppError::Database(err) => {
                 tracing::error!("Database error: {:?}", err);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Internal server error".to_string(),
-                )
+                internal()
+            }
+            AppError::Authentication(message) => {
+                (StatusCode::UNAUTHORIZED, "unauthorized", "Authentication required", message.clone())
+            }
+            AppError::Authorization(message) => {
+                (StatusCode::FORBIDDEN, "forbidden", "Permission denied", message.clone())
+            }
+            AppError::Validation(message) => {
+                (StatusCode::BAD_REQUEST, "validation-error", "Invalid request", message.clone())
+            }
+            AppError::InvalidFields(errors) => {
+                let fields: Vec<String> = field_errors(errors).into_keys().collect();
+                let detail = format!("Invalid value for: {}", fields.join(", "));
+                (StatusCode::BAD_REQUEST, "validation-error", "Invalid request", detail)
+            }
+            AppError::Rejected { status, message } => {
+                (*status, "malformed-request", "Malformed request", message.clone())
             }
-            AppError::Authentication(ref message) => {
-                (StatusCode::UNAUTHORIZED, message.clone())
+            AppError::NotFound(message) => {
+                (StatusCode::NOT_FOUND, "not-found", "Resource not found", message.clone())
             }
-            AppError::Authorization(ref message) => {
-                (StatusCode::FORBIDDEN, message.clone())
+            AppError::Conflict(message) => {
+                (StatusCode::CONFLICT, "conflict", "Resource conflict", message.clone())
             }
-            AppError::Validation(ref message) => {
-                (StatusCode::BAD_REQUEST, message.clone())
+            AppError::PreconditionFailed(message) => {
+                (StatusCode::PRECONDITION_FAILED, "precondition-failed", "Precondition failed", message.clone())
             }
-            AppError::NotFound(ref message) => {
-                (StatusCode::NOT_FOUND, message.clone())
+            AppError::PreconditionRequired(message) => {
+                (StatusCode::PRECONDITION_REQUIRED, "precondition-required", "Precondition required", message.clone())
             }
-            AppError::Conflict(ref message) => {
-                (StatusCode::CONFLICT, message.clone())
+            AppError::RateLimited { message, .. } => {
+                (StatusCode::TOO_MANY_REQUESTS, "rate-limited", "Too many requests", message.clone())
             }
-            AppError::Internal(ref message) => {
+            AppError::Internal(message) => {
                 tracing::error!("Internal error: {}", message);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Internal server error".to_string(),
-                )
+                internal()
             }
-            AppError::Jwt(ref err) => {
+            AppError::Jwt(err) => {
                 tracing::warn!("JWT error: {:?}", err);
-                (StatusCode::UNAUTHORIZED, "Invalid token".to_string())
+                (StatusCode::UNAUTHORIZED, "unauthorized", "Authentication required", "Invalid token".to_string())
             }
-            AppError::PasswordHashing(ref err) => {
+            AppError::PasswordHashing(err) => {
                 tracing::error!("Password hashing error: {:?}", err);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Internal server error".to_string(),
-                )
+                internal()
             }
             AppError::UuidParsing(_) => {
-                (StatusCode::BAD_REQUEST, "Invalid ID format".to_string())
+                (StatusCode::BAD_REQUEST, "validation-error", "Invalid request", "Invalid ID format".to_string())
             }
-            AppError::SerdeJson(ref err) => {
+            AppError::SerdeJson(err) => {
                 tracing::error!("JSON serialization error: {:?}", err);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Internal server error".to_string(),
-                )
+                internal()
             }
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, slug, title, detail) = self.problem();
+        let context = request_id::current();
+
+        let body = ProblemDetails {
+            problem_type: format!("/problems/{}", slug),
+            title: title.to_string(),
+            status: status.as_u16(),
+            detail,
+            instance: context.as_ref().map(|context| context.path.clone()),
+            request_id: context.map(|context| context.id),
+            errors: match &self {
+                AppError::InvalidFields(errors) => field_errors(errors),
+                _ => BTreeMap::new(),
+            },
         };
 
-        let body = Json(json!({
-            "error": error_message,
-            "status": status.as_u16()
-        }));
+        let mut response = (status, [(header::CONTENT_TYPE, PROBLEM_CONTENT_TYPE)], Json(body)).into_response();
+        if let AppError::RateLimited { retry_after, .. } = &self {
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after_seconds(*retry_after)));
+        }
+        response
+    }
+}
+
+// Whole seconds, rounded up so that a retry at that time is not refused again
+fn retry_after_seconds(retry_after: chrono::Duration) -> i64 {
+    let millis = retry_after.num_milliseconds();
+    ((millis + 999) / 1000).max(1)
+}
 
-        (status, body).into_response()
+// Flattens nested validation errors into one map keyed by field path, e.g.
+// `tags` or `items[2].name`
+fn field_errors(errors: &ValidationErrors) -> BTreeMap<String, Vec<FieldError>> {
+    fn collect(prefix: &str, errors: &ValidationErrors, out: &mut BTreeMap<String, Vec<FieldError>>) {
+        for (field, kind) in errors.errors() {
+            let path = if prefix.is_empty() { field.to_string() } else { format!("{}.{}", prefix, field) };
+            match kind {
+                ValidationErrorsKind::Field(errors) => {
+                    out.entry(path).or_default().extend(errors.iter().map(|error| FieldError {
+                        code: error.code.to_string(),
+                        message: describe(error),
+                    }));
+                }
+                ValidationErrorsKind::Struct(errors) => collect(&path, errors, out),
+                ValidationErrorsKind::List(items) => {
+                    for (index, errors) in items {
+                        collect(&format!("{}[{}]", path, index), errors, out);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut out = BTreeMap::new();
+    collect("", errors, &mut out);
+    out
+}
+
+// The error's own message if it has one, otherwise one built from the
+// validator's code and parameters
+fn describe(error: &ValidationError) -> String {
+    if let Some(message) = &error.message {
+        return message.to_string();
+    }
+
+    let param = |name: &str| error.params.get(name).map(|value| value.to_string());
+    match error.code.as_ref() {
+        "length" => match (param("min"), param("max")) {
+            (Some(min), Some(max)) => format!("must be between {} and {} characters", min, max),
+            (Some(min), None) => format!("must be at least {} characters", min),
+            (None, Some(max)) => format!("must be at most {} characters", max),
+            (None, None) => "has an invalid length".to_string(),
+        },
+        "email" => "must be a valid email address".to_string(),
+        code => format!("failed the `{}` check", code),
     }
 }
 
 pub type Result<T> = std::result::Result<T, AppError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use validator::Validate;
+
+    #[derive(Validate)]
+    struct Signup {
+        #[validate(length(min = 3, max = 50))]
+        username: String,
+        #[validate(email, length(min = 10))]
+        email: String,
+        #[validate(nested)]
+        profile: Profile,
+    }
+
+    #[derive(Validate)]
+    struct Profile {
+        #[validate(length(max = 5))]
+        bio: String,
+    }
+
+    #[test]
+    fn test_field_errors_are_keyed_by_path() {
+        let signup = Signup {
+            username: "ab".to_string(),
+            email: "nope".to_string(),
+            profile: Profile { bio: "far too long".to_string() },
+        };
+        let errors = field_errors(&signup.validate().unwrap_err());
+
+        assert_eq!(errors.keys().collect::<Vec<_>>(), ["email", "profile.bio", "username"]);
+        assert_eq!(errors["username"][0].code, "length");
+        assert_eq!(errors["username"][0].message, "must be between 3 and 50 characters");
+        assert_eq!(errors["email"].len(), 2);
+        assert_eq!(errors["profile.bio"][0].message, "must be at most 5 characters");
+
+        let custom = ValidationError::new("invalid_tag").with_message("custom message".into());
+        assert_eq!(describe(&custom), "custom message");
+        assert_eq!(describe(&ValidationError::new("invalid_tag")), "failed the `invalid_tag` check");
+    }
+
+    #[test]
+    fn test_problem_mapping() {
+        let (status, slug, _, detail) = AppError::NotFound("Post not found".to_string()).problem();
+        assert_eq!((status, slug, detail.as_str()), (StatusCode::NOT_FOUND, "not-found", "Post not found"));
+
+        // Internal details stay in the logs
+        let (status, _, _, detail) = AppError::Internal("disk on fire".to_string()).problem();
+        assert_eq!((status, detail.as_str()), (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error"));
+
+        let (status, slug, _, _) = AppError::PreconditionFailed("Post was changed".to_string()).problem();
+        assert_eq!((status, slug), (StatusCode::PRECONDITION_FAILED, "precondition-failed"));
+
+        let (status, slug, _, _) = AppError::from(sqlx::Error::RowNotFound).problem();
+        assert_eq!((status, slug), (StatusCode::INTERNAL_SERVER_ERROR, "internal-error"));
+
+        let limited = AppError::RateLimited {
+            message: "Slow down".to_string(),
+            retry_after: chrono::Duration::milliseconds(1500),
+        };
+        let response = limited.into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "2");
+        assert_eq!(retry_after_seconds(chrono::Duration::zero()), 1);
+    }
+}
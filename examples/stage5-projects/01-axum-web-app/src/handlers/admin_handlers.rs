@@ -0,0 +1,117 @@
+use axum::{extract::State, http::StatusCode};
+use serde::Deserialize;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::models::pagination::DEFAULT_PAGE_SIZE;
+use crate::models::user::AdminUserResponse;
+use crate::auth::AuthUser;
+use crate::error::Result;
+use crate::extract::{Json, Path, Query};
+use crate::repositories::Repository;
+use crate::state::AppState;
+
+// Who may call these is decided by `auth::policy`, not by the route
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PaginationQuery {
+    #[serde(default = "default_limit")]
+    #[param(minimum = 1, maximum = 100, default = 20)]
+    pub limit: i64,
+
+    #[serde(default)]
+    #[param(minimum = 0)]
+    pub offset: i64,
+}
+
+fn default_limit() -> i64 {
+    DEFAULT_PAGE_SIZE
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/admin/users",
+    tag = "admin",
+    params(PaginationQuery),
+    responses(
+        (status = 200, description = "Users, oldest first", body = Vec<AdminUserResponse>),
+        (status = 401, description = "Missing, invalid or revoked access token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Role does not allow this", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn list_users<R: Repository>(
+    State(state): State<AppState<R>>,
+    actor: AuthUser,
+    Query(pagination): Query<PaginationQuery>,
+) -> Result<Json<Vec<AdminUserResponse>>> {
+    let users = state.user_service.list_users(&actor, pagination.limit, pagination.offset).await?;
+    Ok(Json(users))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/admin/users/{id}/ban",
+    tag = "admin",
+    params(("id" = Uuid, Path, description = "User id")),
+    responses(
+        (status = 200, description = "The banned user", body = AdminUserResponse),
+        (status = 401, description = "Missing, invalid or revoked access token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Role does not allow this", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn ban_user<R: Repository>(
+    State(state): State<AppState<R>>,
+    actor: AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<AdminUserResponse>> {
+    let user = state.user_service.ban_user(&actor, &user_id).await?;
+    Ok(Json(user))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/admin/users/{id}/ban",
+    tag = "admin",
+    params(("id" = Uuid, Path, description = "User id")),
+    responses(
+        (status = 200, description = "The unbanned user", body = AdminUserResponse),
+        (status = 401, description = "Missing, invalid or revoked access token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Role does not allow this", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "User not found", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn unban_user<R: Repository>(
+    State(state): State<AppState<R>>,
+    actor: AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<AdminUserResponse>> {
+    let user = state.user_service.unban_user(&actor, &user_id).await?;
+    Ok(Json(user))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/admin/posts/{id}",
+    tag = "admin",
+    params(("id" = Uuid, Path, description = "Post id")),
+    responses(
+        (status = 204, description = "Post deleted"),
+        (status = 401, description = "Missing, invalid or revoked access token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Role does not allow this", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Post not found", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn delete_any_post<R: Repository>(
+    State(state): State<AppState<R>>,
+    actor: AuthUser,
+    Path(post_id): Path<Uuid>,
+) -> Result<StatusCode> {
+    state.post_service.moderate_delete_post(&post_id, &actor).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,76 @@
+use axum::{extract::State, http::StatusCode};
+use uuid::Uuid;
+
+use crate::models::comment::{CommentThread, CommentWithAuthor, CreateCommentRequest};
+use crate::auth::AuthUser;
+use crate::error::Result;
+use crate::extract::{Json, Path};
+use crate::repositories::Repository;
+use crate::state::AppState;
+
+#[utoipa::path(
+    get,
+    path = "/api/posts/{id}/comments",
+    tag = "comments",
+    params(("id" = Uuid, Path, description = "Post id")),
+    responses(
+        (status = 200, description = "The post's comments as threads, oldest first", body = Vec<CommentThread>),
+        (status = 401, description = "Invalid or revoked access token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Post not found, or unpublished and not visible to the caller", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security((), ("bearer_auth" = [])),
+)]
+pub async fn get_comments<R: Repository>(
+    State(state): State<AppState<R>>,
+    viewer: Option<AuthUser>,
+    Path(post_id): Path<Uuid>,
+) -> Result<Json<Vec<CommentThread>>> {
+    let comments = state.comment_service.get_comments(&post_id, viewer.as_ref()).await?;
+    Ok(Json(comments))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/posts/{id}/comments",
+    tag = "comments",
+    params(("id" = Uuid, Path, description = "Post id")),
+    request_body = CreateCommentRequest,
+    responses(
+        (status = 201, description = "Comment created", body = CommentWithAuthor),
+        (status = 400, description = "Invalid input or parent comment", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing, invalid or revoked access token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Post not found or not published", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn create_comment<R: Repository>(
+    State(state): State<AppState<R>>,
+    AuthUser { id: author_id, .. }: AuthUser,
+    Path(post_id): Path<Uuid>,
+    Json(request): Json<CreateCommentRequest>,
+) -> Result<(StatusCode, Json<CommentWithAuthor>)> {
+    let comment = state.comment_service.create_comment(&post_id, &author_id, request).await?;
+    Ok((StatusCode::CREATED, Json(comment)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/comments/{id}",
+    tag = "comments",
+    params(("id" = Uuid, Path, description = "Comment id")),
+    responses(
+        (status = 204, description = "Comment deleted with its replies"),
+        (status = 401, description = "Missing, invalid or revoked access token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Neither the author nor a moderator", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Comment not found", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn delete_comment<R: Repository>(
+    State(state): State<AppState<R>>,
+    actor: AuthUser,
+    Path(comment_id): Path<Uuid>,
+) -> Result<StatusCode> {
+    state.comment_service.delete_comment(&comment_id, &actor).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
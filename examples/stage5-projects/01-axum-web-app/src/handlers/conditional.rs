@@ -0,0 +1,94 @@
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+use crate::error::{AppError, Result};
+use crate::models::etag::EntityTags;
+
+// The `If-Match` header of a write, if any. Post updates and deletes
+// require one; `PostService` checks it once the caller may make the change.
+pub struct IfMatch(pub Option<EntityTags>);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for IfMatch
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self> {
+        entity_tags(&parts.headers, header::IF_MATCH).map(IfMatch)
+    }
+}
+
+// The `If-None-Match` header of a read, if any
+pub struct IfNoneMatch(pub Option<EntityTags>);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for IfNoneMatch
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self> {
+        entity_tags(&parts.headers, header::IF_NONE_MATCH).map(IfNoneMatch)
+    }
+}
+
+// Repeated headers make up one list
+fn entity_tags(headers: &HeaderMap, name: HeaderName) -> Result<Option<EntityTags>> {
+    let values: Vec<&str> = headers
+        .get_all(&name)
+        .iter()
+        .map(|value| value.to_str())
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|_| AppError::Validation(format!("Invalid {} header", name)))?;
+    if values.is_empty() {
+        return Ok(None);
+    }
+
+    values
+        .join(",")
+        .parse()
+        .map(Some)
+        .map_err(|_| AppError::Validation(format!("Invalid {} header: expected `*` or quoted entity tags", name)))
+}
+
+// A response with an `ETag` header. Once `revalidate` finds the tag in the
+// request's If-None-Match, it is a 304 Not Modified without a body.
+pub struct Tagged<T> {
+    etag: String,
+    body: Option<T>,
+}
+
+impl<T> Tagged<T> {
+    pub fn new(etag: String, body: T) -> Self {
+        Self { etag, body: Some(body) }
+    }
+
+    pub fn revalidate(self, IfNoneMatch(tags): IfNoneMatch) -> Self {
+        match tags {
+            Some(tags) if tags.matches_weakly(&self.etag) => Self { body: None, ..self },
+            _ => self,
+        }
+    }
+}
+
+impl<T: IntoResponse> IntoResponse for Tagged<T> {
+    fn into_response(self) -> Response {
+        let mut response = match self.body {
+            Some(body) => body.into_response(),
+            None => StatusCode::NOT_MODIFIED.into_response(),
+        };
+
+        if let Ok(etag) = HeaderValue::from_str(&self.etag) {
+            response.headers_mut().insert(header::ETAG, etag);
+        }
+
+        response
+    }
+}
@@ -1,19 +1,35 @@
+use std::time::{Duration, Instant};
+
 use axum::{
     extract::State,
-    http::StatusCode,
-    response::Json,
+    http::{header, StatusCode},
+    response::{IntoResponse, Json},
 };
-use serde_json::json;
+use serde_json::{json, Map, Value};
 
 use crate::error::Result;
+use crate::metrics::METRICS_CONTENT_TYPE;
+use crate::repositories::Repository;
 use crate::state::AppState;
 
-pub async fn health_check(State(state): State<AppState>) -> Result<Json<serde_json::Value>> {
-    // Test database connection
-    let db = state.user_service.get_db();
-    sqlx::query("SELECT 1")
-        .fetch_one(db)
-        .await?;
+// A database that takes longer than this to answer counts as unreachable
+const READINESS_TIMEOUT: Duration = Duration::from_secs(2);
+
+// A database that answers, but more slowly than this, counts as degraded
+const SLOW_PING: Duration = Duration::from_millis(500);
+
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "health",
+    responses(
+        (status = 200, description = "Service and storage are healthy", body = Object),
+        (status = 500, description = "Storage is unreachable", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+pub async fn health_check<R: Repository>(State(state): State<AppState<R>>) -> Result<Json<serde_json::Value>> {
+    // Test storage connection
+    state.repository.ping().await?;
 
     Ok(Json(json!({
         "status": "healthy",
@@ -23,22 +39,89 @@ pub async fn health_check(State(state): State<AppState>) -> Result<Json<serde_js
     })))
 }
 
-pub async fn readiness_check(State(state): State<AppState>) -> Result<Json<serde_json::Value>> {
-    // Check if database is ready
-    let db = state.user_service.get_db();
-    let _ = sqlx::query("SELECT 1")
-        .fetch_one(db)
-        .await?;
+#[utoipa::path(
+    get,
+    path = "/ready",
+    tag = "health",
+    responses(
+        (status = 200, description = "Ready to serve requests; `status` is `degraded` when a dependency is slow", body = Object),
+        (status = 503, description = "A dependency is down; `checks` says which", body = Object),
+    ),
+)]
+pub async fn readiness_check<R: Repository>(State(state): State<AppState<R>>) -> (StatusCode, Json<Value>) {
+    let mut checks = Map::new();
+    checks.insert("database".to_string(), check_database(&state.repository).await);
 
-    Ok(Json(json!({
-        "status": "ready",
-        "timestamp": chrono::Utc::now().to_rfc3339()
-    })))
+    let statuses: Vec<&str> = checks.values().filter_map(|check| check["status"].as_str()).collect();
+    let (code, status) = if statuses.contains(&"down") {
+        (StatusCode::SERVICE_UNAVAILABLE, "unavailable")
+    } else if statuses.contains(&"degraded") {
+        (StatusCode::OK, "degraded")
+    } else {
+        (StatusCode::OK, "ready")
+    };
+
+    let body = json!({
+        "status": status,
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "checks": checks
+    });
+    (code, Json(body))
+}
+
+// Runs the same `SELECT 1` as `DatabaseService::test_connection` through the
+// repository. The time includes waiting for a free connection, so a saturated
+// pool shows up as a slow, degraded database. Failures are logged; the
+// response only says that it failed.
+async fn check_database<R: Repository>(repository: &R) -> Value {
+    let start = Instant::now();
+    let result = tokio::time::timeout(READINESS_TIMEOUT, repository.ping()).await;
+    let latency = start.elapsed();
+
+    match result {
+        Ok(Ok(())) => {
+            let status = if latency > SLOW_PING { "degraded" } else { "up" };
+            let mut check = json!({ "status": status, "latency_ms": latency.as_millis() as u64 });
+            if let Some(pool) = repository.pool_stats() {
+                check["pool"] = json!({ "size": pool.size, "idle": pool.idle, "max": pool.max });
+            }
+            check
+        }
+        Ok(Err(err)) => {
+            tracing::warn!("Readiness check: database error: {}", err);
+            json!({ "status": "down", "error": "query failed" })
+        }
+        Err(_) => {
+            tracing::warn!("Readiness check: database did not answer within {:?}", READINESS_TIMEOUT);
+            json!({ "status": "down", "error": "timed out" })
+        }
+    }
 }
 
+#[utoipa::path(
+    get,
+    path = "/live",
+    tag = "health",
+    responses(
+        (status = 200, description = "The process is running", body = Object),
+    ),
+)]
 pub async fn liveness_check() -> Json<serde_json::Value> {
     Json(json!({
         "status": "alive",
         "timestamp": chrono::Utc::now().to_rfc3339()
     }))
 }
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "health",
+    responses(
+        (status = 200, description = "Request counts, latency histograms and connection pool usage in the Prometheus text format", body = String, content_type = "text/plain"),
+    ),
+)]
+pub async fn metrics<R: Repository>(State(state): State<AppState<R>>) -> impl IntoResponse {
+    let body = state.metrics.render(state.repository.pool_stats());
+    ([(header::CONTENT_TYPE, METRICS_CONTENT_TYPE)], body)
+}
@@ -1,3 +1,7 @@
 pub mod user_handlers;
 pub mod post_handlers;
 pub mod health_handlers;
+pub mod admin_handlers;
+pub mod comment_handlers;
+pub mod pagination;
+pub mod conditional;
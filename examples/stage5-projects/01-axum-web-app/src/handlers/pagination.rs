@@ -0,0 +1,63 @@
+use axum::{
+    http::{header, HeaderValue, Uri},
+    response::{IntoResponse, Json, Response},
+};
+use serde::Serialize;
+
+use crate::models::pagination::Page;
+
+// A page of a listing, sent as JSON with a `Link: <...>; rel="next"` header
+// when another page follows
+pub struct Paginated<T> {
+    page: Page<T>,
+    uri: Uri,
+}
+
+impl<T> Paginated<T> {
+    // `uri` is the request URI; the next link keeps its query parameters and
+    // replaces the cursor
+    pub fn new(page: Page<T>, uri: Uri) -> Self {
+        Self { page, uri }
+    }
+}
+
+impl<T: Serialize> IntoResponse for Paginated<T> {
+    fn into_response(self) -> Response {
+        let link = self.page.next_cursor.as_deref().map(|cursor| next_link(&self.uri, cursor));
+        let mut response = Json(self.page).into_response();
+
+        if let Some(link) = link.and_then(|link| HeaderValue::from_str(&link).ok()) {
+            response.headers_mut().insert(header::LINK, link);
+        }
+
+        response
+    }
+}
+
+// Cursors are URL-safe base64, so they need no escaping
+fn next_link(uri: &Uri, cursor: &str) -> String {
+    let cursor = format!("cursor={}", cursor);
+    let mut params: Vec<&str> = uri
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|param| !param.is_empty() && !param.starts_with("cursor="))
+        .collect();
+    params.push(&cursor);
+
+    format!("<{}?{}>; rel=\"next\"", uri.path(), params.join("&"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_link_replaces_cursor() {
+        let uri: Uri = "/api/posts/search?q=rust&cursor=old&limit=5".parse().unwrap();
+        assert_eq!(next_link(&uri, "new"), "</api/posts/search?q=rust&limit=5&cursor=new>; rel=\"next\"");
+
+        let uri: Uri = "/api/posts".parse().unwrap();
+        assert_eq!(next_link(&uri, "abc"), "</api/posts?cursor=abc>; rel=\"next\"");
+    }
+}
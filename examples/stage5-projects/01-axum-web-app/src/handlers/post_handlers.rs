@@ -1,119 +1,343 @@
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    extract::State,
+    http::{StatusCode, Uri},
 };
-use serde::Deserialize;
 use uuid::Uuid;
 
+use crate::handlers::conditional::{IfMatch, IfNoneMatch, Tagged};
+use crate::handlers::pagination::Paginated;
+use crate::models::pagination::PageQuery;
+use crate::models::search::{SearchHit, SearchParams};
 use crate::models::post::{
-    CreatePostRequest, UpdatePostRequest, PostResponse, PostWithAuthor
+    CreatePostRequest, UpdatePostRequest, PostStatusQuery, PostWithAuthor, LikeResponse
 };
-use crate::error::{AppError, Result};
+use crate::models::revision::{DiffQuery, PostRevision, RevisionDiff};
+use crate::auth::AuthUser;
+use crate::error::Result;
+use crate::extract::{Json, Path, Query};
+use crate::repositories::Repository;
 use crate::state::AppState;
 
-#[derive(Debug, Deserialize)]
-pub struct PaginationQuery {
-    #[serde(default = "default_limit")]
-    pub limit: i64,
-
-    #[serde(default)]
-    pub offset: i64,
-}
-
-#[derive(Debug, Deserialize)]
-pub struct SearchQuery {
-    pub q: String,
-
-    #[serde(default = "default_limit")]
-    pub limit: i64,
-
-    #[serde(default)]
-    pub offset: i64,
-}
-
-fn default_limit() -> i64 {
-    20
-}
-
-pub async fn create_post(
-    State(state): State<AppState>,
-    claims: axum::extract::Extension<crate::models::user::Claims>,
+#[utoipa::path(
+    post,
+    path = "/api/posts",
+    tag = "posts",
+    request_body = CreatePostRequest,
+    responses(
+        (status = 201, description = "Post created", body = PostWithAuthor, headers(("ETag" = String, description = "Strong entity tag of the post, for If-Match and If-None-Match"))),
+        (status = 400, description = "Invalid input", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing, invalid or revoked access token", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn create_post<R: Repository>(
+    State(state): State<AppState<R>>,
+    AuthUser { id: author_id, .. }: AuthUser,
     Json(request): Json<CreatePostRequest>,
-    ) -> Result<(StatusCode, Json<PostResponse>)> {
-    let author_id = Uuid::parse_str(&claims.sub)
-        .map_err(|_| AppError::Authentication("Invalid user ID in token".to_string()))?;
-
+    ) -> Result<Tagged<(StatusCode, Json<PostWithAuthor>)>> {
     let post = state.post_service.create_post(&author_id, request).await?;
-    Ok((StatusCode::CREATED, Json(post)))
+    Ok(Tagged::new(post.etag(), (StatusCode::CREATED, Json(post))))
 }
 
-pub async fn get_post(
-    State(state): State<AppState>,
+#[utoipa::path(
+    get,
+    path = "/api/posts/{id}",
+    tag = "posts",
+    params(
+        ("id" = Uuid, Path, description = "Post id"),
+        ("If-None-Match" = Option<String>, Header, description = "ETags the client has cached; 304 if one is current"),
+    ),
+    responses(
+        (status = 200, description = "The post", body = PostWithAuthor, headers(("ETag" = String, description = "Strong entity tag of the post, for If-Match and If-None-Match"))),
+        (status = 304, description = "The cached post named by If-None-Match is current", headers(("ETag" = String, description = "Strong entity tag of the post, for If-Match and If-None-Match"))),
+        (status = 400, description = "Malformed If-None-Match", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Invalid or revoked access token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Post not found, or unpublished and not visible to the caller", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security((), ("bearer_auth" = [])),
+)]
+pub async fn get_post<R: Repository>(
+    State(state): State<AppState<R>>,
+    viewer: Option<AuthUser>,
+    if_none_match: IfNoneMatch,
     Path(post_id): Path<Uuid>,
-) -> Result<Json<PostWithAuthor>> {
-    let post = state.post_service.get_post(&post_id).await?;
-    Ok(Json(post))
+) -> Result<Tagged<Json<PostWithAuthor>>> {
+    let post = state.post_service.get_post(&post_id, viewer.as_ref()).await?;
+    Ok(Tagged::new(post.etag(), Json(post)).revalidate(if_none_match))
 }
 
-pub async fn get_posts(
-    State(state): State<AppState>,
-    Query(pagination): Query<PaginationQuery>,
-) -> Result<Json<Vec<PostWithAuthor>>> {
-    let posts = state.post_service.get_posts(pagination.limit, pagination.offset).await?;
-    Ok(Json(posts))
+#[utoipa::path(
+    get,
+    path = "/api/posts",
+    tag = "posts",
+    params(PageQuery),
+    responses(
+        (status = 200, description = "A page of published posts", body = PostPage, headers(("Link" = String, description = "`<...&cursor=...>; rel=\"next\"` when another page follows"))),
+        (status = 400, description = "Invalid cursor, sort or filter", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+pub async fn get_posts<R: Repository>(
+    State(state): State<AppState<R>>,
+    uri: Uri,
+    Query(query): Query<PageQuery>,
+) -> Result<Paginated<PostWithAuthor>> {
+    let page = state.post_service.get_posts(query).await?;
+    Ok(Paginated::new(page, uri))
 }
 
-pub async fn get_user_posts(
-    State(state): State<AppState>,
+#[utoipa::path(
+    get,
+    path = "/api/users/{id}/posts",
+    tag = "posts",
+    params(("id" = Uuid, Path, description = "User id"), PageQuery),
+    responses(
+        (status = 200, description = "A page of the user's published posts", body = PostPage, headers(("Link" = String, description = "`<...&cursor=...>; rel=\"next\"` when another page follows"))),
+        (status = 400, description = "Invalid cursor, sort or filter", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+pub async fn get_user_posts<R: Repository>(
+    State(state): State<AppState<R>>,
+    uri: Uri,
     Path(user_id): Path<Uuid>,
-    Query(pagination): Query<PaginationQuery>,
-    ) -> Result<Json<Vec<PostWithAuthor>>> {
-    let posts = state.post_service.get_user_posts(&user_id, pagination.limit, pagination.offset).await?;
-    Ok(Json(posts))
+    Query(query): Query<PageQuery>,
+    ) -> Result<Paginated<PostWithAuthor>> {
+    let page = state.post_service.get_user_posts(&user_id, query).await?;
+    Ok(Paginated::new(page, uri))
 }
 
-pub async fn update_post(
-    State(state): State<AppState>,
-    claims: axum::extract::Extension<crate::models::user::Claims>,
+#[utoipa::path(
+    put,
+    path = "/api/posts/{id}",
+    tag = "posts",
+    params(
+        ("id" = Uuid, Path, description = "Post id"),
+        ("If-Match" = String, Header, description = "The ETag of the post as last read"),
+    ),
+    request_body = UpdatePostRequest,
+    responses(
+        (status = 200, description = "The updated post", body = PostWithAuthor, headers(("ETag" = String, description = "Strong entity tag of the post, for If-Match and If-None-Match"))),
+        (status = 400, description = "Invalid input or If-Match", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing, invalid or revoked access token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Not the author", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Post not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 412, description = "The post has changed since the ETag in If-Match", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 428, description = "Missing If-Match", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn update_post<R: Repository>(
+    State(state): State<AppState<R>>,
+    actor: AuthUser,
+    IfMatch(if_match): IfMatch,
     Path(post_id): Path<Uuid>,
     Json(request): Json<UpdatePostRequest>,
-) -> Result<Json<PostResponse>> {
-    let author_id = Uuid::parse_str(&claims.sub)
-        .map_err(|_| AppError::Authentication("Invalid user ID in token".to_string()))?;
-
-    let post = state.post_service.update_post(&post_id, &author_id, request).await?;
-    Ok(Json(post))
+) -> Result<Tagged<Json<PostWithAuthor>>> {
+    let post = state.post_service.update_post(&post_id, &actor, request, if_match.as_ref()).await?;
+    Ok(Tagged::new(post.etag(), Json(post)))
 }
 
-pub async fn delete_post(
-    State(state): State<AppState>,
-    claims: axum::extract::Extension<crate::models::user::Claims>,
+#[utoipa::path(
+    delete,
+    path = "/api/posts/{id}",
+    tag = "posts",
+    params(
+        ("id" = Uuid, Path, description = "Post id"),
+        ("If-Match" = String, Header, description = "The ETag of the post as last read"),
+    ),
+    responses(
+        (status = 204, description = "Post deleted"),
+        (status = 400, description = "Malformed If-Match", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing, invalid or revoked access token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Neither the author nor a moderator", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Post not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 412, description = "The post has changed since the ETag in If-Match", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 428, description = "Missing If-Match", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn delete_post<R: Repository>(
+    State(state): State<AppState<R>>,
+    actor: AuthUser,
+    IfMatch(if_match): IfMatch,
     Path(post_id): Path<Uuid>,
     ) -> Result<StatusCode> {
-        let author_id = Uuid::parse_str(&claims.sub)
-            .map_err(|_| AppError::Authentication("Invalid user ID in token".to_string()))?;
-
-        state.post_service.delete_post(&post_id, &author_id).await?;
+        state.post_service.delete_post(&post_id, &actor, if_match.as_ref()).await?;
         Ok(StatusCode::NO_CONTENT)
     }
 
-pub async fn search_posts(
-    State(state): State<AppState>,
-    Query(search_params): Query<SearchQuery>,
-    ) -> Result<Json<Vec<PostWithAuthor>>> {
-        let posts = state.post_service.search_posts(&search_params.q, search_params.limit, search_params.offset).await?;
-        Ok(Json(posts))
+#[utoipa::path(
+    get,
+    path = "/api/posts/search",
+    tag = "posts",
+    params(SearchParams, PageQuery),
+    responses(
+        (status = 200, description = "A page of matching posts, most relevant first by default", body = SearchPage, headers(("Link" = String, description = "`<...&cursor=...>; rel=\"next\"` when another page follows"))),
+        (status = 400, description = "Invalid query, cursor, sort or date range", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+pub async fn search_posts<R: Repository>(
+    State(state): State<AppState<R>>,
+    uri: Uri,
+    Query(search_params): Query<SearchParams>,
+    Query(query): Query<PageQuery>,
+    ) -> Result<Paginated<SearchHit>> {
+        let page = state.post_service.search_posts(search_params, query).await?;
+        Ok(Paginated::new(page, uri))
     }
 
-pub async fn get_current_user_posts(
-    State(state): State<AppState>,
-    claims: axum::extract::Extension<crate::models::user::Claims>,
-    Query(pagination): Query<PaginationQuery>,
-    ) -> Result<Json<Vec<PostWithAuthor>>> {
-        let user_id = Uuid::parse_str(&claims.sub)
-            .map_err(|_| AppError::Authentication("Invalid user ID in token".to_string()))?;
-
-        let posts = state.post_service.get_user_posts(&user_id, pagination.limit, pagination.offset).await?;
-        Ok(Json(posts))
+#[utoipa::path(
+    get,
+    path = "/api/users/me/posts",
+    tag = "posts",
+    params(PostStatusQuery, PageQuery),
+    responses(
+        (status = 200, description = "A page of the current user's posts, whatever their status unless one is given", body = PostPage, headers(("Link" = String, description = "`<...&cursor=...>; rel=\"next\"` when another page follows"))),
+        (status = 400, description = "Invalid cursor, sort or filter", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing, invalid or revoked access token", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn get_current_user_posts<R: Repository>(
+    State(state): State<AppState<R>>,
+    uri: Uri,
+    AuthUser { id: user_id, .. }: AuthUser,
+    Query(PostStatusQuery { status }): Query<PostStatusQuery>,
+    Query(query): Query<PageQuery>,
+    ) -> Result<Paginated<PostWithAuthor>> {
+        let page = state.post_service.get_own_posts(&user_id, status, query).await?;
+        Ok(Paginated::new(page, uri))
     }
+
+#[utoipa::path(
+    get,
+    path = "/api/tags/{tag}/posts",
+    tag = "posts",
+    params(("tag" = String, Path, description = "Tag name, case-insensitive"), PageQuery),
+    responses(
+        (status = 200, description = "A page of published posts with the tag", body = PostPage, headers(("Link" = String, description = "`<...&cursor=...>; rel=\"next\"` when another page follows"))),
+        (status = 400, description = "Invalid cursor, sort or filter", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+pub async fn get_tag_posts<R: Repository>(
+    State(state): State<AppState<R>>,
+    uri: Uri,
+    Path(tag): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> Result<Paginated<PostWithAuthor>> {
+    let page = state.post_service.get_tag_posts(&tag, query).await?;
+    Ok(Paginated::new(page, uri))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/posts/{id}/like",
+    tag = "posts",
+    params(("id" = Uuid, Path, description = "Post id")),
+    responses(
+        (status = 200, description = "Post liked; liking twice is a no-op", body = LikeResponse),
+        (status = 401, description = "Missing, invalid or revoked access token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Post not found or not published", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn like_post<R: Repository>(
+    State(state): State<AppState<R>>,
+    AuthUser { id: user_id, .. }: AuthUser,
+    Path(post_id): Path<Uuid>,
+) -> Result<Json<LikeResponse>> {
+    let like = state.post_service.like_post(&post_id, &user_id).await?;
+    Ok(Json(like))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/posts/{id}/like",
+    tag = "posts",
+    params(("id" = Uuid, Path, description = "Post id")),
+    responses(
+        (status = 200, description = "Like removed, if there was one", body = LikeResponse),
+        (status = 401, description = "Missing, invalid or revoked access token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Post not found or not published", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn unlike_post<R: Repository>(
+    State(state): State<AppState<R>>,
+    AuthUser { id: user_id, .. }: AuthUser,
+    Path(post_id): Path<Uuid>,
+) -> Result<Json<LikeResponse>> {
+    let like = state.post_service.unlike_post(&post_id, &user_id).await?;
+    Ok(Json(like))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/posts/{id}/revisions",
+    tag = "posts",
+    params(("id" = Uuid, Path, description = "Post id")),
+    responses(
+        (status = 200, description = "Every revision of the post, newest first", body = Vec<PostRevision>),
+        (status = 401, description = "Missing, invalid or revoked access token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Neither the author nor a moderator", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Post not found", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn get_post_revisions<R: Repository>(
+    State(state): State<AppState<R>>,
+    actor: AuthUser,
+    Path(post_id): Path<Uuid>,
+) -> Result<Json<Vec<PostRevision>>> {
+    let revisions = state.post_service.get_revisions(&post_id, &actor).await?;
+    Ok(Json(revisions))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/posts/{id}/revisions/diff",
+    tag = "posts",
+    params(("id" = Uuid, Path, description = "Post id"), DiffQuery),
+    responses(
+        (status = 200, description = "Line diffs of the title and content from one revision to another", body = RevisionDiff),
+        (status = 400, description = "Missing or invalid revision numbers", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing, invalid or revoked access token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Neither the author nor a moderator", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Post or revision not found", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn diff_post_revisions<R: Repository>(
+    State(state): State<AppState<R>>,
+    actor: AuthUser,
+    Path(post_id): Path<Uuid>,
+    Query(query): Query<DiffQuery>,
+) -> Result<Json<RevisionDiff>> {
+    let diff = state.post_service.diff_revisions(&post_id, &actor, query).await?;
+    Ok(Json(diff))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/posts/{id}/revisions/{revision}/restore",
+    tag = "posts",
+    params(
+        ("id" = Uuid, Path, description = "Post id"),
+        ("revision" = i32, Path, description = "Revision number"),
+    ),
+    responses(
+        (status = 200, description = "The post with the revision's title and content, saved as a new revision", body = PostWithAuthor, headers(("ETag" = String, description = "Strong entity tag of the post, for If-Match and If-None-Match"))),
+        (status = 401, description = "Missing, invalid or revoked access token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "Not the author", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 404, description = "Post or revision not found", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 412, description = "The post was changed while restoring", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn restore_post_revision<R: Repository>(
+    State(state): State<AppState<R>>,
+    actor: AuthUser,
+    Path((post_id, revision)): Path<(Uuid, i32)>,
+) -> Result<Tagged<Json<PostWithAuthor>>> {
+    let post = state.post_service.restore_revision(&post_id, revision, &actor).await?;
+    Ok(Tagged::new(post.etag(), Json(post)))
+}
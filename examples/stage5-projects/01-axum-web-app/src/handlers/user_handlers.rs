@@ -1,43 +1,118 @@
-use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::Json,
-};
+use axum::{extract::State, http::StatusCode};
 use uuid::Uuid;
 
-use crate::services::UserService;
+use crate::models::token::{LogoutRequest, RefreshRequest, TokenResponse};
 use crate::models::user::{
     CreateUserRequest, UpdateUserRequest, LoginRequest, UserResponse, LoginResponse
 };
-use crate::error::{AppError, Result};
+use crate::auth::AuthUser;
+use crate::error::Result;
+use crate::extract::{Json, Path};
+use crate::repositories::Repository;
 use crate::state::AppState;
 
-pub async fn register(
-    State(state): State<AppState>,
+#[utoipa::path(
+    post,
+    path = "/api/auth/register",
+    tag = "auth",
+    request_body = CreateUserRequest,
+    responses(
+        (status = 201, description = "User created", body = UserResponse),
+        (status = 400, description = "Invalid input", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Username or email already taken", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 429, description = "Too many attempts from this address or for this username", body = ProblemDetails, content_type = "application/problem+json", headers(("Retry-After" = u64, description = "Seconds to wait before retrying"))),
+    ),
+)]
+pub async fn register<R: Repository>(
+    State(state): State<AppState<R>>,
     Json(request): Json<CreateUserRequest>,
 ) -> Result<(StatusCode, Json<UserResponse>)> {
     let user = state.user_service.create_user(request).await?;
     Ok((StatusCode::CREATED, Json(user)))
 }
 
-pub async fn login(
-    State(state): State<AppState>,
+#[utoipa::path(
+    post,
+    path = "/api/auth/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Logged in", body = LoginResponse),
+        (status = 401, description = "Wrong username or password", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "User is banned", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 429, description = "Too many attempts, or the account is temporarily locked", body = ProblemDetails, content_type = "application/problem+json", headers(("Retry-After" = u64, description = "Seconds to wait before retrying"))),
+    ),
+)]
+pub async fn login<R: Repository>(
+    State(state): State<AppState<R>>,
     Json(request): Json<LoginRequest>,
 ) -> Result<Json<LoginResponse>> {
     let login_response = state.user_service.login(request).await?;
     Ok(Json(login_response))
 }
 
-pub async fn get_user(
-    State(state): State<AppState>,
+#[utoipa::path(
+    post,
+    path = "/api/auth/refresh",
+    tag = "auth",
+    request_body = RefreshRequest,
+    responses(
+        (status = 200, description = "New access and refresh tokens", body = TokenResponse),
+        (status = 401, description = "Invalid, expired or reused refresh token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 403, description = "User is banned", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 429, description = "Too many attempts from this address", body = ProblemDetails, content_type = "application/problem+json", headers(("Retry-After" = u64, description = "Seconds to wait before retrying"))),
+    ),
+)]
+pub async fn refresh_token<R: Repository>(
+    State(state): State<AppState<R>>,
+    Json(request): Json<RefreshRequest>,
+) -> Result<Json<TokenResponse>> {
+    let tokens = state.auth_service.refresh(&request.refresh_token).await?;
+    Ok(Json(tokens))
+}
+
+// The body is optional: without a refresh token only the access token is revoked
+#[utoipa::path(
+    post,
+    path = "/api/auth/logout",
+    tag = "auth",
+    request_body = Option<LogoutRequest>,
+    responses(
+        (status = 204, description = "Logged out"),
+        (status = 401, description = "Missing, invalid or revoked access token", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn logout<R: Repository>(
+    State(state): State<AppState<R>>,
+    AuthUser { claims, .. }: AuthUser,
+    request: Option<Json<LogoutRequest>>,
+) -> Result<StatusCode> {
+    let Json(request) = request.unwrap_or_default();
+    state.auth_service.logout(&claims, request.refresh_token.as_deref()).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/users/{id}",
+    tag = "users",
+    params(("id" = Uuid, Path, description = "User id")),
+    responses(
+        (status = 200, description = "The user", body = UserResponse),
+        (status = 404, description = "User not found", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+)]
+pub async fn get_user<R: Repository>(
+    State(state): State<AppState<R>>,
     Path(user_id): Path<Uuid>,
 ) -> Result<Json<UserResponse>> {
     let user = state.user_service.get_user(&user_id).await?;
     Ok(Json(user))
 }
 
-pub async fn update_user(
-    State(state): State<AppState>,
+pub async fn update_user<R: Repository>(
+    State(state): State<AppState<R>>,
     Path(user_id): Path<Uuid>,
     Json(request): Json<UpdateUserRequest>,
     ) -> Result<Json<UserResponse>> {
@@ -45,45 +120,68 @@ pub async fn update_user(
     Ok(Json(user))
 }
 
-pub async fn delete_user(
-    State(state): State<AppState>,
+pub async fn delete_user<R: Repository>(
+    State(state): State<AppState<R>>,
     Path(user_id): Path<Uuid>,
     ) -> Result<StatusCode> {
     state.user_service.delete_user(&user_id).await?;
     Ok(StatusCode::NO_CONTENT)
 }
 
-pub async fn get_current_user(
-    State(state): State<AppState>,
-    // This will be populated by the auth middleware
-    claims: axum::extract::Extension<crate::models::user::Claims>,
+#[utoipa::path(
+    get,
+    path = "/api/users/me",
+    tag = "users",
+    responses(
+        (status = 200, description = "The current user", body = UserResponse),
+        (status = 401, description = "Missing, invalid or revoked access token", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn get_current_user<R: Repository>(
+    State(state): State<AppState<R>>,
+    AuthUser { id: user_id, .. }: AuthUser,
     ) -> Result<Json<UserResponse>> {
-    let user_id = Uuid::parse_str(&claims.sub)
-        .map_err(|_| AppError::Authentication("Invalid user ID in token".to_string()))?;
-
     let user = state.user_service.get_user(&user_id).await?;
     Ok(Json(user))
 }
 
-pub async fn update_current_user(
-    State(state): State<AppState>,
-    claims: axum::extract::Extension<crate::models::user::Claims>,
+#[utoipa::path(
+    put,
+    path = "/api/users/me",
+    tag = "users",
+    request_body = UpdateUserRequest,
+    responses(
+        (status = 200, description = "The updated user", body = UserResponse),
+        (status = 400, description = "Invalid input", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 401, description = "Missing, invalid or revoked access token", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = 409, description = "Username or email already taken", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn update_current_user<R: Repository>(
+    State(state): State<AppState<R>>,
+    AuthUser { id: user_id, .. }: AuthUser,
     Json(request): Json<UpdateUserRequest>,
     ) -> Result<Json<UserResponse>> {
-    let user_id = Uuid::parse_str(&claims.sub)
-        .map_err(|_| AppError::Authentication("Invalid user ID in token".to_string()))?;
-
     let user = state.user_service.update_user(&user_id, request).await?;
     Ok(Json(user))
 }
 
-pub async fn delete_current_user(
-    State(state): State<AppState>,
-    claims: axum::extract::Extension<crate::models::user::Claims>,
+#[utoipa::path(
+    delete,
+    path = "/api/users/me",
+    tag = "users",
+    responses(
+        (status = 204, description = "User deleted, with their posts and comments"),
+        (status = 401, description = "Missing, invalid or revoked access token", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    security(("bearer_auth" = [])),
+)]
+pub async fn delete_current_user<R: Repository>(
+    State(state): State<AppState<R>>,
+    AuthUser { id: user_id, .. }: AuthUser,
     ) -> Result<StatusCode> {
-    let user_id = Uuid::parse_str(&claims.sub)
-        .map_err(|_| AppError::Authentication("Invalid user ID in token".to_string()))?;
-
     state.user_service.delete_user(&user_id).await?;
     Ok(StatusCode::NO_CONTENT)
 }
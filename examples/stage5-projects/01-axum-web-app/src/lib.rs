@@ -0,0 +1,17 @@
+pub mod config;
+pub mod error;
+pub mod extract;
+pub mod metrics;
+pub mod models;
+pub mod auth;
+pub mod database;
+pub mod repositories;
+pub mod search;
+pub mod services;
+pub mod handlers;
+pub mod openapi;
+pub mod rate_limit;
+pub mod request_id;
+pub mod routes;
+pub mod seed;
+pub mod state;
@@ -1,99 +1,285 @@
-mod config;
-mod error;
-mod models;
-mod auth;
-mod database;
-mod services;
-mod handlers;
-mod state;
-
-use axum::{
-    routing::{get, post},
-    Router,
-    http::Method,
-};
-use tower::ServiceBuilder;
-use tower_http::{
-    cors::{Any, CorsLayer},
-    trace::TraceLayer,
-    services::ServeDir,
-};
+use std::future::IntoFuture;
+use std::net::SocketAddr;
+
+use tower_http::services::ServeDir;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use config::Config;
-use database::DatabaseService;
-use auth::JwtService;
-use services::{UserService, PostService};
-use handlers::{
-    health_handlers::{health_check, readiness_check, liveness_check},
-    user_handlers::{register, login},
-    post_handlers::{get_post, get_posts},
+use axum_web_app::{
+    config::{Config, DatabaseBackend, Overrides},
+    database::{self, migrations::MigrationState, DatabaseService, Migrations},
+    auth::JwtService,
+    models::user::CreateUserRequest,
+    rate_limit::RateLimiter,
+    repositories::{InMemoryRepository, PostgresRepository, Repository, SqliteRepository},
+    routes::{cors_layer, create_router},
+    seed,
+    state::AppState,
 };
-use state::AppState;
+
+const USAGE: &str = "usage: axum-web-app [options] [command]
+
+commands:
+  serve [--migrate]                  start the server (the default); --migrate
+                                     first applies pending migrations
+  migrate up                         apply every pending migration
+  migrate down [<version>]           revert the latest migration, or every
+                                     migration after <version> (0 for all)
+  migrate status                     list migrations and whether they are applied
+  create-admin <username> <email>    create an admin account, or promote an
+                                     existing user; the password is read from
+                                     ADMIN_PASSWORD
+  seed                               add demo users, posts and comments to an
+                                     empty database; the password is read from
+                                     SEED_PASSWORD, or is `demo-password`
+
+options, which override the config file and environment variables:
+  --config <file>                    config file (default: CONFIG_FILE or ./config.toml)
+  --host <host>                      address to listen on
+  --port <port>                      port to listen on
+  --database-url <url>               postgres://..., sqlite:... or unset for in-memory
+  --log-level <level>                trace, debug, info, warn or error";
+
+const DEMO_PASSWORD: &str = "demo-password";
+
+enum Command {
+    Serve { migrate: bool },
+    Migrate(MigrateCommand),
+    CreateAdmin { username: String, email: String },
+    Seed,
+}
+
+enum MigrateCommand {
+    Up,
+    Down { target: Option<i64> },
+    Status,
+}
+
+fn parse_command(args: &[String]) -> Result<Command, String> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        [] | ["serve"] => Ok(Command::Serve { migrate: false }),
+        ["serve", "--migrate"] => Ok(Command::Serve { migrate: true }),
+        ["migrate", "up"] => Ok(Command::Migrate(MigrateCommand::Up)),
+        ["migrate", "down"] => Ok(Command::Migrate(MigrateCommand::Down { target: None })),
+        ["migrate", "down", target] => match target.parse::<i64>() {
+            Ok(target) if target >= 0 => Ok(Command::Migrate(MigrateCommand::Down { target: Some(target) })),
+            _ => Err(format!("migrate down: `{}` is not a migration version\n\n{}", target, USAGE)),
+        },
+        ["migrate", "status"] => Ok(Command::Migrate(MigrateCommand::Status)),
+        ["create-admin", username, email] => Ok(Command::CreateAdmin {
+            username: username.to_string(),
+            email: email.to_string(),
+        }),
+        ["seed"] => Ok(Command::Seed),
+        _ => Err(USAGE.to_string()),
+    }
+}
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Load configuration
-    let config = Config::from_env()?;
+async fn main() {
+    // Display rather than Debug, so that messages such as the usage keep
+    // their line breaks
+    if let Err(err) = start().await {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+async fn start() -> Result<(), Box<dyn std::error::Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (overrides, args) = Overrides::from_args(&args)?;
+    let command = parse_command(&args)?;
+
+    // Load configuration; list every problem rather than the first as a Debug dump
+    let config = match Config::load(&overrides) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        }
+    };
 
-    // Initialize logging
+    // Initialize logging; RUST_LOG takes precedence over the configured level
+    let level = &config.app.log_level;
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| format!("{}=debug,tower_http=debug", config.app.name).into()),
+                .unwrap_or_else(|_| format!("{}={},tower_http={}", env!("CARGO_CRATE_NAME"), level, level).into()),
         )
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    // Initialize database
-    let db_service = DatabaseService::new(&config).await?;
+    let jwt_service = JwtService::new(config.jwt_secret())
+        .with_expiry(config.jwt.expires_in, config.jwt.refresh_expires_in);
+
+    // The DATABASE_URL scheme picks the backend; without it everything stays in memory
+    match config.database_backend() {
+        DatabaseBackend::Postgres => {
+            let db_service = DatabaseService::new(&config).await?;
+            db_service.test_connection().await?;
+            if prepare_database(&command, &config, db_service.migrations()).await? {
+                return Ok(());
+            }
+
+            let repository = PostgresRepository::new(db_service.pool().clone());
+            run(command, &config, AppState::new(repository, jwt_service)).await
+        }
+        DatabaseBackend::Sqlite => {
+            let url = config.database_url().unwrap_or_default();
+            let pool = database::connect_sqlite(url).await?;
+            if prepare_database(&command, &config, Migrations::Sqlite(&pool)).await? {
+                return Ok(());
+            }
+
+            let repository = SqliteRepository::new(pool).await?;
+            run(command, &config, AppState::new(repository, jwt_service)).await
+        }
+        DatabaseBackend::InMemory => {
+            if let Command::Migrate(_) = command {
+                return Err("migrate needs a database; set DATABASE_URL or --database-url".into());
+            }
+            tracing::warn!("DATABASE_URL is not set, using the in-memory backend; data is lost on restart");
+            run(command, &config, AppState::new(InMemoryRepository::new(), jwt_service)).await
+        }
+    }
+}
 
-    // Test database connection
-    db_service.test_connection().await?;
+// Runs `migrate` commands, and otherwise makes sure the schema is up to date
+// before anything reads it. Returns true when the command is done.
+async fn prepare_database(
+    command: &Command,
+    config: &Config,
+    migrations: Migrations<'_>,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    if let Command::Migrate(command) = command {
+        migrate(command, &migrations).await?;
+        return Ok(true);
+    }
 
-    // Initialize services
-    let jwt_service = JwtService::new(config.jwt_secret());
-    let user_service = UserService::new(db_service.pool().clone(), jwt_service.clone());
-    let post_service = PostService::new(db_service.pool().clone());
+    let status = migrations.status().await?;
+    let unknown: Vec<String> = status
+        .iter()
+        .filter(|migration| migration.state == MigrationState::Unknown)
+        .map(|migration| migration.version.to_string())
+        .collect();
+    if !unknown.is_empty() {
+        return Err(format!(
+            "the database has migrations this version does not know about ({}); \
+             it was migrated by a newer version of the app",
+            unknown.join(", ")
+        )
+        .into());
+    }
 
-    // Build CORS layer
-    let cors = CorsLayer::new()
-        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE, Method::OPTIONS])
-        .allow_headers(Any)
-        .allow_origin(Any);
+    let pending: Vec<_> = status.iter().filter(|migration| migration.state == MigrationState::Pending).collect();
+    if pending.is_empty() {
+        return Ok(false);
+    }
 
-    // Create application state
-    let app_state = AppState {
-        user_service,
-        post_service,
-        jwt_service,
-    };
+    let auto_migrate = matches!(command, Command::Serve { migrate: true }) || config.database.auto_migrate;
+    if matches!(command, Command::Serve { .. }) && auto_migrate {
+        for migration in migrations.up().await? {
+            tracing::info!("applied migration {} {}", migration.version, migration.description);
+        }
+        return Ok(false);
+    }
 
-    // Build application router
-    let app = Router::new()
-        // Health checks
-        .route("/health", get(health_check))
-        .route("/ready", get(readiness_check))
-        .route("/live", get(liveness_check))
+    let names: Vec<String> =
+        pending.iter().map(|migration| format!("{} {}", migration.version, migration.description)).collect();
+    Err(format!(
+        "the database has {} pending migration(s): {}\n\
+         run `axum-web-app migrate up`, or start the server with `serve --migrate`",
+        pending.len(),
+        names.join(", ")
+    )
+    .into())
+}
+
+async fn migrate(command: &MigrateCommand, migrations: &Migrations<'_>) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        MigrateCommand::Up => {
+            let applied = migrations.up().await?;
+            if applied.is_empty() {
+                println!("no pending migrations");
+            }
+            for migration in applied {
+                println!("applied  {:03} {}", migration.version, migration.description);
+            }
+        }
+        MigrateCommand::Down { target } => {
+            let reverted = migrations.down(*target).await?;
+            if reverted.is_empty() {
+                println!("no migrations to revert");
+            }
+            for migration in reverted {
+                println!("reverted {:03} {}", migration.version, migration.description);
+            }
+        }
+        MigrateCommand::Status => {
+            for migration in migrations.status().await? {
+                let state = match migration.state {
+                    MigrationState::Applied => "applied",
+                    MigrationState::Pending => "pending",
+                    MigrationState::Unknown => "unknown",
+                };
+                println!("{:<8} {:03} {}", state, migration.version, migration.description);
+            }
+        }
+    }
+
+    Ok(())
+}
 
-        // Public routes
-        .route("/api/auth/register", post(register))
-        .route("/api/auth/login", post(login))
+async fn run<R: Repository>(
+    command: Command,
+    config: &Config,
+    app_state: AppState<R>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        Command::Serve { .. } => serve(config, app_state).await,
+        // Handled by `prepare_database`
+        Command::Migrate(_) => Ok(()),
+        Command::CreateAdmin { username, email } => {
+            let password = std::env::var("ADMIN_PASSWORD")
+                .map_err(|_| "ADMIN_PASSWORD must be set for create-admin")?;
+            let admin = app_state
+                .user_service
+                .create_admin(CreateUserRequest { username, email, password })
+                .await?;
+            println!("{} ({}) is now an admin", admin.username, admin.id);
+            Ok(())
+        }
+        Command::Seed => {
+            let password = std::env::var("SEED_PASSWORD").unwrap_or_else(|_| DEMO_PASSWORD.to_string());
+            let usernames: Vec<&str> = seed::DEMO_USERS.iter().map(|(username, _)| *username).collect();
+            match seed::seed(&app_state, &password).await? {
+                Some(summary) => println!(
+                    "created {} users, {} posts, {} comments and {} likes; sign in as {} with the seed password",
+                    summary.users,
+                    summary.posts,
+                    summary.comments,
+                    summary.likes,
+                    usernames.join(", "),
+                ),
+                None => println!("the demo users already exist; nothing to do"),
+            }
+            Ok(())
+        }
+    }
+}
 
-        // Public posts routes
-        .route("/api/posts", get(get_posts))
-        .route("/api/posts/:id", get(get_post))
+async fn serve<R: Repository>(config: &Config, app_state: AppState<R>) -> Result<(), Box<dyn std::error::Error>> {
+    // Rate limit state lives in this process
+    let app_state = app_state.with_rate_limiter(RateLimiter::in_memory(config.rate_limit.clone()));
 
+    // Build application router
+    let app = create_router(app_state)
         // Static files (for serving API documentation, etc.)
         .nest_service("/static", ServeDir::new("static"))
 
-        // Add middleware and state
-        .with_state(app_state.clone())
-        .layer(
-            ServiceBuilder::new()
-                .layer(TraceLayer::new_for_http())
-                .layer(cors),
-        )
+        // CORS from the configuration; request tracing is set up in
+        // `create_router`, with the request id
+        .layer(cors_layer(&config.cors))
         .fallback_service(ServeDir::new("static").append_index_html_on_directories(true));
 
     // Start server
@@ -103,48 +289,62 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing::info!("🏥 Health check available at http://{}/health", server_address);
 
     let listener = tokio::net::TcpListener::bind(&server_address).await?;
-    axum::serve(listener, app).await?;
 
+    // On SIGTERM or Ctrl-C stop accepting connections and let in-flight
+    // requests finish, but give up on them after the shutdown timeout
+    let (stopping, stopped) = tokio::sync::oneshot::channel();
+    let signal = async move {
+        shutdown_signal().await;
+        tracing::info!("shutting down, waiting for in-flight requests to finish");
+        let _ = stopping.send(());
+    };
+    let timeout = config.server.shutdown_timeout.to_std()?;
+    let deadline = async move {
+        match stopped.await {
+            Ok(()) => tokio::time::sleep(timeout).await,
+            Err(_) => std::future::pending().await,
+        }
+    };
 
+    // The peer address is what the per-IP rate limit counts by
+    let server = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(signal);
+    tokio::select! {
+        result = server.into_future() => result?,
+        _ = deadline => tracing::warn!("requests still running after {}s; exiting anyway", timeout.as_secs()),
+    }
+
+    tracing::info!("server stopped");
     Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use axum_test::TestServer;
-
-    #[tokio::test]
-    async fn test_health_check() {
-        let app = Router::new()
-            .route("/health", get(health_check));
-
-        let server = TestServer::new(app).unwrap();
-
-        let response = server.get("/health").await;
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.expect("failed to listen for Ctrl-C");
+    };
 
-        assert_eq!(response.status_code(), 200);
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
 
-        let body: serde_json::Value = response.json();
-        assert_eq!(body["status"], "healthy");
-        assert_eq!(body["service"], "axum-web-app");
-        assert_eq!(body["version"], "0.1.0");
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
     }
+}
 
-    #[tokio::test]
-    async fn test_readiness_check() {
-        let app = Router::new()
-            .route("/ready", get(readiness_check));
-
-        let server = TestServer::new(app).unwrap();
-
-        let response = server.get("/ready").await;
-
-        assert_eq!(response.status_code(), 200);
-
-        let body: serde_json::Value = response.json();
-        assert_eq!(body["status"], "ready");
-    }
+#[cfg(test)]
+mod tests {
+    use super::{parse_command, Command, MigrateCommand};
+    use axum::{routing::get, Router};
+    use axum_test::TestServer;
+    use axum_web_app::handlers::health_handlers::liveness_check;
 
     #[tokio::test]
     async fn test_liveness_check() {
@@ -160,4 +360,33 @@ mod tests {
         let body: serde_json::Value = response.json();
         assert_eq!(body["status"], "alive");
     }
+
+    #[test]
+    fn test_parse_command() {
+        let args = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+
+        assert!(matches!(parse_command(&args(&[])), Ok(Command::Serve { migrate: false })));
+        assert!(matches!(parse_command(&args(&["serve"])), Ok(Command::Serve { migrate: false })));
+        assert!(matches!(parse_command(&args(&["serve", "--migrate"])), Ok(Command::Serve { migrate: true })));
+        assert!(matches!(
+            parse_command(&args(&["create-admin", "root", "root@example.com"])),
+            Ok(Command::CreateAdmin { username, .. }) if username == "root"
+        ));
+        assert!(parse_command(&args(&["create-admin", "root"])).is_err());
+        assert!(parse_command(&args(&["serve", "--port"])).is_err());
+        assert!(matches!(parse_command(&args(&["seed"])), Ok(Command::Seed)));
+
+        assert!(matches!(parse_command(&args(&["migrate", "up"])), Ok(Command::Migrate(MigrateCommand::Up))));
+        assert!(matches!(parse_command(&args(&["migrate", "status"])), Ok(Command::Migrate(MigrateCommand::Status))));
+        assert!(matches!(
+            parse_command(&args(&["migrate", "down"])),
+            Ok(Command::Migrate(MigrateCommand::Down { target: None }))
+        ));
+        assert!(matches!(
+            parse_command(&args(&["migrate", "down", "3"])),
+            Ok(Command::Migrate(MigrateCommand::Down { target: Some(3) }))
+        ));
+        assert!(parse_command(&args(&["migrate", "down", "-1"])).is_err());
+        assert!(parse_command(&args(&["migrate"])).is_err());
+    }
 }
@@ -0,0 +1,145 @@
+use std::time::Instant;
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+
+use crate::repositories::{PoolStats, Repository};
+use crate::state::AppState;
+
+// The content type of the Prometheus text exposition format
+pub const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+// Requests that matched no route share one label, so that scanners probing
+// random paths cannot create a new time series per path
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+// Request counts and latencies by route and status, plus connection pool
+// usage. Each `AppState` has its own registry, so tests do not share counts.
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    latency: HistogramVec,
+    pool_connections: IntGaugeVec,
+    pool_max_connections: IntGauge,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let labels = ["method", "route", "status"];
+        let requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests handled, by route and response status"),
+            &labels,
+        )
+        .unwrap();
+        let latency = HistogramVec::new(
+            HistogramOpts::new("http_request_duration_seconds", "Time to produce a response, by route and status"),
+            &labels,
+        )
+        .unwrap();
+        let pool_connections = IntGaugeVec::new(
+            Opts::new("db_pool_connections", "Open database connections, by whether they are idle or in use"),
+            &["state"],
+        )
+        .unwrap();
+        let pool_max_connections =
+            IntGauge::new("db_pool_max_connections", "Most connections the database pool will open").unwrap();
+
+        let registry = Registry::new();
+        registry.register(Box::new(requests.clone())).unwrap();
+        registry.register(Box::new(latency.clone())).unwrap();
+        registry.register(Box::new(pool_connections.clone())).unwrap();
+        registry.register(Box::new(pool_max_connections.clone())).unwrap();
+
+        Self { registry, requests, latency, pool_connections, pool_max_connections }
+    }
+
+    pub fn observe_request(&self, method: &str, route: &str, status: u16, seconds: f64) {
+        let status = status.to_string();
+        let labels = [method, route, status.as_str()];
+        self.requests.with_label_values(&labels).inc();
+        self.latency.with_label_values(&labels).observe(seconds);
+    }
+
+    // Everything in the text format. Pool gauges are read at scrape time and
+    // left out for backends without a pool.
+    pub fn render(&self, pool: Option<PoolStats>) -> String {
+        if let Some(pool) = pool {
+            let idle = i64::from(pool.idle);
+            self.pool_connections.with_label_values(&["idle"]).set(idle);
+            self.pool_connections.with_label_values(&["active"]).set(i64::from(pool.size) - idle);
+            self.pool_max_connections.set(i64::from(pool.max));
+        }
+
+        let families: Vec<_> = self
+            .registry
+            .gather()
+            .into_iter()
+            .filter(|family| pool.is_some() || !family.get_name().starts_with("db_pool_"))
+            .collect();
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&families, &mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Records every response in `state.metrics`. Routes are labelled by their
+// pattern, e.g. `/api/posts/:id`, not by the path that was requested.
+pub async fn track_metrics<R: Repository>(
+    State(state): State<AppState<R>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or(UNMATCHED_ROUTE, MatchedPath::as_str)
+        .to_string();
+
+    let start = Instant::now();
+    let response = next.run(request).await;
+    let seconds = start.elapsed().as_secs_f64();
+    state.metrics.observe_request(&method, &route, response.status().as_u16(), seconds);
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_text_format() {
+        let metrics = Metrics::new();
+        metrics.observe_request("GET", "/api/posts/:id", 200, 0.02);
+        metrics.observe_request("GET", "/api/posts/:id", 200, 0.3);
+        metrics.observe_request("GET", "/api/posts/:id", 404, 0.01);
+
+        let text = metrics.render(None);
+        assert!(text.contains(r#"http_requests_total{method="GET",route="/api/posts/:id",status="200"} 2"#));
+        assert!(text.contains(r#"http_requests_total{method="GET",route="/api/posts/:id",status="404"} 1"#));
+        assert!(text.contains(
+            r#"http_request_duration_seconds_bucket{method="GET",route="/api/posts/:id",status="200",le="0.025"} 1"#
+        ));
+        assert!(text.contains("# TYPE http_request_duration_seconds histogram"));
+        assert!(!text.contains("db_pool"));
+
+        let text = metrics.render(Some(PoolStats { size: 4, idle: 1, max: 10 }));
+        assert!(text.contains(r#"db_pool_connections{state="active"} 3"#));
+        assert!(text.contains(r#"db_pool_connections{state="idle"} 1"#));
+        assert!(text.contains("db_pool_max_connections 10"));
+    }
+}
@@ -0,0 +1,101 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Comment {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    pub author_id: Uuid,
+    // The comment this one replies to; None for top-level comments
+    pub parent_id: Option<Uuid>,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct CreateCommentRequest {
+    #[validate(length(min = 1, max = 2000))]
+    #[schema(min_length = 1, max_length = 2000)]
+    pub content: String,
+
+    pub parent_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct CommentWithAuthor {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    pub author_id: Uuid,
+    pub author_username: String,
+    pub parent_id: Option<Uuid>,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// A comment with its replies, oldest first at every level
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CommentThread {
+    #[serde(flatten)]
+    pub comment: CommentWithAuthor,
+    pub replies: Vec<CommentThread>,
+}
+
+impl CommentThread {
+    // Nest a post's comments under their parents. Expects `comments` oldest
+    // first, as the repositories return them.
+    pub fn build(comments: Vec<CommentWithAuthor>) -> Vec<CommentThread> {
+        fn attach(parent_id: Option<Uuid>, comments: &[CommentWithAuthor]) -> Vec<CommentThread> {
+            comments
+                .iter()
+                .filter(|comment| comment.parent_id == parent_id)
+                .map(|comment| CommentThread {
+                    comment: comment.clone(),
+                    replies: attach(Some(comment.id), comments),
+                })
+                .collect()
+        }
+
+        attach(None, &comments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(content: &str, parent_id: Option<Uuid>) -> CommentWithAuthor {
+        let now = Utc::now();
+        CommentWithAuthor {
+            id: Uuid::new_v4(),
+            post_id: Uuid::nil(),
+            author_id: Uuid::nil(),
+            author_username: "alice".to_string(),
+            parent_id,
+            content: content.to_string(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_build_thread() {
+        let first = comment("first", None);
+        let reply = comment("reply", Some(first.id));
+        let nested = comment("nested", Some(reply.id));
+        let second = comment("second", None);
+
+        let threads = CommentThread::build(vec![first.clone(), reply, second, nested]);
+
+        let contents: Vec<&str> = threads.iter().map(|t| t.comment.content.as_str()).collect();
+        assert_eq!(contents, vec!["first", "second"]);
+        assert_eq!(threads[0].replies[0].comment.content, "reply");
+        assert_eq!(threads[0].replies[0].replies[0].comment.content, "nested");
+        assert!(threads[1].replies.is_empty());
+    }
+}
@@ -0,0 +1,100 @@
+use std::str::FromStr;
+
+use crate::error::AppError;
+
+// The value of an `If-Match` or `If-None-Match` header: `*`, matching any
+// current representation, or a list of entity tags such as `"3-ab12"` or
+// `W/"3-ab12"`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntityTags {
+    Any,
+    List(Vec<String>),
+}
+
+impl EntityTags {
+    // Strong comparison, as `If-Match` requires, of the key `key_of` takes
+    // from each tag rather than of the whole tag: weak tags and tags without
+    // a key never match
+    pub fn matches_by<K: PartialEq>(&self, key: &K, key_of: impl Fn(&str) -> Option<K>) -> bool {
+        match self {
+            EntityTags::Any => true,
+            EntityTags::List(tags) => tags.iter().any(|tag| !is_weak(tag) && key_of(tag).as_ref() == Some(key)),
+        }
+    }
+
+    // Weak comparison, as `If-None-Match` requires: only the quoted parts
+    // are compared
+    pub fn matches_weakly(&self, etag: &str) -> bool {
+        match self {
+            EntityTags::Any => true,
+            EntityTags::List(tags) => tags.iter().any(|tag| opaque(tag) == opaque(etag)),
+        }
+    }
+}
+
+fn is_weak(etag: &str) -> bool {
+    etag.starts_with("W/")
+}
+
+fn opaque(etag: &str) -> &str {
+    etag.strip_prefix("W/").unwrap_or(etag)
+}
+
+impl FromStr for EntityTags {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || AppError::Validation("Expected `*` or a list of quoted entity tags".to_string());
+        let separator = |c: char| c == ',' || c == ' ' || c == '\t';
+
+        if s.trim() == "*" {
+            return Ok(EntityTags::Any);
+        }
+
+        // Entity tags may contain commas, so the list is split on the quotes
+        let mut tags = Vec::new();
+        let mut rest = s.trim_start_matches(separator);
+        while !rest.is_empty() {
+            let prefix = if is_weak(rest) { 2 } else { 0 };
+            let quoted = rest[prefix..].strip_prefix('"').ok_or_else(invalid)?;
+            let end = prefix + 1 + quoted.find('"').ok_or_else(invalid)? + 1;
+
+            tags.push(rest[..end].to_string());
+            rest = &rest[end..];
+            if !rest.is_empty() && !rest.starts_with(separator) {
+                return Err(invalid());
+            }
+            rest = rest.trim_start_matches(separator);
+        }
+
+        if tags.is_empty() {
+            return Err(invalid());
+        }
+        Ok(EntityTags::List(tags))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_compare_entity_tags() {
+        assert_eq!("*".parse::<EntityTags>().unwrap(), EntityTags::Any);
+
+        let tags: EntityTags = r#""1-aa", W/"2-bb","a,b""#.parse().unwrap();
+        assert_eq!(tags, EntityTags::List(vec![r#""1-aa""#.into(), r#"W/"2-bb""#.into(), r#""a,b""#.into()]));
+
+        // If-Match compares strongly, If-None-Match weakly
+        let version = |tag: &str| tag.trim_matches('"').split_once('-').map(|(version, _)| version.to_string());
+        assert!(tags.matches_by(&"1".to_string(), version));
+        assert!(!tags.matches_by(&"2".to_string(), version));
+        assert!(tags.matches_weakly(r#""2-bb""#));
+        assert!(!tags.matches_weakly(r#""3-cc""#));
+        assert!(EntityTags::Any.matches_by(&"3".to_string(), version));
+
+        for invalid in ["", " , ", "1-aa", r#""1-aa"x"#, r#""unterminated"#, "W/1"] {
+            assert!(invalid.parse::<EntityTags>().is_err(), "{:?}", invalid);
+        }
+    }
+}
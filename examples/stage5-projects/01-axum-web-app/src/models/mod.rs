@@ -1,2 +1,8 @@
 pub mod user;
 pub mod post;
+pub mod token;
+pub mod comment;
+pub mod pagination;
+pub mod search;
+pub mod revision;
+pub mod etag;
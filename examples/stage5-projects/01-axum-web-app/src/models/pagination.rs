@@ -0,0 +1,270 @@
+use std::cmp::Ordering;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::models::post::PostWithAuthor;
+use crate::models::search::{ScoredPost, SearchHit};
+
+pub const DEFAULT_PAGE_SIZE: i64 = 20;
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+// Listing order. Every order ends with the post id so that posts sharing a
+// timestamp, like count or title still have a fixed position.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PostSort {
+    // created_at DESC, id DESC
+    #[default]
+    Newest,
+    // created_at ASC, id ASC
+    Oldest,
+    // like_count DESC, created_at DESC, id DESC
+    MostLiked,
+    // title ASC, id ASC, comparing titles byte by byte
+    Title,
+    // score DESC, id DESC; only for search results
+    Relevance,
+}
+
+impl PostSort {
+    // Compares two sort keys in listing order
+    pub fn compare(self, a: &PostCursor, b: &PostCursor) -> Ordering {
+        let newest = || b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id));
+        match self {
+            PostSort::Newest => newest(),
+            PostSort::Oldest => a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)),
+            PostSort::MostLiked => b.like_count.cmp(&a.like_count).then_with(newest),
+            PostSort::Title => a.title.cmp(&b.title).then(a.id.cmp(&b.id)),
+            PostSort::Relevance => {
+                let score = |cursor: &PostCursor| cursor.score.unwrap_or_default();
+                score(b).total_cmp(&score(a)).then(b.id.cmp(&a.id))
+            }
+        }
+    }
+}
+
+// The sort key of the last post on a page; the next page starts right after
+// it. Clients only see it encoded, as an opaque string.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PostCursor {
+    pub sort: PostSort,
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub like_count: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+}
+
+impl PostCursor {
+    pub fn new(sort: PostSort, post: &PostWithAuthor) -> Self {
+        Self {
+            sort,
+            created_at: post.created_at,
+            id: post.id,
+            like_count: (sort == PostSort::MostLiked).then_some(post.like_count),
+            title: (sort == PostSort::Title).then(|| post.title.clone()),
+            score: None,
+        }
+    }
+
+    // The cursor of a search result
+    pub fn scored(sort: PostSort, hit: &ScoredPost) -> Self {
+        Self {
+            score: (sort == PostSort::Relevance).then_some(hit.score),
+            ..Self::new(sort, &hit.post)
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self> {
+        let invalid = || AppError::Validation("Invalid cursor".to_string());
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+        let cursor: Self = serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+
+        let complete = match cursor.sort {
+            PostSort::MostLiked => cursor.like_count.is_some(),
+            PostSort::Title => cursor.title.is_some(),
+            PostSort::Relevance => cursor.score.is_some(),
+            PostSort::Newest | PostSort::Oldest => true,
+        };
+        if !complete {
+            return Err(invalid());
+        }
+
+        Ok(cursor)
+    }
+}
+
+// Query parameters shared by every post listing
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PageQuery {
+    pub cursor: Option<String>,
+
+    // Values outside 1..=MAX_PAGE_SIZE are clamped
+    #[param(minimum = 1, maximum = 100, default = 20)]
+    pub limit: Option<i64>,
+
+    // Newest first for listings, most relevant first for searches
+    pub sort: Option<PostSort>,
+
+    // Counting every match costs an extra query, so it is opt-in
+    #[serde(default)]
+    pub include_total: bool,
+}
+
+// A validated `PageQuery`
+#[derive(Debug, Clone)]
+pub struct PageRequest {
+    pub sort: PostSort,
+    pub after: Option<PostCursor>,
+    pub limit: i64,
+    pub include_total: bool,
+}
+
+impl PageRequest {
+    pub fn listing(query: PageQuery) -> Result<Self> {
+        if query.sort == Some(PostSort::Relevance) {
+            return Err(AppError::Validation("Sorting by relevance is only available when searching".to_string()));
+        }
+        Self::new(query, PostSort::Newest)
+    }
+
+    pub fn search(query: PageQuery) -> Result<Self> {
+        Self::new(query, PostSort::Relevance)
+    }
+
+    fn new(query: PageQuery, default_sort: PostSort) -> Result<Self> {
+        let sort = query.sort.unwrap_or(default_sort);
+        let after = query.cursor.as_deref().map(PostCursor::decode).transpose()?;
+        if let Some(after) = &after
+            && after.sort != sort
+        {
+            return Err(AppError::Validation("Cursor does not match the requested sort".to_string()));
+        }
+
+        Ok(Self {
+            sort,
+            after,
+            limit: query.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE),
+            include_total: query.include_total,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+#[aliases(PostPage = Page<PostWithAuthor>, SearchPage = Page<SearchHit>)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    // Pass back as `cursor` to get the next page; null on the last page
+    pub next_cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<i64>,
+}
+
+impl<T> Page<T> {
+    // `items` are fetched with one extra item beyond `limit`; if it is there,
+    // another page follows, starting after the last item kept
+    pub fn new(mut items: Vec<T>, limit: i64, cursor: impl Fn(&T) -> PostCursor, total: Option<i64>) -> Self {
+        let next_cursor = if items.len() as i64 > limit {
+            items.truncate(limit as usize);
+            items.last().map(|item| cursor(item).encode())
+        } else {
+            None
+        };
+
+        Self { items, next_cursor, total }
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page { items: self.items.into_iter().map(f).collect(), next_cursor: self.next_cursor, total: self.total }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn cursor(sort: PostSort, seconds: i64, like_count: i64, title: &str) -> PostCursor {
+        PostCursor {
+            sort,
+            created_at: DateTime::<Utc>::UNIX_EPOCH + Duration::seconds(seconds),
+            id: Uuid::new_v4(),
+            like_count: Some(like_count),
+            title: Some(title.to_string()),
+            score: Some(like_count as f64 / 10.0),
+        }
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let original = cursor(PostSort::Title, 42, 0, "Hello, 世界");
+        let encoded = original.encode();
+        assert!(encoded.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(PostCursor::decode(&encoded).unwrap(), original);
+
+        assert!(PostCursor::decode("not a cursor").is_err());
+        assert!(PostCursor::decode(&URL_SAFE_NO_PAD.encode("{}")).is_err());
+
+        let mut incomplete = cursor(PostSort::MostLiked, 0, 3, "");
+        incomplete.like_count = None;
+        assert!(PostCursor::decode(&incomplete.encode()).is_err());
+    }
+
+    #[test]
+    fn test_sort_order() {
+        let old = cursor(PostSort::Newest, 1, 5, "b");
+        let new = cursor(PostSort::Newest, 2, 1, "a");
+
+        assert_eq!(PostSort::Newest.compare(&new, &old), Ordering::Less);
+        assert_eq!(PostSort::Oldest.compare(&new, &old), Ordering::Greater);
+        assert_eq!(PostSort::MostLiked.compare(&old, &new), Ordering::Less);
+        assert_eq!(PostSort::Title.compare(&new, &old), Ordering::Less);
+        assert_eq!(PostSort::Relevance.compare(&old, &new), Ordering::Less);
+        assert_eq!(PostSort::Newest.compare(&old, &old), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_page_request_from_query() {
+        let page = PageRequest::listing(PageQuery::default()).unwrap();
+        assert_eq!((page.sort, page.limit), (PostSort::Newest, DEFAULT_PAGE_SIZE));
+        assert_eq!(PageRequest::search(PageQuery::default()).unwrap().sort, PostSort::Relevance);
+
+        let page = PageRequest::listing(PageQuery { limit: Some(10_000), ..Default::default() }).unwrap();
+        assert_eq!(page.limit, MAX_PAGE_SIZE);
+        let page = PageRequest::listing(PageQuery { limit: Some(-1), ..Default::default() }).unwrap();
+        assert_eq!(page.limit, 1);
+
+        let relevance = PageQuery { sort: Some(PostSort::Relevance), ..Default::default() };
+        assert!(PageRequest::listing(relevance).is_err());
+
+        let title_cursor = cursor(PostSort::Title, 0, 0, "a").encode();
+        let query = PageQuery { cursor: Some(title_cursor.clone()), sort: Some(PostSort::Title), ..Default::default() };
+        assert!(PageRequest::listing(query).unwrap().after.is_some());
+        let query = PageQuery { cursor: Some(title_cursor), ..Default::default() };
+        assert!(matches!(PageRequest::listing(query), Err(AppError::Validation(_))));
+    }
+
+    #[test]
+    fn test_page_from_overfetched_items() {
+        let posts: Vec<PostCursor> = (0..3).map(|i| cursor(PostSort::Newest, i, 0, "")).collect();
+        let page = Page::new(posts.clone(), 2, |c| c.clone(), None);
+        assert_eq!(page.items.len(), 2);
+        assert_eq!(PostCursor::decode(page.next_cursor.as_deref().unwrap()).unwrap(), posts[1]);
+
+        let page = Page::new(posts, 3, |c| c.clone(), Some(3));
+        assert!(page.next_cursor.is_none());
+    }
+}
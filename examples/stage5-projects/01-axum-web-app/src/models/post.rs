@@ -1,8 +1,92 @@
+use std::fmt;
+use std::str::FromStr;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use sqlx::FromRow;
+use utoipa::openapi::{ObjectBuilder, RefOr, Schema, SchemaType};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
-use validator::Validate;
+use validator::{Validate, ValidationError};
+
+use crate::error::AppError;
+
+pub const MAX_TAGS_PER_POST: usize = 10;
+pub const MAX_TAG_LENGTH: usize = 30;
+
+// Tags are compared after trimming and lowercasing, and may only contain
+// letters, digits and `-`
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+fn validate_tags(tags: &[String]) -> Result<(), ValidationError> {
+    if tags.len() > MAX_TAGS_PER_POST {
+        return Err(ValidationError::new("too_many_tags")
+            .with_message(format!("at most {} tags are allowed", MAX_TAGS_PER_POST).into()));
+    }
+
+    for tag in tags.iter().map(|tag| normalize_tag(tag)) {
+        let valid_chars = tag.chars().all(|c| c.is_alphanumeric() || c == '-');
+        if tag.is_empty() || tag.chars().count() > MAX_TAG_LENGTH || !valid_chars {
+            return Err(ValidationError::new("invalid_tag").with_message(
+                format!("tags must be 1 to {} letters, digits or `-`", MAX_TAG_LENGTH).into(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+// Only published posts are listed, searchable and visible to everyone;
+// drafts and archived posts are seen by their author and moderators
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PostStatus {
+    Draft,
+    #[default]
+    Published,
+    Archived,
+}
+
+impl PostStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PostStatus::Draft => "draft",
+            PostStatus::Published => "published",
+            PostStatus::Archived => "archived",
+        }
+    }
+}
+
+impl fmt::Display for PostStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PostStatus {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "draft" => Ok(PostStatus::Draft),
+            "published" => Ok(PostStatus::Published),
+            "archived" => Ok(PostStatus::Archived),
+            _ => Err(AppError::Validation(format!("Unknown post status: {}", s))),
+        }
+    }
+}
+
+// Lets `FromRow` read the TEXT status column
+impl TryFrom<String> for PostStatus {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Post {
@@ -10,72 +94,247 @@ pub struct Post {
     pub title: String,
     pub content: String,
     pub author_id: Uuid,
+    #[sqlx(try_from = "String")]
+    pub status: PostStatus,
+    // When the post was first published; kept when it is archived
+    pub published_at: Option<DateTime<Utc>>,
+    // Starts at 1 and goes up with every update; see `update_post`
+    pub version: i64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+impl Post {
+    pub fn set_status(&mut self, status: PostStatus, now: DateTime<Utc>) {
+        match status {
+            PostStatus::Draft => self.published_at = None,
+            PostStatus::Published => {
+                self.published_at.get_or_insert(now);
+            }
+            PostStatus::Archived => {}
+        }
+        self.status = status;
+    }
+}
+
+// The `schema` attributes repeat the validation rules for the OpenAPI document
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreatePostRequest {
     #[validate(length(min = 1, max = 200))]
+    #[schema(min_length = 1, max_length = 200)]
     pub title: String,
 
     #[validate(length(min = 1))]
+    #[schema(min_length = 1)]
     pub content: String,
+
+    #[serde(default)]
+    #[validate(custom(function = "validate_tags"))]
+    #[schema(max_items = 10, value_type = Vec<Tag>)]
+    pub tags: Vec<String>,
+
+    // Published unless given, as before posts had a status
+    #[serde(default)]
+    pub status: Option<PostStatus>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct UpdatePostRequest {
     #[validate(length(min = 1, max = 200))]
+    #[schema(min_length = 1, max_length = 200)]
     pub title: Option<String>,
 
     #[validate(length(min = 1))]
+    #[schema(min_length = 1)]
     pub content: Option<String>,
+
+    // Replaces all tags when set
+    #[validate(custom(function = "validate_tags"))]
+    #[schema(max_items = 10, value_type = Option<Vec<Tag>>)]
+    pub tags: Option<Vec<String>>,
+
+    pub status: Option<PostStatus>,
 }
 
-#[derive(Debug, Serialize)]
-pub struct PostResponse {
-    pub id: Uuid,
-    pub title: String,
-    pub content: String,
-    pub author_id: Uuid,
-    pub created_at: DateTime<Utc>,
-    pub updated_at: DateTime<Utc>,
+// Narrows the caller's own posts to one status
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PostStatusQuery {
+    pub status: Option<PostStatus>,
+}
+
+// A single tag as `validate_tags` accepts it, for the OpenAPI document
+pub struct Tag;
+
+impl<'s> ToSchema<'s> for Tag {
+    fn schema() -> (&'s str, RefOr<Schema>) {
+        let schema = ObjectBuilder::new()
+            .schema_type(SchemaType::String)
+            .description(Some("Letters, digits and `-`; stored lowercased"))
+            .min_length(Some(1))
+            .max_length(Some(MAX_TAG_LENGTH))
+            .example(Some("web-dev".into()));
+
+        ("Tag", schema.into())
+    }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
 pub struct PostWithAuthor {
     pub id: Uuid,
     pub title: String,
     pub content: String,
     pub author_id: Uuid,
     pub author_username: String,
+    #[sqlx(try_from = "String")]
+    #[schema(value_type = Vec<String>)]
+    pub tags: TagList,
+    #[sqlx(try_from = "String")]
+    pub status: PostStatus,
+    pub published_at: Option<DateTime<Utc>>,
+    pub version: i64,
+    pub like_count: i64,
+    pub comment_count: i64,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-impl From<Post> for PostResponse {
-    fn from(post: Post) -> Self {
-        Self {
-            id: post.id,
-            title: post.title,
-            content: post.content,
-            author_id: post.author_id,
-            created_at: post.created_at,
-            updated_at: post.updated_at,
-        }
+impl PostWithAuthor {
+    // A strong entity tag for the post as it is sent: the version, which
+    // every update bumps, and a digest of the representation, which also
+    // follows likes, comments and the author's username. If-Match only
+    // compares the version, so a like does not fail the author's next edit;
+    // If-None-Match compares the whole tag.
+    pub fn etag(&self) -> String {
+        let body = serde_json::to_vec(self).unwrap_or_default();
+        format!("\"{}-{}\"", self.version, hex::encode(&Sha256::digest(&body)[..8]))
     }
+
+    // The version an entity tag from `etag` was made for
+    pub fn etag_version(etag: &str) -> Option<i64> {
+        let (version, _) = etag.strip_prefix('"')?.split_once('-')?;
+        version.parse().ok()
+    }
+}
+
+// Tag names in alphabetical order. The SQL backends select them as one
+// comma-separated column, which is safe because tags cannot contain commas.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct TagList(pub Vec<String>);
+
+impl From<String> for TagList {
+    fn from(tags: String) -> Self {
+        Self(tags.split(',').filter(|tag| !tag.is_empty()).map(str::to_string).collect())
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LikeResponse {
+    pub post_id: Uuid,
+    pub liked: bool,
+    pub like_count: i64,
 }
 
 impl From<CreatePostRequest> for Post {
     fn from(request: CreatePostRequest) -> Self {
         let now = Utc::now();
-        Self {
+        let mut post = Self {
             id: Uuid::new_v4(),
             title: request.title,
             content: request.content,
             author_id: Uuid::new_v4(), // Will be set by the service layer
+            status: PostStatus::Draft,
+            published_at: None,
+            version: 1,
             created_at: now,
             updated_at: now,
+        };
+        post.set_status(request.status.unwrap_or_default(), now);
+        post
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|tag| tag.to_string()).collect()
+    }
+
+    #[test]
+    fn test_validate_tags() {
+        assert!(validate_tags(&tags(&["rust", " Web-Dev ", "axum2"])).is_ok());
+        assert!(validate_tags(&tags(&[""])).is_err());
+        assert!(validate_tags(&tags(&["a,b"])).is_err());
+        assert!(validate_tags(&tags(&["two words"])).is_err());
+        assert!(validate_tags(&tags(&[&"x".repeat(MAX_TAG_LENGTH + 1)])).is_err());
+        assert!(validate_tags(&vec!["tag".to_string(); MAX_TAGS_PER_POST + 1]).is_err());
+    }
+
+    #[test]
+    fn test_set_status_tracks_published_at() {
+        let mut post = Post::from(CreatePostRequest {
+            title: "Title".to_string(),
+            content: "Content".to_string(),
+            tags: vec![],
+            status: Some(PostStatus::Draft),
+        });
+        assert_eq!(post.published_at, None);
+
+        let first = Utc::now();
+        post.set_status(PostStatus::Published, first);
+        assert_eq!(post.published_at, Some(first));
+
+        // Archiving and republishing keep the first publication date
+        post.set_status(PostStatus::Archived, first + chrono::Duration::hours(1));
+        post.set_status(PostStatus::Published, first + chrono::Duration::hours(2));
+        assert_eq!(post.published_at, Some(first));
+
+        // Back to draft, it is unpublished
+        post.set_status(PostStatus::Draft, first + chrono::Duration::hours(3));
+        assert_eq!(post.published_at, None);
+        assert_eq!("archived".parse::<PostStatus>().unwrap(), PostStatus::Archived);
+        assert!("deleted".parse::<PostStatus>().is_err());
+    }
+
+    #[test]
+    fn test_tag_list_from_column() {
+        assert_eq!(TagList::from(String::new()), TagList(vec![]));
+        assert_eq!(TagList::from("axum,rust".to_string()), TagList(tags(&["axum", "rust"])));
+    }
+
+    #[test]
+    fn test_etag_carries_the_version() {
+        let now = Utc::now();
+        let post = PostWithAuthor {
+            id: Uuid::new_v4(),
+            title: "Title".to_string(),
+            content: "Content".to_string(),
+            author_id: Uuid::new_v4(),
+            author_username: "alice".to_string(),
+            tags: TagList(tags(&["rust"])),
+            status: PostStatus::Published,
+            published_at: Some(now),
+            version: 3,
+            like_count: 0,
+            comment_count: 0,
+            created_at: now,
+            updated_at: now,
+        };
+
+        let etag = post.etag();
+        assert!(etag.starts_with("\"3-") && etag.ends_with('"') && etag.len() == 20, "{}", etag);
+        assert_eq!(post.clone().etag(), etag);
+        assert_eq!(PostWithAuthor::etag_version(&etag), Some(3));
+        // A like changes the representation but not the version
+        let liked = PostWithAuthor { like_count: 1, ..post }.etag();
+        assert_ne!(liked, etag);
+        assert_eq!(PostWithAuthor::etag_version(&liked), Some(3));
+
+        for invalid in ["3-abc", "\"abc\"", "\"x-abc\"", "W/\"3-abc\""] {
+            assert_eq!(PostWithAuthor::etag_version(invalid), None, "{}", invalid);
         }
     }
 }
@@ -0,0 +1,167 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+// Beyond this many line pairs the diff gives up on finding common lines in
+// the changed middle and reports it as deleted and inserted
+const MAX_DIFF_CELLS: usize = 1_000_000;
+
+// The title and content of a post after one edit. Revisions are numbered
+// from 1, which is the post as it was created.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct PostRevision {
+    pub id: Uuid,
+    pub post_id: Uuid,
+    pub revision: i32,
+    pub title: String,
+    pub content: String,
+    // None once the editor's account is deleted
+    pub editor_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DiffQuery {
+    pub from: i32,
+    pub to: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffOp {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct DiffLine {
+    pub op: DiffOp,
+    pub text: String,
+}
+
+// Line diffs turning revision `from` into revision `to`
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RevisionDiff {
+    pub post_id: Uuid,
+    pub from: i32,
+    pub to: i32,
+    pub title: Vec<DiffLine>,
+    pub content: Vec<DiffLine>,
+}
+
+impl RevisionDiff {
+    pub fn new(from: &PostRevision, to: &PostRevision) -> Self {
+        Self {
+            post_id: to.post_id,
+            from: from.revision,
+            to: to.revision,
+            title: diff_lines(&from.title, &to.title),
+            content: diff_lines(&from.content, &to.content),
+        }
+    }
+}
+
+// A line diff from the longest common subsequence, with deletions before
+// insertions where lines were replaced
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let line = |op, text: &str| DiffLine { op, text: text.to_string() };
+
+    // Lines shared at both ends need no table
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (a, b) = (&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]);
+
+    let mut diff: Vec<DiffLine> = old[..prefix].iter().map(|text| line(DiffOp::Equal, text)).collect();
+    if (a.len() + 1) * (b.len() + 1) > MAX_DIFF_CELLS {
+        diff.extend(a.iter().map(|text| line(DiffOp::Delete, text)));
+        diff.extend(b.iter().map(|text| line(DiffOp::Insert, text)));
+    } else {
+        // lcs[i][j] is the length of the longest common subsequence of
+        // a[i..] and b[j..]
+        let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lcs[i][j] = if a[i] == b[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() || j < b.len() {
+            if i < a.len() && j < b.len() && a[i] == b[j] {
+                diff.push(line(DiffOp::Equal, a[i]));
+                i += 1;
+                j += 1;
+            } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+                diff.push(line(DiffOp::Delete, a[i]));
+                i += 1;
+            } else {
+                diff.push(line(DiffOp::Insert, b[j]));
+                j += 1;
+            }
+        }
+    }
+    diff.extend(old[old.len() - suffix..].iter().map(|text| line(DiffOp::Equal, text)));
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(diff: &[DiffLine]) -> Vec<String> {
+        diff.iter()
+            .map(|line| {
+                let sign = match line.op {
+                    DiffOp::Equal => ' ',
+                    DiffOp::Insert => '+',
+                    DiffOp::Delete => '-',
+                };
+                format!("{}{}", sign, line.text)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        assert!(diff_lines("", "").is_empty());
+        assert_eq!(render(&diff_lines("same", "same")), [" same"]);
+        assert_eq!(render(&diff_lines("", "new")), ["+new"]);
+
+        let old = "intro\nold line\nmiddle\nremoved\nend";
+        let new = "intro\nnew line\nmiddle\nend\nappended";
+        assert_eq!(
+            render(&diff_lines(old, new)),
+            [" intro", "-old line", "+new line", " middle", "-removed", " end", "+appended"]
+        );
+    }
+
+    #[test]
+    fn test_diff_lines_beyond_limit() {
+        // Too many changed lines for the table: everything in between is
+        // replaced, but the common ends are still found
+        let old: Vec<String> = (0..1500).map(|i| format!("old {}", i)).collect();
+        let new: Vec<String> = (0..1500).map(|i| format!("new {}", i)).collect();
+        let diff = diff_lines(
+            &format!("first\n{}\nlast", old.join("\n")),
+            &format!("first\n{}\nlast", new.join("\n")),
+        );
+
+        assert_eq!(diff.len(), 3002);
+        assert_eq!(diff[0].op, DiffOp::Equal);
+        assert!(diff[1..1501].iter().all(|line| line.op == DiffOp::Delete));
+        assert!(diff[1501..3001].iter().all(|line| line.op == DiffOp::Insert));
+        assert_eq!(diff[3001], DiffLine { op: DiffOp::Equal, text: "last".to_string() });
+    }
+}
@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::models::post::PostWithAuthor;
+use crate::search::highlight::{highlight, snippet};
+use crate::search::SearchQuery;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchParams {
+    // Words, "quoted phrases" and prefix* terms, all of which must match
+    pub q: String,
+
+    // Only posts by this user
+    pub author: Option<Uuid>,
+
+    // Only posts created in this range, both ends inclusive
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+// A search match as returned by the repositories; higher scores are better
+#[derive(Debug, Clone, FromRow)]
+pub struct ScoredPost {
+    #[sqlx(flatten)]
+    pub post: PostWithAuthor,
+    pub score: f64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub post: PostWithAuthor,
+    pub score: f64,
+    // HTML-escaped, with the matching words wrapped in <mark>
+    pub highlighted_title: String,
+    pub snippet: String,
+}
+
+impl SearchHit {
+    pub fn new(hit: ScoredPost, query: &SearchQuery) -> Self {
+        Self {
+            highlighted_title: highlight(&hit.post.title, query),
+            snippet: snippet(&hit.post.content, query),
+            post: hit.post,
+            score: hit.score,
+        }
+    }
+}
@@ -1,60 +1,134 @@
+use std::fmt;
+use std::str::FromStr;
+
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::error::AppError;
+use crate::models::token::TokenResponse;
+
+// What a user may do is decided by `auth::policy` from their role
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    #[default]
+    User,
+    Moderator,
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::User => "user",
+            Role::Moderator => "moderator",
+            Role::Admin => "admin",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Role {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "user" => Ok(Role::User),
+            "moderator" => Ok(Role::Moderator),
+            "admin" => Ok(Role::Admin),
+            _ => Err(AppError::Validation(format!("Unknown role: {}", s))),
+        }
+    }
+}
+
+// Lets `FromRow` read the TEXT role column
+impl TryFrom<String> for Role {
+    type Error = AppError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct User {
     pub id: Uuid,
     pub username: String,
     pub email: String,
     pub password_hash: String,
+    #[sqlx(try_from = "String")]
+    pub role: Role,
+    // Set while the user is banned
+    pub banned_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+// The `schema` attributes repeat the validation rules for the OpenAPI document
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct CreateUserRequest {
     #[validate(length(min = 3, max = 50))]
+    #[schema(min_length = 3, max_length = 50)]
     pub username: String,
 
     #[validate(email)]
+    #[schema(format = "email")]
     pub email: String,
 
     #[validate(length(min = 8))]
+    #[schema(min_length = 8, format = Password)]
     pub password: String,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct UpdateUserRequest {
     #[validate(length(min = 3, max = 50))]
+    #[schema(min_length = 3, max_length = 50)]
     pub username: Option<String>,
 
     #[validate(email)]
+    #[schema(format = "email")]
     pub email: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct LoginRequest {
     pub username: String,
+    #[schema(format = Password)]
     pub password: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct UserResponse {
     pub id: Uuid,
     pub username: String,
     pub email: String,
+    pub role: Role,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize)]
+// A user as seen by moderators and admins
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AdminUserResponse {
+    #[serde(flatten)]
+    pub user: UserResponse,
+    pub banned_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct LoginResponse {
-    pub access_token: String,
-    pub token_type: String,
-    pub expires_in: String,
+    #[serde(flatten)]
+    pub tokens: TokenResponse,
     pub user: UserResponse,
 }
 
@@ -64,18 +138,30 @@ impl From<User> for UserResponse {
             id: user.id,
             username: user.username,
             email: user.email,
+            role: user.role,
             created_at: user.created_at,
             updated_at: user.updated_at,
         }
     }
 }
 
+impl From<User> for AdminUserResponse {
+    fn from(user: User) -> Self {
+        Self {
+            banned_at: user.banned_at,
+            user: user.into(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String, // user id
     pub username: String,
+    pub role: Role,
     pub exp: usize, // expiration time
     pub iat: usize, // issued at
+    pub jti: String, // token id, checked against the revocation list
 }
 
 impl From<CreateUserRequest> for User {
@@ -86,6 +172,8 @@ impl From<CreateUserRequest> for User {
             username: request.username,
             email: request.email,
             password_hash: String::new(), // Will be set by the service layer
+            role: Role::User,
+            banned_at: None,
             created_at: now,
             updated_at: now,
         }
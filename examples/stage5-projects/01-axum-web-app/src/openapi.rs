@@ -0,0 +1,121 @@
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::error::{FieldError, ProblemDetails};
+use crate::handlers::{admin_handlers, comment_handlers, health_handlers, post_handlers, user_handlers};
+use crate::models::{
+    comment::{CommentThread, CommentWithAuthor, CreateCommentRequest},
+    pagination::{PostPage, PostSort, SearchPage},
+    post::{CreatePostRequest, LikeResponse, PostStatus, PostWithAuthor, Tag, UpdatePostRequest},
+    revision::{DiffLine, DiffOp, PostRevision, RevisionDiff},
+    search::SearchHit,
+    token::{LogoutRequest, RefreshRequest, TokenResponse},
+    user::{AdminUserResponse, CreateUserRequest, LoginRequest, LoginResponse, Role, UpdateUserRequest, UserResponse},
+};
+
+pub const SPEC_PATH: &str = "/api-docs/openapi.json";
+
+// The OpenAPI document, built from the `#[utoipa::path]` attributes on the
+// handlers and the `ToSchema` models. Every route in `routes.rs` must be
+// listed here; `test_openapi_spec_matches_routes` checks both directions.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Axum Web App API"),
+    paths(
+        health_handlers::health_check,
+        health_handlers::readiness_check,
+        health_handlers::liveness_check,
+        health_handlers::metrics,
+        user_handlers::register,
+        user_handlers::login,
+        user_handlers::refresh_token,
+        user_handlers::logout,
+        user_handlers::get_user,
+        user_handlers::get_current_user,
+        user_handlers::update_current_user,
+        user_handlers::delete_current_user,
+        post_handlers::get_posts,
+        post_handlers::create_post,
+        post_handlers::search_posts,
+        post_handlers::get_post,
+        post_handlers::update_post,
+        post_handlers::delete_post,
+        post_handlers::like_post,
+        post_handlers::unlike_post,
+        post_handlers::get_user_posts,
+        post_handlers::get_current_user_posts,
+        post_handlers::get_tag_posts,
+        post_handlers::get_post_revisions,
+        post_handlers::diff_post_revisions,
+        post_handlers::restore_post_revision,
+        comment_handlers::get_comments,
+        comment_handlers::create_comment,
+        comment_handlers::delete_comment,
+        admin_handlers::list_users,
+        admin_handlers::ban_user,
+        admin_handlers::unban_user,
+        admin_handlers::delete_any_post,
+    ),
+    components(schemas(
+        ProblemDetails,
+        FieldError,
+        Role,
+        CreateUserRequest,
+        UpdateUserRequest,
+        LoginRequest,
+        LoginResponse,
+        UserResponse,
+        AdminUserResponse,
+        RefreshRequest,
+        LogoutRequest,
+        TokenResponse,
+        CreatePostRequest,
+        UpdatePostRequest,
+        PostStatus,
+        Tag,
+        PostWithAuthor,
+        PostRevision,
+        RevisionDiff,
+        DiffLine,
+        DiffOp,
+        LikeResponse,
+        PostSort,
+        PostPage,
+        SearchPage,
+        SearchHit,
+        CreateCommentRequest,
+        CommentWithAuthor,
+        CommentThread,
+    )),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "health", description = "Liveness and readiness probes"),
+        (name = "auth", description = "Registration, login and tokens"),
+        (name = "users", description = "User profiles"),
+        (name = "posts", description = "Posts, listings, search, likes and revisions"),
+        (name = "comments", description = "Threaded comments on posts"),
+        (name = "admin", description = "Moderation, for moderators and admins"),
+    )
+)]
+pub struct ApiDoc;
+
+// Protected operations refer to this scheme with `security(("bearer_auth" = []))`
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).bearer_format("JWT").build()),
+        );
+    }
+}
+
+// Swagger UI under /api-docs, reading the document from `SPEC_PATH`
+pub fn swagger_ui() -> SwaggerUi {
+    SwaggerUi::new("/api-docs").url(SPEC_PATH, ApiDoc::openapi())
+}
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Duration, Utc};
+use futures::future::{self, BoxFuture};
+
+use crate::config::BucketConfig;
+use crate::error::Result;
+use crate::rate_limit::{LockoutPolicy, RateLimitStore};
+
+// Above this many entries, state that no longer matters is dropped, so that
+// requests from many addresses or usernames cannot grow the maps forever
+const PRUNE_THRESHOLD: usize = 10_000;
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Failures {
+    count: u32,
+    last_failure: DateTime<Utc>,
+    locked_until: Option<DateTime<Utc>>,
+}
+
+// Rate limit state kept in this process; each instance of the app counts on
+// its own and everything is forgotten on restart
+#[derive(Debug, Default)]
+pub struct InMemoryRateLimitStore {
+    buckets: Mutex<HashMap<String, Bucket>>,
+    failures: Mutex<HashMap<String, Failures>>,
+}
+
+impl InMemoryRateLimitStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn acquire_now(&self, key: &str, config: BucketConfig, now: DateTime<Utc>) -> Option<Duration> {
+        let mut buckets = self.buckets.lock().unwrap();
+        let per_second = f64::from(config.per_minute) / 60.0;
+        let burst = f64::from(config.burst);
+        let refill = |bucket: &Bucket| {
+            let elapsed = (now - bucket.updated_at).num_milliseconds().max(0) as f64 / 1000.0;
+            (bucket.tokens + elapsed * per_second).min(burst)
+        };
+
+        if buckets.len() > PRUNE_THRESHOLD {
+            // A full bucket behaves exactly like a missing one
+            buckets.retain(|_, bucket| refill(bucket) < burst);
+        }
+
+        let bucket = buckets.entry(key.to_string()).or_insert(Bucket { tokens: burst, updated_at: now });
+        bucket.tokens = refill(bucket);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            None
+        } else {
+            let wait = (1.0 - bucket.tokens) / per_second;
+            Some(Duration::milliseconds((wait * 1000.0).ceil() as i64))
+        }
+    }
+
+    fn locked_for_now(&self, account: &str, now: DateTime<Utc>) -> Option<Duration> {
+        let failures = self.failures.lock().unwrap();
+        let locked_until = failures.get(account)?.locked_until?;
+        (locked_until > now).then(|| locked_until - now)
+    }
+
+    fn record_failure_now(&self, account: &str, policy: LockoutPolicy, now: DateTime<Utc>) -> Option<Duration> {
+        let mut failures = self.failures.lock().unwrap();
+        // A streak of failures ends after a quiet period as long as the longest lockout
+        let expired = |entry: &Failures| now - entry.last_failure > policy.max;
+
+        if failures.len() > PRUNE_THRESHOLD {
+            failures.retain(|_, entry| !expired(entry));
+        }
+
+        let entry = failures
+            .entry(account.to_string())
+            .or_insert(Failures { count: 0, last_failure: now, locked_until: None });
+        if expired(entry) {
+            entry.count = 0;
+        }
+        entry.count = entry.count.saturating_add(1);
+        entry.last_failure = now;
+
+        let lockout = policy.lockout(entry.count)?;
+        entry.locked_until = Some(now + lockout);
+        Some(lockout)
+    }
+}
+
+impl RateLimitStore for InMemoryRateLimitStore {
+    fn acquire<'a>(
+        &'a self,
+        key: &'a str,
+        bucket: BucketConfig,
+        now: DateTime<Utc>,
+    ) -> BoxFuture<'a, Result<Option<Duration>>> {
+        Box::pin(future::ready(Ok(self.acquire_now(key, bucket, now))))
+    }
+
+    fn locked_for<'a>(&'a self, account: &'a str, now: DateTime<Utc>) -> BoxFuture<'a, Result<Option<Duration>>> {
+        Box::pin(future::ready(Ok(self.locked_for_now(account, now))))
+    }
+
+    fn record_failure<'a>(
+        &'a self,
+        account: &'a str,
+        policy: LockoutPolicy,
+        now: DateTime<Utc>,
+    ) -> BoxFuture<'a, Result<Option<Duration>>> {
+        Box::pin(future::ready(Ok(self.record_failure_now(account, policy, now))))
+    }
+
+    fn clear_failures<'a>(&'a self, account: &'a str) -> BoxFuture<'a, Result<()>> {
+        self.failures.lock().unwrap().remove(account);
+        Box::pin(future::ready(Ok(())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_allows_burst_then_refills() {
+        let store = InMemoryRateLimitStore::new();
+        let config = BucketConfig { burst: 3, per_minute: 6 };
+        let start = Utc::now();
+
+        for _ in 0..3 {
+            assert_eq!(store.acquire_now("ip:1", config, start), None);
+        }
+        assert_eq!(store.acquire_now("ip:1", config, start), Some(Duration::seconds(10)));
+        assert_eq!(store.acquire_now("ip:2", config, start), None);
+
+        // One token every 10 seconds
+        assert_eq!(store.acquire_now("ip:1", config, start + Duration::seconds(4)), Some(Duration::seconds(6)));
+        assert_eq!(store.acquire_now("ip:1", config, start + Duration::seconds(10)), None);
+        assert!(store.acquire_now("ip:1", config, start + Duration::seconds(11)).is_some());
+
+        // Never more than the burst, however long the bucket sat idle
+        let later = start + Duration::hours(1);
+        for _ in 0..3 {
+            assert_eq!(store.acquire_now("ip:1", config, later), None);
+        }
+        assert!(store.acquire_now("ip:1", config, later).is_some());
+    }
+
+    #[test]
+    fn test_failures_lock_with_backoff_and_expire() {
+        let store = InMemoryRateLimitStore::new();
+        let policy = LockoutPolicy { threshold: 2, base: Duration::seconds(30), max: Duration::minutes(10) };
+        let start = Utc::now();
+
+        assert_eq!(store.record_failure_now("alice", policy, start), None);
+        assert_eq!(store.locked_for_now("alice", start), None);
+        assert_eq!(store.record_failure_now("alice", policy, start), Some(Duration::seconds(30)));
+        assert_eq!(store.locked_for_now("alice", start + Duration::seconds(10)), Some(Duration::seconds(20)));
+        assert_eq!(store.locked_for_now("alice", start + Duration::seconds(30)), None);
+
+        let next = start + Duration::seconds(40);
+        assert_eq!(store.record_failure_now("alice", policy, next), Some(Duration::seconds(60)));
+
+        // After a quiet period the count starts over
+        let much_later = next + Duration::minutes(11);
+        assert_eq!(store.record_failure_now("alice", policy, much_later), None);
+
+        store.record_failure_now("bob", policy, start);
+        assert_eq!(store.record_failure_now("bob", policy, start), Some(Duration::seconds(30)));
+        store.failures.lock().unwrap().remove("bob");
+        assert_eq!(store.locked_for_now("bob", start), None);
+    }
+}
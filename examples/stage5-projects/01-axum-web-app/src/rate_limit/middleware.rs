@@ -0,0 +1,102 @@
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{ConnectInfo, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+};
+use serde::Deserialize;
+
+use crate::error::AppError;
+use crate::repositories::Repository;
+use crate::state::AppState;
+
+// Auth request bodies are small; anything bigger is not worth buffering
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+#[derive(Deserialize)]
+struct Credentials {
+    username: Option<String>,
+}
+
+// Limits requests per client IP and, for bodies with a `username`, per
+// account. The body is buffered to read the username and handed on intact.
+pub async fn rate_limit_middleware<R: Repository>(
+    State(state): State<AppState<R>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let limiter = &state.rate_limiter;
+    if !limiter.config().enabled {
+        return Ok(next.run(request).await);
+    }
+
+    if let Some(ip) = client_ip(&request, limiter.config().trust_forwarded_for) {
+        limiter.check_ip(ip).await?;
+    }
+
+    let (parts, body) = request.into_parts();
+    let bytes = to_bytes(body, MAX_BODY_BYTES).await.map_err(|_| AppError::Rejected {
+        status: StatusCode::PAYLOAD_TOO_LARGE,
+        message: "Request body is too large".to_string(),
+    })?;
+    if let Ok(Credentials { username: Some(username) }) = serde_json::from_slice(&bytes) {
+        limiter.check_account(&username).await?;
+    }
+
+    Ok(next.run(Request::from_parts(parts, Body::from(bytes))).await)
+}
+
+// The peer address, which needs the server to be started with
+// `into_make_service_with_connect_info`, or the first X-Forwarded-For entry
+// when the proxy in front is trusted to set it. None when neither is known,
+// in which case only the per-account limit applies.
+fn client_ip(request: &Request, trust_forwarded_for: bool) -> Option<IpAddr> {
+    let forwarded = || {
+        request
+            .headers()
+            .get("x-forwarded-for")?
+            .to_str()
+            .ok()?
+            .split(',')
+            .next()?
+            .trim()
+            .parse()
+            .ok()
+    };
+    let peer = || request.extensions().get::<ConnectInfo<SocketAddr>>().map(|info| info.0.ip());
+
+    if trust_forwarded_for {
+        forwarded().or_else(peer)
+    } else {
+        peer()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_ip() {
+        let request = |forwarded: Option<&str>| {
+            let mut builder = Request::builder();
+            if let Some(forwarded) = forwarded {
+                builder = builder.header("x-forwarded-for", forwarded);
+            }
+            let mut request = builder.body(Body::empty()).unwrap();
+            request.extensions_mut().insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 4000))));
+            request
+        };
+        let peer: IpAddr = [10, 0, 0, 1].into();
+        let client: IpAddr = [203, 0, 113, 7].into();
+
+        assert_eq!(client_ip(&request(Some("203.0.113.7, 10.0.0.1")), true), Some(client));
+        assert_eq!(client_ip(&request(Some("203.0.113.7")), false), Some(peer));
+        assert_eq!(client_ip(&request(Some("garbage")), true), Some(peer));
+        assert_eq!(client_ip(&request(None), true), Some(peer));
+        assert_eq!(client_ip(&Request::new(Body::empty()), false), None);
+    }
+}
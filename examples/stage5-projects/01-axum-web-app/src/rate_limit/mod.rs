@@ -0,0 +1,202 @@
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use futures::future::BoxFuture;
+
+use crate::config::{BucketConfig, RateLimitConfig};
+use crate::error::{AppError, Result};
+
+pub mod memory;
+pub mod middleware;
+
+pub use memory::InMemoryRateLimitStore;
+pub use middleware::rate_limit_middleware;
+
+// How long an account is locked after failed logins: nothing below the
+// threshold, then `base`, doubling with each further failure up to `max`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LockoutPolicy {
+    pub threshold: u32,
+    pub base: Duration,
+    pub max: Duration,
+}
+
+impl LockoutPolicy {
+    pub fn lockout(&self, failures: u32) -> Option<Duration> {
+        let doublings = failures.checked_sub(self.threshold)?;
+        let factor = 2i32.checked_pow(doublings).unwrap_or(i32::MAX);
+        Some(self.base.checked_mul(factor).map_or(self.max, |lockout| lockout.min(self.max)))
+    }
+}
+
+// Where bucket and lockout state lives. `InMemoryRateLimitStore` keeps it in
+// the process; when several instances run behind a load balancer, a shared
+// store (Redis, the database) implementing this trait makes the limits apply
+// across all of them. Methods return boxed futures so that the store can be
+// picked at runtime as an `Arc<dyn RateLimitStore>`.
+pub trait RateLimitStore: Send + Sync {
+    // Takes a token from the bucket `key`. None if there was one, otherwise
+    // how long until the next token.
+    fn acquire<'a>(
+        &'a self,
+        key: &'a str,
+        bucket: BucketConfig,
+        now: DateTime<Utc>,
+    ) -> BoxFuture<'a, Result<Option<Duration>>>;
+
+    // How much longer `account` is locked out, if it is
+    fn locked_for<'a>(&'a self, account: &'a str, now: DateTime<Utc>) -> BoxFuture<'a, Result<Option<Duration>>>;
+
+    // Counts a failed login and returns the lockout it starts, if any
+    fn record_failure<'a>(
+        &'a self,
+        account: &'a str,
+        policy: LockoutPolicy,
+        now: DateTime<Utc>,
+    ) -> BoxFuture<'a, Result<Option<Duration>>>;
+
+    // Forgets the failures of `account` after a successful login
+    fn clear_failures<'a>(&'a self, account: &'a str) -> BoxFuture<'a, Result<()>>;
+}
+
+// Applies `RateLimitConfig` using a `RateLimitStore`. Everything is a no-op
+// when rate limiting is disabled.
+#[derive(Clone)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    store: Arc<dyn RateLimitStore>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig, store: Arc<dyn RateLimitStore>) -> Self {
+        Self { config, store }
+    }
+
+    pub fn in_memory(config: RateLimitConfig) -> Self {
+        Self::new(config, Arc::new(InMemoryRateLimitStore::new()))
+    }
+
+    pub fn config(&self) -> &RateLimitConfig {
+        &self.config
+    }
+
+    pub async fn check_ip(&self, ip: IpAddr) -> Result<()> {
+        self.acquire(&format!("ip:{}", ip), self.config.per_ip, "Too many requests from this address")
+            .await
+    }
+
+    pub async fn check_account(&self, username: &str) -> Result<()> {
+        let key = format!("account:{}", account_key(username));
+        self.acquire(&key, self.config.per_account, "Too many attempts for this account").await
+    }
+
+    // Fails while the account is locked out, before any password is checked
+    pub async fn check_lockout(&self, username: &str) -> Result<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        match self.store.locked_for(&account_key(username), Utc::now()).await? {
+            Some(retry_after) => Err(AppError::RateLimited {
+                message: "Account is temporarily locked after repeated failed logins".to_string(),
+                retry_after,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    pub async fn login_failed(&self, username: &str) -> Result<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        let policy = LockoutPolicy {
+            threshold: self.config.lockout_threshold,
+            base: self.config.lockout_base,
+            max: self.config.lockout_max,
+        };
+        if let Some(lockout) = self.store.record_failure(&account_key(username), policy, Utc::now()).await? {
+            tracing::warn!("Locking account {} for {}s after failed logins", username, lockout.num_seconds());
+        }
+
+        Ok(())
+    }
+
+    pub async fn login_succeeded(&self, username: &str) -> Result<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        self.store.clear_failures(&account_key(username)).await
+    }
+
+    async fn acquire(&self, key: &str, bucket: BucketConfig, message: &str) -> Result<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        match self.store.acquire(key, bucket, Utc::now()).await? {
+            Some(retry_after) => Err(AppError::RateLimited { message: message.to_string(), retry_after }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::in_memory(RateLimitConfig::default())
+    }
+}
+
+// Usernames differing only in case share limits, so that changing the case
+// does not buy an attacker fresh attempts
+fn account_key(username: &str) -> String {
+    username.trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lockout_doubles_up_to_max() {
+        let policy = LockoutPolicy { threshold: 3, base: Duration::seconds(30), max: Duration::minutes(5) };
+
+        assert_eq!(policy.lockout(2), None);
+        assert_eq!(policy.lockout(3), Some(Duration::seconds(30)));
+        assert_eq!(policy.lockout(4), Some(Duration::seconds(60)));
+        assert_eq!(policy.lockout(6), Some(Duration::seconds(240)));
+        assert_eq!(policy.lockout(7), Some(Duration::minutes(5)));
+        assert_eq!(policy.lockout(u32::MAX), Some(Duration::minutes(5)));
+    }
+
+    #[tokio::test]
+    async fn test_limits_are_per_account_and_case_insensitive() {
+        let config = RateLimitConfig {
+            per_account: BucketConfig { burst: 2, per_minute: 1 },
+            lockout_threshold: 2,
+            ..RateLimitConfig::default()
+        };
+        let limiter = RateLimiter::in_memory(config.clone());
+
+        limiter.check_account("Alice").await.unwrap();
+        limiter.check_account("alice").await.unwrap();
+        assert!(matches!(limiter.check_account("ALICE").await, Err(AppError::RateLimited { .. })));
+        limiter.check_account("bob").await.unwrap();
+
+        limiter.login_failed("alice").await.unwrap();
+        limiter.check_lockout("alice").await.unwrap();
+        limiter.login_failed("Alice").await.unwrap();
+        assert!(limiter.check_lockout("alice").await.is_err());
+        limiter.check_lockout("bob").await.unwrap();
+
+        // Disabled limits never trigger
+        let limiter = RateLimiter::in_memory(RateLimitConfig { enabled: false, ..config });
+        for _ in 0..5 {
+            limiter.check_account("alice").await.unwrap();
+            limiter.login_failed("alice").await.unwrap();
+        }
+        limiter.check_lockout("alice").await.unwrap();
+    }
+}
@@ -0,0 +1,645 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::models::comment::{Comment, CommentWithAuthor};
+use crate::models::pagination::{PostCursor, PostSort};
+use crate::models::post::{Post, PostStatus, PostWithAuthor, TagList};
+use crate::models::revision::PostRevision;
+use crate::models::search::ScoredPost;
+use crate::models::token::RefreshToken;
+use crate::models::user::{Role, UpdateUserRequest, User};
+use crate::repositories::{
+    CommentRepository, PoolStats, PostFilter, PostRepository, Repository, TokenRepository, UserRepository,
+};
+use crate::search::{PostSearch, SearchIndex};
+
+#[derive(Default)]
+struct Store {
+    users: HashMap<Uuid, User>,
+    posts: HashMap<Uuid, Post>,
+    // Oldest first
+    revisions: HashMap<Uuid, Vec<PostRevision>>,
+    comments: HashMap<Uuid, Comment>,
+    post_tags: HashMap<Uuid, BTreeSet<String>>,
+    // (post id, user id)
+    likes: HashSet<(Uuid, Uuid)>,
+    refresh_tokens: HashMap<Uuid, RefreshToken>,
+    // jti -> expiry of the revoked access token
+    revoked_access_tokens: HashMap<String, DateTime<Utc>>,
+    search_index: SearchIndex,
+}
+
+impl Store {
+    fn with_author(&self, post: &Post) -> PostWithAuthor {
+        PostWithAuthor {
+            id: post.id,
+            title: post.title.clone(),
+            content: post.content.clone(),
+            author_id: post.author_id,
+            author_username: self.username(&post.author_id),
+            tags: TagList(self.post_tags.get(&post.id).into_iter().flatten().cloned().collect()),
+            status: post.status,
+            published_at: post.published_at,
+            version: post.version,
+            like_count: self.likes.iter().filter(|(liked_post, _)| *liked_post == post.id).count() as i64,
+            comment_count: self.comments.values().filter(|c| c.post_id == post.id).count() as i64,
+            created_at: post.created_at,
+            updated_at: post.updated_at,
+        }
+    }
+
+    fn comment_with_author(&self, comment: &Comment) -> CommentWithAuthor {
+        CommentWithAuthor {
+            id: comment.id,
+            post_id: comment.post_id,
+            author_id: comment.author_id,
+            author_username: self.username(&comment.author_id),
+            parent_id: comment.parent_id,
+            content: comment.content.clone(),
+            created_at: comment.created_at,
+            updated_at: comment.updated_at,
+        }
+    }
+
+    fn username(&self, user_id: &Uuid) -> String {
+        self.users.get(user_id).map(|u| u.username.clone()).unwrap_or_default()
+    }
+
+    // The in-memory equivalents of the ON DELETE CASCADE foreign keys
+
+    fn remove_post(&mut self, post_id: &Uuid) -> bool {
+        if self.posts.remove(post_id).is_none() {
+            return false;
+        }
+        self.search_index.remove(post_id);
+        self.revisions.remove(post_id);
+        self.comments.retain(|_, comment| comment.post_id != *post_id);
+        self.post_tags.remove(post_id);
+        self.likes.retain(|(liked_post, _)| liked_post != post_id);
+        true
+    }
+
+    // Remove replies whose parent is gone, level by level
+    fn remove_orphaned_replies(&mut self) {
+        loop {
+            let orphans: Vec<Uuid> = self
+                .comments
+                .values()
+                .filter(|c| c.parent_id.is_some_and(|parent| !self.comments.contains_key(&parent)))
+                .map(|c| c.id)
+                .collect();
+            if orphans.is_empty() {
+                break;
+            }
+            for id in orphans {
+                self.comments.remove(&id);
+            }
+        }
+    }
+
+    // Keeps the search index to the published posts
+    fn index_post(&mut self, post: &Post) {
+        if post.status == PostStatus::Published {
+            self.search_index.insert(post);
+        } else {
+            self.search_index.remove(&post.id);
+        }
+    }
+
+    fn add_revision(&mut self, post: &Post, editor_id: &Uuid, created_at: DateTime<Utc>) {
+        let revisions = self.revisions.entry(post.id).or_default();
+        revisions.push(PostRevision {
+            id: Uuid::new_v4(),
+            post_id: post.id,
+            revision: revisions.len() as i32 + 1,
+            title: post.title.clone(),
+            content: post.content.clone(),
+            editor_id: Some(*editor_id),
+            created_at,
+        });
+    }
+
+    fn matches(&self, post: &Post, filter: &PostFilter) -> bool {
+        filter.author_id.is_none_or(|author_id| post.author_id == author_id)
+            && filter.tag.as_ref().is_none_or(|tag| self.post_tags.get(&post.id).is_some_and(|tags| tags.contains(tag)))
+            && filter.status.is_none_or(|status| post.status == status)
+    }
+}
+
+// One page of `items` in `sort` order, like the keyset queries of the SQL backends
+fn page<T>(mut items: Vec<(PostCursor, T)>, sort: PostSort, after: Option<&PostCursor>, limit: i64) -> Vec<T> {
+    items.retain(|(key, _)| after.is_none_or(|after| sort.compare(key, after).is_gt()));
+    items.sort_by(|(a, _), (b, _)| sort.compare(a, b));
+    items.into_iter().take(limit.max(0) as usize).map(|(_, item)| item).collect()
+}
+
+// In-process storage for tests and for running without a database.
+// Data lives as long as the process; clones share the same store.
+#[derive(Clone, Default)]
+pub struct InMemoryRepository {
+    store: Arc<RwLock<Store>>,
+}
+
+impl InMemoryRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Store> {
+        self.store.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, Store> {
+        self.store.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl UserRepository for InMemoryRepository {
+    async fn create_user(&self, user: User) -> Result<User> {
+        let mut store = self.write();
+        // Mirror the UNIQUE constraints on the users table
+        if store.users.values().any(|u| u.username == user.username || u.email == user.email) {
+            return Err(AppError::Conflict("Username or email already exists".to_string()));
+        }
+        store.users.insert(user.id, user.clone());
+        Ok(user)
+    }
+
+    async fn find_user(&self, user_id: &Uuid) -> Result<Option<User>> {
+        Ok(self.read().users.get(user_id).cloned())
+    }
+
+    async fn find_user_by_username(&self, username: &str) -> Result<Option<User>> {
+        Ok(self.read().users.values().find(|u| u.username == username).cloned())
+    }
+
+    async fn find_user_by_email(&self, email: &str) -> Result<Option<User>> {
+        Ok(self.read().users.values().find(|u| u.email == email).cloned())
+    }
+
+    async fn update_user(&self, user_id: &Uuid, changes: &UpdateUserRequest) -> Result<Option<User>> {
+        let mut store = self.write();
+        let Some(user) = store.users.get_mut(user_id) else {
+            return Ok(None);
+        };
+
+        if let Some(username) = &changes.username {
+            user.username = username.clone();
+        }
+        if let Some(email) = &changes.email {
+            user.email = email.clone();
+        }
+        user.updated_at = Utc::now();
+
+        Ok(Some(user.clone()))
+    }
+
+    async fn delete_user(&self, user_id: &Uuid) -> Result<bool> {
+        let mut store = self.write();
+        if store.users.remove(user_id).is_none() {
+            return Ok(false);
+        }
+        let posts: Vec<Uuid> = store.posts.values().filter(|p| p.author_id == *user_id).map(|p| p.id).collect();
+        for post_id in posts {
+            store.remove_post(&post_id);
+        }
+        store.comments.retain(|_, comment| comment.author_id != *user_id);
+        store.remove_orphaned_replies();
+        store.likes.retain(|(_, liker)| liker != user_id);
+        for revision in store.revisions.values_mut().flatten() {
+            if revision.editor_id == Some(*user_id) {
+                revision.editor_id = None;
+            }
+        }
+        store.refresh_tokens.retain(|_, token| token.user_id != *user_id);
+        Ok(true)
+    }
+
+    async fn list_users(&self, limit: i64, offset: i64) -> Result<Vec<User>> {
+        let store = self.read();
+        let mut users: Vec<&User> = store.users.values().collect();
+        users.sort_by(|a, b| b.created_at.cmp(&a.created_at).then(b.id.cmp(&a.id)));
+        Ok(users
+            .into_iter()
+            .skip(offset.max(0) as usize)
+            .take(limit.max(0) as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn set_user_banned(&self, user_id: &Uuid, banned_at: Option<DateTime<Utc>>) -> Result<Option<User>> {
+        let mut store = self.write();
+        let Some(user) = store.users.get_mut(user_id) else {
+            return Ok(None);
+        };
+        user.banned_at = banned_at;
+        user.updated_at = Utc::now();
+        Ok(Some(user.clone()))
+    }
+
+    async fn set_user_role(&self, user_id: &Uuid, role: Role) -> Result<Option<User>> {
+        let mut store = self.write();
+        let Some(user) = store.users.get_mut(user_id) else {
+            return Ok(None);
+        };
+        user.role = role;
+        user.updated_at = Utc::now();
+        Ok(Some(user.clone()))
+    }
+}
+
+impl PostRepository for InMemoryRepository {
+    async fn create_post(&self, post: Post, tags: &[String]) -> Result<Post> {
+        let mut store = self.write();
+        store.index_post(&post);
+        if !tags.is_empty() {
+            store.post_tags.insert(post.id, tags.iter().cloned().collect());
+        }
+        store.add_revision(&post, &post.author_id, post.created_at);
+        store.posts.insert(post.id, post.clone());
+        Ok(post)
+    }
+
+    async fn find_post(&self, post_id: &Uuid) -> Result<Option<Post>> {
+        Ok(self.read().posts.get(post_id).cloned())
+    }
+
+    async fn find_post_with_author(&self, post_id: &Uuid) -> Result<Option<PostWithAuthor>> {
+        let store = self.read();
+        Ok(store.posts.get(post_id).map(|post| store.with_author(post)))
+    }
+
+    async fn list_posts(
+        &self,
+        filter: &PostFilter,
+        sort: PostSort,
+        after: Option<&PostCursor>,
+        limit: i64,
+    ) -> Result<Vec<PostWithAuthor>> {
+        let store = self.read();
+        let posts = store
+            .posts
+            .values()
+            .filter(|post| store.matches(post, filter))
+            .map(|post| {
+                let post = store.with_author(post);
+                (PostCursor::new(sort, &post), post)
+            })
+            .collect();
+
+        Ok(page(posts, sort, after, limit))
+    }
+
+    async fn count_posts(&self, filter: &PostFilter) -> Result<i64> {
+        let store = self.read();
+        Ok(store.posts.values().filter(|post| store.matches(post, filter)).count() as i64)
+    }
+
+    async fn search_posts(
+        &self,
+        search: &PostSearch,
+        sort: PostSort,
+        after: Option<&PostCursor>,
+        limit: i64,
+    ) -> Result<Vec<ScoredPost>> {
+        let store = self.read();
+        let hits = store
+            .search_index
+            .search(search)
+            .into_iter()
+            .filter_map(|(post_id, score)| {
+                let hit = ScoredPost { post: store.with_author(store.posts.get(&post_id)?), score };
+                Some((PostCursor::scored(sort, &hit), hit))
+            })
+            .collect();
+
+        Ok(page(hits, sort, after, limit))
+    }
+
+    async fn count_search_results(&self, search: &PostSearch) -> Result<i64> {
+        Ok(self.read().search_index.search(search).len() as i64)
+    }
+
+    async fn update_post(&self, post: &Post, tags: Option<&[String]>, editor_id: Option<&Uuid>) -> Result<Option<Post>> {
+        let mut store = self.write();
+        let Some(stored) = store.posts.get_mut(&post.id).filter(|stored| stored.version == post.version) else {
+            return Ok(None);
+        };
+
+        stored.title = post.title.clone();
+        stored.content = post.content.clone();
+        stored.status = post.status;
+        stored.published_at = post.published_at;
+        stored.updated_at = post.updated_at;
+        stored.version += 1;
+
+        let post = stored.clone();
+        store.index_post(&post);
+        if let Some(tags) = tags {
+            store.post_tags.insert(post.id, tags.iter().cloned().collect());
+        }
+        if let Some(editor_id) = editor_id {
+            store.add_revision(&post, editor_id, post.updated_at);
+        }
+        Ok(Some(post))
+    }
+
+    async fn delete_post(&self, post_id: &Uuid) -> Result<bool> {
+        Ok(self.write().remove_post(post_id))
+    }
+
+    async fn like_post(&self, post_id: &Uuid, user_id: &Uuid) -> Result<bool> {
+        Ok(self.write().likes.insert((*post_id, *user_id)))
+    }
+
+    async fn unlike_post(&self, post_id: &Uuid, user_id: &Uuid) -> Result<bool> {
+        Ok(self.write().likes.remove(&(*post_id, *user_id)))
+    }
+
+    async fn list_revisions(&self, post_id: &Uuid) -> Result<Vec<PostRevision>> {
+        let store = self.read();
+        Ok(store.revisions.get(post_id).into_iter().flatten().rev().cloned().collect())
+    }
+
+    async fn find_revision(&self, post_id: &Uuid, revision: i32) -> Result<Option<PostRevision>> {
+        let store = self.read();
+        Ok(store.revisions.get(post_id).into_iter().flatten().find(|r| r.revision == revision).cloned())
+    }
+}
+
+impl CommentRepository for InMemoryRepository {
+    async fn create_comment(&self, comment: Comment) -> Result<Comment> {
+        self.write().comments.insert(comment.id, comment.clone());
+        Ok(comment)
+    }
+
+    async fn find_comment(&self, comment_id: &Uuid) -> Result<Option<Comment>> {
+        Ok(self.read().comments.get(comment_id).cloned())
+    }
+
+    async fn find_comment_with_author(&self, comment_id: &Uuid) -> Result<Option<CommentWithAuthor>> {
+        let store = self.read();
+        Ok(store.comments.get(comment_id).map(|comment| store.comment_with_author(comment)))
+    }
+
+    async fn list_comments(&self, post_id: &Uuid) -> Result<Vec<CommentWithAuthor>> {
+        let store = self.read();
+        let mut comments: Vec<&Comment> = store.comments.values().filter(|c| c.post_id == *post_id).collect();
+        comments.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+        Ok(comments.into_iter().map(|comment| store.comment_with_author(comment)).collect())
+    }
+
+    async fn delete_comment(&self, comment_id: &Uuid) -> Result<bool> {
+        let mut store = self.write();
+        if store.comments.remove(comment_id).is_none() {
+            return Ok(false);
+        }
+        store.remove_orphaned_replies();
+        Ok(true)
+    }
+}
+
+impl TokenRepository for InMemoryRepository {
+    async fn create_refresh_token(&self, token: RefreshToken) -> Result<RefreshToken> {
+        self.write().refresh_tokens.insert(token.id, token.clone());
+        Ok(token)
+    }
+
+    async fn find_refresh_token(&self, token_hash: &str) -> Result<Option<RefreshToken>> {
+        Ok(self.read().refresh_tokens.values().find(|t| t.token_hash == token_hash).cloned())
+    }
+
+    async fn revoke_refresh_token(&self, token_id: &Uuid) -> Result<bool> {
+        let mut store = self.write();
+        match store.refresh_tokens.get_mut(token_id) {
+            Some(token) if token.revoked_at.is_none() => {
+                token.revoked_at = Some(Utc::now());
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn revoke_token_family(&self, family_id: &Uuid) -> Result<()> {
+        let now = Utc::now();
+        for token in self.write().refresh_tokens.values_mut() {
+            if token.family_id == *family_id && token.revoked_at.is_none() {
+                token.revoked_at = Some(now);
+            }
+        }
+        Ok(())
+    }
+
+    async fn revoke_user_tokens(&self, user_id: &Uuid) -> Result<()> {
+        let now = Utc::now();
+        for token in self.write().refresh_tokens.values_mut() {
+            if token.user_id == *user_id && token.revoked_at.is_none() {
+                token.revoked_at = Some(now);
+            }
+        }
+        Ok(())
+    }
+
+    async fn revoke_access_token(&self, jti: &str, expires_at: DateTime<Utc>) -> Result<()> {
+        self.write().revoked_access_tokens.insert(jti.to_string(), expires_at);
+        Ok(())
+    }
+
+    async fn is_access_token_revoked(&self, jti: &str) -> Result<bool> {
+        Ok(self.read().revoked_access_tokens.contains_key(jti))
+    }
+
+    async fn delete_expired_tokens(&self, now: DateTime<Utc>) -> Result<()> {
+        let mut store = self.write();
+        store.refresh_tokens.retain(|_, token| token.expires_at >= now);
+        store.revoked_access_tokens.retain(|_, expires_at| *expires_at >= now);
+        Ok(())
+    }
+}
+
+impl Repository for InMemoryRepository {
+    async fn ping(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn pool_stats(&self) -> Option<PoolStats> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::SearchQuery;
+    use chrono::Duration;
+
+    fn user(username: &str) -> User {
+        let now = Utc::now();
+        User {
+            id: Uuid::new_v4(),
+            username: username.to_string(),
+            email: format!("{}@example.com", username),
+            password_hash: "hash".to_string(),
+            role: Role::User,
+            banned_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn post(author: &User, title: &str, minutes_ago: i64) -> Post {
+        let created_at = Utc::now() - Duration::minutes(minutes_ago);
+        Post {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            content: format!("Content of {}", title),
+            author_id: author.id,
+            status: PostStatus::Published,
+            published_at: Some(created_at),
+            version: 1,
+            created_at,
+            updated_at: created_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_user_uniqueness_and_cascade_delete() {
+        let repo = InMemoryRepository::new();
+        let alice = repo.create_user(user("alice")).await.unwrap();
+        assert!(matches!(repo.create_user(user("alice")).await, Err(AppError::Conflict(_))));
+
+        repo.create_post(post(&alice, "Hello", 0), &[]).await.unwrap();
+        assert!(repo.delete_user(&alice.id).await.unwrap());
+        assert!(!repo.delete_user(&alice.id).await.unwrap());
+        assert_eq!(repo.count_posts(&PostFilter::default()).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_post_listing_order_pagination_and_search() {
+        let repo = InMemoryRepository::new();
+        let alice = repo.create_user(user("alice")).await.unwrap();
+        let bob = repo.create_user(user("bob")).await.unwrap();
+        repo.create_post(post(&alice, "Oldest Rust", 30), &[]).await.unwrap();
+        repo.create_post(post(&bob, "Middle", 20), &[]).await.unwrap();
+        repo.create_post(post(&alice, "Newest rust", 10), &[]).await.unwrap();
+
+        let titles = |posts: Vec<PostWithAuthor>| posts.into_iter().map(|p| p.title).collect::<Vec<_>>();
+        let list = |filter: PostFilter, sort: PostSort, after: Option<PostCursor>, limit: i64| {
+            let repo = repo.clone();
+            async move { titles(repo.list_posts(&filter, sort, after.as_ref(), limit).await.unwrap()) }
+        };
+
+        assert_eq!(list(PostFilter::default(), PostSort::Newest, None, 10).await, vec!["Newest rust", "Middle", "Oldest Rust"]);
+        assert_eq!(list(PostFilter::default(), PostSort::Oldest, None, 1).await, vec!["Oldest Rust"]);
+        assert_eq!(list(PostFilter::default(), PostSort::Title, None, 10).await, vec!["Middle", "Newest rust", "Oldest Rust"]);
+        assert_eq!(list(PostFilter::default().author(alice.id), PostSort::Newest, None, 10).await, vec!["Newest rust", "Oldest Rust"]);
+        assert_eq!(repo.count_posts(&PostFilter::default().author(bob.id)).await.unwrap(), 1);
+
+        // The next page starts strictly after the cursor
+        let first = repo.list_posts(&PostFilter::default(), PostSort::Newest, None, 1).await.unwrap();
+        let after = PostCursor::new(PostSort::Newest, &first[0]);
+        let second = repo.list_posts(&PostFilter::default(), PostSort::Newest, Some(&after), 1).await.unwrap();
+        assert_eq!((second[0].title.as_str(), second[0].author_username.as_str()), ("Middle", "bob"));
+
+        // Ties on the like count fall back to newest first
+        repo.like_post(&second[0].id, &alice.id).await.unwrap();
+        assert_eq!(list(PostFilter::default(), PostSort::MostLiked, None, 10).await, vec!["Middle", "Newest rust", "Oldest Rust"]);
+
+        // Drafts are left out of listings filtered by status
+        let draft = Post { status: PostStatus::Draft, published_at: None, ..post(&bob, "Draft rust", 0) };
+        let draft = repo.create_post(draft, &[]).await.unwrap();
+        assert_eq!(repo.count_posts(&PostFilter::default().author(bob.id)).await.unwrap(), 2);
+        assert_eq!(repo.count_posts(&PostFilter::published().author(bob.id)).await.unwrap(), 1);
+
+        // The search index holds the published posts and follows updates and deletes
+        let search = PostSearch { query: SearchQuery::parse("RUST").unwrap(), author_id: None, from: None, to: None };
+        let hits = repo.search_posts(&search, PostSort::Newest, None, 10).await.unwrap();
+        assert_eq!(titles(hits.into_iter().map(|hit| hit.post).collect()), vec!["Newest rust", "Oldest Rust"]);
+        let middle = repo.find_post(&second[0].id).await.unwrap().unwrap();
+        repo.update_post(&Post { title: "Rust too".to_string(), ..middle }, None, None).await.unwrap();
+        assert_eq!(repo.count_search_results(&search).await.unwrap(), 3);
+        repo.update_post(&Post { status: PostStatus::Published, ..draft }, None, None).await.unwrap();
+        assert_eq!(repo.count_search_results(&search).await.unwrap(), 4);
+        repo.delete_user(&alice.id).await.unwrap();
+        assert_eq!(repo.count_search_results(&search).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_edits_are_numbered_revisions() {
+        let repo = InMemoryRepository::new();
+        let alice = repo.create_user(user("alice")).await.unwrap();
+        let bob = repo.create_user(user("bob")).await.unwrap();
+        let hello = repo.create_post(post(&alice, "Hello", 0), &[]).await.unwrap();
+
+        let edited = Post { content: "edited".to_string(), updated_at: Utc::now(), ..hello.clone() };
+        let edited = repo.update_post(&edited, None, Some(&bob.id)).await.unwrap().unwrap();
+        let tags = ["rust".to_string()];
+        repo.update_post(&Post { status: PostStatus::Archived, ..edited.clone() }, Some(&tags), None).await.unwrap().unwrap();
+        let stale = Post { content: "stale".to_string(), ..edited };
+        assert!(repo.update_post(&stale, Some(&["stale".to_string()]), Some(&alice.id)).await.unwrap().is_none());
+        let stored = repo.find_post_with_author(&hello.id).await.unwrap().unwrap();
+        assert_eq!((stored.version, stored.tags.0), (3, tags.to_vec()));
+
+        let revisions = repo.list_revisions(&hello.id).await.unwrap();
+        let numbers: Vec<i32> = revisions.iter().map(|revision| revision.revision).collect();
+        assert_eq!(numbers, [2, 1]);
+        assert_eq!((revisions[0].content.as_str(), revisions[0].editor_id), ("edited", Some(bob.id)));
+
+        repo.delete_user(&bob.id).await.unwrap();
+        assert_eq!(repo.find_revision(&hello.id, 2).await.unwrap().unwrap().editor_id, None);
+        repo.delete_post(&hello.id).await.unwrap();
+        assert!(repo.list_revisions(&hello.id).await.unwrap().is_empty());
+    }
+
+    fn comment(author: &User, post: &Post, parent: Option<&Comment>) -> Comment {
+        let now = Utc::now();
+        Comment {
+            id: Uuid::new_v4(),
+            post_id: post.id,
+            author_id: author.id,
+            parent_id: parent.map(|c| c.id),
+            content: "comment".to_string(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_cascades_to_comments_likes_and_tags() {
+        let repo = InMemoryRepository::new();
+        let alice = repo.create_user(user("alice")).await.unwrap();
+        let bob = repo.create_user(user("bob")).await.unwrap();
+        let hello = repo.create_post(post(&alice, "Hello", 0), &["rust".to_string()]).await.unwrap();
+        let other = repo.create_post(post(&bob, "Other", 0), &[]).await.unwrap();
+
+        assert!(repo.like_post(&hello.id, &bob.id).await.unwrap());
+        assert!(!repo.like_post(&hello.id, &bob.id).await.unwrap());
+        repo.like_post(&other.id, &alice.id).await.unwrap();
+
+        // Bob's comment on his own post has a reply from Alice, which has a reply from Bob
+        let question = repo.create_comment(comment(&bob, &other, None)).await.unwrap();
+        let answer = repo.create_comment(comment(&alice, &other, Some(&question))).await.unwrap();
+        let thanks = repo.create_comment(comment(&bob, &other, Some(&answer))).await.unwrap();
+        repo.create_comment(comment(&bob, &hello, None)).await.unwrap();
+
+        let found = repo.find_post_with_author(&hello.id).await.unwrap().unwrap();
+        assert_eq!((found.like_count, found.comment_count), (1, 1));
+        assert_eq!(found.tags, TagList(vec!["rust".to_string()]));
+        let rust = PostFilter::default().tag("rust".to_string());
+        assert_eq!(repo.count_posts(&rust).await.unwrap(), 1);
+
+        // Deleting Alice removes her post with its comments, likes and tags,
+        // her like on Bob's post, and her reply together with the reply to it
+        repo.delete_user(&alice.id).await.unwrap();
+        assert_eq!(repo.count_posts(&rust).await.unwrap(), 0);
+        let other = repo.find_post_with_author(&other.id).await.unwrap().unwrap();
+        assert_eq!((other.like_count, other.comment_count), (0, 1));
+        assert!(repo.find_comment(&thanks.id).await.unwrap().is_none());
+        assert_eq!(repo.read().likes.len(), 0);
+        assert_eq!(repo.read().comments.len(), 1);
+    }
+}
@@ -0,0 +1,215 @@
+use std::future::Future;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::error::Result;
+use crate::models::comment::{Comment, CommentWithAuthor};
+use crate::models::token::RefreshToken;
+use crate::models::pagination::{PostCursor, PostSort};
+use crate::models::post::{Post, PostStatus, PostWithAuthor};
+use crate::models::revision::PostRevision;
+use crate::models::search::ScoredPost;
+use crate::models::user::{Role, UpdateUserRequest, User};
+use crate::search::PostSearch;
+
+pub mod memory;
+pub mod postgres;
+pub mod sqlite;
+
+pub use memory::InMemoryRepository;
+pub use postgres::PostgresRepository;
+pub use sqlite::SqliteRepository;
+
+// Storage for users. Implementations only store and fetch; validation,
+// uniqueness checks and password hashing live in `UserService`.
+pub trait UserRepository: Clone + Send + Sync + 'static {
+    fn create_user(&self, user: User) -> impl Future<Output = Result<User>> + Send;
+
+    fn find_user(&self, user_id: &Uuid) -> impl Future<Output = Result<Option<User>>> + Send;
+
+    fn find_user_by_username(&self, username: &str) -> impl Future<Output = Result<Option<User>>> + Send;
+
+    fn find_user_by_email(&self, email: &str) -> impl Future<Output = Result<Option<User>>> + Send;
+
+    // Applies the fields that are set; returns None if the user does not exist
+    fn update_user(
+        &self,
+        user_id: &Uuid,
+        changes: &UpdateUserRequest,
+    ) -> impl Future<Output = Result<Option<User>>> + Send;
+
+    // Deletes the user and their posts; returns false if the user does not exist
+    fn delete_user(&self, user_id: &Uuid) -> impl Future<Output = Result<bool>> + Send;
+
+    // Newest first
+    fn list_users(&self, limit: i64, offset: i64) -> impl Future<Output = Result<Vec<User>>> + Send;
+
+    // Sets or clears `banned_at`; returns None if the user does not exist
+    fn set_user_banned(
+        &self,
+        user_id: &Uuid,
+        banned_at: Option<DateTime<Utc>>,
+    ) -> impl Future<Output = Result<Option<User>>> + Send;
+
+    // Returns None if the user does not exist
+    fn set_user_role(&self, user_id: &Uuid, role: Role) -> impl Future<Output = Result<Option<User>>> + Send;
+}
+
+// Which posts a listing includes: those matching every field that is set,
+// so the default is all posts
+#[derive(Debug, Clone, Default)]
+pub struct PostFilter {
+    pub author_id: Option<Uuid>,
+    // A normalized tag name
+    pub tag: Option<String>,
+    pub status: Option<PostStatus>,
+}
+
+impl PostFilter {
+    // What everyone may see
+    pub fn published() -> Self {
+        Self { status: Some(PostStatus::Published), ..Self::default() }
+    }
+
+    pub fn author(mut self, author_id: Uuid) -> Self {
+        self.author_id = Some(author_id);
+        self
+    }
+
+    pub fn tag(mut self, tag: String) -> Self {
+        self.tag = Some(tag);
+        self
+    }
+}
+
+// Storage for posts and their revisions. Only published posts are found by
+// `search_posts`.
+pub trait PostRepository: Clone + Send + Sync + 'static {
+    // Also records the post as its first revision, by the author, and links
+    // it to `tags`, which are already normalized, in the same transaction
+    fn create_post(&self, post: Post, tags: &[String]) -> impl Future<Output = Result<Post>> + Send;
+
+    fn find_post(&self, post_id: &Uuid) -> impl Future<Output = Result<Option<Post>>> + Send;
+
+    fn find_post_with_author(&self, post_id: &Uuid) -> impl Future<Output = Result<Option<PostWithAuthor>>> + Send;
+
+    // One page of matching posts in `sort` order, starting after the `after`
+    // cursor when it is set
+    fn list_posts(
+        &self,
+        filter: &PostFilter,
+        sort: PostSort,
+        after: Option<&PostCursor>,
+        limit: i64,
+    ) -> impl Future<Output = Result<Vec<PostWithAuthor>>> + Send;
+
+    fn count_posts(&self, filter: &PostFilter) -> impl Future<Output = Result<i64>> + Send;
+
+    // One page of full-text search matches, like `list_posts`;
+    // `PostSort::Relevance` orders by score
+    fn search_posts(
+        &self,
+        search: &PostSearch,
+        sort: PostSort,
+        after: Option<&PostCursor>,
+        limit: i64,
+    ) -> impl Future<Output = Result<Vec<ScoredPost>>> + Send;
+
+    fn count_search_results(&self, search: &PostSearch) -> impl Future<Output = Result<i64>> + Send;
+
+    // Stores every field of `post` but its author and creation time, and
+    // bumps its version. With `tags`, which are already normalized, the
+    // post's tags are replaced in the same transaction. With an editor, its
+    // title and content are also recorded as the next revision. Only applies
+    // if the stored version is still `post.version`; returns None if it is
+    // not or the post does not exist.
+    fn update_post(
+        &self,
+        post: &Post,
+        tags: Option<&[String]>,
+        editor_id: Option<&Uuid>,
+    ) -> impl Future<Output = Result<Option<Post>>> + Send;
+
+    // Deletes the post with its comments, likes and tag links; returns false
+    // if the post does not exist
+    fn delete_post(&self, post_id: &Uuid) -> impl Future<Output = Result<bool>> + Send;
+
+    // Liking twice is a no-op; returns false if the user already liked the post
+    fn like_post(&self, post_id: &Uuid, user_id: &Uuid) -> impl Future<Output = Result<bool>> + Send;
+
+    // Returns false if the user had not liked the post
+    fn unlike_post(&self, post_id: &Uuid, user_id: &Uuid) -> impl Future<Output = Result<bool>> + Send;
+
+    // Newest first
+    fn list_revisions(&self, post_id: &Uuid) -> impl Future<Output = Result<Vec<PostRevision>>> + Send;
+
+    fn find_revision(
+        &self,
+        post_id: &Uuid,
+        revision: i32,
+    ) -> impl Future<Output = Result<Option<PostRevision>>> + Send;
+}
+
+// Storage for comments. Deleting a comment deletes its replies.
+pub trait CommentRepository: Clone + Send + Sync + 'static {
+    fn create_comment(&self, comment: Comment) -> impl Future<Output = Result<Comment>> + Send;
+
+    fn find_comment(&self, comment_id: &Uuid) -> impl Future<Output = Result<Option<Comment>>> + Send;
+
+    fn find_comment_with_author(
+        &self,
+        comment_id: &Uuid,
+    ) -> impl Future<Output = Result<Option<CommentWithAuthor>>> + Send;
+
+    // All comments on a post, oldest first
+    fn list_comments(&self, post_id: &Uuid) -> impl Future<Output = Result<Vec<CommentWithAuthor>>> + Send;
+
+    // Returns false if the comment does not exist
+    fn delete_comment(&self, comment_id: &Uuid) -> impl Future<Output = Result<bool>> + Send;
+}
+
+// Storage for refresh tokens and revoked access tokens. Token rules
+// (rotation, reuse detection, expiry) live in `AuthService`.
+pub trait TokenRepository: Clone + Send + Sync + 'static {
+    fn create_refresh_token(&self, token: RefreshToken) -> impl Future<Output = Result<RefreshToken>> + Send;
+
+    fn find_refresh_token(&self, token_hash: &str) -> impl Future<Output = Result<Option<RefreshToken>>> + Send;
+
+    // Revokes a single token; returns false if it was already revoked, so
+    // only one of two concurrent refreshes with the same token succeeds
+    fn revoke_refresh_token(&self, token_id: &Uuid) -> impl Future<Output = Result<bool>> + Send;
+
+    // Revokes every token of a login session
+    fn revoke_token_family(&self, family_id: &Uuid) -> impl Future<Output = Result<()>> + Send;
+
+    // Revokes every refresh token of a user, ending all their sessions
+    fn revoke_user_tokens(&self, user_id: &Uuid) -> impl Future<Output = Result<()>> + Send;
+
+    // Adds an access token's `jti` to the revocation list until it expires
+    fn revoke_access_token(&self, jti: &str, expires_at: DateTime<Utc>) -> impl Future<Output = Result<()>> + Send;
+
+    fn is_access_token_revoked(&self, jti: &str) -> impl Future<Output = Result<bool>> + Send;
+
+    // Drops refresh tokens and revocation entries that expired before `now`
+    fn delete_expired_tokens(&self, now: DateTime<Utc>) -> impl Future<Output = Result<()>> + Send;
+}
+
+// Connection pool usage at one moment, reported by the readiness check and
+// the metrics endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolStats {
+    // Open connections, idle or in use
+    pub size: u32,
+    pub idle: u32,
+    pub max: u32,
+}
+
+// A complete storage backend, as held by `AppState`
+pub trait Repository: UserRepository + PostRepository + CommentRepository + TokenRepository {
+    // Checks that the backend is reachable, used by the health checks
+    fn ping(&self) -> impl Future<Output = Result<()>> + Send;
+
+    // None for backends without a connection pool
+    fn pool_stats(&self) -> Option<PoolStats>;
+}
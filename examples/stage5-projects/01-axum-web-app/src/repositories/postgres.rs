@@ -0,0 +1,700 @@
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, QueryBuilder, Postgres};
+use uuid::Uuid;
+
+use crate::database::Database;
+use crate::error::Result;
+use crate::models::comment::{Comment, CommentWithAuthor};
+use crate::models::pagination::{PostCursor, PostSort};
+use crate::models::post::{Post, PostStatus, PostWithAuthor};
+use crate::models::revision::PostRevision;
+use crate::models::search::ScoredPost;
+use crate::models::token::RefreshToken;
+use crate::models::user::{Role, UpdateUserRequest, User};
+use crate::repositories::{
+    CommentRepository, PoolStats, PostFilter, PostRepository, Repository, TokenRepository, UserRepository,
+};
+use crate::search::PostSearch;
+
+const POST_WITH_AUTHOR_COLUMNS: &str = r#"
+    SELECT
+        p.id, p.title, p.content, p.author_id, p.status, p.published_at, p.version, p.created_at, p.updated_at,
+        u.username as author_username,
+        COALESCE((
+            SELECT string_agg(t.name, ',' ORDER BY t.name)
+            FROM post_tags pt JOIN tags t ON t.id = pt.tag_id
+            WHERE pt.post_id = p.id
+        ), '') as tags,
+        (SELECT COUNT(*) FROM post_likes l WHERE l.post_id = p.id) as like_count,
+        (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id) as comment_count
+    FROM posts p
+    JOIN users u ON p.author_id = u.id
+"#;
+
+const COMMENT_WITH_AUTHOR_COLUMNS: &str = r#"
+    SELECT
+        c.id, c.post_id, c.author_id, u.username as author_username, c.parent_id, c.content,
+        c.created_at, c.updated_at
+    FROM comments c
+    JOIN users u ON c.author_id = u.id
+"#;
+
+const POST_COLUMNS: &str = "id, title, content, author_id, status, published_at, version, created_at, updated_at";
+
+const REVISION_COLUMNS: &str = "id, post_id, revision, title, content, editor_id, created_at";
+
+// Appends the WHERE clause selecting the posts of a listing to a query over
+// `posts p`
+fn push_post_filter(query: &mut QueryBuilder<'_, Postgres>, filter: &PostFilter) {
+    let mut keyword = " WHERE ";
+    if let Some(author_id) = filter.author_id {
+        query.push(keyword).push("p.author_id = ").push_bind(author_id);
+        keyword = " AND ";
+    }
+    if let Some(tag) = &filter.tag {
+        query.push(keyword);
+        query.push("p.id IN (SELECT pt.post_id FROM post_tags pt JOIN tags t ON t.id = pt.tag_id WHERE t.name = ");
+        query.push_bind(tag.clone()).push(")");
+        keyword = " AND ";
+    }
+    if let Some(status) = filter.status {
+        query.push(keyword).push("p.status = ").push_bind(status.as_str());
+    }
+}
+
+// Appends the WHERE clause of a full-text search to a query over `posts p`.
+// `search_vector` is the indexed tsvector column from migration 006.
+fn push_search_filter(query: &mut QueryBuilder<'_, Postgres>, search: &PostSearch) {
+    query.push(" WHERE p.search_vector @@ to_tsquery('simple', ");
+    query.push_bind(search.query.to_tsquery()).push(")");
+    query.push(" AND p.status = ").push_bind(PostStatus::Published.as_str());
+
+    if let Some(author_id) = search.author_id {
+        query.push(" AND p.author_id = ").push_bind(author_id);
+    }
+    if let Some(from) = search.from {
+        query.push(" AND p.created_at >= ").push_bind(from);
+    }
+    if let Some(to) = search.to {
+        query.push(" AND p.created_at <= ").push_bind(to);
+    }
+}
+
+// Appends the keyset condition, order and limit of one page. The listing is
+// wrapped in a subquery so the computed `like_count`, and the `score` of
+// search results, can be compared. Titles
+// use the "C" collation so the order does not depend on the database locale.
+fn push_post_page(query: &mut QueryBuilder<'_, Postgres>, sort: PostSort, after: Option<&PostCursor>, limit: i64) {
+    if let Some(after) = after {
+        match sort {
+            PostSort::Newest => query.push(" WHERE (created_at, id) < ("),
+            PostSort::Oldest => query.push(" WHERE (created_at, id) > ("),
+            PostSort::MostLiked => {
+                query.push(" WHERE (like_count, created_at, id) < (");
+                query.push_bind(after.like_count.unwrap_or_default()).push(", ")
+            }
+            PostSort::Title => {
+                query.push(r#" WHERE (title COLLATE "C", id) > ("#);
+                query.push_bind(after.title.clone().unwrap_or_default()).push(", ")
+            }
+            PostSort::Relevance => {
+                query.push(" WHERE (score, id) < (");
+                query.push_bind(after.score.unwrap_or_default()).push(", ")
+            }
+        };
+        if !matches!(sort, PostSort::Title | PostSort::Relevance) {
+            query.push_bind(after.created_at).push(", ");
+        }
+        query.push_bind(after.id).push(")");
+    }
+
+    query.push(match sort {
+        PostSort::Newest => " ORDER BY created_at DESC, id DESC",
+        PostSort::Oldest => " ORDER BY created_at, id",
+        PostSort::MostLiked => " ORDER BY like_count DESC, created_at DESC, id DESC",
+        PostSort::Title => r#" ORDER BY title COLLATE "C", id"#,
+        PostSort::Relevance => " ORDER BY score DESC, id DESC",
+    });
+    query.push(" LIMIT ").push_bind(limit);
+}
+
+// Replaces the post's tags within the caller's transaction
+async fn replace_post_tags(conn: &mut PgConnection, post_id: &Uuid, tags: &[String]) -> Result<()> {
+    sqlx::query("DELETE FROM post_tags WHERE post_id = $1")
+        .bind(post_id)
+        .execute(&mut *conn)
+        .await?;
+
+    for tag in tags {
+        sqlx::query("INSERT INTO tags (id, name) VALUES ($1, $2) ON CONFLICT (name) DO NOTHING")
+            .bind(Uuid::new_v4())
+            .bind(tag)
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query("INSERT INTO post_tags (post_id, tag_id) SELECT $1, id FROM tags WHERE name = $2")
+            .bind(post_id)
+            .bind(tag)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    Ok(())
+}
+
+// PostgreSQL storage backed by a sqlx connection pool
+#[derive(Clone)]
+pub struct PostgresRepository {
+    db: Database,
+}
+
+impl PostgresRepository {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    pub fn pool(&self) -> &Database {
+        &self.db
+    }
+}
+
+impl UserRepository for PostgresRepository {
+    async fn create_user(&self, user: User) -> Result<User> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            INSERT INTO users (id, username, email, password_hash, role, banned_at, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id, username, email, password_hash, role, banned_at, created_at, updated_at
+            "#,
+        )
+        .bind(user.id)
+        .bind(user.username)
+        .bind(user.email)
+        .bind(user.password_hash)
+        .bind(user.role.as_str())
+        .bind(user.banned_at)
+        .bind(user.created_at)
+        .bind(user.updated_at)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn find_user(&self, user_id: &Uuid) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            "SELECT id, username, email, password_hash, role, banned_at, created_at, updated_at FROM users WHERE id = $1",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn find_user_by_username(&self, username: &str) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            "SELECT id, username, email, password_hash, role, banned_at, created_at, updated_at FROM users WHERE username = $1",
+        )
+        .bind(username)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn find_user_by_email(&self, email: &str) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            "SELECT id, username, email, password_hash, role, banned_at, created_at, updated_at FROM users WHERE email = $1",
+        )
+        .bind(email)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn update_user(&self, user_id: &Uuid, changes: &UpdateUserRequest) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET username = COALESCE($1, username),
+                email = COALESCE($2, email),
+                updated_at = $3
+            WHERE id = $4
+            RETURNING id, username, email, password_hash, role, banned_at, created_at, updated_at
+            "#,
+        )
+        .bind(&changes.username)
+        .bind(&changes.email)
+        .bind(Utc::now())
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn delete_user(&self, user_id: &Uuid) -> Result<bool> {
+        // Posts, comments and likes are removed by ON DELETE CASCADE
+        let result = sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_users(&self, limit: i64, offset: i64) -> Result<Vec<User>> {
+        let users = sqlx::query_as::<_, User>(
+            r#"
+            SELECT id, username, email, password_hash, role, banned_at, created_at, updated_at
+            FROM users
+            ORDER BY created_at DESC
+            LIMIT $1 OFFSET $2
+            "#,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(users)
+    }
+
+    async fn set_user_banned(&self, user_id: &Uuid, banned_at: Option<DateTime<Utc>>) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET banned_at = $1, updated_at = $2
+            WHERE id = $3
+            RETURNING id, username, email, password_hash, role, banned_at, created_at, updated_at
+            "#,
+        )
+        .bind(banned_at)
+        .bind(Utc::now())
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn set_user_role(&self, user_id: &Uuid, role: Role) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET role = $1, updated_at = $2
+            WHERE id = $3
+            RETURNING id, username, email, password_hash, role, banned_at, created_at, updated_at
+            "#,
+        )
+        .bind(role.as_str())
+        .bind(Utc::now())
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(user)
+    }
+}
+
+impl PostRepository for PostgresRepository {
+    async fn create_post(&self, post: Post, tags: &[String]) -> Result<Post> {
+        let mut tx = self.db.begin().await?;
+
+        let post = sqlx::query_as::<_, Post>(&format!(
+            r#"
+            INSERT INTO posts (id, title, content, author_id, status, published_at, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING {}
+            "#,
+            POST_COLUMNS
+        ))
+        .bind(post.id)
+        .bind(post.title)
+        .bind(post.content)
+        .bind(post.author_id)
+        .bind(post.status.as_str())
+        .bind(post.published_at)
+        .bind(post.created_at)
+        .bind(post.updated_at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO post_revisions (id, post_id, revision, title, content, editor_id, created_at)
+            VALUES ($1, $2, 1, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(post.id)
+        .bind(&post.title)
+        .bind(&post.content)
+        .bind(post.author_id)
+        .bind(post.created_at)
+        .execute(&mut *tx)
+        .await?;
+
+        if !tags.is_empty() {
+            replace_post_tags(&mut tx, &post.id, tags).await?;
+        }
+
+        tx.commit().await?;
+        Ok(post)
+    }
+
+    async fn find_post(&self, post_id: &Uuid) -> Result<Option<Post>> {
+        let post = sqlx::query_as::<_, Post>(&format!("SELECT {} FROM posts WHERE id = $1", POST_COLUMNS))
+            .bind(post_id)
+            .fetch_optional(&self.db)
+            .await?;
+
+        Ok(post)
+    }
+
+    async fn find_post_with_author(&self, post_id: &Uuid) -> Result<Option<PostWithAuthor>> {
+        let post = sqlx::query_as::<_, PostWithAuthor>(&format!("{} WHERE p.id = $1", POST_WITH_AUTHOR_COLUMNS))
+            .bind(post_id)
+            .fetch_optional(&self.db)
+            .await?;
+
+        Ok(post)
+    }
+
+    async fn list_posts(
+        &self,
+        filter: &PostFilter,
+        sort: PostSort,
+        after: Option<&PostCursor>,
+        limit: i64,
+    ) -> Result<Vec<PostWithAuthor>> {
+        let mut query = QueryBuilder::new("SELECT * FROM (");
+        query.push(POST_WITH_AUTHOR_COLUMNS);
+        push_post_filter(&mut query, filter);
+        query.push(") AS listing");
+        push_post_page(&mut query, sort, after, limit);
+
+        let posts = query.build_query_as::<PostWithAuthor>().fetch_all(&self.db).await?;
+        Ok(posts)
+    }
+
+    async fn count_posts(&self, filter: &PostFilter) -> Result<i64> {
+        let mut query = QueryBuilder::new("SELECT COUNT(*) FROM posts p");
+        push_post_filter(&mut query, filter);
+
+        let count = query.build_query_scalar::<i64>().fetch_one(&self.db).await?;
+        Ok(count)
+    }
+
+    // Ranked with ts_rank, where title words (weight A) count more than
+    // content words (weight B)
+    async fn search_posts(
+        &self,
+        search: &PostSearch,
+        sort: PostSort,
+        after: Option<&PostCursor>,
+        limit: i64,
+    ) -> Result<Vec<ScoredPost>> {
+        let mut query = QueryBuilder::new("SELECT * FROM (SELECT listing.*, ts_rank(s.search_vector, to_tsquery('simple', ");
+        query.push_bind(search.query.to_tsquery());
+        query.push("))::float8 AS score FROM (");
+        query.push(POST_WITH_AUTHOR_COLUMNS);
+        push_search_filter(&mut query, search);
+        query.push(") AS listing JOIN posts s ON s.id = listing.id) AS listing");
+        push_post_page(&mut query, sort, after, limit);
+
+        let posts = query.build_query_as::<ScoredPost>().fetch_all(&self.db).await?;
+        Ok(posts)
+    }
+
+    async fn count_search_results(&self, search: &PostSearch) -> Result<i64> {
+        let mut query = QueryBuilder::new("SELECT COUNT(*) FROM posts p");
+        push_search_filter(&mut query, search);
+
+        let count = query.build_query_scalar::<i64>().fetch_one(&self.db).await?;
+        Ok(count)
+    }
+
+    async fn update_post(&self, post: &Post, tags: Option<&[String]>, editor_id: Option<&Uuid>) -> Result<Option<Post>> {
+        let mut tx = self.db.begin().await?;
+
+        let updated = sqlx::query_as::<_, Post>(&format!(
+            r#"
+            UPDATE posts
+            SET title = $1, content = $2, status = $3, published_at = $4, updated_at = $5,
+                version = version + 1
+            WHERE id = $6 AND version = $7
+            RETURNING {}
+            "#,
+            POST_COLUMNS
+        ))
+        .bind(&post.title)
+        .bind(&post.content)
+        .bind(post.status.as_str())
+        .bind(post.published_at)
+        .bind(post.updated_at)
+        .bind(post.id)
+        .bind(post.version)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if let (Some(updated), Some(tags)) = (&updated, tags) {
+            replace_post_tags(&mut tx, &updated.id, tags).await?;
+        }
+        if let (Some(updated), Some(editor_id)) = (&updated, editor_id) {
+            // The row lock taken by the UPDATE keeps concurrent edits from
+            // picking the same number
+            sqlx::query(
+                r#"
+                INSERT INTO post_revisions (id, post_id, revision, title, content, editor_id, created_at)
+                SELECT $1, $2, COALESCE(MAX(revision), 0) + 1, $3, $4, $5, $6
+                FROM post_revisions WHERE post_id = $2
+                "#,
+            )
+            .bind(Uuid::new_v4())
+            .bind(updated.id)
+            .bind(&updated.title)
+            .bind(&updated.content)
+            .bind(editor_id)
+            .bind(updated.updated_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(updated)
+    }
+
+    async fn delete_post(&self, post_id: &Uuid) -> Result<bool> {
+        // Comments, likes and tag links are removed by ON DELETE CASCADE
+        let result = sqlx::query("DELETE FROM posts WHERE id = $1")
+            .bind(post_id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn like_post(&self, post_id: &Uuid, user_id: &Uuid) -> Result<bool> {
+        let result = sqlx::query("INSERT INTO post_likes (post_id, user_id, created_at) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING")
+            .bind(post_id)
+            .bind(user_id)
+            .bind(Utc::now())
+            .execute(&self.db)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn unlike_post(&self, post_id: &Uuid, user_id: &Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM post_likes WHERE post_id = $1 AND user_id = $2")
+            .bind(post_id)
+            .bind(user_id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_revisions(&self, post_id: &Uuid) -> Result<Vec<PostRevision>> {
+        let revisions = sqlx::query_as::<_, PostRevision>(&format!(
+            "SELECT {} FROM post_revisions WHERE post_id = $1 ORDER BY revision DESC",
+            REVISION_COLUMNS
+        ))
+        .bind(post_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(revisions)
+    }
+
+    async fn find_revision(&self, post_id: &Uuid, revision: i32) -> Result<Option<PostRevision>> {
+        let revision = sqlx::query_as::<_, PostRevision>(&format!(
+            "SELECT {} FROM post_revisions WHERE post_id = $1 AND revision = $2",
+            REVISION_COLUMNS
+        ))
+        .bind(post_id)
+        .bind(revision)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(revision)
+    }
+}
+
+impl CommentRepository for PostgresRepository {
+    async fn create_comment(&self, comment: Comment) -> Result<Comment> {
+        let comment = sqlx::query_as::<_, Comment>(
+            r#"
+            INSERT INTO comments (id, post_id, author_id, parent_id, content, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, post_id, author_id, parent_id, content, created_at, updated_at
+            "#,
+        )
+        .bind(comment.id)
+        .bind(comment.post_id)
+        .bind(comment.author_id)
+        .bind(comment.parent_id)
+        .bind(comment.content)
+        .bind(comment.created_at)
+        .bind(comment.updated_at)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(comment)
+    }
+
+    async fn find_comment(&self, comment_id: &Uuid) -> Result<Option<Comment>> {
+        let comment = sqlx::query_as::<_, Comment>(
+            "SELECT id, post_id, author_id, parent_id, content, created_at, updated_at FROM comments WHERE id = $1",
+        )
+        .bind(comment_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(comment)
+    }
+
+    async fn find_comment_with_author(&self, comment_id: &Uuid) -> Result<Option<CommentWithAuthor>> {
+        let comment = sqlx::query_as::<_, CommentWithAuthor>(&format!("{} WHERE c.id = $1", COMMENT_WITH_AUTHOR_COLUMNS))
+            .bind(comment_id)
+            .fetch_optional(&self.db)
+            .await?;
+
+        Ok(comment)
+    }
+
+    async fn list_comments(&self, post_id: &Uuid) -> Result<Vec<CommentWithAuthor>> {
+        let comments = sqlx::query_as::<_, CommentWithAuthor>(&format!(
+            "{} WHERE c.post_id = $1 ORDER BY c.created_at, c.id",
+            COMMENT_WITH_AUTHOR_COLUMNS
+        ))
+        .bind(post_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(comments)
+    }
+
+    async fn delete_comment(&self, comment_id: &Uuid) -> Result<bool> {
+        // Replies are removed by ON DELETE CASCADE on parent_id
+        let result = sqlx::query("DELETE FROM comments WHERE id = $1")
+            .bind(comment_id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+impl TokenRepository for PostgresRepository {
+    async fn create_refresh_token(&self, token: RefreshToken) -> Result<RefreshToken> {
+        let token = sqlx::query_as::<_, RefreshToken>(
+            r#"
+            INSERT INTO refresh_tokens (id, user_id, family_id, token_hash, expires_at, created_at, revoked_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, user_id, family_id, token_hash, expires_at, created_at, revoked_at
+            "#,
+        )
+        .bind(token.id)
+        .bind(token.user_id)
+        .bind(token.family_id)
+        .bind(token.token_hash)
+        .bind(token.expires_at)
+        .bind(token.created_at)
+        .bind(token.revoked_at)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(token)
+    }
+
+    async fn find_refresh_token(&self, token_hash: &str) -> Result<Option<RefreshToken>> {
+        let token = sqlx::query_as::<_, RefreshToken>(
+            "SELECT id, user_id, family_id, token_hash, expires_at, created_at, revoked_at FROM refresh_tokens WHERE token_hash = $1",
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(token)
+    }
+
+    async fn revoke_refresh_token(&self, token_id: &Uuid) -> Result<bool> {
+        let result = sqlx::query("UPDATE refresh_tokens SET revoked_at = NOW() WHERE id = $1 AND revoked_at IS NULL")
+            .bind(token_id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn revoke_token_family(&self, family_id: &Uuid) -> Result<()> {
+        sqlx::query("UPDATE refresh_tokens SET revoked_at = NOW() WHERE family_id = $1 AND revoked_at IS NULL")
+            .bind(family_id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn revoke_user_tokens(&self, user_id: &Uuid) -> Result<()> {
+        sqlx::query("UPDATE refresh_tokens SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL")
+            .bind(user_id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn revoke_access_token(&self, jti: &str, expires_at: DateTime<Utc>) -> Result<()> {
+        sqlx::query("INSERT INTO revoked_access_tokens (jti, expires_at) VALUES ($1, $2) ON CONFLICT (jti) DO NOTHING")
+            .bind(jti)
+            .bind(expires_at)
+            .execute(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn is_access_token_revoked(&self, jti: &str) -> Result<bool> {
+        let revoked = sqlx::query("SELECT 1 FROM revoked_access_tokens WHERE jti = $1")
+            .bind(jti)
+            .fetch_optional(&self.db)
+            .await?;
+
+        Ok(revoked.is_some())
+    }
+
+    async fn delete_expired_tokens(&self, now: DateTime<Utc>) -> Result<()> {
+        sqlx::query("DELETE FROM refresh_tokens WHERE expires_at < $1")
+            .bind(now)
+            .execute(&self.db)
+            .await?;
+        sqlx::query("DELETE FROM revoked_access_tokens WHERE expires_at < $1")
+            .bind(now)
+            .execute(&self.db)
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl Repository for PostgresRepository {
+    async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1")
+            .fetch_one(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    fn pool_stats(&self) -> Option<PoolStats> {
+        Some(PoolStats {
+            size: self.db.size(),
+            idle: self.db.num_idle() as u32,
+            max: self.db.options().get_max_connections(),
+        })
+    }
+}
@@ -0,0 +1,933 @@
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use sqlx::{SqliteConnection, QueryBuilder, Sqlite};
+use uuid::Uuid;
+
+use crate::database::SqliteDatabase;
+use crate::error::Result;
+use crate::models::comment::{Comment, CommentWithAuthor};
+use crate::models::pagination::{PostCursor, PostSort};
+use crate::models::post::{Post, PostStatus, PostWithAuthor};
+use crate::models::revision::PostRevision;
+use crate::models::search::ScoredPost;
+use crate::models::token::RefreshToken;
+use crate::models::user::{Role, UpdateUserRequest, User};
+use crate::repositories::{
+    CommentRepository, PoolStats, PostFilter, PostRepository, Repository, TokenRepository, UserRepository,
+};
+use crate::search::{PostSearch, SearchIndex};
+
+const POST_WITH_AUTHOR_COLUMNS: &str = r#"
+    SELECT
+        p.id, p.title, p.content, p.author_id, p.status, p.published_at, p.version, p.created_at, p.updated_at,
+        u.username as author_username,
+        COALESCE((
+            SELECT group_concat(name, ',') FROM (
+                SELECT t.name FROM post_tags pt JOIN tags t ON t.id = pt.tag_id
+                WHERE pt.post_id = p.id ORDER BY t.name
+            )
+        ), '') as tags,
+        (SELECT COUNT(*) FROM post_likes l WHERE l.post_id = p.id) as like_count,
+        (SELECT COUNT(*) FROM comments c WHERE c.post_id = p.id) as comment_count
+    FROM posts p
+    JOIN users u ON p.author_id = u.id
+"#;
+
+const COMMENT_WITH_AUTHOR_COLUMNS: &str = r#"
+    SELECT
+        c.id, c.post_id, c.author_id, u.username as author_username, c.parent_id, c.content,
+        c.created_at, c.updated_at
+    FROM comments c
+    JOIN users u ON c.author_id = u.id
+"#;
+
+// Timestamps are stored as RFC 3339 text. Always writing nine fractional
+// digits keeps the text order equal to the time order, which sorting and the
+// pagination cursors rely on.
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Nanos, false)
+}
+
+const POST_COLUMNS: &str = "id, title, content, author_id, status, published_at, version, created_at, updated_at";
+
+const REVISION_COLUMNS: &str = "id, post_id, revision, title, content, editor_id, created_at";
+
+// Appends the WHERE clause selecting the posts of a listing to a query over
+// `posts p`
+fn push_post_filter(query: &mut QueryBuilder<'_, Sqlite>, filter: &PostFilter) {
+    let mut keyword = " WHERE ";
+    if let Some(author_id) = filter.author_id {
+        query.push(keyword).push("p.author_id = ").push_bind(author_id);
+        keyword = " AND ";
+    }
+    if let Some(tag) = &filter.tag {
+        query.push(keyword);
+        query.push("p.id IN (SELECT pt.post_id FROM post_tags pt JOIN tags t ON t.id = pt.tag_id WHERE t.name = ");
+        query.push_bind(tag.clone()).push(")");
+        keyword = " AND ";
+    }
+    if let Some(status) = filter.status {
+        query.push(keyword).push("p.status = ").push_bind(status.as_str());
+    }
+}
+
+// Appends the keyset condition, order and limit of one page. The listing is
+// wrapped in a subquery so the computed `like_count`, and the `score` of
+// search results, can be compared.
+fn push_post_page(query: &mut QueryBuilder<'_, Sqlite>, sort: PostSort, after: Option<&PostCursor>, limit: i64) {
+    if let Some(after) = after {
+        match sort {
+            PostSort::Newest => query.push(" WHERE (created_at, id) < ("),
+            PostSort::Oldest => query.push(" WHERE (created_at, id) > ("),
+            PostSort::MostLiked => {
+                query.push(" WHERE (like_count, created_at, id) < (");
+                query.push_bind(after.like_count.unwrap_or_default()).push(", ")
+            }
+            PostSort::Title => {
+                query.push(" WHERE (title, id) > (");
+                query.push_bind(after.title.clone().unwrap_or_default()).push(", ")
+            }
+            PostSort::Relevance => {
+                query.push(" WHERE (score, id) < (");
+                query.push_bind(after.score.unwrap_or_default()).push(", ")
+            }
+        };
+        if !matches!(sort, PostSort::Title | PostSort::Relevance) {
+            query.push_bind(timestamp(after.created_at)).push(", ");
+        }
+        query.push_bind(after.id).push(")");
+    }
+
+    query.push(match sort {
+        PostSort::Newest => " ORDER BY created_at DESC, id DESC",
+        PostSort::Oldest => " ORDER BY created_at, id",
+        PostSort::MostLiked => " ORDER BY like_count DESC, created_at DESC, id DESC",
+        PostSort::Title => " ORDER BY title, id",
+        PostSort::Relevance => " ORDER BY score DESC, id DESC",
+    });
+    query.push(" LIMIT ").push_bind(limit);
+}
+
+// Replaces the post's tags within the caller's transaction
+async fn replace_post_tags(conn: &mut SqliteConnection, post_id: &Uuid, tags: &[String]) -> Result<()> {
+    sqlx::query("DELETE FROM post_tags WHERE post_id = ?1")
+        .bind(post_id)
+        .execute(&mut *conn)
+        .await?;
+
+    for tag in tags {
+        sqlx::query("INSERT INTO tags (id, name) VALUES (?1, ?2) ON CONFLICT (name) DO NOTHING")
+            .bind(Uuid::new_v4())
+            .bind(tag)
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query("INSERT INTO post_tags (post_id, tag_id) SELECT ?1, id FROM tags WHERE name = ?2")
+            .bind(post_id)
+            .bind(tag)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    Ok(())
+}
+
+// SQLite storage for local development without a PostgreSQL server.
+//
+// SQLite has no NOW(), so timestamps are generated in Rust and bound as
+// parameters. Full-text search uses an in-process `SearchIndex` of the
+// published posts, built from the posts table on startup and updated with
+// every post write.
+#[derive(Clone)]
+pub struct SqliteRepository {
+    db: SqliteDatabase,
+    search_index: Arc<RwLock<SearchIndex>>,
+}
+
+impl SqliteRepository {
+    pub async fn new(db: SqliteDatabase) -> Result<Self> {
+        let posts = sqlx::query_as::<_, Post>(&format!("SELECT {} FROM posts WHERE status = ?1", POST_COLUMNS))
+            .bind(PostStatus::Published.as_str())
+            .fetch_all(&db)
+            .await?;
+
+        let mut search_index = SearchIndex::new();
+        for post in &posts {
+            search_index.insert(post);
+        }
+
+        Ok(Self { db, search_index: Arc::new(RwLock::new(search_index)) })
+    }
+
+    fn search_index(&self) -> RwLockReadGuard<'_, SearchIndex> {
+        self.search_index.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn search_index_mut(&self) -> RwLockWriteGuard<'_, SearchIndex> {
+        self.search_index.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // Keeps the index to the published posts
+    fn index_post(&self, post: &Post) {
+        let mut search_index = self.search_index_mut();
+        if post.status == PostStatus::Published {
+            search_index.insert(post);
+        } else {
+            search_index.remove(&post.id);
+        }
+    }
+
+    pub fn pool(&self) -> &SqliteDatabase {
+        &self.db
+    }
+}
+
+impl UserRepository for SqliteRepository {
+    async fn create_user(&self, user: User) -> Result<User> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            INSERT INTO users (id, username, email, password_hash, role, banned_at, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            RETURNING id, username, email, password_hash, role, banned_at, created_at, updated_at
+            "#,
+        )
+        .bind(user.id)
+        .bind(user.username)
+        .bind(user.email)
+        .bind(user.password_hash)
+        .bind(user.role.as_str())
+        .bind(user.banned_at)
+        .bind(user.created_at)
+        .bind(user.updated_at)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn find_user(&self, user_id: &Uuid) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            "SELECT id, username, email, password_hash, role, banned_at, created_at, updated_at FROM users WHERE id = ?1",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn find_user_by_username(&self, username: &str) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            "SELECT id, username, email, password_hash, role, banned_at, created_at, updated_at FROM users WHERE username = ?1",
+        )
+        .bind(username)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn find_user_by_email(&self, email: &str) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            "SELECT id, username, email, password_hash, role, banned_at, created_at, updated_at FROM users WHERE email = ?1",
+        )
+        .bind(email)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn update_user(&self, user_id: &Uuid, changes: &UpdateUserRequest) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET username = COALESCE(?1, username),
+                email = COALESCE(?2, email),
+                updated_at = ?3
+            WHERE id = ?4
+            RETURNING id, username, email, password_hash, role, banned_at, created_at, updated_at
+            "#,
+        )
+        .bind(&changes.username)
+        .bind(&changes.email)
+        .bind(Utc::now())
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn delete_user(&self, user_id: &Uuid) -> Result<bool> {
+        // Posts, comments and likes are removed by ON DELETE CASCADE (sqlx enables foreign keys by default)
+        let result = sqlx::query("DELETE FROM users WHERE id = ?1")
+            .bind(user_id)
+            .execute(&self.db)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+        self.search_index_mut().remove_author(user_id);
+        Ok(true)
+    }
+
+    async fn list_users(&self, limit: i64, offset: i64) -> Result<Vec<User>> {
+        let users = sqlx::query_as::<_, User>(
+            r#"
+            SELECT id, username, email, password_hash, role, banned_at, created_at, updated_at
+            FROM users
+            ORDER BY created_at DESC
+            LIMIT ?1 OFFSET ?2
+            "#,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(users)
+    }
+
+    async fn set_user_banned(&self, user_id: &Uuid, banned_at: Option<DateTime<Utc>>) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET banned_at = ?1, updated_at = ?2
+            WHERE id = ?3
+            RETURNING id, username, email, password_hash, role, banned_at, created_at, updated_at
+            "#,
+        )
+        .bind(banned_at)
+        .bind(Utc::now())
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(user)
+    }
+
+    async fn set_user_role(&self, user_id: &Uuid, role: Role) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users
+            SET role = ?1, updated_at = ?2
+            WHERE id = ?3
+            RETURNING id, username, email, password_hash, role, banned_at, created_at, updated_at
+            "#,
+        )
+        .bind(role.as_str())
+        .bind(Utc::now())
+        .bind(user_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(user)
+    }
+}
+
+impl PostRepository for SqliteRepository {
+    async fn create_post(&self, post: Post, tags: &[String]) -> Result<Post> {
+        let mut tx = self.db.begin().await?;
+
+        let post = sqlx::query_as::<_, Post>(&format!(
+            r#"
+            INSERT INTO posts (id, title, content, author_id, status, published_at, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            RETURNING {}
+            "#,
+            POST_COLUMNS
+        ))
+        .bind(post.id)
+        .bind(post.title)
+        .bind(post.content)
+        .bind(post.author_id)
+        .bind(post.status.as_str())
+        .bind(post.published_at.map(timestamp))
+        .bind(timestamp(post.created_at))
+        .bind(timestamp(post.updated_at))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO post_revisions (id, post_id, revision, title, content, editor_id, created_at)
+            VALUES (?1, ?2, 1, ?3, ?4, ?5, ?6)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(post.id)
+        .bind(&post.title)
+        .bind(&post.content)
+        .bind(post.author_id)
+        .bind(timestamp(post.created_at))
+        .execute(&mut *tx)
+        .await?;
+
+        if !tags.is_empty() {
+            replace_post_tags(&mut tx, &post.id, tags).await?;
+        }
+
+        tx.commit().await?;
+        self.index_post(&post);
+        Ok(post)
+    }
+
+    async fn find_post(&self, post_id: &Uuid) -> Result<Option<Post>> {
+        let post = sqlx::query_as::<_, Post>(&format!("SELECT {} FROM posts WHERE id = ?1", POST_COLUMNS))
+            .bind(post_id)
+            .fetch_optional(&self.db)
+            .await?;
+
+        Ok(post)
+    }
+
+    async fn find_post_with_author(&self, post_id: &Uuid) -> Result<Option<PostWithAuthor>> {
+        let post = sqlx::query_as::<_, PostWithAuthor>(&format!("{} WHERE p.id = ?1", POST_WITH_AUTHOR_COLUMNS))
+            .bind(post_id)
+            .fetch_optional(&self.db)
+            .await?;
+
+        Ok(post)
+    }
+
+    async fn list_posts(
+        &self,
+        filter: &PostFilter,
+        sort: PostSort,
+        after: Option<&PostCursor>,
+        limit: i64,
+    ) -> Result<Vec<PostWithAuthor>> {
+        let mut query = QueryBuilder::new("SELECT * FROM (");
+        query.push(POST_WITH_AUTHOR_COLUMNS);
+        push_post_filter(&mut query, filter);
+        query.push(") AS listing");
+        push_post_page(&mut query, sort, after, limit);
+
+        let posts = query.build_query_as::<PostWithAuthor>().fetch_all(&self.db).await?;
+        Ok(posts)
+    }
+
+    async fn count_posts(&self, filter: &PostFilter) -> Result<i64> {
+        let mut query = QueryBuilder::new("SELECT COUNT(*) FROM posts p");
+        push_post_filter(&mut query, filter);
+
+        let count = query.build_query_scalar::<i64>().fetch_one(&self.db).await?;
+        Ok(count)
+    }
+
+    // The index finds the matches and their scores, which are passed in as a
+    // JSON array of [hex id, score] so sorting and paging stay in SQL
+    async fn search_posts(
+        &self,
+        search: &PostSearch,
+        sort: PostSort,
+        after: Option<&PostCursor>,
+        limit: i64,
+    ) -> Result<Vec<ScoredPost>> {
+        let scores: Vec<(String, f64)> = self
+            .search_index()
+            .search(search)
+            .into_iter()
+            .map(|(post_id, score)| (post_id.simple().to_string(), score))
+            .collect();
+        if scores.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut query = QueryBuilder::new(
+            "WITH scores AS (SELECT unhex(value ->> 0) AS id, value ->> 1 AS score FROM json_each(",
+        );
+        query.push_bind(serde_json::to_string(&scores)?);
+        query.push(")) SELECT * FROM (SELECT listing.*, scores.score FROM (");
+        query.push(POST_WITH_AUTHOR_COLUMNS);
+        query.push(" WHERE p.id IN (SELECT id FROM scores)) AS listing JOIN scores ON scores.id = listing.id) AS listing");
+        push_post_page(&mut query, sort, after, limit);
+
+        let posts = query.build_query_as::<ScoredPost>().fetch_all(&self.db).await?;
+        Ok(posts)
+    }
+
+    async fn count_search_results(&self, search: &PostSearch) -> Result<i64> {
+        Ok(self.search_index().search(search).len() as i64)
+    }
+
+    async fn update_post(&self, post: &Post, tags: Option<&[String]>, editor_id: Option<&Uuid>) -> Result<Option<Post>> {
+        let mut tx = self.db.begin().await?;
+
+        let updated = sqlx::query_as::<_, Post>(&format!(
+            r#"
+            UPDATE posts
+            SET title = ?1, content = ?2, status = ?3, published_at = ?4, updated_at = ?5,
+                version = version + 1
+            WHERE id = ?6 AND version = ?7
+            RETURNING {}
+            "#,
+            POST_COLUMNS
+        ))
+        .bind(&post.title)
+        .bind(&post.content)
+        .bind(post.status.as_str())
+        .bind(post.published_at.map(timestamp))
+        .bind(timestamp(post.updated_at))
+        .bind(post.id)
+        .bind(post.version)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if let (Some(updated), Some(tags)) = (&updated, tags) {
+            replace_post_tags(&mut tx, &updated.id, tags).await?;
+        }
+        if let (Some(updated), Some(editor_id)) = (&updated, editor_id) {
+            // The UPDATE took the write lock, so no other edit can pick the
+            // same number
+            sqlx::query(
+                r#"
+                INSERT INTO post_revisions (id, post_id, revision, title, content, editor_id, created_at)
+                SELECT ?1, ?2, COALESCE(MAX(revision), 0) + 1, ?3, ?4, ?5, ?6
+                FROM post_revisions WHERE post_id = ?2
+                "#,
+            )
+            .bind(Uuid::new_v4())
+            .bind(updated.id)
+            .bind(&updated.title)
+            .bind(&updated.content)
+            .bind(editor_id)
+            .bind(timestamp(updated.updated_at))
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        if let Some(updated) = &updated {
+            self.index_post(updated);
+        }
+        Ok(updated)
+    }
+
+    async fn delete_post(&self, post_id: &Uuid) -> Result<bool> {
+        // Comments, likes and tag links are removed by ON DELETE CASCADE
+        let result = sqlx::query("DELETE FROM posts WHERE id = ?1")
+            .bind(post_id)
+            .execute(&self.db)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+        self.search_index_mut().remove(post_id);
+        Ok(true)
+    }
+
+    async fn like_post(&self, post_id: &Uuid, user_id: &Uuid) -> Result<bool> {
+        let result = sqlx::query("INSERT INTO post_likes (post_id, user_id, created_at) VALUES (?1, ?2, ?3) ON CONFLICT DO NOTHING")
+            .bind(post_id)
+            .bind(user_id)
+            .bind(Utc::now())
+            .execute(&self.db)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn unlike_post(&self, post_id: &Uuid, user_id: &Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM post_likes WHERE post_id = ?1 AND user_id = ?2")
+            .bind(post_id)
+            .bind(user_id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_revisions(&self, post_id: &Uuid) -> Result<Vec<PostRevision>> {
+        let revisions = sqlx::query_as::<_, PostRevision>(&format!(
+            "SELECT {} FROM post_revisions WHERE post_id = ?1 ORDER BY revision DESC",
+            REVISION_COLUMNS
+        ))
+        .bind(post_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(revisions)
+    }
+
+    async fn find_revision(&self, post_id: &Uuid, revision: i32) -> Result<Option<PostRevision>> {
+        let revision = sqlx::query_as::<_, PostRevision>(&format!(
+            "SELECT {} FROM post_revisions WHERE post_id = ?1 AND revision = ?2",
+            REVISION_COLUMNS
+        ))
+        .bind(post_id)
+        .bind(revision)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(revision)
+    }
+}
+
+impl CommentRepository for SqliteRepository {
+    async fn create_comment(&self, comment: Comment) -> Result<Comment> {
+        let comment = sqlx::query_as::<_, Comment>(
+            r#"
+            INSERT INTO comments (id, post_id, author_id, parent_id, content, created_at, updated_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            RETURNING id, post_id, author_id, parent_id, content, created_at, updated_at
+            "#,
+        )
+        .bind(comment.id)
+        .bind(comment.post_id)
+        .bind(comment.author_id)
+        .bind(comment.parent_id)
+        .bind(comment.content)
+        .bind(comment.created_at)
+        .bind(comment.updated_at)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(comment)
+    }
+
+    async fn find_comment(&self, comment_id: &Uuid) -> Result<Option<Comment>> {
+        let comment = sqlx::query_as::<_, Comment>(
+            "SELECT id, post_id, author_id, parent_id, content, created_at, updated_at FROM comments WHERE id = ?1",
+        )
+        .bind(comment_id)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(comment)
+    }
+
+    async fn find_comment_with_author(&self, comment_id: &Uuid) -> Result<Option<CommentWithAuthor>> {
+        let comment = sqlx::query_as::<_, CommentWithAuthor>(&format!("{} WHERE c.id = ?1", COMMENT_WITH_AUTHOR_COLUMNS))
+            .bind(comment_id)
+            .fetch_optional(&self.db)
+            .await?;
+
+        Ok(comment)
+    }
+
+    async fn list_comments(&self, post_id: &Uuid) -> Result<Vec<CommentWithAuthor>> {
+        let comments = sqlx::query_as::<_, CommentWithAuthor>(&format!(
+            "{} WHERE c.post_id = ?1 ORDER BY c.created_at, c.id",
+            COMMENT_WITH_AUTHOR_COLUMNS
+        ))
+        .bind(post_id)
+        .fetch_all(&self.db)
+        .await?;
+
+        Ok(comments)
+    }
+
+    async fn delete_comment(&self, comment_id: &Uuid) -> Result<bool> {
+        // Replies are removed by ON DELETE CASCADE on parent_id
+        let result = sqlx::query("DELETE FROM comments WHERE id = ?1")
+            .bind(comment_id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+impl TokenRepository for SqliteRepository {
+    async fn create_refresh_token(&self, token: RefreshToken) -> Result<RefreshToken> {
+        let token = sqlx::query_as::<_, RefreshToken>(
+            r#"
+            INSERT INTO refresh_tokens (id, user_id, family_id, token_hash, expires_at, created_at, revoked_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            RETURNING id, user_id, family_id, token_hash, expires_at, created_at, revoked_at
+            "#,
+        )
+        .bind(token.id)
+        .bind(token.user_id)
+        .bind(token.family_id)
+        .bind(token.token_hash)
+        .bind(token.expires_at)
+        .bind(token.created_at)
+        .bind(token.revoked_at)
+        .fetch_one(&self.db)
+        .await?;
+
+        Ok(token)
+    }
+
+    async fn find_refresh_token(&self, token_hash: &str) -> Result<Option<RefreshToken>> {
+        let token = sqlx::query_as::<_, RefreshToken>(
+            "SELECT id, user_id, family_id, token_hash, expires_at, created_at, revoked_at FROM refresh_tokens WHERE token_hash = ?1",
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.db)
+        .await?;
+
+        Ok(token)
+    }
+
+    async fn revoke_refresh_token(&self, token_id: &Uuid) -> Result<bool> {
+        let result = sqlx::query("UPDATE refresh_tokens SET revoked_at = ?1 WHERE id = ?2 AND revoked_at IS NULL")
+            .bind(Utc::now())
+            .bind(token_id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn revoke_token_family(&self, family_id: &Uuid) -> Result<()> {
+        sqlx::query("UPDATE refresh_tokens SET revoked_at = ?1 WHERE family_id = ?2 AND revoked_at IS NULL")
+            .bind(Utc::now())
+            .bind(family_id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn revoke_user_tokens(&self, user_id: &Uuid) -> Result<()> {
+        sqlx::query("UPDATE refresh_tokens SET revoked_at = ?1 WHERE user_id = ?2 AND revoked_at IS NULL")
+            .bind(Utc::now())
+            .bind(user_id)
+            .execute(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn revoke_access_token(&self, jti: &str, expires_at: DateTime<Utc>) -> Result<()> {
+        sqlx::query("INSERT INTO revoked_access_tokens (jti, expires_at) VALUES (?1, ?2) ON CONFLICT (jti) DO NOTHING")
+            .bind(jti)
+            .bind(expires_at)
+            .execute(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn is_access_token_revoked(&self, jti: &str) -> Result<bool> {
+        let revoked = sqlx::query("SELECT 1 FROM revoked_access_tokens WHERE jti = ?1")
+            .bind(jti)
+            .fetch_optional(&self.db)
+            .await?;
+
+        Ok(revoked.is_some())
+    }
+
+    async fn delete_expired_tokens(&self, now: DateTime<Utc>) -> Result<()> {
+        sqlx::query("DELETE FROM refresh_tokens WHERE expires_at < ?1")
+            .bind(now)
+            .execute(&self.db)
+            .await?;
+        sqlx::query("DELETE FROM revoked_access_tokens WHERE expires_at < ?1")
+            .bind(now)
+            .execute(&self.db)
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl Repository for SqliteRepository {
+    async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1")
+            .fetch_one(&self.db)
+            .await?;
+
+        Ok(())
+    }
+
+    fn pool_stats(&self) -> Option<PoolStats> {
+        Some(PoolStats {
+            size: self.db.size(),
+            idle: self.db.num_idle() as u32,
+            max: self.db.options().get_max_connections(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database;
+    use crate::error::AppError;
+    use crate::search::SearchQuery;
+
+    fn user(username: &str) -> User {
+        let now = Utc::now();
+        User {
+            id: Uuid::new_v4(),
+            username: username.to_string(),
+            email: format!("{}@example.com", username),
+            password_hash: "hash".to_string(),
+            role: Role::User,
+            banned_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn post(author: &User, title: &str) -> Post {
+        let now = Utc::now();
+        Post {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            content: "content".to_string(),
+            author_id: author.id,
+            status: PostStatus::Published,
+            published_at: Some(now),
+            version: 1,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    async fn repository() -> SqliteRepository {
+        SqliteRepository::new(database::connect_sqlite("sqlite::memory:").await.unwrap()).await.unwrap()
+    }
+
+    fn search(query: &str) -> PostSearch {
+        PostSearch { query: SearchQuery::parse(query).unwrap(), author_id: None, from: None, to: None }
+    }
+
+    #[tokio::test]
+    async fn test_unique_violation_is_conflict() {
+        let repository = repository().await;
+        repository.create_user(user("alice")).await.unwrap();
+
+        let mut duplicate = user("alice");
+        duplicate.email = "other@example.com".to_string();
+        let result = repository.create_user(duplicate).await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+    }
+
+    #[tokio::test]
+    async fn test_search_ranks_and_pages_in_sql() {
+        let repo = repository().await;
+        let alice = repo.create_user(user("alice")).await.unwrap();
+        for title in ["rust", "rust rust", "rust rust rust", "python"] {
+            repo.create_post(post(&alice, title), &[]).await.unwrap();
+        }
+
+        let rust = search("rust");
+        let first = repo.search_posts(&rust, PostSort::Relevance, None, 2).await.unwrap();
+        let after = PostCursor::scored(PostSort::Relevance, &first[1]);
+        let rest = repo.search_posts(&rust, PostSort::Relevance, Some(&after), 2).await.unwrap();
+
+        let titles: Vec<&str> = first.iter().chain(&rest).map(|hit| hit.post.title.as_str()).collect();
+        assert_eq!(titles, ["rust rust rust", "rust rust", "rust"]);
+        assert!(first[0].score > first[1].score && first[1].score > rest[0].score);
+        assert_eq!(repo.count_search_results(&rust).await.unwrap(), 3);
+        assert!(repo.search_posts(&search("java"), PostSort::Relevance, None, 2).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_index_follows_writes_and_is_rebuilt_on_startup() {
+        let repo = repository().await;
+        let alice = repo.create_user(user("alice")).await.unwrap();
+        let bob = repo.create_user(user("bob")).await.unwrap();
+        let hello = repo.create_post(post(&alice, "Hello rust"), &[]).await.unwrap();
+        repo.create_post(post(&bob, "Rust from bob"), &[]).await.unwrap();
+        let rust = search("rust");
+
+        let retitled = Post { title: "Hello world".to_string(), ..hello.clone() };
+        let retitled = repo.update_post(&retitled, None, None).await.unwrap().unwrap();
+        assert_eq!(repo.count_search_results(&rust).await.unwrap(), 1);
+        assert_eq!(repo.count_search_results(&search("world")).await.unwrap(), 1);
+
+        // Only published posts are indexed
+        repo.create_post(Post { status: PostStatus::Draft, ..post(&alice, "Draft about rust") }, &[]).await.unwrap();
+        let archived = Post { status: PostStatus::Archived, ..retitled };
+        let archived = repo.update_post(&archived, None, None).await.unwrap().unwrap();
+        assert_eq!(repo.count_search_results(&search("world")).await.unwrap(), 0);
+        repo.update_post(&Post { status: PostStatus::Published, ..archived }, None, None).await.unwrap().unwrap();
+        assert_eq!(repo.count_search_results(&rust).await.unwrap(), 1);
+
+        let reopened = SqliteRepository::new(repo.pool().clone()).await.unwrap();
+        assert_eq!(reopened.count_search_results(&rust).await.unwrap(), 1);
+
+        repo.delete_user(&bob.id).await.unwrap();
+        assert_eq!(repo.count_search_results(&rust).await.unwrap(), 0);
+        repo.delete_post(&hello.id).await.unwrap();
+        assert!(repo.search_index().is_empty());
+    }
+
+    #[test]
+    fn test_timestamp_text_sorts_in_time_order() {
+        let whole = DateTime::<Utc>::UNIX_EPOCH;
+        let later = whole + chrono::Duration::milliseconds(500);
+        assert!(timestamp(whole) < timestamp(later));
+        assert_eq!(timestamp(whole).len(), timestamp(later).len());
+    }
+
+    #[tokio::test]
+    async fn test_keyset_pagination_visits_every_post_once() {
+        let repo = repository().await;
+        let alice = repo.create_user(user("alice")).await.unwrap();
+        // Posts sharing one timestamp are told apart by their id
+        let created_at = Utc::now();
+        for title in ["b", "a", "c", "a", "b"] {
+            repo.create_post(Post { created_at, ..post(&alice, title) }, &[]).await.unwrap();
+        }
+
+        for sort in [PostSort::Newest, PostSort::Oldest, PostSort::MostLiked, PostSort::Title] {
+            let mut seen = Vec::new();
+            let mut after = None;
+            loop {
+                let page = repo.list_posts(&PostFilter::default(), sort, after.as_ref(), 2).await.unwrap();
+                let Some(last) = page.last() else { break };
+                after = Some(PostCursor::new(sort, last));
+                seen.extend(page.into_iter().map(|p| (p.title, p.id)));
+            }
+
+            let everything = repo.list_posts(&PostFilter::default(), sort, None, 10).await.unwrap();
+            assert_eq!(seen, everything.into_iter().map(|p| (p.title, p.id)).collect::<Vec<_>>());
+            assert_eq!(seen.len(), 5);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_edits_are_numbered_revisions() {
+        let repo = repository().await;
+        let alice = repo.create_user(user("alice")).await.unwrap();
+        let bob = repo.create_user(user("bob")).await.unwrap();
+        let hello = repo.create_post(post(&alice, "Hello"), &["axum".to_string()]).await.unwrap();
+
+        let edited = Post { content: "edited".to_string(), updated_at: Utc::now(), ..hello.clone() };
+        let edited = repo.update_post(&edited, None, Some(&bob.id)).await.unwrap().unwrap();
+        assert_eq!(edited.version, 2);
+        // Tags are written with the post and kept by edits that leave them out
+        let stored = repo.find_post_with_author(&hello.id).await.unwrap().unwrap();
+        assert_eq!(stored.tags.0, ["axum".to_string()]);
+        // Status changes are not revisions
+        let tags = ["rust".to_string()];
+        repo.update_post(&Post { status: PostStatus::Archived, ..edited.clone() }, Some(&tags), None).await.unwrap().unwrap();
+        // Updates based on an older version are refused
+        let stale = Post { content: "stale".to_string(), ..edited };
+        assert!(repo.update_post(&stale, Some(&["stale".to_string()]), Some(&alice.id)).await.unwrap().is_none());
+        let stored = repo.find_post_with_author(&hello.id).await.unwrap().unwrap();
+        assert_eq!((stored.version, stored.tags.0), (3, tags.to_vec()));
+
+        let revisions = repo.list_revisions(&hello.id).await.unwrap();
+        let numbers: Vec<i32> = revisions.iter().map(|revision| revision.revision).collect();
+        assert_eq!(numbers, [2, 1]);
+        assert_eq!((revisions[0].content.as_str(), revisions[0].editor_id), ("edited", Some(bob.id)));
+        assert_eq!((revisions[1].content.as_str(), revisions[1].editor_id), ("content", Some(alice.id)));
+
+        // They outlive their editor, and go with the post
+        repo.delete_user(&bob.id).await.unwrap();
+        assert_eq!(repo.find_revision(&hello.id, 2).await.unwrap().unwrap().editor_id, None);
+        repo.delete_post(&hello.id).await.unwrap();
+        assert!(repo.find_revision(&hello.id, 1).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_delete_user_cascades_to_posts() {
+        let repo = repository().await;
+        let alice = repo.create_user(user("alice")).await.unwrap();
+        let hello = repo.create_post(post(&alice, "Hello"), &[]).await.unwrap();
+
+        assert!(repo.delete_user(&alice.id).await.unwrap());
+        assert!(repo.find_post(&hello.id).await.unwrap().is_none());
+    }
+}
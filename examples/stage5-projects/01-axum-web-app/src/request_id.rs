@@ -0,0 +1,88 @@
+use axum::{
+    extract::{MatchedPath, Request},
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Span;
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+// Longer or odd-looking ids from clients are replaced rather than echoed
+const MAX_REQUEST_ID_LENGTH: usize = 64;
+
+// What error responses need to know about the request they answer
+#[derive(Debug, Clone)]
+pub struct RequestContext {
+    pub id: String,
+    pub path: String,
+}
+
+tokio::task_local! {
+    static CONTEXT: RequestContext;
+}
+
+// The context of the request being handled on this task, if any
+pub fn current() -> Option<RequestContext> {
+    CONTEXT.try_with(Clone::clone).ok()
+}
+
+// Gives every request an id, taken from its `x-request-id` header when that
+// is reasonable and generated otherwise, and returns it in the same header.
+// Handlers and extractors run inside this middleware's task-local scope, so
+// `current()` works wherever an `AppError` is turned into a response.
+pub async fn request_context(mut request: Request, next: Next) -> Response {
+    let id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_acceptable(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let context = RequestContext { id: id.clone(), path: request.uri().path().to_string() };
+    request.extensions_mut().insert(context.clone());
+
+    let mut response = CONTEXT.scope(context, next.run(request)).await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+
+    response
+}
+
+// The span `TraceLayer` opens for each request. It carries the request id, so
+// every log line written while handling the request can be matched with the
+// `x-request-id` and `request_id` the client sees.
+pub fn request_span(request: &Request) -> Span {
+    let request_id = request.extensions().get::<RequestContext>().map_or("", |context| context.id.as_str());
+    let route = request.extensions().get::<MatchedPath>().map(MatchedPath::as_str);
+    tracing::info_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        route,
+        request_id,
+    )
+}
+
+fn is_acceptable(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LENGTH
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acceptable_request_ids() {
+        assert!(is_acceptable("3f1c2a9e-0b7d-4d55-9a8e-2c6f1b0d9e41"));
+        assert!(is_acceptable("req_42.retry-1"));
+        assert!(!is_acceptable(""));
+        assert!(!is_acceptable("has space"));
+        assert!(!is_acceptable(&"x".repeat(MAX_REQUEST_ID_LENGTH + 1)));
+    }
+}
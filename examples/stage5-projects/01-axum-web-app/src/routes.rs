@@ -0,0 +1,118 @@
+use axum::{
+    middleware,
+    routing::{delete, get, post, put},
+    Router,
+};
+use axum::http::{header, HeaderValue, Method};
+use tower_http::{
+    cors::{AllowOrigin, CorsLayer},
+    trace::TraceLayer,
+};
+
+use crate::auth::{auth_middleware, optional_auth_middleware};
+use crate::config::CorsConfig;
+use crate::metrics::track_metrics;
+use crate::openapi::swagger_ui;
+use crate::rate_limit::rate_limit_middleware;
+use crate::request_id::{request_context, request_span, REQUEST_ID_HEADER};
+use crate::handlers::{
+    admin_handlers::{list_users, ban_user, unban_user, delete_any_post},
+    comment_handlers::{get_comments, create_comment, delete_comment},
+    health_handlers::{health_check, readiness_check, liveness_check, metrics},
+    user_handlers::{
+        register, login, refresh_token, logout, get_user, get_current_user, update_current_user, delete_current_user,
+    },
+    post_handlers::{
+        create_post, get_post, get_posts, update_post, delete_post, search_posts, get_user_posts,
+        get_current_user_posts, get_tag_posts, like_post, unlike_post, get_post_revisions, diff_post_revisions,
+        restore_post_revision,
+    },
+};
+use crate::repositories::Repository;
+use crate::state::AppState;
+
+// Build the API router: health checks and metrics, rate-limited auth routes,
+// public routes, public routes that show unpublished posts to those allowed
+// to see them, routes protected by `auth_middleware` and the API
+// documentation. From the outside in, every request passes `request_context`,
+// so that every response, errors included, carries a request id; a tracing
+// span tagged with that id; and `track_metrics`.
+pub fn create_router<R: Repository>(state: AppState<R>) -> Router {
+    let auth_routes = Router::new()
+        .route("/api/auth/register", post(register::<R>))
+        .route("/api/auth/login", post(login::<R>))
+        .route("/api/auth/refresh", post(refresh_token::<R>))
+        .route_layer(middleware::from_fn_with_state(state.clone(), rate_limit_middleware::<R>));
+
+    let public_routes = Router::new()
+        .route("/api/posts", get(get_posts::<R>))
+        .route("/api/posts/search", get(search_posts::<R>))
+        .route("/api/tags/:tag/posts", get(get_tag_posts::<R>))
+        .route("/api/users/:id", get(get_user::<R>))
+        .route("/api/users/:id/posts", get(get_user_posts::<R>));
+
+    let viewer_routes = Router::new()
+        .route("/api/posts/:id", get(get_post::<R>))
+        .route("/api/posts/:id/comments", get(get_comments::<R>))
+        .route_layer(middleware::from_fn_with_state(state.clone(), optional_auth_middleware::<R>));
+
+    let protected_routes = Router::new()
+        .route("/api/auth/logout", post(logout::<R>))
+        .route("/api/posts", post(create_post::<R>))
+        .route("/api/posts/:id", put(update_post::<R>).delete(delete_post::<R>))
+        .route("/api/posts/:id/comments", post(create_comment::<R>))
+        .route("/api/posts/:id/like", post(like_post::<R>).delete(unlike_post::<R>))
+        .route("/api/posts/:id/revisions", get(get_post_revisions::<R>))
+        .route("/api/posts/:id/revisions/diff", get(diff_post_revisions::<R>))
+        .route("/api/posts/:id/revisions/:revision/restore", post(restore_post_revision::<R>))
+        .route("/api/comments/:id", delete(delete_comment::<R>))
+        .route(
+            "/api/users/me",
+            get(get_current_user::<R>).put(update_current_user::<R>).delete(delete_current_user::<R>),
+        )
+        .route("/api/users/me/posts", get(get_current_user_posts::<R>))
+        // Moderation; roles are checked per action by `auth::policy`
+        .route("/api/admin/users", get(list_users::<R>))
+        .route("/api/admin/users/:id/ban", post(ban_user::<R>).delete(unban_user::<R>))
+        .route("/api/admin/posts/:id", delete(delete_any_post::<R>))
+        .route_layer(middleware::from_fn_with_state(state.clone(), auth_middleware::<R>));
+
+    Router::new()
+        // Health checks
+        .route("/health", get(health_check::<R>))
+        .route("/ready", get(readiness_check::<R>))
+        .route("/live", get(liveness_check))
+        .route("/metrics", get(metrics::<R>))
+        .merge(auth_routes)
+        .merge(public_routes)
+        .merge(viewer_routes)
+        .merge(protected_routes)
+        .merge(swagger_ui())
+        .layer(middleware::from_fn_with_state(state.clone(), track_metrics::<R>))
+        .layer(TraceLayer::new_for_http().make_span_with(request_span))
+        .layer(middleware::from_fn(request_context))
+        .with_state(state)
+}
+
+// CORS for the origins in the configuration. Origins were validated when the
+// configuration was loaded.
+pub fn cors_layer(config: &CorsConfig) -> CorsLayer {
+    let origins = if config.allows_any_origin() {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(config.allowed_origins.iter().filter_map(|origin| HeaderValue::from_str(origin).ok()))
+    };
+
+    CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE, Method::OPTIONS])
+        .allow_headers([
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+            header::IF_MATCH,
+            header::IF_NONE_MATCH,
+            REQUEST_ID_HEADER,
+        ])
+        .expose_headers([REQUEST_ID_HEADER, header::LINK, header::RETRY_AFTER, header::ETAG])
+        .max_age(config.max_age.to_std().unwrap_or_default())
+}
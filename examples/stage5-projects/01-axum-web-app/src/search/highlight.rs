@@ -0,0 +1,93 @@
+use crate::search::{tokenize, SearchQuery, Token};
+
+// Words shown in a snippet, and how many of them come before the first match
+const SNIPPET_WORDS: usize = 30;
+const SNIPPET_LEAD: usize = 8;
+
+// The whole text, HTML-escaped, with matching words wrapped in <mark>
+pub fn highlight(text: &str, query: &SearchQuery) -> String {
+    let tokens = tokenize(text);
+    render(text, &tokens, 0, text.len(), query)
+}
+
+// About `SNIPPET_WORDS` words of the text around the first match, HTML-escaped
+// with matching words wrapped in <mark>, and "…" where text was cut off
+pub fn snippet(text: &str, query: &SearchQuery) -> String {
+    let tokens = tokenize(text);
+    let first_match = tokens.iter().position(|token| query.matches_word(&token.term)).unwrap_or(0);
+
+    let first = first_match.saturating_sub(SNIPPET_LEAD);
+    let last = (first + SNIPPET_WORDS).min(tokens.len());
+    let start = if first == 0 { 0 } else { tokens[first].start };
+    let end = if last == tokens.len() { text.len() } else { tokens[last - 1].end };
+
+    let mut snippet = render(text, &tokens[first..last], start, end, query);
+    if start > 0 {
+        snippet.insert(0, '…');
+    }
+    if end < text.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+// Renders text[start..end], which contains exactly `tokens`
+fn render(text: &str, tokens: &[Token], start: usize, end: usize, query: &SearchQuery) -> String {
+    let mut html = String::with_capacity(end - start);
+    let mut position = start;
+
+    for token in tokens.iter().filter(|token| query.matches_word(&token.term)) {
+        escape_into(&mut html, &text[position..token.start]);
+        html.push_str("<mark>");
+        escape_into(&mut html, &text[token.start..token.end]);
+        html.push_str("</mark>");
+        position = token.end;
+    }
+    escape_into(&mut html, &text[position..end]);
+
+    html
+}
+
+fn escape_into(html: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            _ => html.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_escapes_and_marks() {
+        let query = SearchQuery::parse("rust* \"web app\"").unwrap();
+        assert_eq!(
+            highlight("<b>Rustacean</b> builds a web-app & more", &query),
+            "&lt;b&gt;<mark>Rustacean</mark>&lt;/b&gt; builds a <mark>web</mark>-<mark>app</mark> &amp; more"
+        );
+    }
+
+    #[test]
+    fn test_snippet_centers_on_first_match() {
+        let query = SearchQuery::parse("needle").unwrap();
+        let words: Vec<String> = (0..100).map(|i| format!("w{}", i)).collect();
+
+        let mut text = words.clone();
+        text[50] = "Needle".to_string();
+        let snippet = snippet(&text.join(" "), &query);
+        assert!(snippet.starts_with("…w42 ") && snippet.ends_with(" w71…"));
+        assert!(snippet.contains("<mark>Needle</mark>"));
+
+        // Without a match the snippet is the start of the text
+        let short = "Just a short text.";
+        assert_eq!(super::snippet(short, &query), short);
+        assert!(super::snippet(&words.join(" "), &query).starts_with("w0 "));
+    }
+}
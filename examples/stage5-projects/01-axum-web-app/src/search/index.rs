@@ -0,0 +1,273 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::models::post::Post;
+use crate::search::{tokenize, PostSearch, SearchTerm};
+
+// A match in the title counts this many times as much as one in the content
+const TITLE_WEIGHT: f64 = 2.5;
+
+// Where a term occurs in one post, as word positions
+#[derive(Debug, Default)]
+struct Positions {
+    title: Vec<usize>,
+    content: Vec<usize>,
+}
+
+#[derive(Debug)]
+struct IndexedPost {
+    author_id: Uuid,
+    created_at: DateTime<Utc>,
+    terms: BTreeSet<String>,
+}
+
+// An inverted index from words to the posts containing them, for backends
+// without built-in full-text search. Callers keep it in step with the posts:
+// `insert` on create and update, `remove` on delete.
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    // Ordered so that prefix queries are a range scan
+    postings: BTreeMap<String, HashMap<Uuid, Positions>>,
+    posts: HashMap<Uuid, IndexedPost>,
+}
+
+impl SearchIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.posts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.posts.is_empty()
+    }
+
+    // Adds a post, replacing what was indexed for it before
+    pub fn insert(&mut self, post: &Post) {
+        self.remove(&post.id);
+
+        let mut terms = BTreeSet::new();
+        for (position, token) in tokenize(&post.title).into_iter().enumerate() {
+            self.positions(&token.term, post.id).title.push(position);
+            terms.insert(token.term);
+        }
+        for (position, token) in tokenize(&post.content).into_iter().enumerate() {
+            self.positions(&token.term, post.id).content.push(position);
+            terms.insert(token.term);
+        }
+
+        self.posts.insert(post.id, IndexedPost { author_id: post.author_id, created_at: post.created_at, terms });
+    }
+
+    pub fn remove(&mut self, post_id: &Uuid) {
+        let Some(post) = self.posts.remove(post_id) else {
+            return;
+        };
+
+        for term in post.terms {
+            if let Some(posts) = self.postings.get_mut(&term) {
+                posts.remove(post_id);
+                if posts.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+    }
+
+    // Removes every post of an author, for when the author is deleted
+    pub fn remove_author(&mut self, author_id: &Uuid) {
+        let posts: Vec<Uuid> = self
+            .posts
+            .iter()
+            .filter(|(_, post)| post.author_id == *author_id)
+            .map(|(id, _)| *id)
+            .collect();
+        for post_id in posts {
+            self.remove(&post_id);
+        }
+    }
+
+    // Every post that matches all terms and passes the filters, with its
+    // score. Each term adds idf * tf / (tf + 1), where tf counts title
+    // matches `TITLE_WEIGHT` times, so repeating a word has diminishing returns
+    // and rare words weigh more than common ones.
+    pub fn search(&self, search: &PostSearch) -> HashMap<Uuid, f64> {
+        let mut scores: Option<HashMap<Uuid, f64>> = None;
+
+        for term in &search.query.terms {
+            let frequencies = self.term_frequencies(term);
+            let idf = (1.0 + self.posts.len() as f64 / frequencies.len().max(1) as f64).ln();
+
+            let matched = frequencies.into_iter().filter_map(|(post_id, tf)| {
+                let previous = match &scores {
+                    Some(scores) => *scores.get(&post_id)?,
+                    None => 0.0,
+                };
+                Some((post_id, previous + idf * tf / (tf + 1.0)))
+            });
+            scores = Some(matched.collect());
+        }
+
+        let mut scores = scores.unwrap_or_default();
+        scores.retain(|post_id, _| {
+            self.posts.get(post_id).is_some_and(|post| search.accepts(&post.author_id, &post.created_at))
+        });
+        scores
+    }
+
+    fn positions(&mut self, term: &str, post_id: Uuid) -> &mut Positions {
+        self.postings.entry(term.to_string()).or_default().entry(post_id).or_default()
+    }
+
+    // Weighted number of matches of one term in each post that has any
+    fn term_frequencies(&self, term: &SearchTerm) -> HashMap<Uuid, f64> {
+        let weigh = |title: usize, content: usize| TITLE_WEIGHT * title as f64 + content as f64;
+        let mut frequencies = HashMap::new();
+
+        match term {
+            SearchTerm::Word(word) => {
+                for (post_id, positions) in self.postings.get(word).into_iter().flatten() {
+                    frequencies.insert(*post_id, weigh(positions.title.len(), positions.content.len()));
+                }
+            }
+            SearchTerm::Prefix(prefix) => {
+                let words = self.postings.range(prefix.clone()..).take_while(|(word, _)| word.starts_with(prefix.as_str()));
+                for (_, posts) in words {
+                    for (post_id, positions) in posts {
+                        *frequencies.entry(*post_id).or_default() += weigh(positions.title.len(), positions.content.len());
+                    }
+                }
+            }
+            SearchTerm::Phrase(words) => {
+                let Some(postings) = words.iter().map(|word| self.postings.get(word)).collect::<Option<Vec<_>>>() else {
+                    return frequencies;
+                };
+                for post_id in postings[0].keys() {
+                    let Some(positions) = postings.iter().map(|posts| posts.get(post_id)).collect::<Option<Vec<_>>>() else {
+                        continue;
+                    };
+                    let title = phrase_count(positions.iter().map(|p| &p.title));
+                    let content = phrase_count(positions.iter().map(|p| &p.content));
+                    if title + content > 0 {
+                        frequencies.insert(*post_id, weigh(title, content));
+                    }
+                }
+            }
+        }
+
+        frequencies
+    }
+}
+
+// How often the words occur one after another, given each word's positions
+fn phrase_count<'a>(mut positions: impl Iterator<Item = &'a Vec<usize>>) -> usize {
+    let Some(first) = positions.next() else {
+        return 0;
+    };
+    let rest: Vec<HashSet<usize>> = positions.map(|p| p.iter().copied().collect()).collect();
+
+    first
+        .iter()
+        .filter(|start| rest.iter().enumerate().all(|(i, p)| p.contains(&(*start + i + 1))))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::post::PostStatus;
+    use crate::search::SearchQuery;
+    use chrono::Duration;
+
+    fn post(title: &str, content: &str, author_id: Uuid, days_ago: i64) -> Post {
+        let created_at = Utc::now() - Duration::days(days_ago);
+        Post {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            content: content.to_string(),
+            author_id,
+            status: PostStatus::Published,
+            published_at: Some(created_at),
+            version: 1,
+            created_at,
+            updated_at: created_at,
+        }
+    }
+
+    fn search(query: &str) -> PostSearch {
+        PostSearch { query: SearchQuery::parse(query).unwrap(), author_id: None, from: None, to: None }
+    }
+
+    #[test]
+    fn test_word_prefix_and_phrase_queries() {
+        let author = Uuid::new_v4();
+        let mut index = SearchIndex::new();
+        let web = post("Axum web framework", "Routing in a web framework built on tower", author, 0);
+        let rust = post("Rust ownership", "Borrowing, framework-free, in plain rust", author, 0);
+        index.insert(&web);
+        index.insert(&rust);
+
+        let ids = |query: &str| {
+            let mut ids: Vec<Uuid> = index.search(&search(query)).into_keys().collect();
+            ids.sort();
+            ids
+        };
+        let mut both = vec![web.id, rust.id];
+        both.sort();
+
+        assert_eq!(ids("FRAMEWORK"), both);
+        assert_eq!(ids("framework rust"), vec![rust.id]);
+        assert_eq!(ids("tow*"), vec![web.id]);
+        assert_eq!(ids("\"web framework\""), vec![web.id]);
+        assert!(ids("\"framework web\"").is_empty());
+        assert!(ids("missing").is_empty());
+    }
+
+    #[test]
+    fn test_title_matches_and_repeats_rank_higher() {
+        let author = Uuid::new_v4();
+        let mut index = SearchIndex::new();
+        let in_title = post("Tokio tutorial", "An introduction", author, 0);
+        let in_content = post("An introduction", "Tokio tutorial", author, 0);
+        let repeated = post("Notes", "tokio tokio tokio tokio", author, 0);
+        for p in [&in_title, &in_content, &repeated] {
+            index.insert(p);
+        }
+
+        let scores = index.search(&search("tokio"));
+        assert!(scores[&in_title.id] > scores[&in_content.id]);
+        assert!(scores[&repeated.id] > scores[&in_content.id]);
+    }
+
+    #[test]
+    fn test_updates_removals_and_filters() {
+        let (alice, bob) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut index = SearchIndex::new();
+        let mut old = post("Old rust post", "", alice, 30);
+        let recent = post("Recent rust post", "", bob, 1);
+        index.insert(&old);
+        index.insert(&recent);
+
+        let mut filtered = search("rust");
+        filtered.author_id = Some(alice);
+        assert_eq!(index.search(&filtered).len(), 1);
+        let mut filtered = search("rust");
+        filtered.from = Some(Utc::now() - Duration::days(7));
+        assert!(index.search(&filtered).contains_key(&recent.id));
+        assert_eq!(index.search(&filtered).len(), 1);
+
+        old.title = "Old post".to_string();
+        index.insert(&old);
+        assert_eq!(index.search(&search("rust")).len(), 1);
+        assert_eq!(index.search(&search("old")).len(), 1);
+
+        index.remove_author(&bob);
+        assert!(index.search(&search("rust")).is_empty());
+        index.remove(&old.id);
+        assert!(index.is_empty() && index.postings.is_empty());
+    }
+}
@@ -0,0 +1,230 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::error::{AppError, Result};
+use crate::models::search::SearchParams;
+
+pub mod highlight;
+pub mod index;
+
+pub use index::SearchIndex;
+
+// Full-text search over posts. PostgreSQL matches and ranks with a `tsvector`
+// column (migration 006); the SQLite and in-memory backends use `SearchIndex`.
+// Both see the same words: `tokenize` splits on anything that is not a letter
+// or digit and lowercases, like PostgreSQL's `simple` text search config.
+
+// Bounds the work one query can cause
+pub const MAX_SEARCH_TERMS: usize = 10;
+
+// A word of a text, with its byte range in that text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub start: usize,
+    pub end: usize,
+    pub term: String,
+}
+
+pub fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+
+    for (i, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        match (start, c.is_alphanumeric()) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                tokens.push(Token { start: s, end: i, term: text[s..i].to_lowercase() });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    tokens
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchTerm {
+    Word(String),
+    // `rust*` matches rust, rustacean, ...
+    Prefix(String),
+    // `"web framework"` matches the words next to each other, in order
+    Phrase(Vec<String>),
+}
+
+// A parsed search. All terms must match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchQuery {
+    pub terms: Vec<SearchTerm>,
+}
+
+impl SearchQuery {
+    // Splits on whitespace outside double quotes. A trailing `*` makes a
+    // prefix, and text that tokenizes to several words (`web-dev`) is
+    // matched as a phrase.
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut terms = Vec::new();
+
+        for (i, part) in input.split('"').enumerate() {
+            if i % 2 == 1 {
+                terms.extend(phrase(tokenize(part).into_iter().map(|t| t.term).collect()));
+                continue;
+            }
+
+            for chunk in part.split_whitespace() {
+                let (chunk, prefix) = match chunk.strip_suffix('*') {
+                    Some(chunk) => (chunk, true),
+                    None => (chunk, false),
+                };
+                let mut words: Vec<String> = tokenize(chunk).into_iter().map(|t| t.term).collect();
+                match (prefix, words.pop()) {
+                    (true, Some(last)) => {
+                        terms.extend(phrase(words));
+                        terms.push(SearchTerm::Prefix(last));
+                    }
+                    (false, Some(last)) => {
+                        words.push(last);
+                        terms.extend(phrase(words));
+                    }
+                    (_, None) => {}
+                }
+            }
+        }
+
+        if terms.is_empty() {
+            return Err(AppError::Validation("Search query must contain at least one word".to_string()));
+        }
+        if terms.len() > MAX_SEARCH_TERMS {
+            return Err(AppError::Validation(format!(
+                "Search query may contain at most {} terms",
+                MAX_SEARCH_TERMS
+            )));
+        }
+
+        Ok(Self { terms })
+    }
+
+    // The query in `to_tsquery` syntax. Terms only contain letters and
+    // digits, so quoting them is enough to keep the syntax intact.
+    pub fn to_tsquery(&self) -> String {
+        let quote = |word: &String| format!("'{}'", word);
+        self.terms
+            .iter()
+            .map(|term| match term {
+                SearchTerm::Word(word) => quote(word),
+                SearchTerm::Prefix(prefix) => format!("{}:*", quote(prefix)),
+                SearchTerm::Phrase(words) => {
+                    format!("({})", words.iter().map(quote).collect::<Vec<_>>().join(" <-> "))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" & ")
+    }
+
+    // Whether a single word of a text should be highlighted
+    pub fn matches_word(&self, word: &str) -> bool {
+        self.terms.iter().any(|term| match term {
+            SearchTerm::Word(w) => w == word,
+            SearchTerm::Prefix(prefix) => word.starts_with(prefix.as_str()),
+            SearchTerm::Phrase(words) => words.iter().any(|w| w == word),
+        })
+    }
+}
+
+fn phrase(mut words: Vec<String>) -> Option<SearchTerm> {
+    match words.len() {
+        0 => None,
+        1 => words.pop().map(SearchTerm::Word),
+        _ => Some(SearchTerm::Phrase(words)),
+    }
+}
+
+// A search with its filters, as passed to the repositories
+#[derive(Debug, Clone)]
+pub struct PostSearch {
+    pub query: SearchQuery,
+    pub author_id: Option<Uuid>,
+    // Both bounds are inclusive
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl PostSearch {
+    pub fn accepts(&self, author_id: &Uuid, created_at: &DateTime<Utc>) -> bool {
+        self.author_id.is_none_or(|id| id == *author_id)
+            && self.from.is_none_or(|from| *created_at >= from)
+            && self.to.is_none_or(|to| *created_at <= to)
+    }
+}
+
+impl TryFrom<SearchParams> for PostSearch {
+    type Error = AppError;
+
+    fn try_from(params: SearchParams) -> Result<Self> {
+        if let (Some(from), Some(to)) = (params.from, params.to)
+            && from > to
+        {
+            return Err(AppError::Validation("`from` must not be after `to`".to_string()));
+        }
+
+        Ok(Self {
+            query: SearchQuery::parse(&params.q)?,
+            author_id: params.author,
+            from: params.from,
+            to: params.to,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str) -> Vec<String> {
+        tokenize(text).into_iter().map(|t| t.term).collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(words("Hello, Axum-web_app! Ünïcode 2024"), ["hello", "axum", "web", "app", "ünïcode", "2024"]);
+        assert!(words(" ... ").is_empty());
+
+        let tokens = tokenize("héllo wörld");
+        assert_eq!((tokens[1].start, tokens[1].end), (7, 13));
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let query = SearchQuery::parse(r#"Rust "web  Framework" tok* web-dev"#).unwrap();
+        assert_eq!(
+            query.terms,
+            vec![
+                SearchTerm::Word("rust".to_string()),
+                SearchTerm::Phrase(vec!["web".to_string(), "framework".to_string()]),
+                SearchTerm::Prefix("tok".to_string()),
+                SearchTerm::Phrase(vec!["web".to_string(), "dev".to_string()]),
+            ]
+        );
+        assert_eq!(query.to_tsquery(), "'rust' & ('web' <-> 'framework') & 'tok':* & ('web' <-> 'dev')");
+
+        // An unterminated quote runs to the end; a lone quoted word is a word
+        let query = SearchQuery::parse(r#""axum" "tower serv"#).unwrap();
+        assert_eq!(query.to_tsquery(), "'axum' & ('tower' <-> 'serv')");
+
+        assert!(SearchQuery::parse(" * \"\" !!").is_err());
+        assert!(SearchQuery::parse(&"word ".repeat(MAX_SEARCH_TERMS + 1)).is_err());
+    }
+
+    #[test]
+    fn test_date_range_is_validated() {
+        let params = |from: &str, to: &str| SearchParams {
+            q: "rust".to_string(),
+            author: None,
+            from: Some(from.parse().unwrap()),
+            to: Some(to.parse().unwrap()),
+        };
+
+        assert!(PostSearch::try_from(params("2024-01-01T00:00:00Z", "2024-01-01T00:00:00Z")).is_ok());
+        assert!(PostSearch::try_from(params("2024-02-01T00:00:00Z", "2024-01-01T00:00:00Z")).is_err());
+    }
+}
@@ -0,0 +1,144 @@
+use crate::error::Result;
+use crate::models::comment::CreateCommentRequest;
+use crate::models::post::CreatePostRequest;
+use crate::models::user::CreateUserRequest;
+use crate::repositories::Repository;
+use crate::state::AppState;
+
+// Demo accounts, created as (username, email)
+pub const DEMO_USERS: &[(&str, &str)] = &[
+    ("alice", "alice@example.com"),
+    ("bob", "bob@example.com"),
+    ("carol", "carol@example.com"),
+];
+
+// (author, title, content, tags), with authors indexing `DEMO_USERS`
+const DEMO_POSTS: &[(usize, &str, &str, &[&str])] = &[
+    (
+        0,
+        "Getting started with Axum",
+        "Axum builds on tower and hyper. Routers, extractors and middleware are all services, so they compose well.",
+        &["rust", "axum"],
+    ),
+    (
+        0,
+        "Choosing between PostgreSQL and SQLite",
+        "SQLite is perfect for local development and small deployments; PostgreSQL adds full-text search and scales further.",
+        &["databases", "rust"],
+    ),
+    (
+        1,
+        "Notes on graceful shutdown",
+        "Stop accepting connections, let in-flight requests finish, then close the database pool.",
+        &["operations"],
+    ),
+    (
+        2,
+        "Hello from Carol",
+        "My first post on this blog. Comments welcome!",
+        &[],
+    ),
+];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SeedSummary {
+    pub users: usize,
+    pub posts: usize,
+    pub comments: usize,
+    pub likes: usize,
+}
+
+// Fills an empty database with demo users, posts, tags, comments and likes,
+// all signing in with `password`. Goes through the services, so the data is
+// valid however the schema evolves. Returns None, changing nothing, if the
+// demo users already exist.
+pub async fn seed<R: Repository>(state: &AppState<R>, password: &str) -> Result<Option<SeedSummary>> {
+    let (first_user, _) = DEMO_USERS[0];
+    if state.repository.find_user_by_username(first_user).await?.is_some() {
+        return Ok(None);
+    }
+
+    let mut summary = SeedSummary::default();
+    let mut users = Vec::new();
+    for (username, email) in DEMO_USERS {
+        let user = state
+            .user_service
+            .create_user(CreateUserRequest {
+                username: username.to_string(),
+                email: email.to_string(),
+                password: password.to_string(),
+            })
+            .await?;
+        users.push(user.id);
+        summary.users += 1;
+    }
+
+    for (index, (author, title, content, tags)) in DEMO_POSTS.iter().enumerate() {
+        let post = state
+            .post_service
+            .create_post(
+                &users[*author],
+                CreatePostRequest {
+                    title: title.to_string(),
+                    content: content.to_string(),
+                    tags: tags.iter().map(|tag| tag.to_string()).collect(),
+                    status: None,
+                },
+            )
+            .await?;
+        summary.posts += 1;
+
+        // Everyone else likes the post, and the next user starts a thread
+        // that the author replies to
+        for user_id in users.iter().filter(|user_id| **user_id != users[*author]) {
+            state.post_service.like_post(&post.id, user_id).await?;
+            summary.likes += 1;
+        }
+
+        let commenter = users[(*author + 1 + index) % users.len()];
+        let comment = state
+            .comment_service
+            .create_comment(
+                &post.id,
+                &commenter,
+                CreateCommentRequest { content: format!("Thanks for writing about \"{}\"!", title), parent_id: None },
+            )
+            .await?;
+        state
+            .comment_service
+            .create_comment(
+                &post.id,
+                &users[*author],
+                CreateCommentRequest { content: "Glad it helped.".to_string(), parent_id: Some(comment.id) },
+            )
+            .await?;
+        summary.comments += 2;
+    }
+
+    Ok(Some(summary))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::JwtService;
+    use crate::models::pagination::PageQuery;
+    use crate::repositories::InMemoryRepository;
+
+    #[tokio::test]
+    async fn test_seed_once() {
+        let state = AppState::new(InMemoryRepository::new(), JwtService::new("seed-test-secret"));
+
+        let summary = seed(&state, "demo-password").await.unwrap().unwrap();
+        assert_eq!(summary, SeedSummary { users: 3, posts: 4, comments: 8, likes: 8 });
+
+        let posts = state.post_service.get_posts(PageQuery::default()).await.unwrap();
+        assert_eq!(posts.items.len(), 4);
+        assert!(posts.items.iter().all(|post| post.like_count == 2 && post.comment_count == 2));
+
+        // Running it again leaves the data alone
+        assert_eq!(seed(&state, "demo-password").await.unwrap(), None);
+        let posts = state.post_service.get_posts(PageQuery::default()).await.unwrap();
+        assert_eq!(posts.items.len(), 4);
+    }
+}
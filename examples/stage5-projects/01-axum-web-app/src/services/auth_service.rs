@@ -0,0 +1,189 @@
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::auth::JwtService;
+use crate::error::{AppError, Result};
+use crate::models::token::{RefreshToken, TokenResponse};
+use crate::models::user::{Claims, User};
+use crate::repositories::{TokenRepository, UserRepository};
+
+// 256 random bits, hex encoded
+fn generate_refresh_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+// Refresh tokens are random rather than user-chosen, so a plain SHA-256 is
+// enough to keep them useless if the table leaks
+fn hash_refresh_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+// Issues, rotates and revokes access and refresh tokens
+#[derive(Clone)]
+pub struct AuthService<R> {
+    repository: R,
+    jwt_service: JwtService,
+}
+
+impl<R: UserRepository + TokenRepository> AuthService<R> {
+    pub fn new(repository: R, jwt_service: JwtService) -> Self {
+        Self { repository, jwt_service }
+    }
+
+    // Start a new session (refresh token family) for a user who just logged in
+    pub async fn issue_tokens(&self, user: &User) -> Result<TokenResponse> {
+        self.issue_tokens_in_family(user, Uuid::new_v4()).await
+    }
+
+    // Exchange a refresh token for a new access token and refresh token.
+    // The presented token is revoked; presenting it again revokes the family.
+    pub async fn refresh(&self, refresh_token: &str) -> Result<TokenResponse> {
+        let stored = self.repository.find_refresh_token(&hash_refresh_token(refresh_token)).await?
+            .ok_or_else(|| AppError::Authentication("Invalid refresh token".to_string()))?;
+
+        if stored.revoked_at.is_some() || !self.repository.revoke_refresh_token(&stored.id).await? {
+            tracing::warn!(
+                user_id = %stored.user_id,
+                family_id = %stored.family_id,
+                "Refresh token reuse detected, revoking the session"
+            );
+            self.repository.revoke_token_family(&stored.family_id).await?;
+            return Err(AppError::Authentication("Refresh token has been revoked".to_string()));
+        }
+
+        if stored.expires_at < Utc::now() {
+            return Err(AppError::Authentication("Refresh token has expired".to_string()));
+        }
+
+        let user = self.repository.find_user(&stored.user_id).await?
+            .ok_or_else(|| AppError::Authentication("Invalid refresh token".to_string()))?;
+        if user.banned_at.is_some() {
+            return Err(AppError::Authorization("Account is banned".to_string()));
+        }
+
+        self.issue_tokens_in_family(&user, stored.family_id).await
+    }
+
+    // Revoke the access token in `claims` and, if given, the session of the
+    // refresh token. Refresh tokens that are unknown or belong to someone
+    // else are ignored.
+    pub async fn logout(&self, claims: &Claims, refresh_token: Option<&str>) -> Result<()> {
+        let expires_at = DateTime::from_timestamp(claims.exp as i64, 0).unwrap_or_else(Utc::now);
+        self.repository.revoke_access_token(&claims.jti, expires_at).await?;
+
+        if let Some(refresh_token) = refresh_token
+            && let Some(stored) = self.repository.find_refresh_token(&hash_refresh_token(refresh_token)).await?
+            && stored.user_id.to_string() == claims.sub
+        {
+            self.repository.revoke_token_family(&stored.family_id).await?;
+        }
+
+        self.repository.delete_expired_tokens(Utc::now()).await
+    }
+
+    // Validate an access token, check that it has not been revoked and that
+    // its user still exists and is not banned
+    pub async fn authenticate(&self, token: &str) -> Result<Claims> {
+        let claims = self.jwt_service.validate_token(token)?;
+
+        if self.repository.is_access_token_revoked(&claims.jti).await? {
+            return Err(AppError::Authentication("Token has been revoked".to_string()));
+        }
+
+        let user_id = Uuid::parse_str(&claims.sub)
+            .map_err(|_| AppError::Authentication("Invalid user ID in token".to_string()))?;
+        let user = self.repository.find_user(&user_id).await?
+            .ok_or_else(|| AppError::Authentication("User no longer exists".to_string()))?;
+        if user.banned_at.is_some() {
+            return Err(AppError::Authorization("Account is banned".to_string()));
+        }
+
+        Ok(claims)
+    }
+
+    async fn issue_tokens_in_family(&self, user: &User, family_id: Uuid) -> Result<TokenResponse> {
+        let access_token = self.jwt_service.generate_token(&user.id.to_string(), &user.username, user.role)?;
+        let refresh_token = generate_refresh_token();
+
+        let now = Utc::now();
+        self.repository.create_refresh_token(RefreshToken {
+            id: Uuid::new_v4(),
+            user_id: user.id,
+            family_id,
+            token_hash: hash_refresh_token(&refresh_token),
+            expires_at: now + self.jwt_service.refresh_token_ttl(),
+            created_at: now,
+            revoked_at: None,
+        }).await?;
+
+        Ok(TokenResponse {
+            access_token,
+            refresh_token,
+            token_type: "Bearer".to_string(),
+            expires_in: self.jwt_service.access_token_ttl().num_seconds(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::user::Role;
+    use crate::repositories::InMemoryRepository;
+    use chrono::Duration;
+
+    async fn service_with_user(jwt_service: JwtService) -> (AuthService<InMemoryRepository>, User) {
+        let repository = InMemoryRepository::new();
+        let now = Utc::now();
+        let user = repository.create_user(User {
+            id: Uuid::new_v4(),
+            username: "alice".to_string(),
+            email: "alice@example.com".to_string(),
+            password_hash: "hash".to_string(),
+            role: Role::User,
+            banned_at: None,
+            created_at: now,
+            updated_at: now,
+        }).await.unwrap();
+        (AuthService::new(repository, jwt_service), user)
+    }
+
+    #[test]
+    fn test_refresh_tokens_are_random_and_hashed() {
+        let token = generate_refresh_token();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, generate_refresh_token());
+        assert_eq!(hash_refresh_token(&token), hash_refresh_token(&token));
+        assert_ne!(hash_refresh_token(&token), token);
+    }
+
+    #[tokio::test]
+    async fn test_reuse_revokes_the_family() {
+        let (service, user) = service_with_user(JwtService::new("secret")).await;
+        let first = service.issue_tokens(&user).await.unwrap();
+        let other_session = service.issue_tokens(&user).await.unwrap();
+
+        let second = service.refresh(&first.refresh_token).await.unwrap();
+        assert!(service.refresh(&first.refresh_token).await.is_err());
+        assert!(service.refresh(&second.refresh_token).await.is_err());
+
+        // Other sessions of the same user are untouched
+        assert!(service.refresh(&other_session.refresh_token).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_expired_refresh_token_is_rejected() {
+        let jwt_service = JwtService::new("secret").with_expiry(Duration::minutes(15), Duration::seconds(-1));
+        let (service, user) = service_with_user(jwt_service).await;
+        let tokens = service.issue_tokens(&user).await.unwrap();
+
+        assert!(matches!(
+            service.refresh(&tokens.refresh_token).await,
+            Err(AppError::Authentication(message)) if message.contains("expired")
+        ));
+    }
+}
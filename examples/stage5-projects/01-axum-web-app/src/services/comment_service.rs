@@ -0,0 +1,83 @@
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::auth::AuthUser;
+use crate::auth::policy::{authorize, can_view_post, Action};
+use crate::error::{AppError, Result};
+use crate::models::comment::{Comment, CommentThread, CommentWithAuthor, CreateCommentRequest};
+use crate::repositories::{CommentRepository, PostRepository};
+
+#[derive(Clone)]
+pub struct CommentService<R> {
+    repository: R,
+}
+
+impl<R: PostRepository + CommentRepository> CommentService<R> {
+    pub fn new(repository: R) -> Self {
+        Self { repository }
+    }
+
+    pub async fn create_comment(
+        &self,
+        post_id: &Uuid,
+        author_id: &Uuid,
+        request: CreateCommentRequest,
+    ) -> Result<CommentWithAuthor> {
+        request.validate()?;
+
+        // Only published posts take new comments
+        self.ensure_post_visible(post_id, None).await?;
+
+        // Replies must stay in the thread of the same post
+        if let Some(parent_id) = &request.parent_id {
+            let parent = self.repository.find_comment(parent_id).await?;
+            if parent.is_none_or(|parent| parent.post_id != *post_id) {
+                return Err(AppError::Validation("Parent comment not found on this post".to_string()));
+            }
+        }
+
+        let now = chrono::Utc::now();
+        let comment = Comment {
+            id: Uuid::new_v4(),
+            post_id: *post_id,
+            author_id: *author_id,
+            parent_id: request.parent_id,
+            content: request.content,
+            created_at: now,
+            updated_at: now,
+        };
+
+        let comment = self.repository.create_comment(comment).await?;
+        self.repository.find_comment_with_author(&comment.id).await?
+            .ok_or_else(|| AppError::NotFound("Comment not found".to_string()))
+    }
+
+    pub async fn get_comments(&self, post_id: &Uuid, viewer: Option<&AuthUser>) -> Result<Vec<CommentThread>> {
+        self.ensure_post_visible(post_id, viewer).await?;
+
+        let comments = self.repository.list_comments(post_id).await?;
+        Ok(CommentThread::build(comments))
+    }
+
+    // Deletes the comment and all replies to it
+    pub async fn delete_comment(&self, comment_id: &Uuid, actor: &AuthUser) -> Result<()> {
+        let comment = self.repository.find_comment(comment_id).await?
+            .ok_or_else(|| AppError::NotFound("Comment not found".to_string()))?;
+        authorize(actor, Action::DeleteComment { author_id: comment.author_id })?;
+
+        if !self.repository.delete_comment(comment_id).await? {
+            return Err(AppError::NotFound("Comment not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    // Posts the viewer may not see are missing to them, as in `PostService::get_post`
+    async fn ensure_post_visible(&self, post_id: &Uuid, viewer: Option<&AuthUser>) -> Result<()> {
+        self.repository.find_post(post_id).await?
+            .filter(|post| can_view_post(viewer, post.author_id, post.status))
+            .ok_or_else(|| AppError::NotFound("Post not found".to_string()))?;
+
+        Ok(())
+    }
+}
@@ -1,5 +1,9 @@
+pub mod auth_service;
 pub mod user_service;
 pub mod post_service;
+pub mod comment_service;
 
+pub use auth_service::*;
 pub use user_service::*;
 pub use post_service::*;
+pub use comment_service::*;
@@ -1,219 +1,274 @@
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::auth::AuthUser;
+use crate::auth::policy::{authorize, can_view_post, Action};
 use crate::error::{AppError, Result};
+use crate::models::etag::EntityTags;
+use crate::models::pagination::{Page, PageQuery, PageRequest, PostCursor};
 use crate::models::post::{
-    CreatePostRequest, UpdatePostRequest, PostResponse, PostWithAuthor
+    normalize_tag, Post, PostStatus, CreatePostRequest, UpdatePostRequest, PostWithAuthor, LikeResponse
 };
+use crate::models::revision::{DiffQuery, PostRevision, RevisionDiff};
+use crate::models::search::{SearchHit, SearchParams};
+use crate::repositories::{PostFilter, PostRepository};
+use crate::search::PostSearch;
+
+const STALE_POST: &str = "The post has changed since it was read; fetch it again and retry";
 
 #[derive(Clone)]
-pub struct PostService {
-    db: crate::database::Database,
+pub struct PostService<R> {
+    repository: R,
 }
 
-impl PostService {
-    pub fn new(db: crate::database::Database) -> Self {
-        Self { db }
-    }
-
-    pub async fn create_post(&self, author_id: &Uuid, request: CreatePostRequest) -> Result<PostResponse> {
-        request.validate()
-            .map_err(|e| AppError::Validation(format!("Validation error: {}", e))?;
-
-        let post_id = uuid::Uuid::new_v4();
-        let row: sqlx::postgres::PgRow = sqlx::query(
-            r#"
-            INSERT INTO posts (id, title, content, author_id, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6)
-            RETURNING id, title, content, author_id, created_at, updated_at
-            "#,
-        )
-        .bind(post_id)
-        .bind(&request.title)
-        .bind(&request.content)
-        .bind(author_id)
-        .bind(chrono::Utc::now())
-        .bind(chrono::Utc::now())
-        .fetch_one(&self.db)
-        .await?;
-
-        Ok(PostResponse {
-            id: row.get("id"),
-            title: row.get("title"),
-            content: row.get("content"),
-            author_id: row.get("author_id"),
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
-        })
-    }
-
-    pub async fn get_post(&self, post_id: &Uuid) -> Result<PostWithAuthor> {
-        let row: sqlx::postgres::PgRow = sqlx::query(
-            r#"
-            SELECT
-                p.id, p.title, p.content, p.author_id, p.created_at, p.updated_at,
-                u.username as author_username
-            FROM posts p
-            JOIN users u ON p.author_id = u.id
-            WHERE p.id = $1
-            "#,
-        )
-        .bind(post_id)
-        .fetch_optional(&self.db)
-        .await?
-        .ok_or_else(|| AppError::NotFound("Post not found".to_string()))?;
-
-        Ok(PostWithAuthor {
-            id: row.get("id"),
-            title: row.get("title"),
-            content: row.get("content"),
-            author_id: row.get("author_id"),
-            author_username: row.get("author_username"),
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
-        })
-    }
-
-    pub async fn get_posts(&self, limit: i64, offset: i64) -> Result<Vec<PostWithAuthor>> {
-        let rows: Vec<sqlx::postgres::PgRow> = sqlx::query(
-            r#"
-            SELECT
-                p.id, p.title, p.content, p.author_id, p.created_at, p.updated_at,
-                u.username as author_username
-            FROM posts p
-            JOIN users u ON p.author_id = u.id
-            ORDER BY p.created_at DESC
-            LIMIT $1 OFFSET $2
-            "#,
-        )
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&self.db)
-        .await?;
-
-        let mut posts = Vec::new();
-        for row in rows {
-            posts.push(PostWithAuthor {
-                id: row.get("id"),
-                title: row.get("title"),
-                content: row.get("content"),
-                author_id: row.get("author_id"),
-                author_username: row.get("author_username"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            });
+impl<R: PostRepository> PostService<R> {
+    pub fn new(repository: R) -> Self {
+        Self { repository }
+    }
+
+    pub async fn create_post(&self, author_id: &Uuid, request: CreatePostRequest) -> Result<PostWithAuthor> {
+        request.validate()?;
+
+        let tags = normalize_tags(&request.tags);
+        let mut post = Post::from(request);
+        post.author_id = *author_id;
+
+        let post = self.repository.create_post(post, &tags).await?;
+
+        self.fetch_post(&post.id).await
+    }
+
+    // Posts that `viewer` may not see are reported as missing, so drafts do
+    // not leak their existence
+    pub async fn get_post(&self, post_id: &Uuid, viewer: Option<&AuthUser>) -> Result<PostWithAuthor> {
+        let post = self.fetch_post(post_id).await?;
+        if !can_view_post(viewer, post.author_id, post.status) {
+            return Err(AppError::NotFound("Post not found".to_string()));
         }
 
-        Ok(posts)
-    }
-
-    pub async fn get_user_posts(&self, user_id: &Uuid, limit: i64, offset: i64) -> Result<Vec<PostWithAuthor>> {
-        let rows: Vec<sqlx::postgres::PgRow> = sqlx::query(
-            r#"
-            SELECT
-                p.id, p.title, p.content, p.author_id, p.created_at, p.updated_at,
-                u.username as author_username
-            FROM posts p
-            JOIN users u ON p.author_id = u.id
-            WHERE p.author_id = $1
-            ORDER BY p.created_at DESC
-            LIMIT $2 OFFSET $3
-            "#,
-        )
-        .bind(user_id)
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&self.db)
-        .await?;
-
-        let mut posts = Vec::new();
-        for row in rows {
-            posts.push(PostWithAuthor {
-                id: row.get("id"),
-                title: row.get("title"),
-                content: row.get("content"),
-                author_id: row.get("author_id"),
-                author_username: row.get("author_username"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            });
+        Ok(post)
+    }
+
+    pub async fn get_posts(&self, query: PageQuery) -> Result<Page<PostWithAuthor>> {
+        self.list_posts(PostFilter::published(), query).await
+    }
+
+    pub async fn get_user_posts(&self, user_id: &Uuid, query: PageQuery) -> Result<Page<PostWithAuthor>> {
+        self.list_posts(PostFilter::published().author(*user_id), query).await
+    }
+
+    // The caller's own posts, including drafts and archived ones
+    pub async fn get_own_posts(
+        &self,
+        user_id: &Uuid,
+        status: Option<PostStatus>,
+        query: PageQuery,
+    ) -> Result<Page<PostWithAuthor>> {
+        let filter = PostFilter { author_id: Some(*user_id), status, ..PostFilter::default() };
+        self.list_posts(filter, query).await
+    }
+
+    pub async fn get_tag_posts(&self, tag: &str, query: PageQuery) -> Result<Page<PostWithAuthor>> {
+        self.list_posts(PostFilter::published().tag(normalize_tag(tag)), query).await
+    }
+
+    // Fetches one post more than requested to learn whether another page follows
+    async fn list_posts(&self, filter: PostFilter, query: PageQuery) -> Result<Page<PostWithAuthor>> {
+        let page = PageRequest::listing(query)?;
+
+        let posts = self.repository
+            .list_posts(&filter, page.sort, page.after.as_ref(), page.limit + 1)
+            .await?;
+        let total = if page.include_total {
+            Some(self.repository.count_posts(&filter).await?)
+        } else {
+            None
+        };
+
+        Ok(Page::new(posts, page.limit, |post| PostCursor::new(page.sort, post), total))
+    }
+
+    pub async fn search_posts(&self, params: SearchParams, query: PageQuery) -> Result<Page<SearchHit>> {
+        let search = PostSearch::try_from(params)?;
+        let page = PageRequest::search(query)?;
+
+        let hits = self.repository
+            .search_posts(&search, page.sort, page.after.as_ref(), page.limit + 1)
+            .await?;
+        let total = if page.include_total {
+            Some(self.repository.count_search_results(&search).await?)
+        } else {
+            None
+        };
+
+        let page = Page::new(hits, page.limit, |hit| PostCursor::scored(page.sort, hit), total);
+        Ok(page.map(|hit| SearchHit::new(hit, &search.query)))
+    }
+
+    // Only applies to the version named by `if_match`, which the client last
+    // saw. Tag changes are saved with the rest and bump the version too.
+    pub async fn update_post(
+        &self,
+        post_id: &Uuid,
+        actor: &AuthUser,
+        request: UpdatePostRequest,
+        if_match: Option<&EntityTags>,
+    ) -> Result<PostWithAuthor> {
+        request.validate()?;
+
+        let post = self.find_post(post_id).await?;
+        authorize(actor, Action::UpdatePost { author_id: post.author_id })?;
+        check_if_match(&post, if_match)?;
+
+        let now = chrono::Utc::now();
+        let mut changed = post.clone();
+        if let Some(title) = request.title {
+            changed.title = title;
+        }
+        if let Some(content) = request.content {
+            changed.content = content;
+        }
+        if let Some(status) = request.status {
+            changed.set_status(status, now);
+        }
+        let tags = request.tags.as_deref().map(normalize_tags);
+        self.save_post(&post, changed, tags.as_deref(), actor, now).await?;
+
+        self.fetch_post(post_id).await
+    }
+
+    // Newest first
+    pub async fn get_revisions(&self, post_id: &Uuid, actor: &AuthUser) -> Result<Vec<PostRevision>> {
+        let post = self.find_post(post_id).await?;
+        authorize(actor, Action::ViewUnpublished { author_id: post.author_id })?;
+
+        self.repository.list_revisions(post_id).await
+    }
+
+    pub async fn diff_revisions(&self, post_id: &Uuid, actor: &AuthUser, query: DiffQuery) -> Result<RevisionDiff> {
+        let post = self.find_post(post_id).await?;
+        authorize(actor, Action::ViewUnpublished { author_id: post.author_id })?;
+
+        let from = self.find_revision(post_id, query.from).await?;
+        let to = self.find_revision(post_id, query.to).await?;
+        Ok(RevisionDiff::new(&from, &to))
+    }
+
+    // Brings back the title and content of an earlier revision, which is
+    // recorded as a new revision; the history is never rewritten
+    pub async fn restore_revision(&self, post_id: &Uuid, revision: i32, actor: &AuthUser) -> Result<PostWithAuthor> {
+        let post = self.find_post(post_id).await?;
+        authorize(actor, Action::UpdatePost { author_id: post.author_id })?;
+
+        let revision = self.find_revision(post_id, revision).await?;
+        let restored = Post { title: revision.title, content: revision.content, ..post.clone() };
+        self.save_post(&post, restored, None, actor, chrono::Utc::now()).await?;
+
+        self.fetch_post(post_id).await
+    }
+
+    // Stores `changed`, and `tags` if given, as edited by `actor` at `now`,
+    // adding a revision when the title or content differ from `post`. Fails
+    // if the post was updated since `post` was read.
+    async fn save_post(
+        &self,
+        post: &Post,
+        mut changed: Post,
+        tags: Option<&[String]>,
+        actor: &AuthUser,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Post> {
+        changed.updated_at = now;
+        let edited = changed.title != post.title || changed.content != post.content;
+
+        match self.repository.update_post(&changed, tags, edited.then_some(&actor.id)).await? {
+            Some(saved) => Ok(saved),
+            None => {
+                self.find_post(&post.id).await?;
+                Err(AppError::PreconditionFailed(STALE_POST.to_string()))
+            }
         }
+    }
 
-        Ok(posts)
-    }
-
-    pub async fn search_posts(&self, query: &str, limit: i64, offset: i64) -> Result<Vec<PostWithAuthor>> {
-        let rows: Vec<sqlx::postgres::PgRow> = sqlx::query(
-            r#"
-            SELECT
-                p.id, p.title, p.content, p.author_id, p.created_at, p.updated_at,
-                u.username as author_username
-            FROM posts p
-            JOIN users u ON p.author_id = u.id
-            WHERE p.title ILIKE $1 OR p.content ILIKE $1
-            ORDER BY p.created_at DESC
-            LIMIT $2 OFFSET $3
-            "#,
-        )
-        .bind(format!("%{}%", query))
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&self.db)
-        .await?;
-        let mut posts = Vec::new();
-        for row in rows {
-            posts.push(PostWithAuthor {
-                id: row.get("id"),
-                title: row.get("title"),
-                content: row.get("content"),
-                author_id: row.get("author_id"),
-                author_username: row.get("author_username"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            });
+    // The check comes before the delete rather than with it: an edit that
+    // races the delete goes with the post either way
+    pub async fn delete_post(&self, post_id: &Uuid, actor: &AuthUser, if_match: Option<&EntityTags>) -> Result<()> {
+        let post = self.find_post(post_id).await?;
+        authorize(actor, Action::DeletePost { author_id: post.author_id })?;
+        check_if_match(&post, if_match)?;
+
+        if !self.repository.delete_post(post_id).await? {
+            return Err(AppError::NotFound("Post not found".to_string()));
         }
 
-        Ok(posts)
-    }
-
-    pub async fn update_post(&self, post_id: &Uuid, author_id: &Uuid, request: UpdatePostRequest) -> Result<PostResponse> {
-        let row: sqlx::postgres::PgRow = sqlx::query(
-            r#"
-            UPDATE posts
-            SET title = COALESCE($1, title),
-            content = COALESCE($2, content),
-            updated_at = NOW()
-            WHERE id = $3 AND author_id = $4
-            RETURNING id, title, content, author_id, created_at, updated_at
-            "#,
-        )
-        .bind(request.title.as_ref())
-        .bind(request.content.as_ref())
-        .bind(post_id)
-        .bind(author_id)
-        .fetch_one(&self.db)
-        .await?;
-
-        Ok(PostResponse {
-            id: row.get("id"),
-            title: row.get("title"),
-            content: row.get("content"),
-            author_id: row.get("author_id"),
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
-        })
-    }
-
-    pub async fn delete_post(&self, post_id: &Uuid, author_id: &Uuid) -> Result<()> {
-        let result = sqlx::query(
-            "DELETE FROM posts WHERE id = $1 AND author_id = $2"
-        )
-        .bind(post_id)
-        .bind(author_id)
-        .execute(&self.db)
-        .await?;
-
-        if result.rows_affected() == 0 {
-            return Err(AppError::NotFound("Post not found or access denied".to_string());
+        Ok(())
+    }
+
+    // Only published posts can be liked
+    pub async fn like_post(&self, post_id: &Uuid, user_id: &Uuid) -> Result<LikeResponse> {
+        self.get_post(post_id, None).await?;
+        self.repository.like_post(post_id, user_id).await?;
+        self.like_response(post_id, true).await
+    }
+
+    pub async fn unlike_post(&self, post_id: &Uuid, user_id: &Uuid) -> Result<LikeResponse> {
+        self.get_post(post_id, None).await?;
+        self.repository.unlike_post(post_id, user_id).await?;
+        self.like_response(post_id, false).await
+    }
+
+    async fn like_response(&self, post_id: &Uuid, liked: bool) -> Result<LikeResponse> {
+        let post = self.fetch_post(post_id).await?;
+        Ok(LikeResponse { post_id: post.id, liked, like_count: post.like_count })
+    }
+
+    // Remove any post as a moderator or admin
+    pub async fn moderate_delete_post(&self, post_id: &Uuid, actor: &AuthUser) -> Result<()> {
+        authorize(actor, Action::ModeratePosts)?;
+
+        if !self.repository.delete_post(post_id).await? {
+            return Err(AppError::NotFound("Post not found".to_string()));
         }
 
         Ok(())
     }
+
+    // Ask the policy only about posts that exist, so missing posts are 404 rather than 403
+    async fn find_post(&self, post_id: &Uuid) -> Result<Post> {
+        self.repository.find_post(post_id).await?
+            .ok_or_else(|| AppError::NotFound("Post not found".to_string()))
+    }
+
+    // Whatever its status, for responses to the post's own author
+    async fn fetch_post(&self, post_id: &Uuid) -> Result<PostWithAuthor> {
+        self.repository.find_post_with_author(post_id).await?
+            .ok_or_else(|| AppError::NotFound("Post not found".to_string()))
+    }
+
+    async fn find_revision(&self, post_id: &Uuid, revision: i32) -> Result<PostRevision> {
+        self.repository.find_revision(post_id, revision).await?
+            .ok_or_else(|| AppError::NotFound("Revision not found".to_string()))
+    }
+}
+
+// Writes must name the version they are based on, so that clients cannot
+// overwrite changes they have not seen. Likes and comments change the ETag
+// but not the version, so they do not fail the author's next edit.
+fn check_if_match(post: &Post, if_match: Option<&EntityTags>) -> Result<()> {
+    let Some(if_match) = if_match else {
+        return Err(AppError::PreconditionRequired("Send the post's ETag in If-Match".to_string()));
+    };
+    if !if_match.matches_by(&post.version, PostWithAuthor::etag_version) {
+        return Err(AppError::PreconditionFailed(STALE_POST.to_string()));
+    }
+
+    Ok(())
+}
+
+// Normalized, without duplicates, in alphabetical order
+fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut tags: Vec<String> = tags.iter().map(|tag| normalize_tag(tag)).collect();
+    tags.sort();
+    tags.dedup();
+    tags
 }
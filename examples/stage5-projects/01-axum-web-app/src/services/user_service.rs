@@ -1,38 +1,64 @@
 use bcrypt::{hash, verify, DEFAULT_COST};
-use sqlx::Row;
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::database::Database;
 use crate::error::{AppError, Result};
+use crate::auth::AuthUser;
+use crate::auth::policy::{authorize, Action};
+use crate::models::pagination::MAX_PAGE_SIZE;
 use crate::models::user::{
-    User, CreateUserRequest, UpdateUserRequest, LoginRequest, UserResponse, LoginResponse
+    User, Role, CreateUserRequest, UpdateUserRequest, LoginRequest, UserResponse, LoginResponse,
+    AdminUserResponse,
 };
-use crate::auth::JwtService;
+use crate::rate_limit::RateLimiter;
+use crate::repositories::{TokenRepository, UserRepository};
+use crate::services::AuthService;
 
 #[derive(Clone)]
-pub struct UserService {
-    db: Database,
-    jwt_service: JwtService,
+pub struct UserService<R> {
+    repository: R,
+    auth_service: AuthService<R>,
+    rate_limiter: RateLimiter,
 }
 
-impl UserService {
-    pub fn new(db: Database, jwt_service: JwtService) -> Self {
-        Self { db, jwt_service }
+impl<R: UserRepository + TokenRepository> UserService<R> {
+    pub fn new(repository: R, auth_service: AuthService<R>) -> Self {
+        Self { repository, auth_service, rate_limiter: RateLimiter::default() }
+    }
+
+    // Track failed logins in `rate_limiter` instead of the default in-memory one
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
     }
 
     pub async fn create_user(&self, request: CreateUserRequest) -> Result<UserResponse> {
+        self.create_user_with_role(request, Role::User).await
+    }
+
+    // Create an admin account, or promote the user if the username is taken.
+    // Used by the `create-admin` command; there is no HTTP route for this.
+    pub async fn create_admin(&self, request: CreateUserRequest) -> Result<UserResponse> {
+        if let Some(user) = self.repository.find_user_by_username(&request.username).await? {
+            let user = self.repository.set_user_role(&user.id, Role::Admin).await?
+                .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+            return Ok(user.into());
+        }
+
+        self.create_user_with_role(request, Role::Admin).await
+    }
+
+    async fn create_user_with_role(&self, request: CreateUserRequest, role: Role) -> Result<UserResponse> {
         // Validate request
-        request.validate()
-            .map_err(|e| AppError::Validation(format!("Validation error: {}", e)))?;
+        request.validate()?;
 
         // Check if username already exists
-        if self.username_exists(&request.username).await? {
+        if self.repository.find_user_by_username(&request.username).await?.is_some() {
             return Err(AppError::Conflict("Username already exists".to_string()));
         }
 
         // Check if email already exists
-        if self.email_exists(&request.email).await? {
+        if self.repository.find_user_by_email(&request.email).await?.is_some() {
             return Err(AppError::Conflict("Email already exists".to_string()));
         }
 
@@ -41,181 +67,134 @@ impl UserService {
             .map_err(AppError::PasswordHashing)?;
 
         // Create user
+        let now = chrono::Utc::now();
         let user = User {
             id: Uuid::new_v4(),
             username: request.username,
             email: request.email,
             password_hash,
-            created_at: chrono::Utc::now(),
-            updated_at: chrono::Utc::now(),
+            role,
+            banned_at: None,
+            created_at: now,
+            updated_at: now,
         };
 
-        let row: sqlx::postgres::PgRow = sqlx::query(
-            r#"
-            INSERT INTO users (id, username, email, password_hash, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6)
-            RETURNING id, username, email, created_at, updated_at
-            "#,
-        )
-        .bind(user.id)
-        .bind(user.username)
-        .bind(user.email)
-        .bind(user.password_hash)
-        .bind(user.created_at)
-        .bind(user.updated_at)
-        .fetch_one(&self.db)
-        .await?;
-
-        Ok(UserResponse {
-            id: row.get("id"),
-            username: row.get("username"),
-            email: row.get("email"),
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
-        })
-    }
-
-    pub fn get_db(&self) -> &Database {
-        &self.db
+        let user = self.repository.create_user(user).await?;
+        Ok(user.into())
     }
 
     pub async fn login(&self, request: LoginRequest) -> Result<LoginResponse> {
-        // Find user by username
-        let user: sqlx::postgres::PgRow = sqlx::query(
-            "SELECT id, username, email, password_hash, created_at, updated_at FROM users WHERE username = $1",
-        )
-        .bind(&request.username)
-        .fetch_optional(&self.db)
-        .await?
-        .ok_or_else(|| AppError::Authentication("Invalid credentials".to_string()))?;
+        // A locked account is refused before the password is even checked
+        self.rate_limiter.check_lockout(&request.username).await?;
+
+        // Find user by username; unknown usernames count as failures too, so
+        // that lockouts do not reveal which accounts exist
+        let Some(user) = self.repository.find_user_by_username(&request.username).await? else {
+            self.rate_limiter.login_failed(&request.username).await?;
+            return Err(AppError::Authentication("Invalid credentials".to_string()));
+        };
 
         // Verify password
-        if !verify(&request.password, &user.get::<_, String>("password_hash"))
+        if !verify(&request.password, &user.password_hash)
             .map_err(AppError::PasswordHashing)?
         {
+            self.rate_limiter.login_failed(&request.username).await?;
             return Err(AppError::Authentication("Invalid credentials".to_string()));
         }
+        self.rate_limiter.login_succeeded(&request.username).await?;
 
-        // Generate JWT token
-        let token = self.jwt_service.generate_token(
-            &user.get::<_, String>("id"),
-            &user.get::<_, String>("username"),
-        )?;
+        // Only tell banned users so once they have proved who they are
+        if user.banned_at.is_some() {
+            return Err(AppError::Authorization("Account is banned".to_string()));
+        }
+
+        // Start a new session with an access token and a refresh token
+        let tokens = self.auth_service.issue_tokens(&user).await?;
 
         Ok(LoginResponse {
-            access_token: token,
-            token_type: "Bearer".to_string(),
-            expires_in: "24h".to_string(),
-            user: UserResponse {
-                id: user.get("id"),
-                username: user.get("username"),
-                email: user.get("email"),
-                created_at: user.get("created_at"),
-                updated_at: user.get("updated_at"),
-            },
+            tokens,
+            user: user.into(),
         })
     }
 
     pub async fn get_user(&self, user_id: &Uuid) -> Result<UserResponse> {
-        let user: sqlx::postgres::PgRow = sqlx::query(
-            "SELECT id, username, email, created_at, updated_at FROM users WHERE id = $1",
-        )
-        .bind(user_id)
-        .fetch_optional(&self.db)
-        .await?
-        .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
-
-        Ok(UserResponse {
-            id: user.get("id"),
-            username: user.get("username"),
-            email: user.get("email"),
-            created_at: user.get("created_at"),
-            updated_at: user.get("updated_at"),
-        })
+        let user = self.repository.find_user(user_id).await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        Ok(user.into())
     }
 
     pub async fn update_user(&self, user_id: &Uuid, request: UpdateUserRequest) -> Result<UserResponse> {
         // Validate request
-        request.validate()
-            .map_err(|e| AppError::Validation(format!("Validation error: {}", e)))?;
+        request.validate()?;
 
         // Check if user exists
-        let mut user = self.get_user(user_id).await?;
-
-        // Update username if provided
-        if let Some(ref username) = request.username {
-            if self.username_exists(username).await? && username != &user.username {
-                return Err(AppError::Conflict("Username already exists".to_string()));
-            }
-            user.username = username.clone();
+        let user = self.get_user(user_id).await?;
+
+        // Username and email must stay unique
+        if let Some(ref username) = request.username
+            && username != &user.username
+            && self.repository.find_user_by_username(username).await?.is_some()
+        {
+            return Err(AppError::Conflict("Username already exists".to_string()));
         }
 
-        // Update email if provided
-        if let Some(ref email) = request.email {
-            if self.email_exists(email).await? && email != &user.email {
-                return Err(AppError::Conflict("Email already exists".to_string()));
-            }
-            user.email = email.clone();
+        if let Some(ref email) = request.email
+            && email != &user.email
+            && self.repository.find_user_by_email(email).await?.is_some()
+        {
+            return Err(AppError::Conflict("Email already exists".to_string()));
         }
 
-        // Update in database
-        let row: sqlx::postgres::PgRow = sqlx::query(
-            r#"
-            UPDATE users
-            SET username = COALESCE($1, username),
-                email = COALESCE($2, email),
-                updated_at = NOW()
-            WHERE id = $3
-            RETURNING id, username, email, created_at, updated_at
-            "#,
-        )
-        .bind(&request.username)
-        .bind(&request.email)
-        .bind(user_id)
-        .fetch_one(&self.db)
-        .await?;
-
-        Ok(UserResponse {
-            id: row.get("id"),
-            username: row.get("username"),
-            email: row.get("email"),
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
-        })
+        let user = self.repository.update_user(user_id, &request).await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        Ok(user.into())
     }
 
     pub async fn delete_user(&self, user_id: &Uuid) -> Result<()> {
-        let result = sqlx::query("DELETE FROM users WHERE id = $1")
-            .bind(user_id)
-            .execute(&self.db)
-            .await?;
-
-        if result.rows_affected() == 0 {
+        if !self.repository.delete_user(user_id).await? {
             return Err(AppError::NotFound("User not found".to_string()));
         }
 
         Ok(())
     }
 
-    async fn username_exists(&self, username: &str) -> Result<bool> {
-        let count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM users WHERE username = $1"
-        )
-        .bind(username)
-        .fetch_one(&self.db)
-        .await?;
+    pub async fn list_users(&self, actor: &AuthUser, limit: i64, offset: i64) -> Result<Vec<AdminUserResponse>> {
+        authorize(actor, Action::ListUsers)?;
+
+        let users = self.repository.list_users(limit.clamp(1, MAX_PAGE_SIZE), offset.max(0)).await?;
+        Ok(users.into_iter().map(AdminUserResponse::from).collect())
+    }
+
+    // Ban a user and end all their sessions. Banning twice keeps the original time.
+    pub async fn ban_user(&self, actor: &AuthUser, user_id: &Uuid) -> Result<AdminUserResponse> {
+        let user = self.find_user_to_moderate(actor, user_id).await?;
+        if user.banned_at.is_some() {
+            return Ok(user.into());
+        }
+
+        let user = self.repository.set_user_banned(user_id, Some(chrono::Utc::now())).await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+        self.repository.revoke_user_tokens(user_id).await?;
+
+        Ok(user.into())
+    }
+
+    pub async fn unban_user(&self, actor: &AuthUser, user_id: &Uuid) -> Result<AdminUserResponse> {
+        self.find_user_to_moderate(actor, user_id).await?;
+
+        let user = self.repository.set_user_banned(user_id, None).await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
 
-        Ok(count > 0)
+        Ok(user.into())
     }
 
-    async fn email_exists(&self, email: &str) -> Result<bool> {
-        let count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM users WHERE email = $1"
-        )
-        .bind(email)
-        .fetch_one(&self.db)
-        .await?;
+    async fn find_user_to_moderate(&self, actor: &AuthUser, user_id: &Uuid) -> Result<User> {
+        let user = self.repository.find_user(user_id).await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+        authorize(actor, Action::BanUser { user_id: user.id, role: user.role })?;
 
-        Ok(count > 0)
+        Ok(user)
     }
 }
@@ -1,10 +1,41 @@
 use crate::auth::JwtService;
-use crate::services::{UserService, PostService};
+use crate::metrics::Metrics;
+use crate::rate_limit::RateLimiter;
+use crate::repositories::Repository;
+use crate::services::{AuthService, UserService, PostService, CommentService};
 
-// Application state
+// Application state, generic over the storage backend
 #[derive(Clone)]
-pub struct AppState {
-    pub user_service: UserService,
-    pub post_service: PostService,
+pub struct AppState<R> {
+    pub repository: R,
+    pub auth_service: AuthService<R>,
+    pub user_service: UserService<R>,
+    pub post_service: PostService<R>,
+    pub comment_service: CommentService<R>,
     pub jwt_service: JwtService,
+    pub rate_limiter: RateLimiter,
+    pub metrics: Metrics,
+}
+
+impl<R: Repository> AppState<R> {
+    pub fn new(repository: R, jwt_service: JwtService) -> Self {
+        let auth_service = AuthService::new(repository.clone(), jwt_service.clone());
+        Self {
+            user_service: UserService::new(repository.clone(), auth_service.clone()),
+            auth_service,
+            post_service: PostService::new(repository.clone()),
+            comment_service: CommentService::new(repository.clone()),
+            repository,
+            jwt_service,
+            rate_limiter: RateLimiter::default(),
+            metrics: Metrics::new(),
+        }
+    }
+
+    // Use `rate_limiter` for both the auth routes and login lockouts
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.user_service = self.user_service.with_rate_limiter(rate_limiter.clone());
+        self.rate_limiter = rate_limiter;
+        self
+    }
 }
@@ -1,83 +1,1348 @@
+use axum::http::{HeaderValue, Method, StatusCode};
 use axum_test::TestServer;
-use reqwest::Client;
-use serde_json::json;
+use serde_json::{json, Value};
+use sqlx::postgres::PgPoolOptions;
+use uuid::Uuid;
 
-// Note: These tests would require a test database setup
-// For now, they serve as integration test examples
+use axum_web_app::{
+    auth::JwtService,
+    config::{BucketConfig, CorsConfig, RateLimitConfig},
+    database,
+    models::{
+        post::{MAX_TAGS_PER_POST, MAX_TAG_LENGTH},
+        user::{CreateUserRequest, Role},
+    },
+    openapi::SPEC_PATH,
+    rate_limit::RateLimiter,
+    repositories::{InMemoryRepository, PostgresRepository, Repository, SqliteRepository},
+    routes::{cors_layer, create_router},
+    state::AppState,
+};
 
-#[tokio::test]
-async fn test_health_endpoints() {
-    let app = axum::Router::new()
-        .route("/health", axum::routing::get(|| async {
-            axum::Json(json!({
-                "status": "healthy",
-                "timestamp": chrono::Utc::now().to_rfc3339()
-            }))
-        }))
-        .route("/ready", axum::routing::get(|| async {
-            axum::Json(json!({
-                "status": "ready"
-            }))
+const JWT_SECRET: &str = "test_secret_key";
+
+fn test_server<R: Repository>(repository: R) -> TestServer {
+    let state = AppState::new(repository, JwtService::new(JWT_SECRET));
+    TestServer::new(create_router(state)).unwrap()
+}
+
+fn in_memory_server() -> TestServer {
+    test_server(InMemoryRepository::new())
+}
+
+fn in_memory_backend() -> (TestServer, InMemoryRepository) {
+    let repository = InMemoryRepository::new();
+    (test_server(repository.clone()), repository)
+}
+
+// The PostgreSQL variants run only when TEST_DATABASE_URL is set
+async fn postgres_backend() -> Option<(TestServer, PostgresRepository)> {
+    let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+        eprintln!("TEST_DATABASE_URL not set, skipping PostgreSQL test");
+        return None;
+    };
+    let pool = PgPoolOptions::new().connect(&url).await.unwrap();
+    database::Migrations::Postgres(&pool).up().await.unwrap();
+    let repository = PostgresRepository::new(pool);
+    Some((test_server(repository.clone()), repository))
+}
+
+async fn sqlite_backend() -> (TestServer, SqliteRepository) {
+    let pool = database::connect_sqlite("sqlite::memory:").await.unwrap();
+    let repository = SqliteRepository::new(pool).await.unwrap();
+    (test_server(repository.clone()), repository)
+}
+
+// Register and log in a fresh user, returning (user id, access token)
+async fn register_and_login(server: &TestServer) -> (String, String) {
+    let (user_id, body) = register_and_login_response(server).await;
+    (user_id, body["access_token"].as_str().unwrap().to_string())
+}
+
+// Register and log in a fresh user, returning the user id and the login response
+async fn register_and_login_response(server: &TestServer) -> (String, Value) {
+    let username = format!("user_{}", &Uuid::new_v4().simple().to_string()[..12]);
+    let response = server
+        .post("/api/auth/register")
+        .json(&json!({
+            "username": username,
+            "email": format!("{}@example.com", username),
+            "password": "password123"
         }))
-        .route("/live", axum::routing::get(|| async {
-            axum::Json(json!({
-                "status": "alive"
-            }))
-        }));
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+
+    let response = server
+        .post("/api/auth/login")
+        .json(&json!({ "username": username, "password": "password123" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
 
-    let server = TestServer::new(app).unwrap();
+    let body: Value = response.json();
+    (body["user"]["id"].as_str().unwrap().to_string(), body)
+}
+
+async fn login(server: &TestServer, username: &Value) -> String {
+    let response = server
+        .post("/api/auth/login")
+        .json(&json!({ "username": username, "password": "password123" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    response.json::<Value>()["access_token"].as_str().unwrap().to_string()
+}
 
-    // Test health endpoint
+// The post's current ETag, as sent for If-Match
+async fn etag(server: &TestServer, post_path: &str, token: &str) -> HeaderValue {
+    let response = server.get(post_path).authorization_bearer(token).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    response.header("etag")
+}
+
+// Seed an admin the way the `create-admin` command does, returning their access token
+async fn create_admin<R: Repository>(server: &TestServer, repository: &R) -> String {
+    let username = format!("admin_{}", &Uuid::new_v4().simple().to_string()[..12]);
+    let state = AppState::new(repository.clone(), JwtService::new(JWT_SECRET));
+    let admin = state
+        .user_service
+        .create_admin(CreateUserRequest {
+            username: username.clone(),
+            email: format!("{}@example.com", username),
+            password: "password123".to_string(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(admin.role, Role::Admin);
+    login(server, &json!(username)).await
+}
+
+async fn health_flow(server: &TestServer) {
     let response = server.get("/health").await;
-    assert_eq!(response.status_code(), 200);
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert_eq!(response.json::<Value>()["status"], "healthy");
 
-    // Test ready endpoint
     let response = server.get("/ready").await;
-    assert_eq!(response.status_code(), 200);
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let body: Value = response.json();
+    assert_eq!(body["status"], "ready");
+    assert_eq!(body["checks"]["database"]["status"], "up");
 
-    // Test live endpoint
     let response = server.get("/live").await;
-    assert_eq!(response.status_code(), 200);
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert_eq!(response.json::<Value>()["status"], "alive");
+
+    // Routes are counted by pattern, not by the requested path
+    server.get(&format!("/api/posts/{}", Uuid::new_v4())).await;
+    server.get("/no/such/route").await;
+    let response = server.get("/metrics").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert!(response.header("content-type").to_str().unwrap().starts_with("text/plain; version=0.0.4"));
+    let text = response.text();
+    assert!(text.contains(r#"http_requests_total{method="GET",route="/health",status="200"} 1"#));
+    assert!(text.contains(r#"http_requests_total{method="GET",route="/api/posts/:id",status="404"}"#));
+    assert!(text.contains(r#"route="unmatched",status="404"}"#));
+    assert!(text.contains(r#"http_request_duration_seconds_count{method="GET",route="/ready",status="200"} 1"#));
+    assert!(!text.contains("/no/such/route"));
+}
+
+async fn user_registration_flow(server: &TestServer) {
+    let (user_id, token) = register_and_login(server).await;
+
+    let response = server.get("/api/users/me").authorization_bearer(&token).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let me: Value = response.json();
+    assert_eq!(me["id"], user_id);
+    assert_eq!(me["role"], "user");
+    assert!(me.get("password_hash").is_none());
+
+    let response = server
+        .post("/api/auth/register")
+        .json(&json!({ "username": me["username"], "email": "other@example.com", "password": "password123" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CONFLICT);
+
+    let response = server
+        .post("/api/auth/login")
+        .json(&json!({ "username": me["username"], "password": "wrong-password" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let new_username = format!("{}_v2", me["username"].as_str().unwrap());
+    let response = server
+        .put("/api/users/me")
+        .authorization_bearer(&token)
+        .json(&json!({ "username": new_username }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert_eq!(response.json::<Value>()["username"], new_username);
+
+    let response = server.delete("/api/users/me").authorization_bearer(&token).await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+    let response = server.get(&format!("/api/users/{}", user_id)).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+}
+
+async fn token_lifecycle_flow(server: &TestServer) {
+    let (_, login) = register_and_login_response(server).await;
+    assert_eq!(login["token_type"], "Bearer");
+    assert_eq!(login["expires_in"], 900);
+    let first_refresh = login["refresh_token"].as_str().unwrap();
+
+    // Rotation: the refresh token is exchanged for a new pair
+    let response = server.post("/api/auth/refresh").json(&json!({ "refresh_token": first_refresh })).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let rotated: Value = response.json();
+    let second_refresh = rotated["refresh_token"].as_str().unwrap();
+    assert_ne!(second_refresh, first_refresh);
+
+    let response = server.get("/api/users/me").authorization_bearer(rotated["access_token"].as_str().unwrap()).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+
+    // Reusing a rotated-out token revokes the whole family, including its successor
+    let response = server.post("/api/auth/refresh").json(&json!({ "refresh_token": first_refresh })).await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    let response = server.post("/api/auth/refresh").json(&json!({ "refresh_token": second_refresh })).await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    let response = server.post("/api/auth/refresh").json(&json!({ "refresh_token": "not-a-token" })).await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    // Logout revokes the access token and the session's refresh token
+    let (_, login) = register_and_login_response(server).await;
+    let access_token = login["access_token"].as_str().unwrap();
+    let response = server
+        .post("/api/auth/logout")
+        .authorization_bearer(access_token)
+        .json(&json!({ "refresh_token": login["refresh_token"] }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+    let response = server.get("/api/users/me").authorization_bearer(access_token).await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    let response = server.post("/api/auth/refresh").json(&json!({ "refresh_token": login["refresh_token"] })).await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    let response = server.post("/api/auth/logout").authorization_bearer(access_token).await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    // Without a body only the access token is revoked
+    let (_, login) = register_and_login_response(server).await;
+    let response = server.post("/api/auth/logout").authorization_bearer(login["access_token"].as_str().unwrap()).await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+    let response = server.post("/api/auth/refresh").json(&json!({ "refresh_token": login["refresh_token"] })).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+}
+
+async fn moderation_flow<R: Repository>(server: &TestServer, repository: &R) {
+    let admin_token = create_admin(server, repository).await;
+    let (user_id, user_login) = register_and_login_response(server).await;
+    let user_token = user_login["access_token"].as_str().unwrap();
+    let (moderator_id, moderator_login) = register_and_login_response(server).await;
+    repository.set_user_role(&moderator_id.parse().unwrap(), Role::Moderator).await.unwrap();
+    let moderator_token = login(server, &moderator_login["user"]["username"]).await;
+
+    let create_post = |title: &'static str| async move {
+        let response = server
+            .post("/api/posts")
+            .authorization_bearer(user_token)
+            .json(&json!({ "title": title, "content": "..." }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        response.json::<Value>()["id"].as_str().unwrap().to_string()
+    };
+    let ban_path = format!("/api/admin/users/{}/ban", user_id);
+
+    // Regular users cannot moderate, not even their own posts through the admin routes
+    let post_id = create_post("Spam").await;
+    let response = server.get("/api/admin/users").authorization_bearer(user_token).await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+    let response = server.post(&ban_path).authorization_bearer(user_token).await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+    let response = server.delete(&format!("/api/admin/posts/{}", post_id)).authorization_bearer(user_token).await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    // Moderators list users and delete any post, but cannot edit posts or ban
+    let response = server.get("/api/admin/users").authorization_bearer(&moderator_token).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert!(response.json::<Value>()[0].get("banned_at").is_some());
+    let response = server
+        .put(&format!("/api/posts/{}", post_id))
+        .authorization_bearer(&moderator_token)
+        .json(&json!({ "title": "Edited" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+    let post_path = format!("/api/posts/{}", post_id);
+    let response = server
+        .delete(&post_path)
+        .authorization_bearer(&moderator_token)
+        .add_header("if-match", etag(server, &post_path, &moderator_token).await)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+    let response = server.post(&ban_path).authorization_bearer(&moderator_token).await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    // Admins delete any post through the admin routes
+    let post_path = format!("/api/admin/posts/{}", create_post("More spam").await);
+    let response = server.delete(&post_path).authorization_bearer(&admin_token).await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+    let response = server.delete(&post_path).authorization_bearer(&admin_token).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    // Banning locks the user out immediately and ends their sessions
+    let response = server.post(&ban_path).authorization_bearer(&admin_token).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert!(response.json::<Value>()["banned_at"].is_string());
+    let response = server.get("/api/users/me").authorization_bearer(user_token).await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+    let response = server
+        .post("/api/auth/refresh")
+        .json(&json!({ "refresh_token": user_login["refresh_token"] }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    let response = server
+        .post("/api/auth/login")
+        .json(&json!({ "username": user_login["user"]["username"], "password": "password123" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    // Admins cannot be banned, and missing users are 404
+    let response = server.get("/api/users/me").authorization_bearer(&admin_token).await;
+    let admin_id = response.json::<Value>()["id"].as_str().unwrap().to_string();
+    let response = server
+        .post(&format!("/api/admin/users/{}/ban", admin_id))
+        .authorization_bearer(&admin_token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+    let response = server
+        .post(&format!("/api/admin/users/{}/ban", Uuid::new_v4()))
+        .authorization_bearer(&admin_token)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    let response = server.delete(&ban_path).authorization_bearer(&admin_token).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert!(response.json::<Value>()["banned_at"].is_null());
+    login(server, &user_login["user"]["username"]).await;
+}
+
+async fn engagement_flow(server: &TestServer) {
+    let (author_id, author_token) = register_and_login(server).await;
+    let (_, reader_token) = register_and_login(server).await;
+    let tag = format!("tag-{}", &Uuid::new_v4().simple().to_string()[..8]);
+
+    // Tags are normalized and deduplicated
+    let response = server
+        .post("/api/posts")
+        .authorization_bearer(&author_token)
+        .json(&json!({ "title": "Tagged", "content": "...", "tags": [tag.to_uppercase(), "Rust", " rust "] }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let post: Value = response.json();
+    assert_eq!(post["tags"], json!(["rust", tag]));
+    assert_eq!((post["like_count"].as_i64(), post["comment_count"].as_i64()), (Some(0), Some(0)));
+    let post_path = format!("/api/posts/{}", post["id"].as_str().unwrap());
+
+    let response = server
+        .post("/api/posts")
+        .authorization_bearer(&author_token)
+        .json(&json!({ "title": "Bad tags", "content": "...", "tags": ["no spaces"] }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    let response = server.get(&format!("/api/tags/{}/posts", tag.to_uppercase())).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert_eq!(response.json::<Value>()["items"].as_array().unwrap().len(), 1);
+
+    let response = server
+        .put(&post_path)
+        .authorization_bearer(&author_token)
+        .add_header("if-match", etag(server, &post_path, &author_token).await)
+        .json(&json!({ "tags": ["axum"] }))
+        .await;
+    assert_eq!(response.json::<Value>()["tags"], json!(["axum"]));
+    let response = server.get(&format!("/api/tags/{}/posts", tag)).await;
+    assert!(response.json::<Value>()["items"].as_array().unwrap().is_empty());
+
+    // Likes are per user and idempotent
+    for token in [&reader_token, &reader_token, &author_token] {
+        let response = server.post(&format!("{}/like", post_path)).authorization_bearer(token).await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+    }
+    let response = server.delete(&format!("{}/like", post_path)).authorization_bearer(&author_token).await;
+    assert_eq!(response.json::<Value>()["like_count"], 1);
+    let response = server.post(&format!("/api/posts/{}/like", Uuid::new_v4())).authorization_bearer(&reader_token).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    // Threaded comments
+    let comments_path = format!("{}/comments", post_path);
+    let response = server
+        .post(&comments_path)
+        .authorization_bearer(&reader_token)
+        .json(&json!({ "content": "Great post" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let question: Value = response.json();
+    let response = server
+        .post(&comments_path)
+        .authorization_bearer(&author_token)
+        .json(&json!({ "content": "Thanks!", "parent_id": question["id"] }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    assert_eq!(response.json::<Value>()["author_id"], author_id);
+
+    let response = server
+        .post(&comments_path)
+        .authorization_bearer(&reader_token)
+        .json(&json!({ "content": "" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    let response = server
+        .post(&comments_path)
+        .authorization_bearer(&reader_token)
+        .json(&json!({ "content": "Lost reply", "parent_id": Uuid::new_v4() }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    let response = server.get(&comments_path).await;
+    let threads: Value = response.json();
+    assert_eq!(threads.as_array().unwrap().len(), 1);
+    assert_eq!(threads[0]["replies"][0]["content"], "Thanks!");
+
+    let response = server.get(&post_path).await;
+    let post: Value = response.json();
+    assert_eq!((post["like_count"].as_i64(), post["comment_count"].as_i64()), (Some(1), Some(2)));
+
+    // Only the comment's author (or a moderator) may delete it; replies go with it
+    let comment_path = format!("/api/comments/{}", question["id"].as_str().unwrap());
+    let response = server.delete(&comment_path).authorization_bearer(&author_token).await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+    let response = server.delete(&comment_path).authorization_bearer(&reader_token).await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+    let response = server.get(&comments_path).await;
+    assert!(response.json::<Value>().as_array().unwrap().is_empty());
+
+    // Deleting the post removes its comments and likes
+    server
+        .post(&comments_path)
+        .authorization_bearer(&reader_token)
+        .json(&json!({ "content": "Still here?" }))
+        .await;
+    let response = server
+        .delete(&post_path)
+        .authorization_bearer(&author_token)
+        .add_header("if-match", etag(server, &post_path, &author_token).await)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+    let response = server.get(&comments_path).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    let response = server.get("/api/tags/axum/posts").add_query_param("limit", 100).await;
+    assert!(response.json::<Value>()["items"].as_array().unwrap().iter().all(|p| p["title"] != "Tagged"));
+}
+
+async fn post_crud_flow(server: &TestServer) {
+    let (author_id, author_token) = register_and_login(server).await;
+    let (_, other_token) = register_and_login(server).await;
+
+    let response = server
+        .post("/api/posts")
+        .authorization_bearer(&author_token)
+        .json(&json!({ "title": "Test Post", "content": "This is a test post content." }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let post: Value = response.json();
+    assert_eq!(post["author_id"], author_id);
+    let post_path = format!("/api/posts/{}", post["id"].as_str().unwrap());
+
+    let response = server.get(&post_path).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert_eq!(response.json::<Value>()["title"], "Test Post");
+
+    let response = server.get(&format!("/api/users/{}/posts", author_id)).await;
+    assert_eq!(response.json::<Value>()["items"].as_array().unwrap().len(), 1);
+
+    // Someone else's post
+    let response = server
+        .put(&post_path)
+        .authorization_bearer(&other_token)
+        .json(&json!({ "title": "Hijacked" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+    let response = server.delete(&post_path).authorization_bearer(&other_token).await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    let response = server
+        .put(&post_path)
+        .authorization_bearer(&author_token)
+        .json(&json!({ "title": "" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    let response = server
+        .put(&post_path)
+        .authorization_bearer(&author_token)
+        .add_header("if-match", etag(server, &post_path, &author_token).await)
+        .json(&json!({ "title": "Updated Post" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert_eq!(response.json::<Value>()["title"], "Updated Post");
+
+    let response = server
+        .delete(&post_path)
+        .authorization_bearer(&author_token)
+        .add_header("if-match", response.header("etag"))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+
+    let response = server.get(&post_path).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    let response = server.delete(&post_path).authorization_bearer(&author_token).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+}
+
+// Drafts stay private until published, and every edit is a revision that can
+// be listed, compared and restored
+async fn post_workflow_flow<R: Repository>(server: &TestServer, repository: &R) {
+    let (author_id, author_token) = register_and_login(server).await;
+    let (_, other_token) = register_and_login(server).await;
+    let admin_token = create_admin(server, repository).await;
+    // A word no other flow uses, so search results are ours alone
+    let word = format!("draftword{}", &Uuid::new_v4().simple().to_string()[..8]);
+
+    let response = server
+        .post("/api/posts")
+        .authorization_bearer(&author_token)
+        .json(&json!({ "title": "Work in progress", "content": format!("First {}\nline two", word), "status": "draft" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let post: Value = response.json();
+    assert_eq!((&post["status"], &post["published_at"]), (&json!("draft"), &Value::Null));
+    let post_path = format!("/api/posts/{}", post["id"].as_str().unwrap());
+    let user_posts = format!("/api/users/{}/posts", author_id);
+
+    // Hidden from everyone but the author and moderators
+    assert_eq!(server.get(&post_path).await.status_code(), StatusCode::NOT_FOUND);
+    let response = server.get(&post_path).authorization_bearer(&other_token).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    assert_eq!(server.get(&format!("{}/comments", post_path)).await.status_code(), StatusCode::NOT_FOUND);
+    assert_eq!(server.get(&post_path).authorization_bearer(&author_token).await.status_code(), StatusCode::OK);
+    assert_eq!(server.get(&post_path).authorization_bearer(&admin_token).await.status_code(), StatusCode::OK);
+    assert_eq!(server.get(&post_path).authorization_bearer("invalid").await.status_code(), StatusCode::UNAUTHORIZED);
+    let response = server.post(&format!("{}/like", post_path)).authorization_bearer(&other_token).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+    let response = server
+        .post(&format!("{}/comments", post_path))
+        .authorization_bearer(&other_token)
+        .json(&json!({ "content": "Too early" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    // Left out of public listings and search, but not of the author's own
+    assert_eq!(server.get(&user_posts).await.json::<Value>()["items"], json!([]));
+    let response = server.get(&format!("/api/posts/search?q={}", word)).await;
+    assert_eq!(response.json::<Value>()["items"], json!([]));
+    let response = server.get("/api/users/me/posts?status=draft").authorization_bearer(&author_token).await;
+    assert_eq!(response.json::<Value>()["items"][0]["id"], post["id"]);
+    let response = server.get("/api/users/me/posts?status=published").authorization_bearer(&author_token).await;
+    assert_eq!(response.json::<Value>()["items"], json!([]));
+    let response = server.get("/api/users/me/posts?status=deleted").authorization_bearer(&author_token).await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    // Publishing sets published_at; status changes alone are not revisions
+    let response = server
+        .put(&post_path)
+        .authorization_bearer(&author_token)
+        .add_header("if-match", etag(server, &post_path, &author_token).await)
+        .json(&json!({ "status": "published" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let published: Value = response.json();
+    assert_eq!(published["status"], "published");
+    assert!(published["published_at"].is_string());
+    assert_eq!(server.get(&post_path).await.status_code(), StatusCode::OK);
+    assert_eq!(server.get(&user_posts).await.json::<Value>()["items"].as_array().unwrap().len(), 1);
+    let response = server.get(&format!("/api/posts/search?q={}", word)).await;
+    assert_eq!(response.json::<Value>()["items"][0]["id"], post["id"]);
+
+    // Every edit of the title or content is a revision
+    let response = server
+        .put(&post_path)
+        .authorization_bearer(&author_token)
+        .add_header("if-match", etag(server, &post_path, &author_token).await)
+        .json(&json!({ "content": format!("First {}\nline 2\nline three", word) }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let revisions_path = format!("{}/revisions", post_path);
+    let response = server.get(&revisions_path).authorization_bearer(&author_token).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let revisions: Value = response.json();
+    assert_eq!(revisions.as_array().unwrap().iter().map(|r| r["revision"].clone()).collect::<Vec<_>>(), [2, 1]);
+    assert_eq!(revisions[0]["editor_id"], author_id);
+    assert_eq!(server.get(&revisions_path).authorization_bearer(&admin_token).await.status_code(), StatusCode::OK);
+    let response = server.get(&revisions_path).authorization_bearer(&other_token).await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+
+    let response = server.get(&format!("{}/diff?from=1&to=2", revisions_path)).authorization_bearer(&author_token).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let diff: Value = response.json();
+    assert_eq!(diff["title"], json!([{ "op": "equal", "text": "Work in progress" }]));
+    let ops: Vec<(&str, &str)> = diff["content"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|line| (line["op"].as_str().unwrap(), line["text"].as_str().unwrap()))
+        .collect();
+    assert_eq!(ops[1..], [("delete", "line two"), ("insert", "line 2"), ("insert", "line three")]);
+    let response = server.get(&format!("{}/diff?from=1&to=9", revisions_path)).authorization_bearer(&author_token).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    // Restoring adds a revision with the old text
+    let response = server.post(&format!("{}/1/restore", revisions_path)).authorization_bearer(&other_token).await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+    let response = server.post(&format!("{}/1/restore", revisions_path)).authorization_bearer(&author_token).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert_eq!(response.json::<Value>()["content"], post["content"]);
+    let response = server.get(&revisions_path).authorization_bearer(&author_token).await;
+    assert_eq!(response.json::<Value>()[0]["revision"], 3);
+    let response = server.post(&format!("{}/9/restore", revisions_path)).authorization_bearer(&author_token).await;
+    assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+
+    // Archiving hides the post again but keeps its publication date
+    let response = server
+        .put(&post_path)
+        .authorization_bearer(&author_token)
+        .add_header("if-match", etag(server, &post_path, &author_token).await)
+        .json(&json!({ "status": "archived" }))
+        .await;
+    assert_eq!(response.json::<Value>()["published_at"], published["published_at"]);
+    assert_eq!(server.get(&post_path).await.status_code(), StatusCode::NOT_FOUND);
+    assert_eq!(server.get(&user_posts).await.json::<Value>()["items"], json!([]));
+}
+
+// Writes must name the version they are based on, so two clients editing the
+// same post cannot overwrite each other; reads can be revalidated
+async fn conditional_request_flow(server: &TestServer) {
+    let (_, author_token) = register_and_login(server).await;
+    let (_, reader_token) = register_and_login(server).await;
+
+    let response = server
+        .post("/api/posts")
+        .authorization_bearer(&author_token)
+        .json(&json!({ "title": "Shared", "content": "Edited in two tabs" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+    let created = response.header("etag");
+    let post: Value = response.json();
+    assert_eq!(post["version"], 1);
+    let post_path = format!("/api/posts/{}", post["id"].as_str().unwrap());
+
+    // Reads carry the ETag, and answer 304 without a body while it is current
+    let response = server.get(&post_path).await;
+    assert_eq!(response.header("etag"), created);
+    for cached in [created.to_str().unwrap().to_string(), format!("W/{}", created.to_str().unwrap()), "*".to_string()] {
+        let response = server.get(&post_path).add_header("if-none-match", cached).await;
+        assert_eq!(response.status_code(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.header("etag"), created);
+        assert!(response.as_bytes().is_empty());
+    }
+    let response = server.get(&post_path).add_header("if-none-match", "\"1-0000000000000000\"").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let response = server.get(&post_path).add_header("if-none-match", "unquoted").await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    // Writes without If-Match are refused, after the usual permission checks
+    let response = server
+        .put(&post_path)
+        .authorization_bearer(&reader_token)
+        .json(&json!({ "title": "Hijacked" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::FORBIDDEN);
+    let response = server
+        .put(&post_path)
+        .authorization_bearer(&author_token)
+        .json(&json!({ "title": "No precondition" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::PRECONDITION_REQUIRED);
+    assert_eq!(response.json::<Value>()["type"], "/problems/precondition-required");
+    let response = server.delete(&post_path).authorization_bearer(&author_token).await;
+    assert_eq!(response.status_code(), StatusCode::PRECONDITION_REQUIRED);
+
+    // The first tab saves; the second, still on the old ETag, is told so
+    let response = server
+        .put(&post_path)
+        .authorization_bearer(&author_token)
+        .add_header("if-match", created.clone())
+        .json(&json!({ "title": "First tab" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let updated = response.header("etag");
+    assert_ne!(updated, created);
+    assert_eq!(response.json::<Value>()["version"], 2);
+    let response = server
+        .put(&post_path)
+        .authorization_bearer(&author_token)
+        .add_header("if-match", created.clone())
+        .json(&json!({ "title": "Second tab" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::PRECONDITION_FAILED);
+    assert_eq!(response.json::<Value>()["type"], "/problems/precondition-failed");
+    let response = server.get(&post_path).add_header("if-none-match", created).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert_eq!(response.json::<Value>()["title"], "First tab");
+    let response = server
+        .put(&post_path)
+        .authorization_bearer(&author_token)
+        .add_header("if-match", "not a tag")
+        .json(&json!({ "title": "Malformed" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    // Likes change the representation, and so the ETag, but not the version:
+    // the author's edit based on the ETag from before the like still applies
+    server.post(&format!("{}/like", post_path)).authorization_bearer(&reader_token).await;
+    let liked = etag(server, &post_path, &author_token).await;
+    assert_ne!(liked, updated);
+    let response = server.get(&post_path).add_header("if-none-match", updated.clone()).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let response = server
+        .put(&post_path)
+        .authorization_bearer(&author_token)
+        .add_header("if-match", updated.clone())
+        .json(&json!({ "tags": ["rust"] }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let tagged = response.header("etag");
+    let post: Value = response.json();
+    assert_eq!(post["tags"], json!(["rust"]));
+    assert_eq!(post["version"], 3);
+
+    // Tag changes bump the version like any other edit
+    let response = server.delete(&post_path).authorization_bearer(&author_token).add_header("if-match", updated).await;
+    assert_eq!(response.status_code(), StatusCode::PRECONDITION_FAILED);
+    let response = server.delete(&post_path).authorization_bearer(&author_token).add_header("if-match", tagged).await;
+    assert_eq!(response.status_code(), StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn test_health_endpoints() {
+    health_flow(&in_memory_server()).await;
+}
+
+#[tokio::test]
+async fn test_readiness_reports_database_outage() {
+    let (server, repository) = sqlite_backend().await;
+
+    let response = server.get("/ready").await;
+    let body: Value = response.json();
+    assert_eq!(body["status"], "ready");
+    assert_eq!(body["checks"]["database"]["pool"]["max"], 1);
+    let text = server.get("/metrics").await.text();
+    assert!(text.contains("db_pool_max_connections 1"));
+
+    repository.pool().close().await;
+    let response = server.get("/ready").await;
+    assert_eq!(response.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+    let body: Value = response.json();
+    assert_eq!(body["status"], "unavailable");
+    assert_eq!(body["checks"]["database"]["status"], "down");
+
+    // Liveness does not depend on the database
+    assert_eq!(server.get("/live").await.status_code(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_protected_routes_require_token() {
+    let server = in_memory_server();
+    let post_path = format!("/api/posts/{}", Uuid::new_v4());
+
+    let responses = vec![
+        server.post("/api/posts").json(&json!({ "title": "t", "content": "c" })).await,
+        server.put(&post_path).json(&json!({ "title": "t" })).await,
+        server.delete(&post_path).await,
+        server.get("/api/users/me").await,
+        server.put("/api/users/me").json(&json!({ "username": "someone" })).await,
+        server.delete("/api/users/me").await,
+        server.get("/api/users/me/posts").await,
+        server.post("/api/auth/logout").await,
+        server.get("/api/admin/users").await,
+        server.post(&format!("/api/admin/users/{}/ban", Uuid::new_v4())).await,
+        server.delete(&post_path.replace("/api/", "/api/admin/")).await,
+        server.post(&format!("{}/comments", post_path)).json(&json!({ "content": "c" })).await,
+        server.post(&format!("{}/like", post_path)).await,
+        server.delete(&format!("{}/like", post_path)).await,
+        server.delete(&format!("/api/comments/{}", Uuid::new_v4())).await,
+    ];
+
+    for response in responses {
+        assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+        assert_eq!(response.json::<Value>()["status"], 401);
+    }
+}
+
+#[tokio::test]
+async fn test_errors_are_problem_details() {
+    let server = in_memory_server();
+
+    // Validation failures name each invalid field
+    let response = server
+        .post("/api/auth/register")
+        .add_header("x-request-id", "client-req-1")
+        .json(&json!({ "username": "ab", "email": "not-an-email", "password": "password123" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    assert_eq!(response.header("content-type"), "application/problem+json");
+    assert_eq!(response.header("x-request-id"), "client-req-1");
+    let problem: Value = response.json();
+    assert_eq!(problem["type"], "/problems/validation-error");
+    assert_eq!(problem["status"], 400);
+    assert_eq!(problem["instance"], "/api/auth/register");
+    assert_eq!(problem["request_id"], "client-req-1");
+    let errors = problem["errors"].as_object().unwrap();
+    assert_eq!(errors.keys().collect::<Vec<_>>(), ["email", "username"]);
+    assert_eq!(errors["username"][0]["code"], "length");
+    assert_eq!(errors["email"][0]["message"], "must be a valid email address");
+
+    let (_, token) = register_and_login(&server).await;
+    let response = server
+        .post("/api/posts")
+        .authorization_bearer(&token)
+        .json(&json!({ "title": "t", "content": "c", "tags": ["no spaces allowed"] }))
+        .await;
+    let problem: Value = response.json();
+    assert_eq!(problem["errors"]["tags"][0]["code"], "invalid_tag");
+
+    // Extractor rejections use the same format; generated ids are echoed too
+    let response = server
+        .post("/api/posts")
+        .authorization_bearer(&token)
+        .content_type("application/json")
+        .bytes("{ not json".into())
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    let problem: Value = response.json();
+    assert_eq!(problem["type"], "/problems/malformed-request");
+    assert_eq!(problem["request_id"], response.header("x-request-id").to_str().unwrap());
+    assert!(problem.get("errors").is_none());
+
+    let response = server.get("/api/posts/not-a-uuid").await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    assert_eq!(response.json::<Value>()["type"], "/problems/malformed-request");
+    let response = server.get("/api/posts").add_query_param("limit", "many").await;
+    assert_eq!(response.json::<Value>()["status"], 400);
+
+    // Unacceptable client ids are replaced
+    let response = server
+        .get(&format!("/api/posts/{}", Uuid::nil()))
+        .add_header("x-request-id", "bad id!")
+        .await;
+    let problem: Value = response.json();
+    assert_eq!(problem["type"], "/problems/not-found");
+    assert_ne!(problem["request_id"], "bad id!");
+    assert_eq!(problem["request_id"], response.header("x-request-id").to_str().unwrap());
+}
+
+#[tokio::test]
+async fn test_auth_routes_are_rate_limited() {
+    let config = RateLimitConfig {
+        per_ip: BucketConfig { burst: 4, per_minute: 1 },
+        per_account: BucketConfig { burst: 5, per_minute: 1 },
+        lockout_threshold: 2,
+        lockout_base: chrono::Duration::minutes(1),
+        trust_forwarded_for: true,
+        ..RateLimitConfig::default()
+    };
+    let state = AppState::new(InMemoryRepository::new(), JwtService::new(JWT_SECRET))
+        .with_rate_limiter(RateLimiter::in_memory(config));
+    let server = TestServer::new(create_router(state)).unwrap();
+
+    let mut next_ip = 0;
+    let mut attempt = |ip: Option<&str>, path: &str, body: Value| {
+        next_ip += 1;
+        let ip = ip.map_or_else(|| format!("192.0.2.{}", next_ip), str::to_string);
+        server.post(path).add_header("x-forwarded-for", ip).json(&body)
+    };
+    let credentials = |username: &str, password: &str| json!({ "username": username, "password": password });
+    let signup = |username: &str| {
+        json!({ "username": username, "email": format!("{}@example.com", username), "password": "password123" })
+    };
+
+    // Repeated wrong passwords lock the account, even against the right one
+    assert_eq!(attempt(None, "/api/auth/register", signup("alice")).await.status_code(), StatusCode::CREATED);
+    for _ in 0..2 {
+        let response = attempt(None, "/api/auth/login", credentials("alice", "wrong-password")).await;
+        assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    }
+    let response = attempt(None, "/api/auth/login", credentials("Alice", "password123")).await;
+    assert_eq!(response.status_code(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: i64 = response.header("retry-after").to_str().unwrap().parse().unwrap();
+    assert!((1..=60).contains(&retry_after));
+    let problem: Value = response.json();
+    assert_eq!(problem["type"], "/problems/rate-limited");
+    assert!(problem["detail"].as_str().unwrap().contains("locked"));
+
+    // Each account has its own bucket, whatever address the attempts come from
+    assert_eq!(attempt(None, "/api/auth/register", signup("bob")).await.status_code(), StatusCode::CREATED);
+    for _ in 0..4 {
+        let response = attempt(None, "/api/auth/login", credentials("bob", "password123")).await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+    }
+    let response = attempt(None, "/api/auth/login", credentials("bob", "password123")).await;
+    assert_eq!(response.status_code(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: i64 = response.header("retry-after").to_str().unwrap().parse().unwrap();
+    assert!((1..=60).contains(&retry_after));
+    assert!(response.json::<Value>()["detail"].as_str().unwrap().contains("account"));
+
+    // And each address has its own, whatever accounts it tries
+    let ip = Some("198.51.100.7");
+    for i in 0..4 {
+        let response = attempt(ip, "/api/auth/login", credentials(&format!("nobody{}", i), "password123")).await;
+        assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    }
+    let response = attempt(ip, "/api/auth/refresh", json!({ "refresh_token": "nope" })).await;
+    assert_eq!(response.status_code(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(response.json::<Value>()["detail"].as_str().unwrap().contains("address"));
+    let response = attempt(None, "/api/auth/register", signup("carol")).await;
+    assert_eq!(response.status_code(), StatusCode::CREATED);
+
+    // Other routes are not limited
+    for _ in 0..5 {
+        let response = server.get("/api/posts").add_header("x-forwarded-for", "198.51.100.7").await;
+        assert_eq!(response.status_code(), StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn test_cors_follows_config() {
+    let config = CorsConfig { allowed_origins: vec!["https://blog.example.com".to_string()], ..CorsConfig::default() };
+    let state = AppState::new(InMemoryRepository::new(), JwtService::new(JWT_SECRET));
+    let server = TestServer::new(create_router(state).layer(cors_layer(&config))).unwrap();
+    let preflight = |origin: &'static str| {
+        server
+            .method(Method::OPTIONS, "/api/posts")
+            .add_header("origin", origin)
+            .add_header("access-control-request-method", "POST")
+            .add_header("access-control-request-headers", "authorization,content-type,if-match")
+    };
+
+    let response = preflight("https://blog.example.com").await;
+    assert_eq!(response.header("access-control-allow-origin"), "https://blog.example.com");
+    assert_eq!(response.header("access-control-max-age"), "3600");
+    let allowed_headers = response.header("access-control-allow-headers");
+    assert!(allowed_headers.to_str().unwrap().contains("authorization"));
+    assert!(allowed_headers.to_str().unwrap().contains("if-match"));
+
+    let response = preflight("https://evil.example.com").await;
+    assert!(response.maybe_header("access-control-allow-origin").is_none());
+
+    let response = server.get("/api/posts").add_header("origin", "https://blog.example.com").await;
+    assert_eq!(response.header("access-control-allow-origin"), "https://blog.example.com");
+    let exposed_headers = response.header("access-control-expose-headers");
+    assert!(exposed_headers.to_str().unwrap().contains("x-request-id"));
+    assert!(exposed_headers.to_str().unwrap().contains("etag"));
+}
+
+#[tokio::test]
+async fn test_invalid_token_is_rejected() {
+    let server = in_memory_server();
+
+    let response = server.get("/api/users/me").authorization_bearer("invalid.jwt.token").await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    // Token signed with a different secret
+    let token = JwtService::new("another_secret")
+        .generate_token(&Uuid::new_v4().to_string(), "mallory", Role::Admin)
+        .unwrap();
+    let response = server.get("/api/users/me").authorization_bearer(token).await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    // Valid signature but the subject is not a user ID
+    let token = JwtService::new(JWT_SECRET).generate_token("not-a-uuid", "mallory", Role::User).unwrap();
+    let response = server.get("/api/users/me").authorization_bearer(token).await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+
+    // Valid signature but the user does not exist (e.g. deleted)
+    let token = JwtService::new(JWT_SECRET)
+        .generate_token(&Uuid::new_v4().to_string(), "ghost", Role::Admin)
+        .unwrap();
+    let response = server.get("/api/admin/users").authorization_bearer(token).await;
+    assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
 }
 
 #[tokio::test]
 async fn test_user_registration_flow() {
-    // This test would require a database and full app setup
-    // For demonstration purposes only
+    user_registration_flow(&in_memory_server()).await;
+}
 
-    let registration_payload = json!({
-        "username": "testuser",
-        "email": "test@example.com",
-        "password": "password123"
-    });
+#[tokio::test]
+async fn test_token_lifecycle_flow() {
+    token_lifecycle_flow(&in_memory_server()).await;
+}
 
-    // In a real test:
-    // 1. Start the full app with test database
-    // 2. POST to /api/auth/register
-    // 3. Verify user is created
-    // 4. POST to /api/auth/login
-    // 5. Verify JWT token is returned
-    // 6. Use token to access protected endpoints
+#[tokio::test]
+async fn test_moderation_flow() {
+    let (server, repository) = in_memory_backend();
+    moderation_flow(&server, &repository).await;
+}
 
-    assert!(registration_payload["username"].is_string());
-    assert!(registration_payload["email"].is_string());
-    assert!(registration_payload["password"].is_string());
+#[tokio::test]
+async fn test_engagement_flow() {
+    engagement_flow(&in_memory_server()).await;
 }
 
 #[tokio::test]
 async fn test_post_crud_flow() {
-    let post_payload = json!({
-        "title": "Test Post",
-        "content": "This is a test post content."
-    });
-
-    // This would test the full CRUD cycle for posts:
-    // 1. Create a user and authenticate
-    // 2. Create a post
-    // 3. Get the post
-    // 4. Update the post
-    // 5. Delete the post
-
-    assert_eq!(post_payload["title"], "Test Post");
-    assert_eq!(post_payload["content"], "This is a test post content.");
+    post_crud_flow(&in_memory_server()).await;
+}
+
+#[tokio::test]
+async fn test_post_workflow_flow() {
+    let (server, repository) = in_memory_backend();
+    post_workflow_flow(&server, &repository).await;
+}
+
+#[tokio::test]
+async fn test_conditional_request_flow() {
+    conditional_request_flow(&in_memory_server()).await;
+}
+
+async fn post_listing_and_search_flow(server: &TestServer) {
+    let (_, token) = register_and_login(server).await;
+
+    for title in ["Learning Rust", "Axum routing", "rust ownership"] {
+        let response = server
+            .post("/api/posts")
+            .authorization_bearer(&token)
+            .json(&json!({ "title": title, "content": "..." }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+    }
+
+    let titles = |body: Value| -> Vec<String> {
+        body["items"].as_array().unwrap().iter().map(|p| p["title"].as_str().unwrap().to_string()).collect()
+    };
+
+    let response = server.get("/api/posts").add_query_param("limit", 2).await;
+    assert_eq!(titles(response.json()), vec!["rust ownership", "Axum routing"]);
+
+    let response = server
+        .get("/api/posts/search")
+        .add_query_param("q", "RUST")
+        .add_query_param("sort", "newest")
+        .await;
+    assert_eq!(titles(response.json()), vec!["rust ownership", "Learning Rust"]);
+
+    let response = server.get("/api/users/me/posts").authorization_bearer(&token).await;
+    assert_eq!(titles(response.json()).len(), 3);
+}
+
+#[tokio::test]
+async fn test_post_listing_and_search() {
+    post_listing_and_search_flow(&in_memory_server()).await;
+}
+
+async fn pagination_flow(server: &TestServer) {
+    let (author_id, token) = register_and_login(server).await;
+    let (_, reader_token) = register_and_login(server).await;
+    let posts_path = format!("/api/users/{}/posts", author_id);
+
+    let mut ids = Vec::new();
+    for title in ["delta", "alpha", "echo", "charlie", "bravo"] {
+        let response = server
+            .post("/api/posts")
+            .authorization_bearer(&token)
+            .json(&json!({ "title": title, "content": "..." }))
+            .await;
+        ids.push(response.json::<Value>()["id"].as_str().unwrap().to_string());
+    }
+    for (id, likers) in [(&ids[1], vec![&token, &reader_token]), (&ids[3], vec![&token])] {
+        for liker in likers {
+            server.post(&format!("/api/posts/{}/like", id)).authorization_bearer(liker).await;
+        }
+    }
+
+    // Walk every page by following the cursor and the Link header
+    let walk = |sort: &'static str| {
+        let posts_path = posts_path.clone();
+        async move {
+            let mut titles = Vec::new();
+            let mut cursor: Option<String> = None;
+            loop {
+                let mut request = server.get(&posts_path).add_query_param("sort", sort).add_query_param("limit", 2);
+                if let Some(cursor) = &cursor {
+                    request = request.add_query_param("cursor", cursor);
+                }
+                let response = request.await;
+                assert_eq!(response.status_code(), StatusCode::OK);
+                let link = response.maybe_header("link");
+                let page: Value = response.json();
+                for post in page["items"].as_array().unwrap() {
+                    titles.push(post["title"].as_str().unwrap().to_string());
+                }
+
+                match page["next_cursor"].as_str() {
+                    Some(next) => {
+                        let link = link.expect("Link header on a page with a next cursor");
+                        let link = link.to_str().unwrap();
+                        assert!(link.contains(&format!("cursor={}", next)) && link.contains("sort=") && link.ends_with("rel=\"next\""));
+                        cursor = Some(next.to_string());
+                    }
+                    None => {
+                        assert!(link.is_none());
+                        break titles;
+                    }
+                }
+            }
+        }
+    };
+
+    assert_eq!(walk("newest").await, ["bravo", "charlie", "echo", "alpha", "delta"]);
+    assert_eq!(walk("oldest").await, ["delta", "alpha", "echo", "charlie", "bravo"]);
+    assert_eq!(walk("title").await, ["alpha", "bravo", "charlie", "delta", "echo"]);
+    assert_eq!(walk("most_liked").await, ["alpha", "charlie", "bravo", "echo", "delta"]);
+
+    // The total is only counted on request
+    let response = server.get(&posts_path).add_query_param("limit", 1).await;
+    let page: Value = response.json();
+    assert!(page.get("total").is_none());
+    let response = server.get(&posts_path).add_query_param("include_total", true).await;
+    let page: Value = response.json();
+    assert_eq!((page["total"].as_i64(), page["next_cursor"].is_null()), (Some(5), true));
+
+    // Page sizes are capped instead of rejected
+    let response = server.get(&posts_path).add_query_param("limit", 100_000).await;
+    assert_eq!(response.json::<Value>()["items"].as_array().unwrap().len(), 5);
+
+    let response = server.get(&posts_path).add_query_param("cursor", "garbage").await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    let response = server.get(&posts_path).add_query_param("sort", "random").await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+
+    // A cursor only continues the listing order it came from
+    let response = server.get(&posts_path).add_query_param("limit", 1).await;
+    let cursor = response.json::<Value>()["next_cursor"].as_str().unwrap().to_string();
+    let response = server
+        .get(&posts_path)
+        .add_query_param("sort", "title")
+        .add_query_param("cursor", &cursor)
+        .await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_pagination_flow() {
+    pagination_flow(&in_memory_server()).await;
+}
+
+async fn search_flow(server: &TestServer) {
+    let (author_id, token) = register_and_login(server).await;
+    let (other_id, other_token) = register_and_login(server).await;
+    // A word no other test uses keeps the results to this flow's posts
+    let word = format!("zq{}", &Uuid::new_v4().simple().to_string()[..8]);
+
+    let create = |token: String, title: String, content: String| async move {
+        let response = server
+            .post("/api/posts")
+            .authorization_bearer(&token)
+            .json(&json!({ "title": title, "content": content }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::CREATED);
+        response.json::<Value>()
+    };
+    let in_title = create(token.clone(), format!("Async {} in depth", word), "Futures and executors.".to_string()).await;
+    let in_content = create(
+        token.clone(),
+        "Notes".to_string(),
+        format!("{} <b>{}ers</b> love async runtimes, {}", "lorem ".repeat(20), word, word),
+    )
+    .await;
+    let by_other = create(other_token.clone(), "Other".to_string(), format!("Async ideas about {}", word)).await;
+
+    let search = |params: Vec<(&'static str, String)>| async move {
+        let mut request = server.get("/api/posts/search");
+        for (name, value) in &params {
+            request = request.add_query_param(name, value);
+        }
+        request.await
+    };
+    let ids = |page: &Value| -> Vec<String> {
+        page["items"].as_array().unwrap().iter().map(|hit| hit["id"].as_str().unwrap().to_string()).collect()
+    };
+
+    // Title matches rank first; results carry a score and highlighted text
+    let response = search(vec![("q", word.to_uppercase()), ("include_total", "true".to_string())]).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let page: Value = response.json();
+    assert_eq!(page["total"], 3);
+    assert_eq!(ids(&page)[0], in_title["id"].as_str().unwrap());
+    assert!(page["items"][0]["score"].as_f64().unwrap() > page["items"][2]["score"].as_f64().unwrap());
+    assert_eq!(page["items"][0]["highlighted_title"], format!("Async <mark>{}</mark> in depth", word));
+
+    // Prefix queries, with the snippet cut around the match and HTML escaped
+    let response = search(vec![("q", format!("{}er*", word))]).await;
+    let page: Value = response.json();
+    assert_eq!(ids(&page), [in_content["id"].as_str().unwrap()]);
+    let snippet = page["items"][0]["snippet"].as_str().unwrap();
+    assert!(snippet.starts_with('…') && snippet.contains(&format!("&lt;b&gt;<mark>{}ers</mark>&lt;/b&gt;", word)));
+
+    // Phrases match words next to each other, in order
+    let response = search(vec![("q", format!("\"async {}\"", word))]).await;
+    assert_eq!(ids(&response.json()), [in_title["id"].as_str().unwrap()]);
+    let response = search(vec![("q", format!("\"{} async\"", word))]).await;
+    assert!(ids(&response.json()).is_empty());
+
+    // Filters by author and creation time
+    let response = search(vec![("q", word.clone()), ("author", other_id.clone())]).await;
+    assert_eq!(ids(&response.json()), [by_other["id"].as_str().unwrap()]);
+    let created_at = by_other["created_at"].as_str().unwrap().to_string();
+    let response = search(vec![("q", word.clone()), ("from", created_at.clone()), ("author", other_id)]).await;
+    assert_eq!(ids(&response.json()).len(), 1);
+    let response = search(vec![("q", word.clone()), ("to", in_title["created_at"].as_str().unwrap().to_string())]).await;
+    assert_eq!(ids(&response.json()), [in_title["id"].as_str().unwrap()]);
+
+    // Paging by relevance follows the cursor
+    let response = search(vec![("q", word.clone()), ("limit", "2".to_string())]).await;
+    let first: Value = response.json();
+    let cursor = first["next_cursor"].as_str().unwrap().to_string();
+    let response = search(vec![("q", word.clone()), ("limit", "2".to_string()), ("cursor", cursor)]).await;
+    let second: Value = response.json();
+    assert_eq!(ids(&second).len(), 1);
+    assert!(!ids(&first).contains(&ids(&second)[0]));
+
+    // Edits are searchable right away, deleted posts disappear
+    let in_title_path = format!("/api/posts/{}", in_title["id"].as_str().unwrap());
+    let response = server
+        .put(&in_title_path)
+        .authorization_bearer(&token)
+        .add_header("if-match", etag(server, &in_title_path, &token).await)
+        .json(&json!({ "title": "Renamed" }))
+        .await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let response = search(vec![("q", "renamed".to_string()), ("author", author_id)]).await;
+    assert_eq!(ids(&response.json()), [in_title["id"].as_str().unwrap()]);
+    let by_other_path = format!("/api/posts/{}", by_other["id"].as_str().unwrap());
+    server
+        .delete(&by_other_path)
+        .authorization_bearer(&other_token)
+        .add_header("if-match", etag(server, &by_other_path, &other_token).await)
+        .await;
+    let response = search(vec![("q", word.clone()), ("include_total", "true".to_string())]).await;
+    let page: Value = response.json();
+    assert_eq!(page["total"], 1);
+    assert_eq!(ids(&page), [in_content["id"].as_str().unwrap()]);
+
+    for bad in [
+        vec![("q", " !! ".to_string())],
+        vec![("q", word.clone()), ("from", "2024-02-01T00:00:00Z".to_string()), ("to", "2024-01-01T00:00:00Z".to_string())],
+        vec![("q", word.clone()), ("from", "yesterday".to_string())],
+    ] {
+        assert_eq!(search(bad).await.status_code(), StatusCode::BAD_REQUEST);
+    }
+    let response = server.get("/api/posts").add_query_param("sort", "relevance").await;
+    assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_search_flow() {
+    search_flow(&in_memory_server()).await;
+}
+
+// The route paths registered in src/routes.rs, in OpenAPI `{param}` syntax
+fn registered_paths() -> Vec<String> {
+    include_str!("../src/routes.rs")
+        .split(".route(")
+        .skip(1)
+        .map(|route| {
+            let path = route.trim_start().trim_start_matches('"');
+            let path = &path[..path.find('"').unwrap()];
+            path.split('/')
+                .map(|segment| match segment.strip_prefix(':') {
+                    Some(param) => format!("{{{}}}", param),
+                    None => segment.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_openapi_spec_matches_routes() {
+    let server = in_memory_server();
+    let response = server.get(SPEC_PATH).await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    let spec: Value = response.json();
+    let paths = spec["paths"].as_object().unwrap();
+
+    // Every route is documented, and nothing else is
+    let mut documented: Vec<&String> = paths.keys().collect();
+    let mut registered = registered_paths();
+    documented.sort();
+    registered.sort();
+    registered.dedup();
+    assert_eq!(documented, registered.iter().collect::<Vec<_>>());
+
+    // On every path, exactly the documented methods reach a handler. The
+    // router answers other methods with 405 and unknown paths with an empty
+    // 404; handlers and `auth_middleware` always send a JSON body. Requests
+    // for undocumented methods carry a token, or `auth_middleware` would
+    // answer 401 before the router gets to say 405; documented ones go
+    // without, so that they never change anything.
+    let (_, token) = register_and_login(&server).await;
+    for (path, operations) in paths {
+        let url = path.replace("{id}", &Uuid::nil().to_string()).replace("{tag}", "rust").replace("{revision}", "1");
+        for method in [Method::GET, Method::POST, Method::PUT, Method::DELETE, Method::PATCH] {
+            let documented = operations.get(method.as_str().to_lowercase()).is_some();
+            let mut request = server.method(method.clone(), &url);
+            if !documented {
+                request = request.authorization_bearer(&token);
+            }
+            let response = request.await;
+            let routed = response.status_code() != StatusCode::METHOD_NOT_ALLOWED
+                && !(response.status_code() == StatusCode::NOT_FOUND && response.as_bytes().is_empty());
+            assert_eq!(routed, documented, "{} {} is routed: {}, documented: {}", method, path, routed, documented);
+        }
+    }
+
+    // Error bodies and validation limits match the code
+    let schemas = &spec["components"]["schemas"];
+    let response = server.get(&format!("/api/posts/{}", Uuid::nil())).await;
+    let error: Value = response.json();
+    let properties = schemas["ProblemDetails"]["properties"].as_object().unwrap();
+    assert!(error.as_object().unwrap().keys().all(|key| properties.contains_key(key)));
+    assert_eq!(schemas["CreatePostRequest"]["properties"]["tags"]["maxItems"], MAX_TAGS_PER_POST);
+    assert_eq!(schemas["Tag"]["maxLength"], MAX_TAG_LENGTH);
+    assert_eq!(schemas["CreateUserRequest"]["properties"]["password"]["minLength"], 8);
+
+    let response = server.get("/api-docs/").await;
+    assert_eq!(response.status_code(), StatusCode::OK);
+    assert!(response.text().contains("swagger-ui"));
+}
+
+#[tokio::test]
+async fn test_sqlite_backend() {
+    let (server, repository) = sqlite_backend().await;
+    health_flow(&server).await;
+    user_registration_flow(&server).await;
+    token_lifecycle_flow(&server).await;
+    moderation_flow(&server, &repository).await;
+    engagement_flow(&server).await;
+    post_crud_flow(&server).await;
+    post_workflow_flow(&server, &repository).await;
+    conditional_request_flow(&server).await;
+    post_listing_and_search_flow(&server).await;
+    pagination_flow(&server).await;
+    search_flow(&server).await;
+}
+
+#[tokio::test]
+async fn test_postgres_backend() {
+    let Some((server, repository)) = postgres_backend().await else { return };
+    health_flow(&server).await;
+    user_registration_flow(&server).await;
+    token_lifecycle_flow(&server).await;
+    moderation_flow(&server, &repository).await;
+    engagement_flow(&server).await;
+    post_crud_flow(&server).await;
+    post_workflow_flow(&server, &repository).await;
+    conditional_request_flow(&server).await;
+    pagination_flow(&server).await;
+    search_flow(&server).await;
 }
@@ -0,0 +1,355 @@
+//! 自适应测验（计算机化自适应测试）
+//!
+//! 使用单参数 Rasch 模型：能力为 θ 的学习者答对难度为 b 的题目的概率为 1 / (1 + e^-(θ - b))。
+//! 题目难度由所属阶段和难度等级换算，阶段3的中等题难度为 0，每相差一个阶段难度相差 1。
+//! 能力用网格上的后验均值（EAP）估计，先验为标准正态分布，因此全对或全错时估计值仍然有限。
+
+use crate::error::AssessmentError;
+use crate::input::Input;
+use crate::question::{Difficulty, Question};
+use crate::quiz::{self, Quiz, QuizReport, SeededRng};
+use progress_tracker::LearningStage;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// 答对本阶段中等题的概率达到该值即视为已掌握
+pub const MASTERY_PROBABILITY: f64 = 0.6;
+
+/// 每次从信息量最大的几道题中随机挑选，避免每次测验题目完全相同
+const CANDIDATE_COUNT: usize = 3;
+
+/// 题目难度参数 b
+pub fn item_difficulty(q: &Question) -> f64 {
+    let level = match q.difficulty {
+        Difficulty::Easy => -0.5,
+        Difficulty::Medium => 0.0,
+        Difficulty::Hard => 0.5,
+    };
+    stage_difficulty(&q.stage) + level
+}
+
+/// 阶段中等题的难度参数
+fn stage_difficulty(stage: &LearningStage) -> f64 {
+    stage.number() as f64 - 3.0
+}
+
+/// 答对概率
+fn probability(theta: f64, difficulty: f64) -> f64 {
+    1.0 / (1.0 + (difficulty - theta).exp())
+}
+
+/// 能力估计
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AbilityEstimate {
+    pub theta: f64,
+    pub standard_error: f64,
+}
+
+impl AbilityEstimate {
+    /// 根据作答记录 `(题目难度, 是否答对)` 估计能力
+    pub fn from_responses(responses: &[(f64, bool)]) -> Self {
+        let grid: Vec<f64> = (0..=160).map(|i| -4.0 + i as f64 * 0.05).collect();
+        let log_posterior: Vec<f64> = grid.iter()
+            .map(|&theta| {
+                let log_likelihood: f64 = responses.iter()
+                    .map(|&(b, correct)| {
+                        let p = probability(theta, b);
+                        if correct { p.ln() } else { (1.0 - p).ln() }
+                    })
+                    .sum();
+                log_likelihood - theta * theta / 2.0
+            })
+            .collect();
+
+        // 减去最大值避免下溢
+        let max = log_posterior.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let weights: Vec<f64> = log_posterior.iter().map(|l| (l - max).exp()).collect();
+        let total: f64 = weights.iter().sum();
+
+        let theta = grid.iter().zip(&weights).map(|(t, w)| t * w).sum::<f64>() / total;
+        let variance = grid.iter().zip(&weights).map(|(t, w)| (t - theta).powi(2) * w).sum::<f64>() / total;
+
+        Self { theta, standard_error: variance.sqrt() }
+    }
+}
+
+/// 单个阶段的掌握程度
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageProficiency {
+    pub stage: LearningStage,
+    /// 答对该阶段中等题的预测概率
+    pub probability: f64,
+    pub answered: usize,
+    pub correct: usize,
+}
+
+impl StageProficiency {
+    pub fn mastered(&self) -> bool {
+        self.probability >= MASTERY_PROBABILITY
+    }
+}
+
+/// 按能力估计换算各阶段的掌握程度
+pub fn stage_proficiency(estimate: &AbilityEstimate, report: &QuizReport) -> Vec<StageProficiency> {
+    LearningStage::all_stages().into_iter()
+        .map(|stage| {
+            let records: Vec<_> = report.records.iter().filter(|r| r.question.stage == stage).collect();
+            StageProficiency {
+                probability: probability(estimate.theta, stage_difficulty(&stage)),
+                answered: records.len(),
+                correct: records.iter().filter(|r| r.correct).count(),
+                stage,
+            }
+        })
+        .collect()
+}
+
+/// 建议的起始阶段：第一个尚未掌握的阶段
+pub fn recommended_stage(proficiency: &[StageProficiency]) -> LearningStage {
+    proficiency.iter()
+        .find(|p| !p.mastered())
+        .map(|p| p.stage.clone())
+        .unwrap_or(LearningStage::Stage5Projects)
+}
+
+/// 选出下一题：在未作答的题目中，从难度最接近当前能力（信息量最大）的几道题里随机挑选
+pub fn next_question(pool: &[Question], used: &[bool], theta: f64, rng: &mut SeededRng) -> Option<usize> {
+    let mut candidates: Vec<(usize, f64)> = pool.iter()
+        .enumerate()
+        .filter(|(i, _)| !used[*i])
+        .map(|(i, q)| (i, (item_difficulty(q) - theta).abs()))
+        .collect();
+    if candidates.is_empty() {
+        return None;
+    }
+
+    candidates.sort_by(|a, b| a.1.total_cmp(&b.1));
+    let count = candidates.len().min(CANDIDATE_COUNT);
+    Some(candidates[rng.below(count)].0)
+}
+
+/// 自适应测验选项
+#[derive(Debug, Clone)]
+pub struct AdaptiveOptions {
+    pub min_questions: usize,
+    pub max_questions: usize,
+    /// 能力估计的标准误低于该值时停止
+    pub target_standard_error: f64,
+}
+
+impl Default for AdaptiveOptions {
+    fn default() -> Self {
+        Self {
+            min_questions: 3,
+            max_questions: 15,
+            target_standard_error: 0.6,
+        }
+    }
+}
+
+/// 自适应测验结果
+#[derive(Debug, Clone)]
+pub struct AdaptiveReport {
+    pub quiz: QuizReport,
+    pub estimate: AbilityEstimate,
+    /// 是否达到目标精度
+    pub converged: bool,
+    pub stages: Vec<StageProficiency>,
+    pub recommended_stage: LearningStage,
+}
+
+impl AdaptiveReport {
+    /// 生成成绩报告文本
+    pub fn render(&self) -> String {
+        let mut output = self.quiz.render();
+
+        let low = self.estimate.theta - 1.96 * self.estimate.standard_error;
+        let high = self.estimate.theta + 1.96 * self.estimate.standard_error;
+        output.push_str(&format!(
+            "\n🧭 能力估计: θ = {:.2} ± {:.2}（95% 区间 {:.2} ~ {:.2}）{}\n",
+            self.estimate.theta,
+            self.estimate.standard_error,
+            low,
+            high,
+            if self.converged { "" } else { "，未达到目标精度" }
+        ));
+
+        output.push_str("\n📈 各阶段掌握程度:\n");
+        for stage in &self.stages {
+            output.push_str(&format!(
+                "  {} {}  {:.0}%（答题 {}，答对 {}）\n",
+                if stage.mastered() { "✅" } else { "📖" },
+                stage.stage.name(),
+                stage.probability * 100.0,
+                stage.answered,
+                stage.correct
+            ));
+        }
+
+        output.push_str(&format!("\n🎯 建议从 {} 开始学习。\n", self.recommended_stage.name()));
+        output
+    }
+}
+
+/// 运行自适应测验，`quiz.questions` 为题库（可先按知识点筛选）
+pub fn run(quiz: &Quiz, options: &AdaptiveOptions, rng: &mut SeededRng, input: &Input) -> Result<AdaptiveReport, AssessmentError> {
+    let pool = quiz.questions;
+    let mut report = QuizReport::default();
+    let mut used = vec![false; pool.len()];
+    let mut responses = Vec::new();
+    let mut estimate = AbilityEstimate::from_responses(&responses);
+    let deadline = quiz.options.total_time_limit.map(|limit| Instant::now() + limit);
+
+    while report.records.len() < options.max_questions {
+        if report.records.len() >= options.min_questions && estimate.standard_error <= options.target_standard_error {
+            break;
+        }
+
+        let remaining = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+        if remaining == Some(Duration::ZERO) {
+            report.out_of_time = true;
+            break;
+        }
+
+        let Some(index) = next_question(pool, &used, estimate.theta, rng) else {
+            break;
+        };
+        used[index] = true;
+
+        let q = &pool[index];
+        let record = match quiz.ask(q, report.records.len() + 1, None, input, remaining) {
+            Ok(record) => record,
+            Err(AssessmentError::InputClosed) => {
+                report.aborted = true;
+                break;
+            }
+            Err(e) => return Err(e),
+        };
+
+        responses.push((item_difficulty(q), record.correct));
+        estimate = AbilityEstimate::from_responses(&responses);
+        report.records.push(record);
+
+        match quiz::skip_late_input(input) {
+            Ok(()) => {}
+            Err(AssessmentError::InputClosed) => {
+                report.aborted = true;
+                break;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    let stages = stage_proficiency(&estimate, &report);
+    Ok(AdaptiveReport {
+        converged: estimate.standard_error <= options.target_standard_error,
+        recommended_stage: recommended_stage(&stages),
+        quiz: report,
+        estimate,
+        stages,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code_grader::CodeGrader;
+    use crate::question::{OptionKey, QuestionKind};
+    use crate::quiz::QuizOptions;
+    use std::io::Cursor;
+
+    fn question(id: &str, stage: LearningStage, difficulty: Difficulty) -> Question {
+        Question {
+            id: id.to_string(),
+            topic: "测试".to_string(),
+            stage,
+            difficulty,
+            question: format!("{} 的题目", id),
+            explanation: None,
+            kind: QuestionKind::SingleChoice {
+                options: vec!["甲".to_string(), "乙".to_string()],
+                answer: OptionKey(1),
+            },
+        }
+    }
+
+    /// 每个阶段三道题；阶段1-2的答案为 B，之后为 A，始终回答 B 相当于只掌握了前两个阶段
+    fn pool() -> Vec<Question> {
+        let mut pool = Vec::new();
+        for stage in LearningStage::all_stages() {
+            for difficulty in [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard] {
+                let id = format!("s{}-{}", stage.number(), difficulty.name());
+                let mut q = question(&id, stage.clone(), difficulty);
+                if stage.number() > 2 {
+                    q.kind = QuestionKind::SingleChoice {
+                        options: vec!["甲".to_string(), "乙".to_string()],
+                        answer: OptionKey(0),
+                    };
+                }
+                pool.push(q);
+            }
+        }
+        pool
+    }
+
+    #[test]
+    fn test_ability_estimate() {
+        let prior = AbilityEstimate::from_responses(&[]);
+        assert!(prior.theta.abs() < 1e-6);
+        assert!((prior.standard_error - 1.0).abs() < 0.02);
+
+        let strong = AbilityEstimate::from_responses(&[(0.0, true), (1.0, true), (2.0, true)]);
+        let weak = AbilityEstimate::from_responses(&[(0.0, false), (-1.0, false), (-2.0, false)]);
+        assert!(strong.theta > 0.5 && strong.theta < 4.0);
+        assert!(weak.theta < -0.5 && weak.theta > -4.0);
+        assert!(strong.standard_error < prior.standard_error);
+    }
+
+    #[test]
+    fn test_next_question_prefers_matching_difficulty() {
+        let pool = pool();
+        let mut used = vec![false; pool.len()];
+        let mut rng = SeededRng::new(3);
+
+        for _ in 0..10 {
+            let index = next_question(&pool, &used, 2.0, &mut rng).unwrap();
+            assert!((item_difficulty(&pool[index]) - 2.0).abs() <= 0.5);
+        }
+
+        used.iter_mut().for_each(|u| *u = true);
+        assert_eq!(next_question(&pool, &used, 0.0, &mut rng), None);
+    }
+
+    #[test]
+    fn test_recommended_stage() {
+        let report = QuizReport::default();
+        let beginner = stage_proficiency(&AbilityEstimate { theta: -2.5, standard_error: 0.5 }, &report);
+        assert_eq!(recommended_stage(&beginner), LearningStage::Stage1Basics);
+
+        let intermediate = stage_proficiency(&AbilityEstimate { theta: 0.0, standard_error: 0.5 }, &report);
+        assert!(intermediate[1].mastered());
+        assert_eq!(recommended_stage(&intermediate), LearningStage::Stage3AdvancedConcepts);
+
+        let expert = stage_proficiency(&AbilityEstimate { theta: 3.5, standard_error: 0.5 }, &report);
+        assert_eq!(recommended_stage(&expert), LearningStage::Stage5Projects);
+    }
+
+    #[test]
+    fn test_adaptive_run_stops_when_confident() {
+        let pool = pool();
+        let quiz_options = QuizOptions::default();
+        let grader = CodeGrader::default();
+        let quiz = Quiz { questions: &pool, options: &quiz_options, grader: &grader, answers: None };
+        let options = AdaptiveOptions::default();
+
+        let input = Input::from_reader(Cursor::new("B\n".repeat(pool.len())));
+        let report = run(&quiz, &options, &mut SeededRng::new(1), &input).unwrap();
+
+        assert!(report.converged);
+        assert!(report.quiz.total() >= options.min_questions);
+        assert!(report.quiz.total() < pool.len());
+        assert!(report.stages[0].mastered());
+        assert_eq!(report.recommended_stage, LearningStage::Stage3AdvancedConcepts);
+        assert!(report.render().contains("建议从 阶段3: 高级概念 开始学习"));
+    }
+}
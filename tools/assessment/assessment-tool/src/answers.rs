@@ -0,0 +1,101 @@
+//! 非交互模式的答案文件
+//!
+//! 每行一个答案，格式为 `题目 ID: 答案`，答案的写法与交互作答相同。
+//! 代码题写成 `题目 ID: @文件路径`，路径相对于答案文件所在目录。
+//! 空行和以 `#` 开头的行会被忽略。
+
+use crate::error::AssessmentError;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// 按题目 ID 索引的答案
+#[derive(Debug, Clone, Default)]
+pub struct AnswerSheet {
+    answers: HashMap<String, String>,
+}
+
+impl AnswerSheet {
+    /// 读取答案文件，同时读入代码题引用的源文件
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, AssessmentError> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|e| AssessmentError::ReadFile { path: path.to_path_buf(), source: e })?;
+        let base = path.parent().unwrap_or(Path::new("."));
+
+        Self::parse(&content, |file| fs::read_to_string(base.join(file)).map_err(|e| e.to_string()))
+            .map_err(|(line, message)| AssessmentError::InvalidAnswerSheet { path: path.to_path_buf(), line, message })
+    }
+
+    /// 解析答案文件内容，`read_source` 读取 `@` 引用的代码文件；出错时返回行号和说明
+    pub fn parse(
+        content: &str,
+        read_source: impl Fn(&str) -> Result<String, String>,
+    ) -> Result<Self, (usize, String)> {
+        let mut answers = HashMap::new();
+
+        for (i, line) in content.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((id, answer)) = line.split_once(':') else {
+                return Err((line_number, "应为 `题目 ID: 答案`".to_string()));
+            };
+            let (id, answer) = (id.trim(), answer.trim());
+            if id.is_empty() {
+                return Err((line_number, "题目 ID 不能为空".to_string()));
+            }
+
+            let answer = match answer.strip_prefix('@') {
+                Some(file) => read_source(file.trim())
+                    .map_err(|e| (line_number, format!("无法读取代码文件 {}: {}", file.trim(), e)))?,
+                None => answer.to_string(),
+            };
+            if answers.insert(id.to_string(), answer).is_some() {
+                return Err((line_number, format!("题目 `{}` 的答案重复", id)));
+            }
+        }
+
+        Ok(Self { answers })
+    }
+
+    /// 某道题的答案
+    pub fn get(&self, id: &str) -> Option<&str> {
+        self.answers.get(id).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_files(file: &str) -> Result<String, String> {
+        Err(format!("{} 不存在", file))
+    }
+
+    #[test]
+    fn test_parse_answer_sheet() {
+        let content = "# 第二阶段\nmove-semantics: B\n\nborrow-rules : A, C\nfix-code: @fix.rs\nnote: a: b\n";
+        let sheet = AnswerSheet::parse(content, |file| Ok(format!("// {}", file))).unwrap();
+
+        assert_eq!(sheet.get("move-semantics"), Some("B"));
+        assert_eq!(sheet.get("borrow-rules"), Some("A, C"));
+        assert_eq!(sheet.get("fix-code"), Some("// fix.rs"));
+        assert_eq!(sheet.get("note"), Some("a: b"));
+        assert_eq!(sheet.get("missing"), None);
+    }
+
+    #[test]
+    fn test_parse_answer_sheet_errors() {
+        assert_eq!(AnswerSheet::parse("q1: A\nB\n", no_files).unwrap_err().0, 2);
+        assert_eq!(AnswerSheet::parse(": A", no_files).unwrap_err().0, 1);
+        assert_eq!(AnswerSheet::parse("q1: A\n\nq1: B", no_files).unwrap_err().0, 3);
+
+        let (line, message) = AnswerSheet::parse("code: @gone.rs", no_files).unwrap_err();
+        assert_eq!(line, 1);
+        assert!(message.contains("gone.rs 不存在"));
+    }
+}
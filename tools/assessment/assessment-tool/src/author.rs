@@ -0,0 +1,236 @@
+//! 交互式编写题目
+
+use crate::editor;
+use crate::input::{Input, ReadResult};
+use crate::lint::{self, Severity};
+use crate::markdown;
+use crate::question::{parse_bool, parse_stage, Answer, CodeTest, Difficulty, OptionKey, Question, QuestionKind};
+use std::io::{self, Write};
+
+const KINDS: [(&str, &str); 6] = [
+    ("single_choice", "单选题"),
+    ("multiple_choice", "多选题"),
+    ("true_false", "判断题"),
+    ("fill_in_blank", "填空题"),
+    ("ordering", "排序题"),
+    ("code", "代码题"),
+];
+
+/// 逐项询问并生成一道题目；输入结束时返回 None
+pub fn prompt_question(input: &Input, existing_ids: &[String]) -> io::Result<Option<Question>> {
+    let Some(id) = prompt_until(input, "题目 ID", |value| {
+        if value.is_empty() {
+            Err("ID 不能为空".to_string())
+        } else if existing_ids.iter().any(|id| id == value) {
+            Err(format!("ID `{}` 已存在", value))
+        } else {
+            Ok(value.to_string())
+        }
+    })?
+    else {
+        return Ok(None);
+    };
+    let Some(topic) = prompt_until(input, "知识点", non_empty)? else { return Ok(None) };
+    let Some(stage) = prompt_until(input, "学习阶段 (1-5)", parse_stage)? else { return Ok(None) };
+    let Some(difficulty) = prompt_until(input, "难度 easy/medium/hard [medium]", |value| {
+        if value.is_empty() {
+            return Ok(Difficulty::Medium);
+        }
+        Difficulty::parse(value).ok_or_else(|| format!("无效的难度: {}", value))
+    })?
+    else {
+        return Ok(None);
+    };
+
+    for (i, (_, name)) in KINDS.iter().enumerate() {
+        println!("  {}. {}", i + 1, name);
+    }
+    let Some(kind) = prompt_until(input, "题型编号", |value| {
+        value.parse::<usize>()
+            .ok()
+            .and_then(|n| n.checked_sub(1))
+            .and_then(|i| KINDS.get(i))
+            .map(|(kind, _)| *kind)
+            .ok_or_else(|| format!("请输入 1-{}", KINDS.len()))
+    })?
+    else {
+        return Ok(None);
+    };
+    let Some(text) = prompt_until(input, "题干", non_empty)? else { return Ok(None) };
+
+    let kind = match kind {
+        "single_choice" | "multiple_choice" => {
+            let Some(options) = prompt_list(input, "选项", 2)? else { return Ok(None) };
+            let mut draft = Question {
+                id: id.clone(),
+                topic: topic.clone(),
+                stage: stage.clone(),
+                difficulty,
+                question: text.clone(),
+                explanation: None,
+                kind: if kind == "single_choice" {
+                    QuestionKind::SingleChoice { options: options.clone(), answer: OptionKey(0) }
+                } else {
+                    QuestionKind::MultipleChoice { options: options.clone(), answers: Vec::new() }
+                },
+            };
+            // 借用作答解析来读取标准答案
+            let parse = |value: &str| draft.parse_answer(value).map_err(|e| e.to_string());
+            let Some(answer) = prompt_until(input, draft.input_hint(), parse)? else {
+                return Ok(None);
+            };
+            match answer {
+                Answer::Choice(answer) => draft.kind = QuestionKind::SingleChoice { options, answer },
+                Answer::Choices(answers) => draft.kind = QuestionKind::MultipleChoice { options, answers },
+                _ => {}
+            }
+            draft.kind
+        }
+        "true_false" => {
+            let Some(answer) = prompt_until(input, "答案 (T/F)", |value| {
+                parse_bool(value).ok_or_else(|| format!("无效的判断: {}", value))
+            })?
+            else {
+                return Ok(None);
+            };
+            QuestionKind::TrueFalse { answer }
+        }
+        "fill_in_blank" => {
+            let Some(accepted) = prompt_until(input, "可接受的答案，多个用 | 分隔", non_empty)? else { return Ok(None) };
+            QuestionKind::FillInBlank {
+                accepted: accepted.split('|').map(|a| a.trim().to_string()).collect(),
+                case_sensitive: false,
+            }
+        }
+        "ordering" => {
+            let Some(items) = prompt_list(input, "按正确顺序输入各项", 2)? else { return Ok(None) };
+            markdown::scrambled_ordering(&id, &items)
+        }
+        _ => {
+            println!("在编辑器中编写初始代码（编辑器: {}）", editor::editor_command());
+            let starter = editor::edit("", &format!("{}-starter.rs", id))?;
+            let mut tests = Vec::new();
+            loop {
+                let Some(name) = prompt(input, "隐藏测试名称（直接回车结束）")? else { return Ok(None) };
+                if name.is_empty() {
+                    break;
+                }
+                let Some(code) = prompt_until(input, "测试代码（单行，例如 assert_eq!(f(1), 2);）", non_empty)? else {
+                    return Ok(None);
+                };
+                tests.push(CodeTest { name, code });
+            }
+            let Some(with_solution) = prompt(input, "是否编写参考答案? (y/N)")? else { return Ok(None) };
+            let solution = if parse_bool(&with_solution) == Some(true) {
+                Some(editor::edit(&starter, &format!("{}-solution.rs", id))?)
+            } else {
+                None
+            };
+            QuestionKind::Code { starter, tests, timeout_secs: 10, solution }
+        }
+    };
+
+    let Some(explanation) = prompt(input, "解析（可选）")? else { return Ok(None) };
+
+    Ok(Some(Question {
+        id,
+        topic,
+        stage,
+        difficulty,
+        question: text,
+        explanation: (!explanation.is_empty()).then_some(explanation),
+        kind,
+    }))
+}
+
+/// 显示题目预览和检查结果，返回是否确认保存；输入结束时视为取消
+pub fn confirm(input: &Input, question: &Question) -> io::Result<bool> {
+    println!("\n{}", serde_json::to_string_pretty(question)?);
+
+    let issues = lint::check_question(question);
+    for (severity, message) in &issues {
+        println!("{}: {}", severity.name(), message);
+    }
+    if issues.iter().any(|(severity, _)| *severity == Severity::Error) {
+        println!("❌ 题目存在错误，未保存。");
+        return Ok(false);
+    }
+
+    let answer = prompt(input, "保存到题库? (Y/n)")?;
+    Ok(answer.is_some_and(|a| a.is_empty() || parse_bool(&a) == Some(true)))
+}
+
+fn non_empty(value: &str) -> Result<String, String> {
+    if value.is_empty() {
+        Err("不能为空".to_string())
+    } else {
+        Ok(value.to_string())
+    }
+}
+
+/// 读取一行；输入结束时返回 None
+fn prompt(input: &Input, label: &str) -> io::Result<Option<String>> {
+    print!("{}: ", label);
+    io::stdout().flush()?;
+    match input.read_line(None) {
+        ReadResult::Line(line) => Ok(Some(line.trim().to_string())),
+        _ => Ok(None),
+    }
+}
+
+/// 反复读取直到输入有效
+fn prompt_until<T>(input: &Input, label: &str, parse: impl Fn(&str) -> Result<T, String>) -> io::Result<Option<T>> {
+    loop {
+        let Some(value) = prompt(input, label)? else { return Ok(None) };
+        match parse(&value) {
+            Ok(value) => return Ok(Some(value)),
+            Err(message) => println!("{}", message),
+        }
+    }
+}
+
+/// 逐行读取列表，空行结束，至少需要 `min` 项
+fn prompt_list(input: &Input, label: &str, min: usize) -> io::Result<Option<Vec<String>>> {
+    println!("{}（每行一项，空行结束）:", label);
+    let mut items = Vec::new();
+    loop {
+        let Some(item) = prompt(input, &format!("  {}", items.len() + 1))? else { return Ok(None) };
+        if !item.is_empty() {
+            items.push(item);
+        } else if items.len() >= min {
+            return Ok(Some(items));
+        } else {
+            println!("至少需要 {} 项", min);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use progress_tracker::LearningStage;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_prompt_single_choice() {
+        let script = "mut-keyword\nnew-id\n变量\n9\n1\n\n1\n哪个关键字声明可变变量？\nlet\nmut\n\nE\nb\n默认不可变\n";
+        let input = Input::from_reader(Cursor::new(script));
+        let question = prompt_question(&input, &["mut-keyword".to_string()]).unwrap().unwrap();
+
+        assert_eq!(question.id, "new-id");
+        assert_eq!(question.stage, LearningStage::Stage1Basics);
+        assert_eq!(question.difficulty, Difficulty::Medium);
+        assert_eq!(question.kind, QuestionKind::SingleChoice {
+            options: vec!["let".to_string(), "mut".to_string()],
+            answer: OptionKey(1),
+        });
+        assert_eq!(question.explanation.as_deref(), Some("默认不可变"));
+        assert!(lint::check_question(&question).is_empty());
+    }
+
+    #[test]
+    fn test_prompt_aborts_on_eof() {
+        let input = Input::from_reader(Cursor::new("only-id\n"));
+        assert_eq!(prompt_question(&input, &[]).unwrap(), None);
+    }
+}
@@ -0,0 +1,239 @@
+//! 题库文件的读写
+
+use crate::error::AssessmentError;
+use crate::lint::{self, LintIssue, Severity};
+use crate::question::Question;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// 默认题库文件
+pub const DEFAULT_BANK_FILE: &str = "questions.json";
+
+/// 题库中的一个条目，`line` 为条目在源文件中开始的行号（从 1 开始）
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub line: usize,
+    pub question: Result<Question, String>,
+}
+
+/// 随工具发布的题库，编译进二进制文件，不依赖源码目录
+pub const BUNDLED_BANK: &str = include_str!("../questions.json");
+
+/// 内置题库在提示信息中的名称
+pub const BUNDLED_BANK_NAME: &str = "<内置题库>";
+
+/// 测验和检查读取的题库
+#[derive(Debug, Clone, PartialEq)]
+pub enum BankSource {
+    File(PathBuf),
+    /// 内置题库，只读
+    Bundled,
+}
+
+impl BankSource {
+    /// 优先使用 `--bank` 指定的文件，其次是当前目录下的 questions.json，都没有时使用内置题库
+    ///
+    /// 会修改题库的命令使用 [`resolve_writable_path`]。
+    pub fn resolve(explicit: Option<&Path>) -> Self {
+        if let Some(path) = explicit {
+            return BankSource::File(path.to_path_buf());
+        }
+        let local = PathBuf::from(DEFAULT_BANK_FILE);
+        if local.exists() { BankSource::File(local) } else { BankSource::Bundled }
+    }
+
+    /// 提示信息中使用的路径
+    pub fn path(&self) -> PathBuf {
+        match self {
+            BankSource::File(path) => path.clone(),
+            BankSource::Bundled => PathBuf::from(BUNDLED_BANK_NAME),
+        }
+    }
+
+    /// 读取题库内容
+    pub fn read(&self) -> Result<String, AssessmentError> {
+        match self {
+            BankSource::File(path) => read_bank_file(path),
+            BankSource::Bundled => Ok(BUNDLED_BANK.to_string()),
+        }
+    }
+
+    /// 加载并检查题库，见 [`load`]
+    pub fn load(&self) -> Result<Vec<Question>, AssessmentError> {
+        parse_bank(&self.path(), &self.read()?)
+    }
+}
+
+/// 确定要修改的题库路径：`--bank` 指定的文件或当前目录下的 questions.json
+///
+/// 内置题库不能修改，两者都没有时返回 `BankNotFound`。
+pub fn resolve_writable_path(explicit: Option<&Path>) -> Result<PathBuf, AssessmentError> {
+    let path = explicit.map_or_else(|| PathBuf::from(DEFAULT_BANK_FILE), Path::to_path_buf);
+    if explicit.is_none() && !path.exists() {
+        return Err(AssessmentError::BankNotFound(path));
+    }
+    Ok(path)
+}
+
+/// 加载题库文件，并按 `lint` 的规则检查
+///
+/// 存在错误时返回 `InvalidBank`，只报告第一个错误；警告不影响加载。
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<Question>, AssessmentError> {
+    let path = path.as_ref();
+    parse_bank(path, &read_bank_file(path)?)
+}
+
+fn read_bank_file(path: &Path) -> Result<String, AssessmentError> {
+    fs::read_to_string(path).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => AssessmentError::BankNotFound(path.to_path_buf()),
+        _ => AssessmentError::ReadFile { path: path.to_path_buf(), source: e },
+    })
+}
+
+fn parse_bank(path: &Path, content: &str) -> Result<Vec<Question>, AssessmentError> {
+    let invalid = |message: String| AssessmentError::InvalidBank { path: path.to_path_buf(), message };
+    let entries = parse_entries(content).map_err(invalid)?;
+
+    let errors: Vec<LintIssue> = lint::lint_entries(&entries)
+        .into_iter()
+        .filter(|issue| issue.severity == Severity::Error)
+        .collect();
+    if let Some(first) = errors.first() {
+        let id = first.id.as_deref().map(|id| format!(" [{}]", id)).unwrap_or_default();
+        return Err(invalid(format!(
+            "第 {} 行{}: {}（共 {} 个错误，可运行 `lint` 查看全部）",
+            first.line, id, first.message, errors.len()
+        )));
+    }
+
+    Ok(entries.into_iter().filter_map(|entry| entry.question.ok()).collect())
+}
+
+/// 保存题库，格式与手工编辑的 questions.json 保持一致（两个空格缩进）
+pub fn save<P: AsRef<Path>>(path: P, questions: &[Question]) -> Result<(), AssessmentError> {
+    let path = path.as_ref();
+    let mut content = serde_json::to_string_pretty(questions).map_err(io::Error::from)?;
+    content.push('\n');
+    fs::write(path, content).map_err(|e| AssessmentError::WriteFile { path: path.to_path_buf(), source: e })
+}
+
+/// 逐条解析题库，单个条目格式错误不影响其他条目
+///
+/// 整个文件不是合法的 JSON 数组时返回错误说明（包含行列号）。
+pub fn parse_entries(content: &str) -> Result<Vec<Entry>, String> {
+    let values: Vec<serde_json::Value> = serde_json::from_str(content)
+        .map_err(|e| format!("题库不是合法的 JSON 数组: {}", e))?;
+    let lines = entry_lines(content);
+
+    Ok(values.into_iter()
+        .enumerate()
+        .map(|(i, value)| Entry {
+            line: lines.get(i).copied().unwrap_or(1),
+            question: serde_json::from_value(value).map_err(|e| e.to_string()),
+        })
+        .collect())
+}
+
+/// 顶层数组中每个元素开始的行号
+fn entry_lines(content: &str) -> Vec<usize> {
+    let mut lines = Vec::new();
+    let mut line = 1;
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut expecting_element = false;
+
+    for c in content.chars() {
+        if c == '\n' {
+            line += 1;
+        }
+
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        if depth == 1 && expecting_element && !c.is_whitespace() && c != ']' {
+            lines.push(line);
+            expecting_element = false;
+        }
+
+        match c {
+            '"' => in_string = true,
+            '[' | '{' => {
+                depth += 1;
+                expecting_element = depth == 1;
+            }
+            ']' | '}' => depth -= 1,
+            ',' if depth == 1 => expecting_element = true,
+            _ => {}
+        }
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_lines_and_errors() {
+        let content = r#"[
+  {
+    "id": "a", "topic": "t", "stage": "Stage1Basics", "difficulty": "easy",
+    "question": "包含 ] 和 { 的 \"题目\"", "type": "true_false", "answer": true
+  },
+  {
+    "id": "b", "type": "unknown"
+  }, 3
+]"#;
+        let entries = parse_entries(content).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries.iter().map(|e| e.line).collect::<Vec<_>>(), vec![2, 6, 8]);
+        assert!(entries[0].question.is_ok());
+        assert!(entries[1].question.is_err());
+        assert!(entries[2].question.is_err());
+
+        assert!(parse_entries("{").is_err());
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let missing = std::env::temp_dir().join("assessment-missing-bank.json");
+        assert!(matches!(load(&missing), Err(AssessmentError::BankNotFound(path)) if path == missing));
+
+        let invalid = std::env::temp_dir().join(format!("assessment-invalid-bank-{}.json", std::process::id()));
+        fs::write(&invalid, "[{\"id\": 1}]").unwrap();
+        let error = load(&invalid).unwrap_err();
+        fs::remove_file(&invalid).unwrap();
+        assert!(matches!(error, AssessmentError::InvalidBank { .. }));
+        assert!(error.to_string().contains("第 1 行: 无法解析"));
+
+        // 能解析但未通过检查的题库同样拒绝加载
+        fs::write(&invalid, r#"[
+  { "id": "a", "topic": "t", "stage": "Stage1Basics", "difficulty": "easy",
+    "question": "题目", "type": "single_choice", "options": ["只有一个"], "answer": "A" }
+]"#).unwrap();
+        let error = load(&invalid).unwrap_err();
+        fs::remove_file(&invalid).unwrap();
+        assert!(error.to_string().contains("第 2 行 [a]: `options` 至少需要两个选项"), "{}", error);
+    }
+
+    #[test]
+    fn test_bank_sources() {
+        let mine = BankSource::resolve(Some(Path::new("mine.json")));
+        assert_eq!(mine, BankSource::File(PathBuf::from("mine.json")));
+        assert_eq!(resolve_writable_path(Some(Path::new("mine.json"))).unwrap(), PathBuf::from("mine.json"));
+
+        // 内置题库编译在工具中，同样经过检查
+        assert!(!BankSource::Bundled.load().unwrap().is_empty());
+        assert_eq!(BankSource::Bundled.path(), PathBuf::from(BUNDLED_BANK_NAME));
+    }
+}
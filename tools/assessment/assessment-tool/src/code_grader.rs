@@ -0,0 +1,412 @@
+//! 代码题评分：在临时 cargo 项目中编译学习者代码并运行隐藏测试
+//!
+//! 全部在本地离线完成，编译和运行阶段分别设置超时。
+
+use crate::question::CodeTest;
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 编译阶段的默认超时（首次编译需要构建标准库以外的少量产物）
+pub const DEFAULT_BUILD_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// 隐藏测试所在模块名
+const TEST_MODULE: &str = "hidden_tests";
+
+static PROJECT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// 单个隐藏测试的结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestOutcome {
+    pub name: String,
+    pub passed: bool,
+    /// 测试输出中是否有它的结果；运行超时或进程崩溃时后面的测试没有结果，按未通过计
+    pub reported: bool,
+}
+
+/// 代码题评分报告
+#[derive(Debug, Clone, Default)]
+pub struct CodeReport {
+    pub compiled: bool,
+    pub timed_out: bool,
+    /// 编译器诊断信息（编译失败时）
+    pub diagnostics: String,
+    /// 测试失败时的输出
+    pub test_output: String,
+    pub tests: Vec<TestOutcome>,
+    /// 测试进程以非零状态退出，但所有测试都报告通过：输出不可信，测试部分不得分
+    pub abnormal_exit: Option<String>,
+}
+
+impl CodeReport {
+    /// 通过的测试数量
+    pub fn passed_count(&self) -> usize {
+        self.tests.iter().filter(|t| t.passed).count()
+    }
+
+    /// 编译通过且所有隐藏测试都通过
+    pub fn passed(&self) -> bool {
+        self.compiled && !self.timed_out && self.abnormal_exit.is_none() && self.tests.iter().all(|t| t.passed)
+    }
+
+    /// 得分（0.0 - 1.0）：编译成功占一半，其余按测试通过比例计算
+    pub fn score(&self) -> f32 {
+        if !self.compiled {
+            return 0.0;
+        }
+        if self.abnormal_exit.is_some() {
+            return 0.5;
+        }
+        if self.tests.is_empty() {
+            return if self.timed_out { 0.5 } else { 1.0 };
+        }
+        0.5 + 0.5 * self.passed_count() as f32 / self.tests.len() as f32
+    }
+
+    /// 生成适合在终端显示的结果说明
+    pub fn summary(&self) -> String {
+        let mut output = String::new();
+
+        if !self.compiled {
+            if self.timed_out {
+                output.push_str("❌ 编译超时\n");
+            } else {
+                output.push_str("❌ 编译失败，编译器输出:\n");
+                output.push_str(&self.diagnostics);
+            }
+            return output;
+        }
+
+        output.push_str(&format!("✅ 编译通过（得分 {:.0}%）\n", self.score() * 100.0));
+        if self.timed_out {
+            output.push_str("⏱️ 运行测试超时（是否存在死循环？）\n");
+        }
+        if let Some(status) = &self.abnormal_exit {
+            output.push_str(&format!("⚠️ 测试进程异常退出（{}），测试结果不计分\n", status));
+        }
+        if !self.tests.is_empty() {
+            output.push_str(&format!("🧪 隐藏测试: {}/{} 通过\n", self.passed_count(), self.tests.len()));
+            for test in self.tests.iter().filter(|t| !t.passed) {
+                let reason = match (test.reported, self.timed_out) {
+                    (true, _) => "",
+                    (false, true) => "（超时，没有结果）",
+                    (false, false) => "（没有结果）",
+                };
+                output.push_str(&format!("  ❌ {}{}\n", test.name, reason));
+            }
+            if !self.test_output.is_empty() && !self.passed() {
+                output.push_str(&self.test_output);
+            }
+        }
+
+        output
+    }
+}
+
+/// 代码评分器
+pub struct CodeGrader {
+    pub build_timeout: Duration,
+    work_root: PathBuf,
+}
+
+impl Default for CodeGrader {
+    fn default() -> Self {
+        Self::new(env::temp_dir().join("assessment-tool"))
+    }
+}
+
+impl CodeGrader {
+    /// 在指定目录下创建临时项目，各题共享同一个 target 目录以加快编译
+    pub fn new(work_root: PathBuf) -> Self {
+        Self {
+            build_timeout: DEFAULT_BUILD_TIMEOUT,
+            work_root,
+        }
+    }
+
+    /// 编译学习者代码并运行隐藏测试
+    pub fn grade(&self, source: &str, tests: &[CodeTest], run_timeout: Duration) -> io::Result<CodeReport> {
+        let project = self.create_project(source, tests)?;
+        let result = self.build_and_run(&project, tests, run_timeout);
+        let _ = fs::remove_dir_all(&project);
+        result
+    }
+
+    fn create_project(&self, source: &str, tests: &[CodeTest]) -> io::Result<PathBuf> {
+        let id = PROJECT_COUNTER.fetch_add(1, Ordering::SeqCst);
+        let project = self.work_root.join(format!("snippet-{}-{}", std::process::id(), id));
+        fs::create_dir_all(project.join("src"))?;
+
+        fs::write(
+            project.join("Cargo.toml"),
+            "[package]\nname = \"snippet\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]\n\n[workspace]\n",
+        )?;
+        fs::write(project.join("src").join("lib.rs"), generate_lib_source(source, tests))?;
+
+        Ok(project)
+    }
+
+    fn build_and_run(&self, project: &Path, tests: &[CodeTest], run_timeout: Duration) -> io::Result<CodeReport> {
+        let mut report = CodeReport::default();
+
+        // 编译阶段
+        let build = Command::new(env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()))
+            .args(["test", "--no-run", "--offline", "--quiet", "--message-format=json"])
+            .current_dir(project)
+            .env("CARGO_TARGET_DIR", self.work_root.join("target"))
+            .env("CARGO_NET_OFFLINE", "true")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let build = wait_with_timeout(build, self.build_timeout)?;
+        if build.timed_out {
+            report.timed_out = true;
+            return Ok(report);
+        }
+
+        let (executable, diagnostics) = parse_build_messages(&build.stdout);
+        report.compiled = build.status.success() && executable.is_some();
+        if !report.compiled {
+            report.diagnostics = if diagnostics.is_empty() { build.stderr } else { diagnostics };
+            return Ok(report);
+        }
+
+        // 运行阶段：直接执行测试二进制，超时后可以可靠地终止
+        let run = Command::new(executable.unwrap_or_default())
+            .args([TEST_MODULE, "--test-threads=1", "--color=never"])
+            .current_dir(project)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        // 超时时保留已经输出的结果，只有没有结果的测试算作未通过
+        let run = wait_with_timeout(run, run_timeout)?;
+        report.timed_out = run.timed_out;
+        report.tests = tests.iter().map(|test| parse_test_outcome(&run.stdout, &test.name)).collect();
+
+        // 非零退出却没有测试失败，说明输出与实际运行不符（例如测试后调用了 process::exit）
+        let all_ok = report.tests.iter().all(|t| t.passed);
+        if !run.timed_out && !run.status.success() && all_ok {
+            report.abnormal_exit = Some(run.status.to_string());
+        }
+
+        if let Some(start) = run.stdout.find("failures:") {
+            report.test_output = run.stdout[start..].to_string();
+        }
+
+        Ok(report)
+    }
+}
+
+/// 生成临时项目的 lib.rs：学习者代码 + 隐藏测试模块
+pub fn generate_lib_source(source: &str, tests: &[CodeTest]) -> String {
+    let mut lib = String::from("#![allow(dead_code, unused)]\n\n");
+    lib.push_str(source);
+    lib.push_str(&format!("\n\n#[cfg(test)]\nmod {} {{\n    use super::*;\n", TEST_MODULE));
+
+    for test in tests {
+        lib.push_str(&format!("\n    #[test]\n    fn {}() {{\n", test.name));
+        for line in test.code.lines() {
+            lib.push_str("        ");
+            lib.push_str(line);
+            lib.push('\n');
+        }
+        lib.push_str("    }\n");
+    }
+
+    lib.push_str("}\n");
+    lib
+}
+
+/// 从测试输出中找出单个测试的结果行（`--test-threads=1` 时每个测试一行）
+fn parse_test_outcome(stdout: &str, name: &str) -> TestOutcome {
+    let prefix = format!("test {}::{} ... ", TEST_MODULE, name);
+    let result = stdout.lines().find_map(|line| line.trim().strip_prefix(&prefix));
+
+    TestOutcome {
+        name: name.to_string(),
+        passed: result == Some("ok"),
+        reported: matches!(result, Some("ok" | "FAILED")),
+    }
+}
+
+/// 从 cargo 的 JSON 输出中提取测试二进制路径和编译诊断
+fn parse_build_messages(stdout: &str) -> (Option<String>, String) {
+    let mut executable = None;
+    let mut diagnostics = String::new();
+
+    for line in stdout.lines() {
+        let Ok(message) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+
+        match message["reason"].as_str() {
+            Some("compiler-artifact") => {
+                if let Some(path) = message["executable"].as_str() {
+                    executable = Some(path.to_string());
+                }
+            }
+            Some("compiler-message") => {
+                if let Some(rendered) = message["message"]["rendered"].as_str() {
+                    diagnostics.push_str(rendered);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    (executable, diagnostics)
+}
+
+struct ProcessOutput {
+    status: ExitStatus,
+    stdout: String,
+    stderr: String,
+    timed_out: bool,
+}
+
+/// 等待子进程结束，超时则终止进程；两种情况都返回已经读到的输出
+fn wait_with_timeout(mut child: Child, timeout: Duration) -> io::Result<ProcessOutput> {
+    // 在后台线程读取输出，避免管道写满导致子进程阻塞
+    let stdout = child.stdout.take().map(read_in_background);
+    let stderr = child.stderr.take().map(read_in_background);
+
+    let start = Instant::now();
+    let (status, timed_out) = loop {
+        if let Some(status) = child.try_wait()? {
+            break (status, false);
+        }
+        if start.elapsed() >= timeout {
+            // 进程结束后管道关闭，后台线程随之读完
+            let _ = child.kill();
+            break (child.wait()?, true);
+        }
+        thread::sleep(Duration::from_millis(20));
+    };
+
+    let collect = |handle: Option<thread::JoinHandle<String>>| {
+        handle.and_then(|h| h.join().ok()).unwrap_or_default()
+    };
+
+    Ok(ProcessOutput {
+        status,
+        stdout: collect(stdout),
+        stderr: collect(stderr),
+        timed_out,
+    })
+}
+
+fn read_in_background<R: Read + Send + 'static>(mut reader: R) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = reader.read_to_end(&mut buffer);
+        String::from_utf8_lossy(&buffer).into_owned()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_case(name: &str, code: &str) -> CodeTest {
+        CodeTest { name: name.to_string(), code: code.to_string() }
+    }
+
+    fn grader() -> CodeGrader {
+        CodeGrader::new(env::temp_dir().join("assessment-tool-tests"))
+    }
+
+    #[test]
+    fn test_generate_lib_source() {
+        let lib = generate_lib_source("pub fn one() -> i32 { 1 }", &[test_case("returns_one", "assert_eq!(one(), 1);")]);
+        assert!(lib.contains("pub fn one() -> i32 { 1 }"));
+        assert!(lib.contains("mod hidden_tests {"));
+        assert!(lib.contains("    fn returns_one() {\n        assert_eq!(one(), 1);\n    }"));
+    }
+
+    #[test]
+    fn test_grade_passing_and_failing_code() {
+        let tests = [
+            test_case("adds", "assert_eq!(add(2, 3), 5);"),
+            test_case("adds_negative", "assert_eq!(add(-2, -3), -5);"),
+        ];
+
+        let report = grader().grade("pub fn add(a: i32, b: i32) -> i32 { a + b }", &tests, Duration::from_secs(10)).unwrap();
+        assert!(report.passed(), "{}", report.summary());
+        assert_eq!(report.score(), 1.0);
+
+        let report = grader().grade("pub fn add(a: i32, b: i32) -> i32 { a.abs() + b.abs() }", &tests, Duration::from_secs(10)).unwrap();
+        assert!(report.compiled);
+        assert!(!report.passed());
+        assert_eq!(report.passed_count(), 1);
+        assert_eq!(report.score(), 0.75);
+        assert!(report.summary().contains("adds_negative"));
+    }
+
+    #[test]
+    fn test_grade_compile_error() {
+        let source = "pub fn greet() -> String { let s = String::new(); s.push_str(\"hi\"); s }";
+        let report = grader().grade(source, &[], Duration::from_secs(10)).unwrap();
+
+        assert!(!report.compiled);
+        assert_eq!(report.score(), 0.0);
+        assert!(report.diagnostics.contains("E0596"), "{}", report.diagnostics);
+        assert!(report.summary().contains("编译失败"));
+    }
+
+    #[test]
+    fn test_bundled_code_questions() {
+        use crate::question::{Question, QuestionKind};
+
+        let questions: Vec<Question> = serde_json::from_str(include_str!("../questions.json")).unwrap();
+        for q in &questions {
+            let QuestionKind::Code { starter, tests, timeout_secs, solution } = &q.kind else {
+                continue;
+            };
+            let timeout = Duration::from_secs(*timeout_secs);
+
+            let report = grader().grade(starter, tests, timeout).unwrap();
+            assert!(!report.passed(), "{} 的初始代码不应通过", q.id);
+
+            let solution = solution.as_ref().expect("代码题应提供参考答案");
+            let report = grader().grade(solution, tests, timeout).unwrap();
+            assert!(report.passed(), "{} 的参考答案未通过: {}", q.id, report.summary());
+        }
+    }
+
+    #[test]
+    fn test_grade_timeout() {
+        // 测试按名称顺序运行：finishes 先通过，never_ends 卡住
+        let tests = [test_case("never_ends", "spin();"), test_case("finishes", "assert_eq!(1 + 1, 2);")];
+        let report = grader().grade("pub fn spin() { loop { std::hint::spin_loop(); } }", &tests, Duration::from_secs(1)).unwrap();
+
+        assert!(report.compiled);
+        assert!(report.timed_out);
+        assert!(!report.passed());
+        assert_eq!(report.tests[0], TestOutcome { name: "never_ends".to_string(), passed: false, reported: false });
+        assert_eq!(report.tests[1], TestOutcome { name: "finishes".to_string(), passed: true, reported: true });
+        assert_eq!(report.score(), 0.75);
+        assert!(report.summary().contains("never_ends（超时，没有结果）"));
+    }
+
+    #[test]
+    fn test_grade_abnormal_exit() {
+        // 绕过输出捕获伪造通过的结果行，随后以非零状态退出
+        let forged = "use std::io::Write;\nwriteln!(std::io::stdout(), \"ok\").unwrap();\nstd::process::exit(3);";
+        let report = grader().grade("", &[test_case("forged", forged)], Duration::from_secs(10)).unwrap();
+
+        assert!(report.tests[0].passed, "{}", report.summary());
+        assert!(report.abnormal_exit.is_some());
+        assert!(!report.passed());
+        assert_eq!(report.score(), 0.5);
+        assert!(report.summary().contains("测试进程异常退出"));
+    }
+}
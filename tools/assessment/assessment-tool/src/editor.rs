@@ -0,0 +1,49 @@
+//! 调用外部编辑器编辑代码
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+/// 获取编辑器命令：优先 `$VISUAL`，其次 `$EDITOR`
+pub fn editor_command() -> String {
+    env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .ok()
+        .filter(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad".to_string() } else { "vi".to_string() })
+}
+
+/// 将内容写入临时文件并在编辑器中打开，返回保存后的内容
+///
+/// 编辑器命令可以带参数，例如 `code --wait`。
+pub fn edit(initial: &str, file_name: &str) -> io::Result<String> {
+    let dir = env::temp_dir().join("assessment-tool").join(format!("edit-{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(file_name);
+    fs::write(&path, initial)?;
+
+    let result = run_editor(&editor_command(), &path).and_then(|_| fs::read_to_string(&path));
+    let _ = fs::remove_file(&path);
+    result
+}
+
+fn run_editor(editor: &str, path: &Path) -> io::Result<()> {
+    let status = if cfg!(windows) {
+        Command::new("cmd").arg("/C").arg(format!("{} \"{}\"", editor, path.display())).status()?
+    } else {
+        Command::new("sh")
+            .arg("-c")
+            .arg(format!("{} \"$1\"", editor))
+            .arg("editor")
+            .arg(path)
+            .status()?
+    };
+
+    if !status.success() {
+        return Err(io::Error::other(format!("编辑器 {} 退出状态异常: {}", editor, status)));
+    }
+
+    Ok(())
+}
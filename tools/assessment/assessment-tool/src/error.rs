@@ -0,0 +1,74 @@
+//! 测验工具的错误类型
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// 测验工具的错误
+#[derive(Debug)]
+pub enum AssessmentError {
+    /// 题库文件不存在
+    BankNotFound(PathBuf),
+    /// 题库文件不是合法的题目列表
+    InvalidBank { path: PathBuf, message: String },
+    /// 读取文件失败
+    ReadFile { path: PathBuf, source: io::Error },
+    /// 写入文件失败
+    WriteFile { path: PathBuf, source: io::Error },
+    /// 作答格式无效，交互模式下提示后重新输入
+    InvalidAnswer(String),
+    /// 输入已结束，测验中止
+    InputClosed,
+    /// 答案文件格式错误
+    InvalidAnswerSheet { path: PathBuf, line: usize, message: String },
+    /// 题库检查或导入时发现错误
+    LintFailed,
+    /// 测验历史文件无法读取或格式错误
+    InvalidHistory { path: PathBuf, message: String },
+    /// 无法把成绩记录到进度文件
+    ProgressUpdate { path: PathBuf, message: String },
+    Io(io::Error),
+}
+
+impl fmt::Display for AssessmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssessmentError::BankNotFound(path) => {
+                write!(f, "题库文件不存在: {}（可使用 --bank 指定题库路径）", path.display())
+            }
+            AssessmentError::InvalidBank { path, message } => write!(f, "题库 {} 格式错误: {}", path.display(), message),
+            AssessmentError::ReadFile { path, source } => write!(f, "无法读取 {}: {}", path.display(), source),
+            AssessmentError::WriteFile { path, source } => write!(f, "无法写入 {}: {}", path.display(), source),
+            AssessmentError::InvalidAnswer(message) => write!(f, "{}", message),
+            AssessmentError::InputClosed => write!(f, "输入已结束"),
+            AssessmentError::InvalidAnswerSheet { path, line, message } => {
+                write!(f, "答案文件 {}:{}: {}", path.display(), line, message)
+            }
+            AssessmentError::LintFailed => write!(f, "题库检查未通过"),
+            AssessmentError::InvalidHistory { path, message } => {
+                write!(f, "无法读取测验历史 {}: {}", path.display(), message)
+            }
+            AssessmentError::ProgressUpdate { path, message } => {
+                write!(f, "无法更新进度文件 {}: {}", path.display(), message)
+            }
+            AssessmentError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for AssessmentError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AssessmentError::ReadFile { source, .. } | AssessmentError::WriteFile { source, .. } => Some(source),
+            AssessmentError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for AssessmentError {
+    fn from(e: io::Error) -> Self {
+        AssessmentError::Io(e)
+    }
+}
@@ -0,0 +1,243 @@
+//! 测验历史记录
+
+use crate::adaptive::{AbilityEstimate, AdaptiveReport};
+use crate::question::Answer;
+use crate::quiz::{QuizOptions, QuizReport, TopicScore};
+use chrono::{DateTime, Local, Utc};
+use progress_tracker::LearningStage;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// 默认历史记录文件
+pub const DEFAULT_HISTORY_FILE: &str = "assessment-history.json";
+
+/// 单题作答情况
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttemptAnswer {
+    pub question_id: String,
+    pub topic: String,
+    pub answer: Option<Answer>,
+    pub correct: bool,
+    #[serde(default)]
+    pub timed_out: bool,
+}
+
+/// 一次测验记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attempt {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    /// 组卷条件
+    pub stage: Option<LearningStage>,
+    pub topic: Option<String>,
+    pub seed: Option<u64>,
+    pub score: usize,
+    pub total: usize,
+    /// 百分制得分
+    pub percentage: f32,
+    #[serde(default)]
+    pub aborted: bool,
+    pub topics: Vec<TopicScore>,
+    pub answers: Vec<AttemptAnswer>,
+    /// 自适应测验的能力估计与建议起始阶段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placement: Option<Placement>,
+}
+
+/// 自适应测验的定级结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Placement {
+    pub ability: AbilityEstimate,
+    pub recommended_stage: LearningStage,
+}
+
+impl From<&AdaptiveReport> for Placement {
+    fn from(report: &AdaptiveReport) -> Self {
+        Self {
+            ability: report.estimate,
+            recommended_stage: report.recommended_stage.clone(),
+        }
+    }
+}
+
+impl Attempt {
+    /// 根据测验结果生成记录
+    pub fn new(report: &QuizReport, options: &QuizOptions, seed: Option<u64>, started_at: DateTime<Utc>) -> Self {
+        let percentage = if report.total() > 0 {
+            report.score() as f32 / report.total() as f32 * 100.0
+        } else {
+            0.0
+        };
+
+        Self {
+            started_at,
+            finished_at: Utc::now(),
+            stage: options.stage.clone(),
+            topic: options.topic.clone(),
+            seed,
+            score: report.score(),
+            total: report.total(),
+            percentage,
+            aborted: report.aborted,
+            topics: report.topic_breakdown(),
+            answers: report.records.iter()
+                .map(|r| AttemptAnswer {
+                    question_id: r.question.id.clone(),
+                    topic: r.question.topic.clone(),
+                    answer: r.answer.clone(),
+                    correct: r.correct,
+                    timed_out: r.timed_out,
+                })
+                .collect(),
+            placement: None,
+        }
+    }
+
+    /// 单行摘要
+    pub fn summary(&self) -> String {
+        let scope = match (&self.stage, &self.topic) {
+            (Some(stage), Some(topic)) => format!("{} · {}", stage.name(), topic),
+            (Some(stage), None) => stage.name().to_string(),
+            (None, Some(topic)) => topic.clone(),
+            (None, None) => "全部题目".to_string(),
+        };
+        let mut line = format!(
+            "{}  {}/{} ({:.0}%)  {}",
+            self.started_at.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+            self.score,
+            self.total,
+            self.percentage,
+            scope
+        );
+        if let Some(placement) = &self.placement {
+            line.push_str(&format!(
+                "  θ={:.2}±{:.2} → {}",
+                placement.ability.theta,
+                placement.ability.standard_error,
+                placement.recommended_stage.name()
+            ));
+        }
+        if let Some(seed) = self.seed {
+            line.push_str(&format!("  seed={}", seed));
+        }
+        if self.aborted {
+            line.push_str("  (中止)");
+        }
+        line
+    }
+}
+
+/// 测验历史
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AttemptHistory {
+    pub attempts: Vec<Attempt>,
+}
+
+impl AttemptHistory {
+    /// 从文件加载，文件不存在时返回空历史
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// 保存到文件
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// 生成历史记录文本，包含各知识点的累计正确率
+    pub fn render(&self) -> String {
+        if self.attempts.is_empty() {
+            return "还没有测验记录。\n".to_string();
+        }
+
+        let mut output = format!("📜 共 {} 次测验:\n", self.attempts.len());
+        for (i, attempt) in self.attempts.iter().enumerate() {
+            output.push_str(&format!("  {:>3}. {}\n", i + 1, attempt.summary()));
+        }
+
+        let mut topics: Vec<TopicScore> = Vec::new();
+        for score in self.attempts.iter().flat_map(|a| &a.topics) {
+            match topics.iter_mut().find(|t| t.topic == score.topic) {
+                Some(total) => {
+                    total.correct += score.correct;
+                    total.total += score.total;
+                }
+                None => topics.push(score.clone()),
+            }
+        }
+
+        output.push_str("\n📊 各知识点累计正确率:\n");
+        for topic in topics {
+            output.push_str(&format!(
+                "  {}: {}/{} ({:.0}%)\n",
+                topic.topic,
+                topic.correct,
+                topic.total,
+                topic.correct as f32 / topic.total as f32 * 100.0
+            ));
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::question::{Difficulty, OptionKey, Question, QuestionKind};
+    use crate::quiz::QuestionRecord;
+
+    fn record(id: &str, topic: &str, correct: bool) -> QuestionRecord {
+        QuestionRecord {
+            question: Question {
+                id: id.to_string(),
+                topic: topic.to_string(),
+                stage: LearningStage::Stage2Ownership,
+                difficulty: Difficulty::Easy,
+                question: "题目".to_string(),
+                explanation: None,
+                kind: QuestionKind::TrueFalse { answer: true },
+            },
+            answer: Some(Answer::Choice(OptionKey(0))),
+            correct,
+            timed_out: false,
+        }
+    }
+
+    #[test]
+    fn test_attempt_history_roundtrip() {
+        let report = QuizReport {
+            records: vec![record("q1", "所有权", true), record("q2", "借用", false), record("q3", "所有权", true)],
+            ..Default::default()
+        };
+        let options = QuizOptions { stage: Some(LearningStage::Stage2Ownership), ..Default::default() };
+        let attempt = Attempt::new(&report, &options, Some(42), Utc::now());
+        assert_eq!(attempt.score, 2);
+        assert!((attempt.percentage - 66.666).abs() < 0.01);
+        assert!(attempt.summary().contains("seed=42"));
+
+        let path = std::env::temp_dir().join(format!("assessment-history-{}.json", std::process::id()));
+        let mut history = AttemptHistory::load(&path).unwrap();
+        assert!(history.attempts.is_empty());
+        history.attempts.push(attempt.clone());
+        history.attempts.push(attempt);
+        history.save(&path).unwrap();
+
+        let loaded = AttemptHistory::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.attempts.len(), 2);
+        assert_eq!(loaded.attempts[0].answers[1].question_id, "q2");
+
+        let text = loaded.render();
+        assert!(text.contains("所有权: 4/4 (100%)"));
+        assert!(text.contains("借用: 0/2 (0%)"));
+    }
+}
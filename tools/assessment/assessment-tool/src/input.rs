@@ -0,0 +1,116 @@
+//! 支持超时的逐行输入
+//!
+//! 后台线程只在收到读取请求时才读取一行，避免在调用外部编辑器时与编辑器争抢终端输入。
+
+use std::cell::Cell;
+use std::io::{self, BufRead};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// 读取结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReadResult {
+    Line(String),
+    Eof,
+    TimedOut,
+}
+
+/// 逐行输入源
+pub struct Input {
+    requests: Sender<()>,
+    lines: Receiver<Option<String>>,
+    pending: Cell<bool>,
+}
+
+impl Input {
+    /// 从标准输入读取
+    pub fn stdin() -> Self {
+        Self::from_reader(io::BufReader::new(io::stdin()))
+    }
+
+    /// 从任意输入源读取
+    pub fn from_reader<R: BufRead + Send + 'static>(mut reader: R) -> Self {
+        let (request_tx, request_rx) = mpsc::channel::<()>();
+        let (line_tx, line_rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            while request_rx.recv().is_ok() {
+                let mut line = String::new();
+                let result = match reader.read_line(&mut line) {
+                    Ok(0) | Err(_) => None,
+                    Ok(_) => Some(line.trim_end_matches(['\n', '\r']).to_string()),
+                };
+                let eof = result.is_none();
+                if line_tx.send(result).is_err() || eof {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            requests: request_tx,
+            lines: line_rx,
+            pending: Cell::new(false),
+        }
+    }
+
+    /// 读取一行，`timeout` 为 None 时一直等待
+    ///
+    /// 超时后读取请求仍然保留，下一次调用会拿到学习者随后输入的那一行。
+    pub fn read_line(&self, timeout: Option<Duration>) -> ReadResult {
+        if !self.pending.get() {
+            if self.requests.send(()).is_err() {
+                return ReadResult::Eof;
+            }
+            self.pending.set(true);
+        }
+
+        let received = match timeout {
+            Some(timeout) => self.lines.recv_timeout(timeout),
+            None => self.lines.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+
+        match received {
+            Ok(Some(line)) => {
+                self.pending.set(false);
+                ReadResult::Line(line)
+            }
+            Ok(None) | Err(RecvTimeoutError::Disconnected) => {
+                self.pending.set(false);
+                ReadResult::Eof
+            }
+            Err(RecvTimeoutError::Timeout) => ReadResult::TimedOut,
+        }
+    }
+
+    /// 是否有超时后尚未取回的输入请求
+    pub fn has_pending(&self) -> bool {
+        self.pending.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_lines_until_eof() {
+        let input = Input::from_reader(Cursor::new("B\r\n  a,c \n"));
+        assert_eq!(input.read_line(None), ReadResult::Line("B".to_string()));
+        assert_eq!(input.read_line(Some(Duration::from_secs(1))), ReadResult::Line("  a,c ".to_string()));
+        assert_eq!(input.read_line(None), ReadResult::Eof);
+        assert_eq!(input.read_line(None), ReadResult::Eof);
+    }
+
+    #[test]
+    fn test_read_line_timeout() {
+        // 管道的写端保持打开，读取会一直阻塞
+        let (reader, _writer) = std::io::pipe().unwrap();
+        let input = Input::from_reader(io::BufReader::new(reader));
+
+        assert_eq!(input.read_line(Some(Duration::from_millis(50))), ReadResult::TimedOut);
+        assert!(input.has_pending());
+    }
+}
@@ -0,0 +1,244 @@
+//! 题库检查
+
+use crate::bank::Entry;
+use crate::question::{OptionKey, Question, QuestionKind, MAX_OPTIONS};
+use std::collections::HashSet;
+
+/// 问题级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Error => "错误",
+            Severity::Warning => "警告",
+        }
+    }
+}
+
+/// 一条检查结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintIssue {
+    pub severity: Severity,
+    /// 条目在源文件中的行号
+    pub line: usize,
+    /// 题目 ID，条目无法解析时为 None
+    pub id: Option<String>,
+    pub message: String,
+}
+
+/// 检查题库中的全部条目
+pub fn lint_entries(entries: &[Entry]) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    let mut seen_ids: HashSet<&str> = HashSet::new();
+
+    for entry in entries {
+        let question = match &entry.question {
+            Ok(question) => question,
+            Err(message) => {
+                issues.push(LintIssue { severity: Severity::Error, line: entry.line, id: None, message: format!("无法解析: {}", message) });
+                continue;
+            }
+        };
+
+        if !question.id.is_empty() && !seen_ids.insert(&question.id) {
+            issues.push(LintIssue {
+                severity: Severity::Error,
+                line: entry.line,
+                id: Some(question.id.clone()),
+                message: "题目 ID 重复".to_string(),
+            });
+        }
+
+        for (severity, message) in check_question(question) {
+            issues.push(LintIssue { severity, line: entry.line, id: Some(question.id.clone()), message });
+        }
+    }
+
+    issues
+}
+
+/// 检查单道题目的内容
+pub fn check_question(q: &Question) -> Vec<(Severity, String)> {
+    let mut issues = Vec::new();
+    let mut error = |message: String| issues.push((Severity::Error, message));
+
+    for (field, value) in [("id", &q.id), ("topic", &q.topic), ("question", &q.question)] {
+        if value.trim().is_empty() {
+            error(format!("`{}` 不能为空", field));
+        }
+    }
+
+    match &q.kind {
+        QuestionKind::SingleChoice { options, answer } => {
+            check_options(options, "options", &mut error);
+            check_key(*answer, options.len(), "answer", &mut error);
+        }
+        QuestionKind::MultipleChoice { options, answers } => {
+            check_options(options, "options", &mut error);
+            if answers.is_empty() {
+                error("`answers` 至少需要一个正确选项".to_string());
+            }
+            for key in answers {
+                check_key(*key, options.len(), "answers", &mut error);
+            }
+            if has_duplicates(answers) {
+                error("`answers` 中有重复的选项".to_string());
+            }
+        }
+        QuestionKind::TrueFalse { .. } => {}
+        QuestionKind::FillInBlank { accepted, .. } => {
+            if accepted.iter().all(|a| a.trim().is_empty()) {
+                error("`accepted` 至少需要一个非空答案".to_string());
+            } else if accepted.iter().any(|a| a.trim().is_empty()) {
+                error("`accepted` 中有空答案".to_string());
+            }
+        }
+        QuestionKind::Ordering { items, answer } => {
+            check_options(items, "items", &mut error);
+            for key in answer {
+                check_key(*key, items.len(), "answer", &mut error);
+            }
+            if answer.len() != items.len() || has_duplicates(answer) {
+                error(format!("`answer` 必须把全部 {} 个选项各排列一次", items.len()));
+            }
+        }
+        QuestionKind::Code { starter, tests, timeout_secs, solution } => {
+            if starter.trim().is_empty() {
+                error("`starter` 不能为空".to_string());
+            }
+            if *timeout_secs == 0 {
+                error("`timeout_secs` 必须大于 0".to_string());
+            }
+            let mut names = HashSet::new();
+            for test in tests {
+                if !is_identifier(&test.name) {
+                    error(format!("测试名称 `{}` 不是合法的 Rust 标识符", test.name));
+                } else if !names.insert(test.name.as_str()) {
+                    error(format!("测试名称 `{}` 重复", test.name));
+                }
+                if test.code.trim().is_empty() {
+                    error(format!("测试 `{}` 的代码为空", test.name));
+                }
+            }
+            if tests.is_empty() {
+                issues.push((Severity::Warning, "没有隐藏测试，只要编译通过就算答对".to_string()));
+            }
+            if solution.is_none() {
+                issues.push((Severity::Warning, "没有参考答案 `solution`，无法自动验证题目".to_string()));
+            }
+        }
+    }
+
+    if q.explanation.as_deref().is_none_or(|e| e.trim().is_empty()) {
+        issues.push((Severity::Warning, "缺少解析 `explanation`".to_string()));
+    }
+
+    issues
+}
+
+fn check_options(options: &[String], field: &str, error: &mut impl FnMut(String)) {
+    if options.len() < 2 {
+        error(format!("`{}` 至少需要两个选项", field));
+    }
+    if options.len() > MAX_OPTIONS {
+        error(format!("`{}` 最多 {} 个选项", field, MAX_OPTIONS));
+    }
+    for (i, option) in options.iter().enumerate() {
+        if option.trim().is_empty() {
+            error(format!("`{}` 的第 {} 项为空", field, OptionKey(i)));
+        } else if options[..i].iter().any(|o| o.trim() == option.trim()) {
+            error(format!("`{}` 中选项 `{}` 重复", field, option.trim()));
+        }
+    }
+}
+
+fn check_key(key: OptionKey, option_count: usize, field: &str, error: &mut impl FnMut(String)) {
+    if key.0 >= option_count {
+        error(format!("`{}` 的选项 {} 不在选项范围内（共 {} 个选项）", field, key, option_count));
+    }
+}
+
+fn has_duplicates(keys: &[OptionKey]) -> bool {
+    let unique: HashSet<_> = keys.iter().collect();
+    unique.len() != keys.len()
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// 生成检查报告，`file` 用于显示位置
+pub fn render_issues(file: &str, issues: &[LintIssue]) -> String {
+    let mut output = String::new();
+    for issue in issues {
+        let id = issue.id.as_deref().map(|id| format!(" [{}]", id)).unwrap_or_default();
+        output.push_str(&format!("{}:{}: {}{}: {}\n", file, issue.line, issue.severity.name(), id, issue.message));
+    }
+
+    let errors = issues.iter().filter(|i| i.severity == Severity::Error).count();
+    let warnings = issues.len() - errors;
+    output.push_str(&format!("\n{} 个错误，{} 个警告\n", errors, warnings));
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bank;
+
+    fn messages(content: &str) -> Vec<(Severity, String)> {
+        let entries = bank::parse_entries(content).unwrap();
+        lint_entries(&entries).into_iter().map(|i| (i.severity, i.message)).collect()
+    }
+
+    #[test]
+    fn test_bundled_bank_is_clean() {
+        let entries = bank::parse_entries(include_str!("../questions.json")).unwrap();
+        assert_eq!(lint_entries(&entries), Vec::new());
+    }
+
+    #[test]
+    fn test_lint_reports_all_errors() {
+        let content = r#"[
+  { "id": "q1", "topic": "t", "stage": "Stage1Basics", "difficulty": "easy", "question": "题目",
+    "explanation": "解析", "type": "single_choice", "options": ["a", "a"], "answer": "B" },
+  { "id": "q1", "topic": "", "stage": "Stage1Basics", "difficulty": "easy", "question": " ",
+    "explanation": "解析", "type": "ordering", "items": ["x", "y"], "answer": ["A", "A"] },
+  { "id": "q3", "stage": "Stage1Basics" },
+  { "id": "q4", "topic": "t", "stage": "Stage1Basics", "difficulty": "easy", "question": "题目",
+    "type": "single_choice", "options": ["a", "b"], "answer": 200 },
+  { "id": "q5", "topic": "t", "stage": "Stage1Basics", "difficulty": "easy", "question": "题目",
+    "type": "multiple_choice", "options": [], "answers": ["A"] }
+]"#;
+        let entries = bank::parse_entries(content).unwrap();
+        let issues = lint_entries(&entries);
+
+        let lines: Vec<usize> = issues.iter().map(|i| i.line).collect();
+        assert_eq!(lines, vec![2, 4, 4, 4, 4, 6, 7, 9]);
+        let text = render_issues("questions.json", &issues);
+        assert!(text.contains("questions.json:2: 错误 [q1]: `options` 中选项 `a` 重复"));
+        assert!(text.contains("questions.json:4: 错误 [q1]: 题目 ID 重复"));
+        assert!(text.contains("questions.json:6: 错误: 无法解析"));
+        assert!(text.contains("questions.json:7: 错误: 无法解析: 无效的选项编号: 200"));
+        assert!(text.contains("questions.json:9: 错误: 无法解析: `options` 不能为空"));
+        assert!(text.contains("8 个错误，0 个警告"));
+    }
+
+    #[test]
+    fn test_lint_code_question() {
+        let content = r#"[
+  { "id": "c", "topic": "t", "stage": "Stage1Basics", "difficulty": "easy", "question": "题目",
+    "type": "code", "starter": "fn f() {}", "tests": [{ "name": "bad name", "code": "f();" }] }
+]"#;
+        let issues = messages(content);
+        assert!(issues.contains(&(Severity::Error, "测试名称 `bad name` 不是合法的 Rust 标识符".to_string())));
+        assert_eq!(issues.iter().filter(|(s, _)| *s == Severity::Warning).count(), 2);
+    }
+}
@@ -1,40 +1,439 @@
-use serde::{Deserialize, Serialize};
-use std::fs::File;
-use std::io::{self, BufReader};
+mod adaptive;
+mod answers;
+mod author;
+mod bank;
+mod code_grader;
+mod editor;
+mod error;
+mod history;
+mod input;
+mod lint;
+mod markdown;
+mod progress;
+mod question;
+mod quiz;
 
-#[derive(Debug, Serialize, Deserialize)]
-struct Question {
-    question: String,
-    options: Vec<String>,
-    answer: String,
+use adaptive::AdaptiveOptions;
+use answers::AnswerSheet;
+use bank::BankSource;
+use chrono::Utc;
+use clap::{Args, Parser, Subcommand};
+use code_grader::CodeGrader;
+use error::AssessmentError;
+use history::{Attempt, AttemptHistory, Placement, DEFAULT_HISTORY_FILE};
+use input::Input;
+use progress_tracker::LearningStage;
+use question::{parse_stage, Question};
+use quiz::{Quiz, QuizOptions, SeededRng};
+use std::path::{Path, PathBuf};
+use std::fs;
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Parser)]
+#[command(name = "assessment-tool", about = "Rust 学习测验工具", version, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    quiz: QuizArgs,
+
+    /// 测验历史记录文件
+    #[arg(long, global = true, value_name = "FILE", default_value = DEFAULT_HISTORY_FILE)]
+    history: PathBuf,
+
+    /// 题库文件，默认为当前目录下的 questions.json，不存在时使用内置题库（`add` 和 `import` 不会修改内置题库）
+    #[arg(long, global = true, value_name = "FILE")]
+    bank: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 查看测验历史
+    History,
+    /// 检查题库（JSON）或 Markdown 测验文件中的错误
+    Lint {
+        /// 要检查的文件，默认为题库文件
+        files: Vec<PathBuf>,
+    },
+    /// 交互式添加一道题目到题库
+    Add,
+    /// 从 Markdown 测验文件导入题目到题库
+    Import {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// 只检查和预览，不写入题库
+        #[arg(long)]
+        dry_run: bool,
+        /// 覆盖题库中 ID 相同的题目
+        #[arg(long)]
+        replace: bool,
+    },
+}
+
+#[derive(Args)]
+struct QuizArgs {
+    /// 只出指定阶段的题目（1-5 或 stage1-stage5）
+    #[arg(long, value_parser = parse_stage)]
+    stage: Option<LearningStage>,
+
+    /// 只出知识点包含该关键字的题目
+    #[arg(long)]
+    topic: Option<String>,
+
+    /// 随机抽取的题目数量；自适应模式下为最多出题数
+    #[arg(short = 'n', long)]
+    count: Option<usize>,
+
+    /// 随机种子，相同的种子抽到相同的题目
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// 打乱题目顺序
+    #[arg(long)]
+    shuffle: bool,
+
+    /// 打乱选择题和排序题的选项顺序
+    #[arg(long)]
+    shuffle_options: bool,
+
+    /// 每道题的时间限制（秒）
+    #[arg(long, value_name = "SECS")]
+    question_time: Option<u64>,
+
+    /// 整场测验的时间限制（秒）
+    #[arg(long, value_name = "SECS")]
+    time_limit: Option<u64>,
+
+    /// 把成绩记录到 progress-tracker 的进度文件
+    #[arg(long, value_name = "FILE")]
+    progress: Option<PathBuf>,
+
+    /// 要完成的评估单元 ID，默认为 stageN-assessment
+    #[arg(long, value_name = "ID", requires = "progress")]
+    unit: Option<String>,
+
+    /// 从答案文件读取作答（非交互模式），每行格式为 `题目 ID: 答案`
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["question_time", "time_limit"])]
+    answers: Option<PathBuf>,
+
+    /// 自适应模式：根据作答情况选题，估计能力并建议起始阶段
+    #[arg(long, conflicts_with_all = ["stage", "shuffle", "progress"])]
+    adaptive: bool,
+
+    /// 自适应模式下能力估计的目标标准误，越小出题越多
+    #[arg(long, value_name = "SE", requires = "adaptive", default_value_t = AdaptiveOptions::default().target_standard_error)]
+    target_se: f64,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let bank_source = BankSource::resolve(cli.bank.as_deref());
+
+    let result = match cli.command {
+        Some(Command::History) => show_history(&cli.history),
+        Some(Command::Lint { files }) => lint_files(&files, &bank_source),
+        Some(Command::Add) => bank::resolve_writable_path(cli.bank.as_deref()).and_then(|path| add_question(&path)),
+        Some(Command::Import { files, dry_run, replace }) => bank::resolve_writable_path(cli.bank.as_deref())
+            .and_then(|path| import_markdown(&files, &path, dry_run, replace)),
+        None => run_quiz(cli.quiz, &bank_source, &cli.history),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run_quiz(args: QuizArgs, bank_source: &BankSource, history_file: &Path) -> Result<(), AssessmentError> {
+    let bank = bank_source.load()?;
+    let answers = args.answers.as_deref().map(AnswerSheet::load).transpose()?;
+
+    let options = QuizOptions {
+        stage: args.stage.clone(),
+        topic: args.topic.clone(),
+        count: args.count,
+        shuffle_questions: args.shuffle,
+        shuffle_options: args.shuffle_options,
+        question_time_limit: args.question_time.map(Duration::from_secs),
+        total_time_limit: args.time_limit.map(Duration::from_secs),
+    };
+
+    if args.adaptive {
+        return run_adaptive(&bank, options, &args, answers.as_ref(), history_file);
+    }
+
+    let randomized = options.count.is_some() || options.shuffle_questions || options.shuffle_options;
+    let seed = args.seed.unwrap_or_else(|| {
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or_default()
+    });
+    if randomized {
+        println!("🎲 随机种子: {}（使用 --seed {} 可复现本次测验）", seed, seed);
+    }
+
+    let questions = quiz::select_questions(&bank, &options, &mut SeededRng::new(seed));
+    if questions.is_empty() {
+        println!("没有符合条件的题目。");
+        return Ok(());
+    }
+
+    let started_at = Utc::now();
+    let grader = CodeGrader::default();
+    let quiz = Quiz { questions: &questions, options: &options, grader: &grader, answers: answers.as_ref() };
+    let report = quiz.run(&Input::stdin())?;
+    print!("{}", report.render());
+
+    let attempt = Attempt::new(&report, &options, randomized.then_some(seed), started_at);
+    save_attempt(history_file, &attempt);
+
+    if let Some(progress_file) = &args.progress {
+        if report.aborted {
+            println!("⚠️  测验未完成，不更新进度文件。");
+        } else {
+            sync_progress(progress_file, args.unit.as_deref(), &options, &questions, &attempt)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// 自适应测验：按作答情况逐题选题，能力估计足够精确时停止
+fn run_adaptive(
+    bank: &[Question],
+    options: QuizOptions,
+    args: &QuizArgs,
+    answers: Option<&AnswerSheet>,
+    history_file: &Path,
+) -> Result<(), AssessmentError> {
+    let seed = args.seed.unwrap_or_else(|| {
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or_default()
+    });
+    // 题目池的顺序和选项顺序也由种子决定，相同的 --seed 可以复现整场测验
+    let mut rng = SeededRng::new(seed);
+    let pool = quiz::select_questions(bank, &QuizOptions { count: None, ..options.clone() }, &mut rng);
+    if pool.is_empty() {
+        println!("没有符合条件的题目。");
+        return Ok(());
+    }
+
+    let adaptive_options = AdaptiveOptions {
+        max_questions: args.count.unwrap_or(AdaptiveOptions::default().max_questions),
+        target_standard_error: args.target_se,
+        ..Default::default()
+    };
+    println!("🧭 自适应测验：最多 {} 题，能力估计足够精确时自动结束（随机种子: {}）", adaptive_options.max_questions, seed);
+
+    let started_at = Utc::now();
+    let grader = CodeGrader::default();
+    let quiz = Quiz { questions: &pool, options: &options, grader: &grader, answers };
+    let report = adaptive::run(&quiz, &adaptive_options, &mut rng, &Input::stdin())?;
+    print!("{}", report.render());
+
+    let mut attempt = Attempt::new(&report.quiz, &options, Some(seed), started_at);
+    attempt.placement = Some(Placement::from(&report));
+    save_attempt(history_file, &attempt);
+
+    Ok(())
 }
 
-fn main() -> io::Result<()> {
-    let file = File::open("questions.json")?;
-    let reader = BufReader::new(file);
-    let questions: Vec<Question> = serde_json::from_reader(reader)?;
+/// 追加测验记录到历史文件
+///
+/// 历史文件无法读取时不保存本次记录，以免覆盖已有的历史。
+fn save_attempt(history_file: &Path, attempt: &Attempt) {
+    let mut history = match load_history(history_file) {
+        Ok(history) => history,
+        Err(e) => {
+            eprintln!("⚠️  {}，本次测验记录未保存（请修复或移走该文件）", e);
+            return;
+        }
+    };
+    history.attempts.push(attempt.clone());
+    match history.save(history_file) {
+        Ok(()) => println!("\n💾 测验记录已保存到: {}", history_file.display()),
+        Err(e) => eprintln!("⚠️  无法保存测验历史 {}: {}", history_file.display(), e),
+    }
+}
 
-    let mut score = 0;
+fn load_history(history_file: &Path) -> Result<AttemptHistory, AssessmentError> {
+    AttemptHistory::load(history_file).map_err(|e| AssessmentError::InvalidHistory {
+        path: history_file.to_path_buf(),
+        message: e.to_string(),
+    })
+}
 
-    for (i, q) in questions.iter().enumerate() {
-        println!("\nQuestion {}: {}", i + 1, q.question);
-        for (j, option) in q.options.iter().enumerate() {
-            println!("  {}. {}", j + 1, option);
+/// 把成绩同步到进度文件中对应的评估单元，单元不是该阶段的评估单元时返回 `ProgressUpdate`
+fn sync_progress(
+    path: &Path,
+    unit: Option<&str>,
+    options: &QuizOptions,
+    questions: &[Question],
+    attempt: &Attempt,
+) -> Result<(), AssessmentError> {
+    let stage = quiz_stage(options, questions);
+    let (unit_id, stage) = match (unit, stage) {
+        (Some(unit), Some(stage)) => (unit.to_string(), stage),
+        (None, Some(stage)) => (stage.assessment_unit_id(), stage),
+        (Some(unit), None) => {
+            // 跨阶段测验归入其中最高的阶段
+            let highest = questions.iter().map(|q| q.stage.clone()).max_by_key(|s| s.number());
+            (unit.to_string(), highest.unwrap_or(LearningStage::Stage1Basics))
+        }
+        (None, None) => {
+            eprintln!("⚠️  本次测验包含多个阶段的题目，请使用 --stage 或 --unit 指定要完成的评估单元。");
+            return Ok(());
         }
+    };
 
-        let mut user_answer = String::new();
-        io::stdin().read_line(&mut user_answer)?;
-        let user_answer = user_answer.trim();
+    let update = progress::record_attempt(path, &unit_id, stage, attempt).map_err(|e| AssessmentError::ProgressUpdate {
+        path: path.to_path_buf(),
+        message: e.to_string(),
+    })?;
+    println!("📈 已完成评估单元 {}（{}），分数: {:.1}", update.unit_name, update.unit_id, update.score);
+    if !update.unlocked.is_empty() {
+        println!("\n🎉 恭喜！解锁了新成就:");
+        for achievement in &update.unlocked {
+            println!("  🏆 {} - {}", achievement.name, achievement.description);
+        }
+    }
+    Ok(())
+}
+
+/// 测验题目所属的阶段；题目跨多个阶段时返回 None
+fn quiz_stage(options: &QuizOptions, questions: &[Question]) -> Option<LearningStage> {
+    if options.stage.is_some() {
+        return options.stage.clone();
+    }
+    let first = questions.first()?.stage.clone();
+    questions.iter().all(|q| q.stage == first).then_some(first)
+}
 
-        if user_answer == q.answer {
-            println!("Correct!");
-            score += 1;
+fn show_history(history_file: &Path) -> Result<(), AssessmentError> {
+    print!("{}", load_history(history_file)?.render());
+    Ok(())
+}
+
+/// 检查题库文件，没有指定文件时检查当前使用的题库，发现错误时返回 `LintFailed`
+fn lint_files(files: &[PathBuf], bank_source: &BankSource) -> Result<(), AssessmentError> {
+    let sources: Vec<(PathBuf, String)> = if files.is_empty() {
+        vec![(bank_source.path(), bank_source.read()?)]
+    } else {
+        files.iter().map(|file| Ok((file.clone(), read_file(file)?))).collect::<Result<_, AssessmentError>>()?
+    };
+
+    let mut has_errors = false;
+    for (file, content) in &sources {
+        let is_markdown = file.extension().is_some_and(|ext| ext == "md");
+        let entries = if is_markdown {
+            markdown::parse_quiz(content)
         } else {
-            println!("Incorrect. The correct answer is: {}", q.answer);
+            match bank::parse_entries(content) {
+                Ok(entries) => entries,
+                Err(message) => {
+                    println!("{}: 错误: {}", file.display(), message);
+                    has_errors = true;
+                    continue;
+                }
+            }
+        };
+
+        let issues = lint::lint_entries(&entries);
+        has_errors |= issues.iter().any(|i| i.severity == lint::Severity::Error);
+        println!("🔍 {}: {} 道题", file.display(), entries.len());
+        print!("{}", lint::render_issues(&file.display().to_string(), &issues));
+    }
+
+    if has_errors {
+        return Err(AssessmentError::LintFailed);
+    }
+    Ok(())
+}
+
+/// 交互式添加题目
+fn add_question(bank_file: &Path) -> Result<(), AssessmentError> {
+    let mut questions = bank::load(bank_file)?;
+    let ids: Vec<String> = questions.iter().map(|q| q.id.clone()).collect();
+
+    let input = Input::stdin();
+    let Some(question) = author::prompt_question(&input, &ids)? else {
+        println!("\n❌ 已取消");
+        return Ok(());
+    };
+    if !author::confirm(&input, &question)? {
+        return Ok(());
+    }
+
+    let id = question.id.clone();
+    questions.push(question);
+    bank::save(bank_file, &questions)?;
+    println!("✅ 已添加题目 {}，题库共 {} 道题", id, questions.len());
+    Ok(())
+}
+
+/// 从 Markdown 测验文件导入题目；任何文件有错误时都不写入
+fn import_markdown(files: &[PathBuf], bank_file: &Path, dry_run: bool, replace: bool) -> Result<(), AssessmentError> {
+    let mut questions = bank::load(bank_file)?;
+    let mut imported = Vec::new();
+    let mut has_errors = false;
+
+    for file in files {
+        let entries = markdown::parse_quiz(&read_file(file)?);
+        let mut issues = lint::lint_entries(&entries);
+        for entry in &entries {
+            if let Ok(question) = &entry.question
+                && !replace
+                && questions.iter().any(|q| q.id == question.id)
+            {
+                issues.push(lint::LintIssue {
+                    severity: lint::Severity::Error,
+                    line: entry.line,
+                    id: Some(question.id.clone()),
+                    message: "题库中已有相同 ID 的题目（使用 --replace 覆盖）".to_string(),
+                });
+            }
+        }
+        issues.sort_by_key(|i| i.line);
+
+        has_errors |= issues.iter().any(|i| i.severity == lint::Severity::Error);
+        println!("📄 {}: {} 道题", file.display(), entries.len());
+        if !issues.is_empty() {
+            print!("{}", lint::render_issues(&file.display().to_string(), &issues));
         }
+        imported.extend(entries.into_iter().filter_map(|e| e.question.ok()));
     }
 
-    println!("\nYou scored {} out of {}.", score, questions.len());
+    if has_errors {
+        println!("\n❌ 存在错误，未导入任何题目");
+        return Err(AssessmentError::LintFailed);
+    }
 
+    let mut added = 0;
+    let mut replaced = 0;
+    for question in imported {
+        match questions.iter_mut().find(|q| q.id == question.id) {
+            Some(existing) => {
+                *existing = question;
+                replaced += 1;
+            }
+            None => {
+                questions.push(question);
+                added += 1;
+            }
+        }
+    }
+
+    if dry_run {
+        println!("\n🔎 预览：将新增 {} 道题，覆盖 {} 道题（--dry-run 未写入）", added, replaced);
+        return Ok(());
+    }
+    bank::save(bank_file, &questions)?;
+    println!("\n✅ 新增 {} 道题，覆盖 {} 道题，题库共 {} 道题", added, replaced, questions.len());
     Ok(())
 }
+
+fn read_file(path: &Path) -> Result<String, AssessmentError> {
+    fs::read_to_string(path).map_err(|e| AssessmentError::ReadFile { path: path.to_path_buf(), source: e })
+}
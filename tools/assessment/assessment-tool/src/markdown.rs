@@ -0,0 +1,367 @@
+//! 从 Markdown 测验文件导入题目
+//!
+//! 每道题以二级标题开头，标题为题目 ID，格式示例：
+//!
+//! ```markdown
+//! ## mut-keyword
+//! - topic: 变量与可变性
+//! - stage: 1
+//! - difficulty: easy
+//!
+//! 在 Rust 中，哪个关键字用于声明一个可变变量？
+//!
+//! - [ ] let
+//! - [x] mut
+//!
+//! > Rust 中的变量默认不可变，需要使用 `let mut`。
+//! ```
+//!
+//! 题型根据内容推断，也可以用 `- type:` 指定：
+//! - 勾选框列表：一个 `[x]` 为单选题，多个为多选题
+//! - 有序列表（`1.`）：排序题，按正确顺序书写，导入时打乱展示顺序
+//! - `- answer: true/false`：判断题
+//! - `- answer: 答案1 | 答案2`：填空题
+//! - 代码块 `rust starter`、`rust test <名称>`、`rust solution`：代码题
+//!
+//! 引用块（`>`）为解析。
+
+use crate::bank::Entry;
+use crate::question::{parse_bool, parse_stage, CodeTest, Difficulty, OptionKey, Question, QuestionKind};
+use crate::quiz::SeededRng;
+
+/// 解析 Markdown 测验文件，每道题对应一个条目
+pub fn parse_quiz(content: &str) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut current: Option<(usize, Draft)> = None;
+    let mut fence: Option<Fence> = None;
+
+    for (index, raw) in content.lines().enumerate() {
+        let line_number = index + 1;
+
+        // 代码块内的内容原样保留
+        if let Some(mut open) = fence.take() {
+            if raw.trim_start().starts_with("```") {
+                if let Some((_, draft)) = current.as_mut() {
+                    draft.blocks.push(open);
+                }
+            } else {
+                open.code.push_str(raw);
+                open.code.push('\n');
+                fence = Some(open);
+            }
+            continue;
+        }
+
+        let line = raw.trim();
+        if let Some(id) = line.strip_prefix("## ") {
+            if let Some((line, draft)) = current.take() {
+                entries.push(Entry { line, question: draft.build() });
+            }
+            current = Some((line_number, Draft { id: id.trim().to_string(), ..Default::default() }));
+            continue;
+        }
+
+        let Some((_, draft)) = current.as_mut() else {
+            // 第一道题之前的内容（例如一级标题）忽略
+            continue;
+        };
+
+        if let Some(info) = line.strip_prefix("```") {
+            fence = Some(Fence { info: info.trim().to_string(), code: String::new() });
+        } else if let Some(text) = line.strip_prefix("- [ ]") {
+            draft.checkboxes.push((text.trim().to_string(), false));
+        } else if let Some(text) = line.strip_prefix("- [x]").or_else(|| line.strip_prefix("- [X]")) {
+            draft.checkboxes.push((text.trim().to_string(), true));
+        } else if let Some(text) = line.strip_prefix('>') {
+            draft.explanation.push(text.trim().to_string());
+        } else if let Some(text) = ordered_item(line) {
+            draft.ordered.push(text.to_string());
+        } else if let Some((key, value)) = line.strip_prefix("- ").and_then(|l| l.split_once(':')).filter(|_| draft.text.is_empty()) {
+            draft.meta.push((key.trim().to_lowercase(), value.trim().to_string()));
+        } else if !line.is_empty() {
+            draft.text.push(line.to_string());
+        }
+    }
+
+    if let Some((line, mut draft)) = current.take() {
+        // 未闭合的代码块也收进来，由后续检查报告缺失的部分
+        if let Some(open) = fence.take() {
+            draft.blocks.push(open);
+        }
+        entries.push(Entry { line, question: draft.build() });
+    }
+
+    entries
+}
+
+/// 有序列表项，例如 `1. cargo new`
+fn ordered_item(line: &str) -> Option<&str> {
+    let (number, rest) = line.split_once(". ")?;
+    (!number.is_empty() && number.chars().all(|c| c.is_ascii_digit())).then(|| rest.trim())
+}
+
+#[derive(Debug)]
+struct Fence {
+    info: String,
+    code: String,
+}
+
+/// 解析中的题目
+#[derive(Debug, Default)]
+struct Draft {
+    id: String,
+    meta: Vec<(String, String)>,
+    text: Vec<String>,
+    checkboxes: Vec<(String, bool)>,
+    ordered: Vec<String>,
+    blocks: Vec<Fence>,
+    explanation: Vec<String>,
+}
+
+impl Draft {
+    fn meta(&self, key: &str) -> Option<&str> {
+        self.meta.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+
+    fn build(self) -> Result<Question, String> {
+        for (key, _) in &self.meta {
+            if !["topic", "stage", "difficulty", "type", "answer", "case_sensitive", "timeout_secs"].contains(&key.as_str()) {
+                return Err(format!("未知的字段 `{}`", key));
+            }
+        }
+
+        let topic = self.meta("topic").ok_or("缺少 `- topic:`")?.to_string();
+        let stage = parse_stage(self.meta("stage").ok_or("缺少 `- stage:`")?)?;
+        let difficulty = match self.meta("difficulty") {
+            Some(value) => Difficulty::parse(value).ok_or_else(|| format!("无效的难度: {}", value))?,
+            None => Difficulty::Medium,
+        };
+
+        let kind = self.build_kind()?;
+        let explanation = self.explanation.join("\n");
+        let explanation = explanation.strip_prefix("解析：").or_else(|| explanation.strip_prefix("解析:")).unwrap_or(&explanation);
+
+        Ok(Question {
+            id: self.id,
+            topic,
+            stage,
+            difficulty,
+            question: self.text.join("\n"),
+            explanation: (!explanation.trim().is_empty()).then(|| explanation.trim().to_string()),
+            kind,
+        })
+    }
+
+    fn build_kind(&self) -> Result<QuestionKind, String> {
+        let kind = match self.meta("type") {
+            Some(kind) => kind.to_string(),
+            None => self.infer_kind()?.to_string(),
+        };
+
+        let options: Vec<String> = self.checkboxes.iter().map(|(text, _)| text.clone()).collect();
+        let checked: Vec<OptionKey> = self.checkboxes.iter()
+            .enumerate()
+            .filter(|(_, (_, checked))| *checked)
+            .map(|(i, _)| OptionKey(i))
+            .collect();
+        let answer = || self.meta("answer").ok_or_else(|| format!("{} 缺少 `- answer:`", kind));
+
+        match kind.as_str() {
+            "single_choice" => match checked.as_slice() {
+                [answer] => Ok(QuestionKind::SingleChoice { options, answer: *answer }),
+                _ => Err("单选题需要恰好一个 `- [x]` 选项".to_string()),
+            },
+            "multiple_choice" => Ok(QuestionKind::MultipleChoice { options, answers: checked }),
+            "true_false" => {
+                let value = answer()?;
+                parse_bool(value)
+                    .map(|answer| QuestionKind::TrueFalse { answer })
+                    .ok_or_else(|| format!("无效的判断答案: {}", value))
+            }
+            "fill_in_blank" => Ok(QuestionKind::FillInBlank {
+                accepted: answer()?.split('|').map(|a| a.trim().to_string()).collect(),
+                case_sensitive: self.meta("case_sensitive").and_then(parse_bool).unwrap_or(false),
+            }),
+            "ordering" => Ok(scrambled_ordering(&self.id, &self.ordered)),
+            "code" => {
+                let block = |name: &str| self.blocks.iter().find(|b| fence_role(&b.info) == Some((name, ""))).map(|b| b.code.clone());
+                let tests = self.blocks.iter()
+                    .filter_map(|b| match fence_role(&b.info) {
+                        Some(("test", name)) => Some(CodeTest { name: name.to_string(), code: b.code.trim_end().to_string() }),
+                        _ => None,
+                    })
+                    .collect();
+                let timeout_secs = match self.meta("timeout_secs") {
+                    Some(value) => value.parse().map_err(|_| format!("无效的 timeout_secs: {}", value))?,
+                    None => 10,
+                };
+                Ok(QuestionKind::Code {
+                    starter: block("starter").ok_or("代码题缺少 ```rust starter 代码块")?,
+                    tests,
+                    timeout_secs,
+                    solution: block("solution"),
+                })
+            }
+            other => Err(format!("未知的题型: {}", other)),
+        }
+    }
+
+    fn infer_kind(&self) -> Result<&'static str, String> {
+        if self.blocks.iter().any(|b| fence_role(&b.info).is_some_and(|(role, _)| role == "starter")) {
+            return Ok("code");
+        }
+        if !self.checkboxes.is_empty() {
+            let checked = self.checkboxes.iter().filter(|(_, c)| *c).count();
+            return Ok(if checked > 1 { "multiple_choice" } else { "single_choice" });
+        }
+        if !self.ordered.is_empty() {
+            return Ok("ordering");
+        }
+        match self.meta("answer") {
+            Some(answer) if parse_bool(answer).is_some() => Ok("true_false"),
+            Some(_) => Ok("fill_in_blank"),
+            None => Err("无法推断题型：请添加选项、答案或 `- type:`".to_string()),
+        }
+    }
+}
+
+/// 代码块的用途：`rust starter` → ("starter", "")，`rust test name` → ("test", "name")
+fn fence_role(info: &str) -> Option<(&str, &str)> {
+    let mut parts = info.split_whitespace();
+    if parts.next() != Some("rust") {
+        return None;
+    }
+    let role = parts.next()?;
+    Some((role, parts.next().unwrap_or("")))
+}
+
+/// 排序题按正确顺序书写，导入时以题目 ID 为种子打乱展示顺序，保证重复导入结果一致
+pub fn scrambled_ordering(id: &str, correct: &[String]) -> QuestionKind {
+    let seed = id.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, b| (hash ^ b as u64).wrapping_mul(0x100_0000_01b3));
+    let mut rng = SeededRng::new(seed);
+
+    // order[展示位置] = 正确顺序中的位置
+    let mut order: Vec<usize> = (0..correct.len()).collect();
+    while correct.len() > 1 && order.iter().enumerate().all(|(i, &o)| i == o) {
+        rng.shuffle(&mut order);
+    }
+
+    let items = order.iter().map(|&i| correct[i].clone()).collect();
+    let answer = (0..correct.len())
+        .map(|position| OptionKey(order.iter().position(|&o| o == position).unwrap_or(0)))
+        .collect();
+    QuestionKind::Ordering { items, answer }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUIZ: &str = r#"# 借用测验
+
+## borrow-rules
+- topic: 借用
+- stage: 2
+- difficulty: easy
+
+以下哪些说法符合借用规则？
+
+- [x] 同一时刻可以有多个不可变引用
+- [ ] 同一时刻可以有多个可变引用
+- [x] 引用必须始终有效
+
+> 解析：可变引用在同一时刻只能有一个。
+
+## move-semantics
+- topic: 所有权
+- stage: stage2
+- answer: 对
+
+把 `String` 赋值给另一个变量会转移所有权。
+
+## cargo-order
+- topic: Cargo
+- stage: 4
+
+请排列以下命令：
+
+1. cargo new
+2. cargo build
+3. cargo publish
+
+## fix-it
+- topic: 借用
+- stage: 2
+- timeout_secs: 5
+
+修复代码。
+
+```rust starter
+pub fn one() -> i32 { 0 }
+```
+
+```rust test returns_one
+assert_eq!(one(), 1);
+```
+
+```rust solution
+pub fn one() -> i32 { 1 }
+```
+
+## broken
+- topic: 借用
+
+没有阶段。
+"#;
+
+    #[test]
+    fn test_parse_markdown_quiz() {
+        let entries = parse_quiz(QUIZ);
+        assert_eq!(entries.iter().map(|e| e.line).collect::<Vec<_>>(), vec![3, 16, 23, 33, 52]);
+
+        let borrow = entries[0].question.as_ref().unwrap();
+        assert_eq!(borrow.stage, progress_tracker::LearningStage::Stage2Ownership);
+        assert_eq!(borrow.difficulty, Difficulty::Easy);
+        assert_eq!(borrow.question, "以下哪些说法符合借用规则？");
+        assert_eq!(borrow.explanation.as_deref(), Some("可变引用在同一时刻只能有一个。"));
+        assert_eq!(borrow.kind, QuestionKind::MultipleChoice {
+            options: vec![
+                "同一时刻可以有多个不可变引用".to_string(),
+                "同一时刻可以有多个可变引用".to_string(),
+                "引用必须始终有效".to_string(),
+            ],
+            answers: vec![OptionKey(0), OptionKey(2)],
+        });
+
+        let moved = entries[1].question.as_ref().unwrap();
+        assert_eq!(moved.kind, QuestionKind::TrueFalse { answer: true });
+        assert_eq!(moved.difficulty, Difficulty::Medium);
+
+        let ordering = entries[2].question.as_ref().unwrap();
+        assert_ne!(ordering.options()[0], "cargo new");
+        assert_eq!(ordering.correct_answer_text(), "cargo new → cargo build → cargo publish");
+
+        let QuestionKind::Code { starter, tests, timeout_secs, solution } = &entries[3].question.as_ref().unwrap().kind else {
+            panic!("应为代码题");
+        };
+        assert_eq!(starter, "pub fn one() -> i32 { 0 }\n");
+        assert_eq!(tests, &vec![CodeTest { name: "returns_one".to_string(), code: "assert_eq!(one(), 1);".to_string() }]);
+        assert_eq!(*timeout_secs, 5);
+        assert!(solution.is_some());
+
+        assert_eq!(entries[4].question, Err("缺少 `- stage:`".to_string()));
+    }
+
+    #[test]
+    fn test_single_choice_and_fill_in_blank() {
+        let entries = parse_quiz("## a\n- topic: t\n- stage: 1\n\n题目\n\n- [ ] x\n- [x] y\n\n## b\n- topic: t\n- stage: 1\n- answer: Box | Box<T>\n\n填空\n");
+        assert_eq!(entries[0].question.as_ref().unwrap().kind, QuestionKind::SingleChoice {
+            options: vec!["x".to_string(), "y".to_string()],
+            answer: OptionKey(1),
+        });
+        assert_eq!(entries[1].question.as_ref().unwrap().kind, QuestionKind::FillInBlank {
+            accepted: vec!["Box".to_string(), "Box<T>".to_string()],
+            case_sensitive: false,
+        });
+    }
+}
@@ -0,0 +1,123 @@
+//! 把测验成绩同步到 progress-tracker 的进度文件
+
+use crate::history::Attempt;
+use progress_tracker::events::{self, HooksConfig};
+use progress_tracker::{Achievement, LearningStage, ProgressTracker};
+use std::path::Path;
+
+/// 同步结果
+#[derive(Debug, Clone)]
+pub struct ProgressUpdate {
+    pub unit_id: String,
+    pub unit_name: String,
+    pub score: f32,
+    /// 本次新解锁的成就
+    pub unlocked: Vec<Achievement>,
+}
+
+/// 以百分制成绩完成进度文件中对应的评估单元，检查成就并通知事件订阅者
+pub fn record_attempt<P: AsRef<Path>>(
+    path: P,
+    unit_id: &str,
+    stage: LearningStage,
+    attempt: &Attempt,
+) -> Result<ProgressUpdate, Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    let mut tracker = ProgressTracker::from_file(path)?;
+    let before = tracker.clone();
+
+    let unit = tracker.record_assessment(unit_id, stage, attempt.percentage, attempt.started_at)?;
+    let unit_name = unit.name.clone();
+
+    let unlocked = tracker.check_achievements().into_iter()
+        .filter_map(|id| tracker.achievements.iter().find(|a| a.id == id).cloned())
+        .collect();
+
+    tracker.to_file(path)?;
+    notify_hooks(&before, &tracker);
+
+    Ok(ProgressUpdate {
+        unit_id: unit_id.to_string(),
+        unit_name,
+        score: attempt.percentage,
+        unlocked,
+    })
+}
+
+/// 与 progress-tracker 使用同一份钩子配置分发事件
+fn notify_hooks(before: &ProgressTracker, after: &ProgressTracker) {
+    let hooks_file = HooksConfig::default_path();
+    match events::notify_hooks(&hooks_file, before, after) {
+        Ok(notification) => {
+            for error in &notification.errors {
+                eprintln!("⚠️  事件通知失败 [{}] {}: {}", error.event.name(), error.subscriber, error.error);
+            }
+        }
+        Err(e) => eprintln!("⚠️  无法加载钩子配置 {}: {}", hooks_file.display(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use progress_tracker::{LearningUnitStatus, LearningUnitType};
+
+    fn attempt(percentage: f32) -> Attempt {
+        Attempt {
+            started_at: Utc::now(),
+            finished_at: Utc::now(),
+            stage: Some(LearningStage::Stage2Ownership),
+            topic: None,
+            seed: None,
+            score: 0,
+            total: 0,
+            percentage,
+            aborted: false,
+            topics: Vec::new(),
+            answers: Vec::new(),
+            placement: None,
+        }
+    }
+
+    #[test]
+    fn test_record_attempt_unlocks_achievements() {
+        let path = std::env::temp_dir().join(format!("assessment-progress-{}.json", std::process::id()));
+        ProgressTracker::new("learner".to_string(), "学习者".to_string()).to_file(&path).unwrap();
+
+        let update = record_attempt(&path, "stage2-assessment", LearningStage::Stage2Ownership, &attempt(100.0)).unwrap();
+        assert_eq!(update.score, 100.0);
+        assert!(update.unlocked.iter().any(|a| a.id == "first_steps"));
+
+        // 五次满分评估解锁 perfect_student
+        for stage in [
+            LearningStage::Stage1Basics,
+            LearningStage::Stage3AdvancedConcepts,
+            LearningStage::Stage4Ecosystem,
+            LearningStage::Stage5Projects,
+        ] {
+            record_attempt(&path, &stage.assessment_unit_id(), stage, &attempt(95.0)).unwrap();
+        }
+
+        let tracker = ProgressTracker::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let unit = tracker.get_unit("stage2-assessment").unwrap();
+        assert_eq!(unit.unit_type, LearningUnitType::Assessment);
+        assert_eq!(unit.status, LearningUnitStatus::Completed);
+        assert!(tracker.achievements.iter().any(|a| a.id == "perfect_student" && a.unlocked_at.is_some()));
+    }
+
+    #[test]
+    fn test_record_attempt_rejects_other_units() {
+        let path = std::env::temp_dir().join(format!("assessment-progress-lesson-{}.json", std::process::id()));
+        ProgressTracker::new("learner".to_string(), "学习者".to_string()).to_file(&path).unwrap();
+
+        let result = record_attempt(&path, "stage1-environment", LearningStage::Stage1Basics, &attempt(100.0));
+        let tracker = ProgressTracker::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(result.unwrap_err().to_string().contains("不是评估单元"));
+        assert_eq!(tracker.get_unit("stage1-environment").unwrap().status, LearningUnitStatus::NotStarted);
+    }
+}
@@ -0,0 +1,553 @@
+//! 题库模型：支持单选、多选、判断、填空、排序和代码题
+
+use crate::error::AssessmentError;
+use progress_tracker::LearningStage;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// 每道题最多的选项数，选项编号为 A-Z
+pub const MAX_OPTIONS: usize = 26;
+
+/// 选项编号，内部以 0 开始的下标存储
+///
+/// 在题库中既可以写字母（`"B"`），也可以写从 1 开始的数字（`2`）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OptionKey(pub usize);
+
+impl OptionKey {
+    /// 选项字母，例如 0 → 'A'；超出 A-Z 的编号显示为 '?'
+    pub fn letter(&self) -> char {
+        u8::try_from(self.0)
+            .ok()
+            .filter(|&index| (index as usize) < MAX_OPTIONS)
+            .map_or('?', |index| (b'A' + index) as char)
+    }
+
+    /// 解析单个选项编号：字母（不区分大小写）或从 1 开始的数字
+    pub fn parse(input: &str, option_count: usize) -> Option<OptionKey> {
+        let input = input.trim();
+        let index = if let Ok(number) = input.parse::<usize>() {
+            number.checked_sub(1)?
+        } else {
+            let mut chars = input.chars();
+            let c = chars.next()?.to_ascii_uppercase();
+            if chars.next().is_some() || !c.is_ascii_uppercase() {
+                return None;
+            }
+            (c as u8 - b'A') as usize
+        };
+
+        (index < option_count).then_some(OptionKey(index))
+    }
+}
+
+impl fmt::Display for OptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.letter())
+    }
+}
+
+impl Serialize for OptionKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.letter().to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for OptionKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Number(usize),
+            Letter(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Number(0) => Err(serde::de::Error::custom("选项编号从 1 开始")),
+            Raw::Number(n) if n > MAX_OPTIONS => Err(serde::de::Error::custom(format!(
+                "无效的选项编号: {}（最多 {} 个选项）",
+                n, MAX_OPTIONS
+            ))),
+            Raw::Number(n) => Ok(OptionKey(n - 1)),
+            Raw::Letter(s) => OptionKey::parse(&s, MAX_OPTIONS)
+                .ok_or_else(|| serde::de::Error::custom(format!("无效的选项编号: {}", s))),
+        }
+    }
+}
+
+/// 题目难度
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    /// 获取难度名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "简单",
+            Difficulty::Medium => "中等",
+            Difficulty::Hard => "困难",
+        }
+    }
+
+    /// 解析难度：英文标识或中文名称
+    pub fn parse(input: &str) -> Option<Difficulty> {
+        match input.trim().to_lowercase().as_str() {
+            "easy" | "简单" => Some(Difficulty::Easy),
+            "medium" | "中等" => Some(Difficulty::Medium),
+            "hard" | "困难" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+}
+
+/// 解析学习阶段：`1`-`5` 或 `stage1`-`stage5`
+pub fn parse_stage(value: &str) -> Result<LearningStage, String> {
+    let number = value.trim().to_lowercase();
+    let number = number.strip_prefix("stage").unwrap_or(&number);
+    number
+        .parse::<u32>()
+        .ok()
+        .and_then(LearningStage::from_number)
+        .ok_or_else(|| format!("无效的阶段: {}（应为 1-5）", value))
+}
+
+/// 题目类型及其标准答案
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum QuestionKind {
+    /// 单选题
+    SingleChoice { options: Vec<String>, answer: OptionKey },
+    /// 多选题（全部选对才得分）
+    MultipleChoice { options: Vec<String>, answers: Vec<OptionKey> },
+    /// 判断题
+    TrueFalse { answer: bool },
+    /// 填空题，可以接受多个等价答案
+    FillInBlank {
+        accepted: Vec<String>,
+        #[serde(default, skip_serializing_if = "is_false")]
+        case_sensitive: bool,
+    },
+    /// 排序题：`items` 为展示顺序，`answer` 为正确顺序
+    Ordering { items: Vec<String>, answer: Vec<OptionKey> },
+    /// 代码题：在编辑器中修改 `starter`，编译通过并通过全部隐藏测试才得分
+    Code {
+        starter: String,
+        #[serde(default)]
+        tests: Vec<CodeTest>,
+        #[serde(default = "default_code_timeout_secs")]
+        timeout_secs: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        solution: Option<String>,
+    },
+}
+
+/// 代码题的隐藏测试，`code` 为测试函数体
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CodeTest {
+    pub name: String,
+    pub code: String,
+}
+
+fn default_code_timeout_secs() -> u64 {
+    10
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+/// 学习者的作答
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum Answer {
+    Choice(OptionKey),
+    Choices(Vec<OptionKey>),
+    Bool(bool),
+    Text(String),
+    Code(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Choice(key) => write!(f, "{}", key),
+            Answer::Choices(keys) => {
+                let letters: Vec<String> = keys.iter().map(|k| k.to_string()).collect();
+                write!(f, "{}", letters.join(", "))
+            }
+            Answer::Bool(true) => write!(f, "正确"),
+            Answer::Bool(false) => write!(f, "错误"),
+            Answer::Text(text) => write!(f, "{}", text),
+            Answer::Code(source) => write!(f, "{}", source),
+        }
+    }
+}
+
+/// 题目
+///
+/// 反序列化时会检查选项和答案编号：选项不能为空，答案必须落在选项范围内，
+/// 否则出题和评分都无从谈起。其余问题由 `lint` 报告。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(try_from = "UncheckedQuestion")]
+pub struct Question {
+    pub id: String,
+    pub topic: String,
+    pub stage: LearningStage,
+    pub difficulty: Difficulty,
+    pub question: String,
+    /// 答错后展示的解析
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
+    #[serde(flatten)]
+    pub kind: QuestionKind,
+}
+
+/// 未经检查的题目，字段与 `Question` 相同
+#[derive(Deserialize)]
+struct UncheckedQuestion {
+    id: String,
+    topic: String,
+    stage: LearningStage,
+    difficulty: Difficulty,
+    question: String,
+    #[serde(default)]
+    explanation: Option<String>,
+    #[serde(flatten)]
+    kind: QuestionKind,
+}
+
+impl TryFrom<UncheckedQuestion> for Question {
+    type Error = String;
+
+    fn try_from(raw: UncheckedQuestion) -> Result<Self, Self::Error> {
+        check_keys(&raw.kind)?;
+        Ok(Question {
+            id: raw.id,
+            topic: raw.topic,
+            stage: raw.stage,
+            difficulty: raw.difficulty,
+            question: raw.question,
+            explanation: raw.explanation,
+            kind: raw.kind,
+        })
+    }
+}
+
+/// 检查选项非空、不超过 `MAX_OPTIONS` 个，且答案编号都在选项范围内
+fn check_keys(kind: &QuestionKind) -> Result<(), String> {
+    let (field, options, keys) = match kind {
+        QuestionKind::SingleChoice { options, answer } => ("options", options, std::slice::from_ref(answer)),
+        QuestionKind::MultipleChoice { options, answers } => ("options", options, answers.as_slice()),
+        QuestionKind::Ordering { items, answer } => ("items", items, answer.as_slice()),
+        QuestionKind::TrueFalse { .. } | QuestionKind::FillInBlank { .. } | QuestionKind::Code { .. } => return Ok(()),
+    };
+
+    if options.is_empty() {
+        return Err(format!("`{}` 不能为空", field));
+    }
+    if options.len() > MAX_OPTIONS {
+        return Err(format!("`{}` 最多 {} 个选项", field, MAX_OPTIONS));
+    }
+    match keys.iter().find(|key| key.0 >= options.len()) {
+        Some(key) => Err(format!("答案中的选项 {} 不在选项范围内（共 {} 个选项）", key, options.len())),
+        None => Ok(()),
+    }
+}
+
+impl Question {
+    /// 题型名称
+    pub fn kind_name(&self) -> &'static str {
+        match self.kind {
+            QuestionKind::SingleChoice { .. } => "单选题",
+            QuestionKind::MultipleChoice { .. } => "多选题",
+            QuestionKind::TrueFalse { .. } => "判断题",
+            QuestionKind::FillInBlank { .. } => "填空题",
+            QuestionKind::Ordering { .. } => "排序题",
+            QuestionKind::Code { .. } => "代码题",
+        }
+    }
+
+    /// 需要展示的选项（判断题和填空题没有选项）
+    pub fn options(&self) -> &[String] {
+        match &self.kind {
+            QuestionKind::SingleChoice { options, .. } | QuestionKind::MultipleChoice { options, .. } => options,
+            QuestionKind::Ordering { items, .. } => items,
+            QuestionKind::TrueFalse { .. } | QuestionKind::FillInBlank { .. } | QuestionKind::Code { .. } => &[],
+        }
+    }
+
+    /// 作答提示
+    pub fn input_hint(&self) -> &'static str {
+        match self.kind {
+            QuestionKind::SingleChoice { .. } => "请输入选项字母或编号，例如 B 或 2",
+            QuestionKind::MultipleChoice { .. } => "请输入所有正确选项，例如 A,C 或 13",
+            QuestionKind::TrueFalse { .. } => "请输入 T/F（对/错）",
+            QuestionKind::FillInBlank { .. } => "请输入答案",
+            QuestionKind::Ordering { .. } => "请按正确顺序输入选项，例如 CAB 或 3,1,2",
+            QuestionKind::Code { .. } => "请在编辑器中修改代码，保存后关闭编辑器",
+        }
+    }
+
+    /// 解析学习者输入，输入格式无效时返回 `InvalidAnswer`
+    pub fn parse_answer(&self, input: &str) -> Result<Answer, AssessmentError> {
+        self.parse_answer_text(input).map_err(AssessmentError::InvalidAnswer)
+    }
+
+    fn parse_answer_text(&self, input: &str) -> Result<Answer, String> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Err("答案不能为空".to_string());
+        }
+
+        match &self.kind {
+            QuestionKind::SingleChoice { options, .. } => OptionKey::parse(input, options.len())
+                .map(Answer::Choice)
+                .ok_or_else(|| invalid_key(input, options.len())),
+            QuestionKind::MultipleChoice { options, .. } => {
+                let mut keys = parse_key_list(input, options.len())?;
+                keys.sort();
+                keys.dedup();
+                Ok(Answer::Choices(keys))
+            }
+            QuestionKind::TrueFalse { .. } => parse_bool(input)
+                .map(Answer::Bool)
+                .ok_or_else(|| format!("无效的判断: {}（请输入 T 或 F）", input)),
+            QuestionKind::FillInBlank { .. } => Ok(Answer::Text(input.to_string())),
+            QuestionKind::Code { .. } => Ok(Answer::Code(input.to_string())),
+            QuestionKind::Ordering { items, .. } => {
+                let keys = parse_key_list(input, items.len())?;
+                let mut sorted = keys.clone();
+                sorted.sort();
+                sorted.dedup();
+                if sorted.len() != items.len() || keys.len() != items.len() {
+                    return Err(format!("请将全部 {} 个选项各排列一次", items.len()));
+                }
+                Ok(Answer::Choices(keys))
+            }
+        }
+    }
+
+    /// 判断作答是否正确
+    ///
+    /// 代码题需要编译运行，由 `code_grader::CodeGrader` 评分，这里始终返回 false。
+    pub fn is_correct(&self, answer: &Answer) -> bool {
+        match (&self.kind, answer) {
+            (QuestionKind::SingleChoice { answer: expected, .. }, Answer::Choice(key)) => expected == key,
+            (QuestionKind::MultipleChoice { answers, .. }, Answer::Choices(keys)) => {
+                let mut expected = answers.clone();
+                expected.sort();
+                expected.dedup();
+                let mut actual = keys.clone();
+                actual.sort();
+                actual.dedup();
+                expected == actual
+            }
+            (QuestionKind::TrueFalse { answer: expected }, Answer::Bool(value)) => expected == value,
+            (QuestionKind::FillInBlank { accepted, case_sensitive }, Answer::Text(text)) => {
+                let actual = normalize_text(text, *case_sensitive);
+                accepted.iter().any(|a| normalize_text(a, *case_sensitive) == actual)
+            }
+            (QuestionKind::Ordering { answer: expected, .. }, Answer::Choices(keys)) => expected == keys,
+            _ => false,
+        }
+    }
+
+    /// 标准答案的展示文本
+    pub fn correct_answer_text(&self) -> String {
+        match &self.kind {
+            QuestionKind::SingleChoice { options, answer } => {
+                format!("{}. {}", answer, options.get(answer.0).map(String::as_str).unwrap_or(""))
+            }
+            QuestionKind::MultipleChoice { options, answers } => answers.iter()
+                .map(|k| format!("{}. {}", k, options.get(k.0).map(String::as_str).unwrap_or("")))
+                .collect::<Vec<_>>()
+                .join("; "),
+            QuestionKind::TrueFalse { answer } => Answer::Bool(*answer).to_string(),
+            QuestionKind::FillInBlank { accepted, .. } => accepted.join(" / "),
+            QuestionKind::Ordering { items, answer } => answer.iter()
+                .map(|k| items.get(k.0).map(String::as_str).unwrap_or(""))
+                .collect::<Vec<_>>()
+                .join(" → "),
+            QuestionKind::Code { solution: Some(solution), .. } => format!("参考答案:\n{}", solution),
+            QuestionKind::Code { .. } => "编译通过并通过全部隐藏测试".to_string(),
+        }
+    }
+}
+
+/// 解析选项列表，支持 "A,C"、"A C"、"AC"、"1,3" 等写法
+fn parse_key_list(input: &str, option_count: usize) -> Result<Vec<OptionKey>, String> {
+    let parts: Vec<&str> = if input.contains([',', ' ', '，', '、']) {
+        input.split([',', ' ', '，', '、']).filter(|s| !s.is_empty()).collect()
+    } else {
+        // 紧凑写法："AC" 或 "13"
+        input.char_indices().map(|(i, c)| &input[i..i + c.len_utf8()]).collect()
+    };
+
+    parts.iter()
+        .map(|part| OptionKey::parse(part, option_count)
+            .ok_or_else(|| invalid_key(part, option_count)))
+        .collect()
+}
+
+fn invalid_key(input: &str, option_count: usize) -> String {
+    match option_count.checked_sub(1) {
+        Some(last) => format!("无效的选项: {}（可选 A-{}）", input, OptionKey(last)),
+        None => format!("无效的选项: {}（本题没有选项）", input),
+    }
+}
+
+/// 解析判断：T/F、true/false、对/错等
+pub fn parse_bool(input: &str) -> Option<bool> {
+    match input.to_lowercase().as_str() {
+        "t" | "true" | "y" | "yes" | "对" | "是" | "正确" => Some(true),
+        "f" | "false" | "n" | "no" | "错" | "否" | "错误" => Some(false),
+        _ => None,
+    }
+}
+
+/// 规范化填空答案：去掉首尾空白并合并连续空白
+fn normalize_text(text: &str, case_sensitive: bool) -> String {
+    let joined = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if case_sensitive { joined } else { joined.to_lowercase() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn question(kind: QuestionKind) -> Question {
+        Question {
+            id: "q".to_string(),
+            topic: "测试".to_string(),
+            stage: LearningStage::Stage1Basics,
+            difficulty: Difficulty::Easy,
+            question: "测试题目".to_string(),
+            explanation: None,
+            kind,
+        }
+    }
+
+    fn options(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("选项{}", i + 1)).collect()
+    }
+
+    #[test]
+    fn test_option_key_parsing() {
+        assert_eq!(OptionKey::parse("b", 4), Some(OptionKey(1)));
+        assert_eq!(OptionKey::parse("2", 4), Some(OptionKey(1)));
+        assert_eq!(OptionKey::parse("E", 4), None);
+        assert_eq!(OptionKey::parse("0", 4), None);
+        assert_eq!(OptionKey::parse("mut", 4), None);
+        assert_eq!(OptionKey(2).to_string(), "C");
+
+        let keys: Vec<OptionKey> = serde_json::from_str(r#"["B", 3]"#).unwrap();
+        assert_eq!(keys, vec![OptionKey(1), OptionKey(2)]);
+        assert_eq!(serde_json::to_string(&keys).unwrap(), r#"["B","C"]"#);
+        assert!(serde_json::from_str::<OptionKey>("0").is_err());
+        assert!(serde_json::from_str::<OptionKey>("200").is_err());
+        assert_eq!(OptionKey(200).letter(), '?');
+    }
+
+    #[test]
+    fn test_keys_checked_on_deserialize() {
+        let parse = |kind: &str| {
+            let json = format!(
+                r#"{{"id": "q", "topic": "t", "stage": "Stage1Basics", "difficulty": "easy", "question": "?", {}}}"#,
+                kind
+            );
+            serde_json::from_str::<Question>(&json).map_err(|e| e.to_string())
+        };
+
+        assert!(parse(r#""type": "single_choice", "options": ["a", "b"], "answer": "B""#).is_ok());
+        assert!(parse(r#""type": "single_choice", "options": [], "answer": "A""#).unwrap_err().contains("`options` 不能为空"));
+        assert!(parse(r#""type": "single_choice", "options": ["a", "b"], "answer": 3"#).unwrap_err().contains("选项 C 不在选项范围内"));
+        assert!(parse(r#""type": "multiple_choice", "options": ["a"], "answers": [200]"#).is_err());
+        assert!(parse(r#""type": "ordering", "items": [], "answer": []"#).unwrap_err().contains("`items` 不能为空"));
+    }
+
+    #[test]
+    fn test_parse_stage_and_difficulty() {
+        assert_eq!(parse_stage("2"), Ok(LearningStage::Stage2Ownership));
+        assert_eq!(parse_stage("Stage3"), Ok(LearningStage::Stage3AdvancedConcepts));
+        assert!(parse_stage("6").is_err());
+        assert_eq!(Difficulty::parse("Hard"), Some(Difficulty::Hard));
+        assert_eq!(Difficulty::parse("简单"), Some(Difficulty::Easy));
+        assert_eq!(Difficulty::parse("extreme"), None);
+    }
+
+    #[test]
+    fn test_single_choice() {
+        let q = question(QuestionKind::SingleChoice { options: options(4), answer: OptionKey(1) });
+        for input in ["B", "b", "2", " 2 "] {
+            assert!(q.is_correct(&q.parse_answer(input).unwrap()));
+        }
+        assert!(!q.is_correct(&q.parse_answer("A").unwrap()));
+        assert!(q.parse_answer("选项2").is_err());
+        assert!(q.parse_answer("5").is_err());
+        assert_eq!(q.correct_answer_text(), "B. 选项2");
+    }
+
+    #[test]
+    fn test_multiple_choice() {
+        let q = question(QuestionKind::MultipleChoice { options: options(4), answers: vec![OptionKey(2), OptionKey(0)] });
+        for input in ["A,C", "c a", "AC", "1，3", "3、1"] {
+            assert!(q.is_correct(&q.parse_answer(input).unwrap()), "{}", input);
+        }
+        assert!(!q.is_correct(&q.parse_answer("A").unwrap()));
+        assert!(!q.is_correct(&q.parse_answer("ABC").unwrap()));
+        assert!(q.parse_answer("AZ").is_err());
+    }
+
+    #[test]
+    fn test_true_false() {
+        let q = question(QuestionKind::TrueFalse { answer: false });
+        assert!(q.is_correct(&q.parse_answer("F").unwrap()));
+        assert!(q.is_correct(&q.parse_answer("错").unwrap()));
+        assert!(!q.is_correct(&q.parse_answer("true").unwrap()));
+        assert!(q.parse_answer("maybe").is_err());
+    }
+
+    #[test]
+    fn test_fill_in_blank() {
+        let q = question(QuestionKind::FillInBlank {
+            accepted: vec!["Box<T>".to_string(), "Box".to_string()],
+            case_sensitive: false,
+        });
+        assert!(q.is_correct(&q.parse_answer("  box<t> ").unwrap()));
+        assert!(q.is_correct(&q.parse_answer("Box").unwrap()));
+        assert!(!q.is_correct(&q.parse_answer("Rc").unwrap()));
+
+        let strict = question(QuestionKind::FillInBlank { accepted: vec!["Vec".to_string()], case_sensitive: true });
+        assert!(!strict.is_correct(&strict.parse_answer("vec").unwrap()));
+    }
+
+    #[test]
+    fn test_ordering() {
+        let q = question(QuestionKind::Ordering {
+            items: options(3),
+            answer: vec![OptionKey(2), OptionKey(0), OptionKey(1)],
+        });
+        assert!(q.is_correct(&q.parse_answer("CAB").unwrap()));
+        assert!(q.is_correct(&q.parse_answer("3,1,2").unwrap()));
+        assert!(!q.is_correct(&q.parse_answer("ABC").unwrap()));
+        assert!(q.parse_answer("CA").is_err());
+        assert!(q.parse_answer("CAA").is_err());
+        assert_eq!(q.correct_answer_text(), "选项3 → 选项1 → 选项2");
+    }
+
+    #[test]
+    fn test_bundled_question_bank() {
+        let content = include_str!("../questions.json");
+        let questions: Vec<Question> = serde_json::from_str(content).unwrap();
+        assert!(!questions.is_empty());
+
+        let mut_question = questions.iter().find(|q| q.id == "mut-keyword").unwrap();
+        assert!(mut_question.is_correct(&mut_question.parse_answer("2").unwrap()));
+        assert!(mut_question.is_correct(&mut_question.parse_answer("B").unwrap()));
+    }
+}
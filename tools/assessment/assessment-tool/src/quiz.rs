@@ -0,0 +1,591 @@
+//! 组卷、作答流程与成绩报告
+
+use crate::answers::AnswerSheet;
+use crate::code_grader::CodeGrader;
+use crate::editor;
+use crate::error::AssessmentError;
+use crate::input::{Input, ReadResult};
+use crate::question::{Answer, OptionKey, Question, QuestionKind};
+use progress_tracker::LearningStage;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+/// 组卷与计时选项
+#[derive(Debug, Clone, Default)]
+pub struct QuizOptions {
+    pub stage: Option<LearningStage>,
+    pub topic: Option<String>,
+    /// 随机抽取的题目数量，None 表示全部
+    pub count: Option<usize>,
+    pub shuffle_questions: bool,
+    pub shuffle_options: bool,
+    pub question_time_limit: Option<Duration>,
+    pub total_time_limit: Option<Duration>,
+}
+
+/// 可复现的伪随机数生成器（SplitMix64）
+///
+/// 不依赖外部库，保证相同的种子在不同版本中抽到相同的题目。
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// 返回 [0, bound) 之间的随机数
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    /// Fisher-Yates 洗牌
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+/// 根据阶段、知识点筛选题目，并按需要随机抽题和打乱选项
+pub fn select_questions(bank: &[Question], options: &QuizOptions, rng: &mut SeededRng) -> Vec<Question> {
+    let topic = options.topic.as_ref().map(|t| t.to_lowercase());
+    let mut selected: Vec<Question> = bank.iter()
+        .filter(|q| options.stage.as_ref().is_none_or(|s| q.stage == *s))
+        .filter(|q| topic.as_ref().is_none_or(|t| q.topic.to_lowercase().contains(t.as_str())))
+        .cloned()
+        .collect();
+
+    if options.shuffle_questions || options.count.is_some() {
+        rng.shuffle(&mut selected);
+    }
+    if let Some(count) = options.count {
+        selected.truncate(count);
+    }
+    if options.shuffle_options {
+        for question in &mut selected {
+            shuffle_options(question, rng);
+        }
+    }
+
+    selected
+}
+
+/// 打乱选择题和排序题的选项顺序，并同步更新标准答案
+pub fn shuffle_options(question: &mut Question, rng: &mut SeededRng) {
+    let items = match &mut question.kind {
+        QuestionKind::SingleChoice { options, .. } | QuestionKind::MultipleChoice { options, .. } => options,
+        QuestionKind::Ordering { items, .. } => items,
+        _ => return,
+    };
+
+    // order[新位置] = 原位置
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    rng.shuffle(&mut order);
+    let original = items.clone();
+    *items = order.iter().map(|&i| original[i].clone()).collect();
+
+    let remap = |key: &mut OptionKey| {
+        if let Some(new_index) = order.iter().position(|&i| i == key.0) {
+            key.0 = new_index;
+        }
+    };
+
+    match &mut question.kind {
+        QuestionKind::SingleChoice { answer, .. } => remap(answer),
+        QuestionKind::MultipleChoice { answers, .. } => answers.iter_mut().for_each(remap),
+        QuestionKind::Ordering { answer, .. } => answer.iter_mut().for_each(remap),
+        _ => {}
+    }
+}
+
+/// 单题作答记录
+#[derive(Debug, Clone)]
+pub struct QuestionRecord {
+    pub question: Question,
+    pub answer: Option<Answer>,
+    pub correct: bool,
+    pub timed_out: bool,
+}
+
+/// 知识点得分
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TopicScore {
+    pub topic: String,
+    pub correct: usize,
+    pub total: usize,
+}
+
+/// 测验结果
+#[derive(Debug, Clone, Default)]
+pub struct QuizReport {
+    pub records: Vec<QuestionRecord>,
+    /// 因输入结束而中止
+    pub aborted: bool,
+    /// 中止时尚未作答的题目数量，不计入成绩
+    pub unanswered: usize,
+    /// 因总时间用完而结束
+    pub out_of_time: bool,
+}
+
+impl QuizReport {
+    pub fn score(&self) -> usize {
+        self.records.iter().filter(|r| r.correct).count()
+    }
+
+    pub fn total(&self) -> usize {
+        self.records.len()
+    }
+
+    /// 按知识点统计得分，按首次出现的顺序排列
+    pub fn topic_breakdown(&self) -> Vec<TopicScore> {
+        let mut breakdown: Vec<TopicScore> = Vec::new();
+        for record in &self.records {
+            let index = match breakdown.iter().position(|t| t.topic == record.question.topic) {
+                Some(index) => index,
+                None => {
+                    breakdown.push(TopicScore { topic: record.question.topic.clone(), correct: 0, total: 0 });
+                    breakdown.len() - 1
+                }
+            };
+            breakdown[index].total += 1;
+            if record.correct {
+                breakdown[index].correct += 1;
+            }
+        }
+        breakdown
+    }
+
+    /// 答错（含超时、总时间用完未作答）的题目
+    pub fn wrong_answers(&self) -> Vec<&QuestionRecord> {
+        self.records.iter().filter(|r| !r.correct).collect()
+    }
+
+    /// 生成成绩报告文本
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+
+        if self.aborted {
+            output.push_str("\nQuiz aborted.\n");
+            if self.unanswered > 0 {
+                output.push_str(&format!("还有 {} 道题未作答，不计入成绩。\n", self.unanswered));
+            }
+        }
+        if self.out_of_time {
+            output.push_str("\n⏰ 总时间已用完，剩余题目按未作答处理。\n");
+        }
+
+        output.push_str(&format!("\nYou scored {} out of {}.\n", self.score(), self.total()));
+
+        output.push_str("\n📊 各知识点得分:\n");
+        for topic in self.topic_breakdown() {
+            output.push_str(&format!(
+                "  {}: {}/{} ({:.0}%)\n",
+                topic.topic,
+                topic.correct,
+                topic.total,
+                topic.correct as f32 / topic.total as f32 * 100.0
+            ));
+        }
+
+        let wrong = self.wrong_answers();
+        if !wrong.is_empty() {
+            output.push_str("\n📝 错题回顾:\n");
+            for record in wrong {
+                let q = &record.question;
+                output.push_str(&format!("\n  [{}] {}\n", q.topic, q.question));
+                let yours = match (&record.answer, record.timed_out) {
+                    (_, true) => "超时".to_string(),
+                    (Some(Answer::Code(_)), _) => "未通过隐藏测试".to_string(),
+                    (Some(answer), _) => answer.to_string(),
+                    (None, _) => "未作答".to_string(),
+                };
+                output.push_str(&format!("  你的答案: {}\n", yours));
+                output.push_str(&format!("  正确答案: {}\n", q.correct_answer_text()));
+                if let Some(explanation) = &q.explanation {
+                    output.push_str(&format!("  解析: {}\n", explanation));
+                }
+            }
+        }
+
+        output
+    }
+}
+
+/// 一次测验
+pub struct Quiz<'a> {
+    pub questions: &'a [Question],
+    pub options: &'a QuizOptions,
+    pub grader: &'a CodeGrader,
+    /// 非交互模式下从答案文件读取作答
+    pub answers: Option<&'a AnswerSheet>,
+}
+
+impl Quiz<'_> {
+    /// 逐题作答，返回成绩
+    ///
+    /// 输入结束时测验中止，已作答的题目照常计分，其余题目不计入成绩。
+    pub fn run(&self, input: &Input) -> Result<QuizReport, AssessmentError> {
+        let mut report = QuizReport::default();
+        let deadline = self.options.total_time_limit.map(|limit| Instant::now() + limit);
+
+        for (i, q) in self.questions.iter().enumerate() {
+            let remaining = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+            if remaining == Some(Duration::ZERO) {
+                report.out_of_time = true;
+                break;
+            }
+
+            let result = self.ask(q, i + 1, Some(self.questions.len()), input, remaining).and_then(|record| {
+                report.records.push(record);
+                if i + 1 < self.questions.len() { skip_late_input(input) } else { Ok(()) }
+            });
+            match result {
+                Ok(()) => {}
+                Err(AssessmentError::InputClosed) => {
+                    report.aborted = true;
+                    report.unanswered = self.questions.len() - report.records.len();
+                    return Ok(report);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        // 总时间用完时，未作答的题目计为错误
+        for q in &self.questions[report.records.len()..] {
+            report.records.push(QuestionRecord { question: q.clone(), answer: None, correct: false, timed_out: false });
+        }
+
+        Ok(report)
+    }
+
+    /// 显示并作答一道题，`remaining` 为剩余总时间；输入结束时返回 `InputClosed`
+    pub fn ask(
+        &self,
+        q: &Question,
+        number: usize,
+        total: Option<usize>,
+        input: &Input,
+        remaining: Option<Duration>,
+    ) -> Result<QuestionRecord, AssessmentError> {
+        let position = match total {
+            Some(total) => format!("{}/{}", number, total),
+            None => number.to_string(),
+        };
+        println!(
+            "\nQuestion {} [{} · {} · {}]: {}",
+            position,
+            q.kind_name(),
+            q.topic,
+            q.difficulty.name(),
+            q.question
+        );
+        for (j, option) in q.options().iter().enumerate() {
+            println!("  {}. {}", OptionKey(j), option);
+        }
+
+        // 本题可用时间：单题限时与剩余总时间中较短者
+        let limit = match (self.options.question_time_limit, remaining) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        if let Some(limit) = limit {
+            println!("⏱️ 限时 {} 秒", limit.as_secs());
+        }
+
+        let outcome = if let Some(sheet) = self.answers {
+            self.answer_from_sheet(q, sheet)?
+        } else if let QuestionKind::Code { .. } = q.kind {
+            self.answer_code_question(q, input, limit)?
+        } else {
+            self.answer_question(q, input, limit)?
+        };
+
+        let record = match outcome {
+            Outcome::Answered(answer, correct) => QuestionRecord {
+                question: q.clone(),
+                answer: Some(answer),
+                correct,
+                timed_out: false,
+            },
+            Outcome::TimedOut => {
+                println!("\n⏰ 时间到！");
+                QuestionRecord { question: q.clone(), answer: None, correct: false, timed_out: true }
+            }
+            Outcome::Unanswered => QuestionRecord { question: q.clone(), answer: None, correct: false, timed_out: false },
+        };
+
+        if record.correct {
+            println!("Correct!");
+        } else {
+            println!("Incorrect. The correct answer is: {}", q.correct_answer_text());
+        }
+
+        Ok(record)
+    }
+
+    /// 从答案文件作答；没有答案或答案格式无效时计为答错
+    fn answer_from_sheet(&self, q: &Question, sheet: &AnswerSheet) -> Result<Outcome, AssessmentError> {
+        let Some(text) = sheet.get(&q.id) else {
+            println!("（答案文件中没有这道题的答案）");
+            return Ok(Outcome::Unanswered);
+        };
+
+        if let QuestionKind::Code { tests, timeout_secs, .. } = &q.kind {
+            println!("🔨 正在编译并运行隐藏测试...");
+            let report = self.grader.grade(text, tests, Duration::from_secs(*timeout_secs))?;
+            print!("{}", report.summary());
+            return Ok(Outcome::Answered(Answer::Code(text.to_string()), report.passed()));
+        }
+
+        println!("{}: {}", q.input_hint(), text);
+        match q.parse_answer(text) {
+            Ok(answer) => {
+                let correct = q.is_correct(&answer);
+                Ok(Outcome::Answered(answer, correct))
+            }
+            Err(AssessmentError::InvalidAnswer(message)) => {
+                println!("⚠️  {}", message);
+                Ok(Outcome::Unanswered)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn answer_question(&self, q: &Question, input: &Input, limit: Option<Duration>) -> Result<Outcome, AssessmentError> {
+        let start = Instant::now();
+
+        loop {
+            print!("{}: ", q.input_hint());
+            io::stdout().flush()?;
+
+            let remaining = limit.map(|l| l.saturating_sub(start.elapsed()));
+            let line = match input.read_line(remaining) {
+                ReadResult::Line(line) => line,
+                ReadResult::TimedOut => return Ok(Outcome::TimedOut),
+                ReadResult::Eof => return Err(AssessmentError::InputClosed),
+            };
+
+            match q.parse_answer(&line) {
+                Ok(answer) => {
+                    let correct = q.is_correct(&answer);
+                    return Ok(Outcome::Answered(answer, correct));
+                }
+                Err(AssessmentError::InvalidAnswer(message)) => println!("{}", message),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// 在编辑器中作答代码题；编辑器运行期间无法打断，结束后再检查是否超时
+    fn answer_code_question(
+        &self,
+        q: &Question,
+        input: &Input,
+        limit: Option<Duration>,
+    ) -> Result<Outcome, AssessmentError> {
+        let QuestionKind::Code { starter, tests, timeout_secs, .. } = &q.kind else {
+            return Ok(Outcome::Answered(Answer::Code(String::new()), false));
+        };
+
+        let start = Instant::now();
+        println!("\n```rust\n{}```", starter);
+        let mut source = starter.clone();
+
+        loop {
+            println!("{}（编辑器: {}）", q.input_hint(), editor::editor_command());
+            source = editor::edit(&source, &format!("{}.rs", q.id))?;
+
+            if limit.is_some_and(|l| start.elapsed() > l) {
+                return Ok(Outcome::TimedOut);
+            }
+
+            println!("🔨 正在编译并运行隐藏测试...");
+            let report = self.grader.grade(&source, tests, Duration::from_secs(*timeout_secs))?;
+            print!("{}", report.summary());
+
+            if report.passed() {
+                return Ok(Outcome::Answered(Answer::Code(source), true));
+            }
+
+            print!("是否重新编辑? (y/N): ");
+            io::stdout().flush()?;
+            let remaining = limit.map(|l| l.saturating_sub(start.elapsed()));
+            match input.read_line(remaining) {
+                ReadResult::Line(line) if matches!(line.trim().to_lowercase().as_str(), "y" | "yes") => continue,
+                ReadResult::Line(_) => return Ok(Outcome::Answered(Answer::Code(source), false)),
+                ReadResult::TimedOut => return Ok(Outcome::TimedOut),
+                ReadResult::Eof => return Err(AssessmentError::InputClosed),
+            }
+        }
+    }
+}
+
+/// 超时后丢弃学习者迟到的输入；输入结束时返回 `InputClosed`
+pub fn skip_late_input(input: &Input) -> Result<(), AssessmentError> {
+    if !input.has_pending() {
+        return Ok(());
+    }
+    print!("按 Enter 继续...");
+    io::stdout().flush()?;
+    match input.read_line(None) {
+        ReadResult::Eof => Err(AssessmentError::InputClosed),
+        _ => Ok(()),
+    }
+}
+
+enum Outcome {
+    Answered(Answer, bool),
+    TimedOut,
+    Unanswered,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::question::Difficulty;
+    use std::io::Cursor;
+
+    fn choice_question(id: &str, topic: &str, stage: LearningStage) -> Question {
+        Question {
+            id: id.to_string(),
+            topic: topic.to_string(),
+            stage,
+            difficulty: Difficulty::Easy,
+            question: format!("{} 的题目", id),
+            explanation: Some(format!("{} 的解析", id)),
+            kind: QuestionKind::SingleChoice {
+                options: vec!["甲".to_string(), "乙".to_string(), "丙".to_string(), "丁".to_string()],
+                answer: OptionKey(1),
+            },
+        }
+    }
+
+    fn bank() -> Vec<Question> {
+        vec![
+            choice_question("q1", "所有权", LearningStage::Stage2Ownership),
+            choice_question("q2", "借用", LearningStage::Stage2Ownership),
+            choice_question("q3", "所有权", LearningStage::Stage2Ownership),
+            choice_question("q4", "错误处理", LearningStage::Stage3AdvancedConcepts),
+            choice_question("q5", "变量", LearningStage::Stage1Basics),
+        ]
+    }
+
+    fn ids(questions: &[Question]) -> Vec<&str> {
+        questions.iter().map(|q| q.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_filter_by_stage_and_topic() {
+        let mut rng = SeededRng::new(1);
+        let options = QuizOptions { stage: Some(LearningStage::Stage2Ownership), ..Default::default() };
+        assert_eq!(ids(&select_questions(&bank(), &options, &mut rng)), vec!["q1", "q2", "q3"]);
+
+        let options = QuizOptions { topic: Some("所有权".to_string()), ..Default::default() };
+        assert_eq!(ids(&select_questions(&bank(), &options, &mut rng)), vec!["q1", "q3"]);
+    }
+
+    #[test]
+    fn test_random_draw_is_reproducible() {
+        let options = QuizOptions { count: Some(3), ..Default::default() };
+        let first = select_questions(&bank(), &options, &mut SeededRng::new(42));
+        let second = select_questions(&bank(), &options, &mut SeededRng::new(42));
+
+        assert_eq!(first.len(), 3);
+        assert_eq!(ids(&first), ids(&second));
+
+        let mut unique = ids(&first);
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), 3);
+    }
+
+    #[test]
+    fn test_shuffle_options_keeps_answer() {
+        let mut rng = SeededRng::new(7);
+        for _ in 0..20 {
+            let mut q = choice_question("q", "t", LearningStage::Stage1Basics);
+            shuffle_options(&mut q, &mut rng);
+            let QuestionKind::SingleChoice { options, answer } = &q.kind else { unreachable!() };
+            assert_eq!(options[answer.0], "乙");
+        }
+
+        let mut q = choice_question("q", "t", LearningStage::Stage1Basics);
+        q.kind = QuestionKind::Ordering {
+            items: vec!["一".to_string(), "二".to_string(), "三".to_string()],
+            answer: vec![OptionKey(2), OptionKey(0), OptionKey(1)],
+        };
+        shuffle_options(&mut q, &mut rng);
+        assert_eq!(q.correct_answer_text(), "三 → 一 → 二");
+    }
+
+    #[test]
+    fn test_run_quiz_and_report() {
+        let questions = bank();
+        let options = QuizOptions::default();
+        let grader = CodeGrader::default();
+        let quiz = Quiz { questions: &questions, options: &options, grader: &grader, answers: None };
+
+        // 第二题先输入无效答案再重新作答，第四题之后输入结束
+        let input = Input::from_reader(Cursor::new("B\nZ\nA\nb\n2\n"));
+        let report = quiz.run(&input).unwrap();
+
+        assert_eq!(report.total(), 4);
+        assert_eq!(report.score(), 3);
+        assert!(report.aborted);
+        assert_eq!(report.unanswered, 1);
+        assert_eq!(report.topic_breakdown()[0], TopicScore { topic: "所有权".to_string(), correct: 2, total: 2 });
+        assert_eq!(report.topic_breakdown()[1], TopicScore { topic: "借用".to_string(), correct: 0, total: 1 });
+
+        let text = report.render();
+        assert!(text.contains("Quiz aborted."));
+        assert!(text.contains("还有 1 道题未作答"));
+        assert!(text.contains("You scored 3 out of 4."));
+        assert!(text.contains("q2 的解析"));
+        assert!(!text.contains("q5 的题目"));
+    }
+
+    #[test]
+    fn test_run_quiz_from_answer_sheet() {
+        let questions = bank();
+        let options = QuizOptions::default();
+        let grader = CodeGrader::default();
+        let sheet = AnswerSheet::parse("q1: B\nq2: 甲\nq3: c\nq4: 2\n", |_| Err(String::new())).unwrap();
+        let quiz = Quiz { questions: &questions, options: &options, grader: &grader, answers: Some(&sheet) };
+
+        // 答案文件模式不读取输入
+        let report = quiz.run(&Input::from_reader(Cursor::new(""))).unwrap();
+
+        assert!(!report.aborted);
+        assert_eq!(report.total(), 5);
+        assert_eq!(report.score(), 2);
+        let unanswered: Vec<&str> = report.records.iter().filter(|r| r.answer.is_none()).map(|r| r.question.id.as_str()).collect();
+        assert_eq!(unanswered, vec!["q2", "q5"]);
+    }
+
+    #[test]
+    fn test_question_time_limit() {
+        let questions = vec![choice_question("q1", "所有权", LearningStage::Stage2Ownership)];
+        let options = QuizOptions { question_time_limit: Some(Duration::from_millis(50)), ..Default::default() };
+        let grader = CodeGrader::default();
+        let quiz = Quiz { questions: &questions, options: &options, grader: &grader, answers: None };
+
+        let (reader, _writer) = std::io::pipe().unwrap();
+        let report = quiz.run(&Input::from_reader(io::BufReader::new(reader))).unwrap();
+
+        assert_eq!(report.score(), 0);
+        assert!(report.records[0].timed_out);
+        assert!(report.render().contains("超时"));
+    }
+}
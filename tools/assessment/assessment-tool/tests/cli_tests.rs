@@ -0,0 +1,130 @@
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+const BANK: &str = r#"[
+  { "id": "mut-keyword", "topic": "变量", "stage": "Stage1Basics", "difficulty": "easy",
+    "question": "哪个关键字声明可变变量？", "type": "single_choice", "options": ["let", "mut", "const"], "answer": "B" },
+  { "id": "shadowing", "topic": "变量", "stage": "Stage1Basics", "difficulty": "easy",
+    "question": "同名变量可以被遮蔽。", "type": "true_false", "answer": true },
+  { "id": "move-semantics", "topic": "所有权", "stage": "Stage2Ownership", "difficulty": "medium",
+    "question": "String 赋值给另一个变量后原变量还能使用吗？", "type": "true_false", "answer": false }
+]
+"#;
+
+/// 每个测试使用独立的临时目录
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("assessment-cli-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn run(dir: &PathBuf, args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_assessment-tool"))
+        .current_dir(dir)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn test_grade_quiz_from_answers_file() {
+    let dir = temp_dir("answers");
+    fs::write(dir.join("bank.json"), BANK).unwrap();
+    fs::write(dir.join("answers.txt"), "# 第一阶段\nmut-keyword: B\nshadowing: F\nmove-semantics: 错\n").unwrap();
+
+    let output = run(&dir, &["--bank", "bank.json", "--answers", "answers.txt", "--history", "history.json"], "");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(stdout.contains("You scored 2 out of 3."), "{}", stdout);
+    let history: serde_json::Value = serde_json::from_str(&fs::read_to_string(dir.join("history.json")).unwrap()).unwrap();
+    assert_eq!(history["attempts"][0]["score"], 2);
+    assert_eq!(history["attempts"][0]["aborted"], false);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_eof_aborts_without_scoring_remaining_questions() {
+    let dir = temp_dir("eof");
+    fs::write(dir.join("bank.json"), BANK).unwrap();
+
+    // 第一题先输入无效答案，重新作答后输入结束
+    let output = run(&dir, &["--bank", "bank.json", "--history", "history.json"], "Z\nB\n");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success());
+    assert!(stdout.contains("无效的选项: Z"), "{}", stdout);
+    assert!(stdout.contains("Quiz aborted."));
+    assert!(stdout.contains("还有 2 道题未作答"));
+    assert!(stdout.contains("You scored 1 out of 1."));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_missing_and_invalid_bank() {
+    let dir = temp_dir("bank");
+
+    let output = run(&dir, &["--bank", "missing.json"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("题库文件不存在: missing.json"));
+
+    fs::write(dir.join("broken.json"), "[{").unwrap();
+    let output = run(&dir, &["--bank", "broken.json"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("题库 broken.json 格式错误"));
+
+    fs::write(dir.join("bank.json"), BANK).unwrap();
+    fs::write(dir.join("answers.txt"), "mut-keyword B\n").unwrap();
+    let output = run(&dir, &["--bank", "bank.json", "--answers", "answers.txt"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("答案文件 answers.txt:1"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_unreadable_history_is_kept() {
+    let dir = temp_dir("history");
+    fs::write(dir.join("bank.json"), BANK).unwrap();
+    fs::write(dir.join("answers.txt"), "mut-keyword: B\nshadowing: T\nmove-semantics: F\n").unwrap();
+    fs::write(dir.join("assessment-history.json"), "{ not json").unwrap();
+
+    let output = run(&dir, &["--bank", "bank.json", "--answers", "answers.txt"], "");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("本次测验记录未保存"));
+    assert_eq!(fs::read_to_string(dir.join("assessment-history.json")).unwrap(), "{ not json");
+
+    let output = run(&dir, &["history"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("无法读取测验历史 assessment-history.json"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_write_commands_need_a_local_bank() {
+    let dir = temp_dir("readonly");
+    fs::write(dir.join("quiz.md"), "").unwrap();
+
+    // 当前目录没有题库时不会改写内置题库
+    let output = run(&dir, &["import", "quiz.md"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("题库文件不存在: questions.json"));
+
+    // 读取时使用编译在工具中的内置题库
+    let output = run(&dir, &["lint"], "");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("<内置题库>"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -1,6 +1,7 @@
 //! 学习进度可视化仪表板模块
 
 use crate::{ProgressTracker, LearningUnitStatus, LearningStage};
+use crate::goals::GoalStatus;
 use serde::{Deserialize, Serialize};
 
 /// 仪表板配置
@@ -8,6 +9,7 @@ use serde::{Deserialize, Serialize};
 pub struct DashboardConfig {
     pub show_progress_bars: bool,
     pub show_stage_breakdown: bool,
+    pub show_goals: bool,
     pub show_achievements: bool,
     pub show_recommendations: bool,
     pub show_suggestions: bool,
@@ -37,6 +39,7 @@ impl Default for DashboardConfig {
         Self {
             show_progress_bars: true,
             show_stage_breakdown: true,
+            show_goals: true,
             show_achievements: true,
             show_recommendations: true,
             show_suggestions: true,
@@ -82,6 +85,11 @@ impl DashboardRenderer {
             output.push_str(&self.render_stage_breakdown(tracker));
         }
         
+        // 学习目标与预测
+        if self.config.show_goals {
+            output.push_str(&self.render_goals(tracker));
+        }
+        
         // 成就展示
         if self.config.show_achievements {
             output.push_str(&self.render_achievements(tracker));
@@ -187,6 +195,50 @@ impl DashboardRenderer {
         output
     }
 
+    /// 渲染学习目标与完成预测
+    fn render_goals(&self, tracker: &ProgressTracker) -> String {
+        let mut output = String::new();
+        
+        output.push_str("\n🗓️ 学习目标与预测\n");
+        output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+        
+        match tracker.learning_velocity() {
+            Some(velocity) => output.push_str(&format!(
+                "⚡ 学习速度: 每个活跃日 {:.0} 分钟（{} 个活跃日，平均每天 {:.0} 分钟）\n",
+                velocity.minutes_per_active_day,
+                velocity.active_days,
+                velocity.minutes_per_calendar_day
+            )),
+            None => output.push_str("⚡ 学习速度: 暂无完成记录，按阶段预计周数估算\n"),
+        }
+        
+        let forecasts = tracker.forecast_goals();
+        if forecasts.is_empty() {
+            output.push_str("💡 尚未设置学习目标，可运行: progress-tracker goal <阶段> <YYYY-MM-DD>\n");
+        }
+        
+        for forecast in forecasts {
+            output.push_str(&format!(
+                "  {} {} - 截止 {} [{}]\n",
+                forecast.status.icon(),
+                forecast.goal.stage.name(),
+                forecast.goal.deadline,
+                forecast.status.name()
+            ));
+            
+            if forecast.status != GoalStatus::Achieved {
+                output.push_str(&format!(
+                    "     预计完成: {}（剩余 {} 分钟，依据: {}）\n",
+                    forecast.forecast_date,
+                    forecast.remaining_minutes,
+                    forecast.basis.name()
+                ));
+            }
+        }
+        
+        output
+    }
+
     /// 渲染成就展示
     fn render_achievements(&self, tracker: &ProgressTracker) -> String {
         let unlocked_achievements: Vec<_> = tracker.achievements.iter()
@@ -304,6 +356,7 @@ pub fn generate_html_dashboard(tracker: &ProgressTracker) -> String {
     let stats = tracker.get_progress_stats();
     let recommendation = tracker.get_learning_path_recommendation();
     let suggestions = tracker.get_personalized_suggestions();
+    let forecasts = tracker.forecast_goals();
     
     let unlocked_achievements: Vec<_> = tracker.achievements.iter()
         .filter(|a| a.unlocked_at.is_some())
@@ -314,6 +367,7 @@ pub fn generate_html_dashboard(tracker: &ProgressTracker) -> String {
     let estimated_capacity = 15_000 + 
         unlocked_achievements.len() * 500 + 
         suggestions.len() * 200 + 
+        forecasts.len() * 300 +
         recommendation.next_units.len() * 300;
     
     let mut html = String::with_capacity(estimated_capacity);
@@ -499,6 +553,35 @@ pub fn generate_html_dashboard(tracker: &ProgressTracker) -> String {
             margin: 20px 0;
         }
         
+        .goal-list {
+            list-style: none;
+            margin: 20px 0;
+        }
+        
+        .goal-item {
+            background: white;
+            margin: 10px 0;
+            padding: 15px;
+            border-radius: 8px;
+            box-shadow: 0 3px 10px rgba(0,0,0,0.1);
+        }
+        
+        .goal-achieved {
+            border-left: 4px solid #6c757d;
+        }
+        
+        .goal-ahead {
+            border-left: 4px solid #28a745;
+        }
+        
+        .goal-on-track {
+            border-left: 4px solid #17a2b8;
+        }
+        
+        .goal-at-risk {
+            border-left: 4px solid #dc3545;
+        }
+        
         .suggestion-item {
             background: white;
             margin: 10px 0;
@@ -610,6 +693,51 @@ pub fn generate_html_dashboard(tracker: &ProgressTracker) -> String {
     html.push_str(&achievement_html);
     html.push_str("                    </div>\n                </div>\n");
     
+    // 添加学习目标与预测
+    html.push_str("                <div class=\"section\">\n                    <h2>🗓️ 学习目标与预测</h2>\n");
+    match tracker.learning_velocity() {
+        Some(velocity) => html.push_str(&format!(
+            "                    <p><strong>学习速度:</strong> 每个活跃日 {:.0} 分钟（{} 个活跃日，平均每天 {:.0} 分钟）</p>\n",
+            velocity.minutes_per_active_day,
+            velocity.active_days,
+            velocity.minutes_per_calendar_day
+        )),
+        None => html.push_str("                    <p><strong>学习速度:</strong> 暂无完成记录，按阶段预计周数估算</p>\n"),
+    }
+    html.push_str("                    <ul class=\"goal-list\">\n");
+    
+    for forecast in &forecasts {
+        let status_class = match forecast.status {
+            GoalStatus::Achieved => "goal-achieved",
+            GoalStatus::Ahead => "goal-ahead",
+            GoalStatus::OnTrack => "goal-on-track",
+            GoalStatus::AtRisk => "goal-at-risk",
+        };
+        html.push_str(&format!(
+            "                        <li class=\"goal-item {}\">{} <strong>{}</strong> - 截止 {} [{}]",
+            status_class,
+            forecast.status.icon(),
+            forecast.goal.stage.name(),
+            forecast.goal.deadline,
+            forecast.status.name()
+        ));
+        if forecast.status != GoalStatus::Achieved {
+            html.push_str(&format!(
+                "<br>预计完成: {}（剩余 {} 分钟，依据: {}）",
+                forecast.forecast_date,
+                forecast.remaining_minutes,
+                forecast.basis.name()
+            ));
+        }
+        html.push_str("</li>\n");
+    }
+    
+    if forecasts.is_empty() {
+        html.push_str("                        <p style='text-align: center; color: #666;'>暂未设置学习目标</p>\n");
+    }
+    
+    html.push_str("                    </ul>\n                </div>\n");
+    
     // 添加学习推荐 - 使用预分配的字符串构建
     let recommendation_capacity = 500 + recommendation.next_units.len() * 150; // 基础500 + 每个推荐150字符
     let mut recommendation_html = String::with_capacity(recommendation_capacity);
@@ -705,5 +833,22 @@ mod tests {
         assert!(html.contains("progress-fill"));
         assert!(html.contains("achievement-grid"));
         assert!(html.contains("recommendation-list"));
+        assert!(html.contains("goal-list"));
+    }
+    
+    #[test]
+    fn test_goals_in_dashboards() {
+        let mut tracker = ProgressTracker::new("test-learner".to_string(), "测试学习者".to_string());
+        let deadline = chrono::Utc::now().date_naive() - chrono::Duration::days(1);
+        tracker.set_goal(LearningStage::Stage1Basics, deadline);
+        
+        let dashboard = DashboardRenderer::new(DashboardConfig::default()).render(&tracker);
+        assert!(dashboard.contains("学习目标与预测"));
+        assert!(dashboard.contains(&format!("截止 {}", deadline)));
+        assert!(dashboard.contains("存在风险"));
+        
+        let html = generate_html_dashboard(&tracker);
+        assert!(html.contains("goal-at-risk"));
+        assert!(html.contains("目标提醒"));
     }
 }
\ No newline at end of file
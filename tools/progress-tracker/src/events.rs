@@ -0,0 +1,815 @@
+//! 学习事件通知模块
+//!
+//! 比较更新前后的进度快照，生成单元状态变化、阶段完成和成就解锁事件，
+//! 并分发给订阅者（Shell 命令、JSONL 文件、Webhook 等）。
+
+use crate::{AchievementRarity, LearningStage, LearningUnitStatus, ProgressTracker};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// 默认的钩子配置文件名
+pub const DEFAULT_HOOKS_FILE: &str = "progress-hooks.json";
+
+/// 学习事件
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    /// 学习单元状态变化
+    UnitStatusChanged {
+        unit_id: String,
+        unit_name: String,
+        stage: LearningStage,
+        from: LearningUnitStatus,
+        to: LearningUnitStatus,
+        score: Option<f32>,
+    },
+    /// 阶段全部完成
+    StageCompleted {
+        stage: LearningStage,
+        stage_name: String,
+    },
+    /// 成就解锁
+    AchievementUnlocked {
+        achievement_id: String,
+        name: String,
+        description: String,
+        icon: String,
+        rarity: AchievementRarity,
+    },
+}
+
+/// 事件类型（用于订阅过滤）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    UnitStatusChanged,
+    StageCompleted,
+    AchievementUnlocked,
+}
+
+impl EventKind {
+    /// 获取事件类型名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventKind::UnitStatusChanged => "unit_status_changed",
+            EventKind::StageCompleted => "stage_completed",
+            EventKind::AchievementUnlocked => "achievement_unlocked",
+        }
+    }
+}
+
+impl ProgressEvent {
+    /// 获取事件类型
+    pub fn kind(&self) -> EventKind {
+        match self {
+            ProgressEvent::UnitStatusChanged { .. } => EventKind::UnitStatusChanged,
+            ProgressEvent::StageCompleted { .. } => EventKind::StageCompleted,
+            ProgressEvent::AchievementUnlocked { .. } => EventKind::AchievementUnlocked,
+        }
+    }
+
+    /// 生成面向人的通知文本（例如发送到团队聊天机器人）
+    pub fn message(&self, learner_name: &str) -> String {
+        match self {
+            ProgressEvent::UnitStatusChanged { unit_name, to, score, .. } => match score {
+                Some(s) if to.is_completed() => {
+                    format!("📝 {} 的学习单元「{}」状态变为{}（分数 {:.1}）", learner_name, unit_name, to.name(), s)
+                }
+                _ => format!("📝 {} 的学习单元「{}」状态变为{}", learner_name, unit_name, to.name()),
+            },
+            ProgressEvent::StageCompleted { stage_name, .. } => {
+                format!("🎓 {} 完成了 {}！", learner_name, stage_name)
+            }
+            ProgressEvent::AchievementUnlocked { name, description, icon, rarity, .. } => {
+                format!("🎉 {} 解锁了{}成就 {} {} - {}", learner_name, rarity.name(), icon, name, description)
+            }
+        }
+    }
+}
+
+/// 发送给订阅者的事件信封
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventEnvelope {
+    pub learner_id: String,
+    pub learner_name: String,
+    pub timestamp: DateTime<Utc>,
+    pub message: String,
+    pub event: ProgressEvent,
+}
+
+impl EventEnvelope {
+    /// 为指定学习者创建事件信封
+    pub fn new(tracker: &ProgressTracker, event: ProgressEvent) -> Self {
+        Self {
+            learner_id: tracker.learner_id.clone(),
+            learner_name: tracker.learner_name.clone(),
+            timestamp: Utc::now(),
+            message: event.message(&tracker.learner_name),
+            event,
+        }
+    }
+}
+
+/// 比较更新前后的进度，生成事件列表
+///
+/// 事件顺序：单元状态变化 → 阶段完成 → 成就解锁。
+pub fn collect_events(before: &ProgressTracker, after: &ProgressTracker) -> Vec<ProgressEvent> {
+    let mut events = Vec::new();
+
+    // 单元状态变化（新增的单元视为从未开始状态转换）
+    for unit in &after.learning_units {
+        let previous = before.get_unit(&unit.id)
+            .map(|u| u.status.clone())
+            .unwrap_or(LearningUnitStatus::NotStarted);
+
+        if previous != unit.status {
+            events.push(ProgressEvent::UnitStatusChanged {
+                unit_id: unit.id.clone(),
+                unit_name: unit.name.clone(),
+                stage: unit.stage.clone(),
+                from: previous,
+                to: unit.status.clone(),
+                score: unit.score,
+            });
+        }
+    }
+
+    // 阶段完成
+    let before_stats = before.get_progress_stats();
+    let after_stats = after.get_progress_stats();
+    for stage in LearningStage::all_stages() {
+        let key = format!("{:?}", stage);
+        let was_complete = before_stats.stage_progress.get(&key).copied().unwrap_or(0.0) >= 100.0;
+        let is_complete = after_stats.stage_progress.get(&key).copied().unwrap_or(0.0) >= 100.0;
+
+        if is_complete && !was_complete {
+            events.push(ProgressEvent::StageCompleted {
+                stage_name: stage.name().to_string(),
+                stage,
+            });
+        }
+    }
+
+    // 成就解锁
+    for achievement in &after.achievements {
+        if achievement.unlocked_at.is_none() {
+            continue;
+        }
+
+        let was_unlocked = before.achievements.iter()
+            .find(|a| a.id == achievement.id)
+            .map(|a| a.unlocked_at.is_some())
+            .unwrap_or(false);
+
+        if !was_unlocked {
+            events.push(ProgressEvent::AchievementUnlocked {
+                achievement_id: achievement.id.clone(),
+                name: achievement.name.clone(),
+                description: achievement.description.clone(),
+                icon: achievement.icon.clone(),
+                rarity: achievement.rarity.clone(),
+            });
+        }
+    }
+
+    events
+}
+
+/// 事件订阅者
+pub trait EventSubscriber {
+    /// 订阅者名称（用于错误报告）
+    fn name(&self) -> String;
+
+    /// 是否关心该类型的事件
+    fn accepts(&self, _kind: EventKind) -> bool {
+        true
+    }
+
+    /// 处理事件
+    fn handle(&self, envelope: &EventEnvelope) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+/// 事件分发失败记录
+#[derive(Debug)]
+pub struct DispatchError {
+    pub subscriber: String,
+    pub event: EventKind,
+    pub error: String,
+}
+
+/// 事件总线
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: Vec<Box<dyn EventSubscriber>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 根据钩子配置创建事件总线
+    pub fn from_config(config: &HooksConfig) -> Self {
+        let mut bus = Self::new();
+        for hook in &config.hooks {
+            bus.subscribe(hook.build());
+        }
+        bus
+    }
+
+    /// 添加订阅者
+    pub fn subscribe(&mut self, subscriber: Box<dyn EventSubscriber>) {
+        self.subscribers.push(subscriber);
+    }
+
+    /// 订阅者数量
+    pub fn len(&self) -> usize {
+        self.subscribers.len()
+    }
+
+    /// 是否没有订阅者
+    pub fn is_empty(&self) -> bool {
+        self.subscribers.is_empty()
+    }
+
+    /// 将事件分发给所有订阅者
+    ///
+    /// 单个订阅者失败不会影响其他订阅者，所有失败会被收集后返回。
+    pub fn publish(&self, tracker: &ProgressTracker, events: &[ProgressEvent]) -> Vec<DispatchError> {
+        let mut errors = Vec::new();
+
+        for event in events {
+            let envelope = EventEnvelope::new(tracker, event.clone());
+            for subscriber in &self.subscribers {
+                if !subscriber.accepts(event.kind()) {
+                    continue;
+                }
+
+                if let Err(e) = subscriber.handle(&envelope) {
+                    errors.push(DispatchError {
+                        subscriber: subscriber.name(),
+                        event: event.kind(),
+                        error: e.to_string(),
+                    });
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+/// 钩子配置文件
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HooksConfig {
+    #[serde(default)]
+    pub hooks: Vec<HookConfig>,
+}
+
+impl HooksConfig {
+    /// 钩子配置文件路径：优先使用环境变量 PROGRESS_HOOKS，否则为默认文件名
+    pub fn default_path() -> PathBuf {
+        std::env::var_os("PROGRESS_HOOKS")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_HOOKS_FILE))
+    }
+
+    /// 从文件加载钩子配置
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path)?;
+        let config: Self = serde_json::from_str(&content)?;
+        Ok(config)
+    }
+}
+
+/// 一次钩子通知的结果
+#[derive(Debug, Default)]
+pub struct Notification {
+    /// 本次更新产生的事件数
+    pub events: usize,
+    /// 分发失败记录
+    pub errors: Vec<DispatchError>,
+}
+
+/// 按钩子配置文件分发 `before` 到 `after` 之间产生的事件
+///
+/// 配置文件不存在或没有新事件时什么也不做；配置无法加载时返回错误。
+/// progress-tracker 和测验工具保存进度后都通过它通知订阅者。
+pub fn notify_hooks<P: AsRef<Path>>(
+    hooks_file: P,
+    before: &ProgressTracker,
+    after: &ProgressTracker,
+) -> Result<Notification, Box<dyn std::error::Error>> {
+    let hooks_file = hooks_file.as_ref();
+    if !hooks_file.exists() {
+        return Ok(Notification::default());
+    }
+
+    let config = HooksConfig::from_file(hooks_file)?;
+    let events = collect_events(before, after);
+    if events.is_empty() {
+        return Ok(Notification::default());
+    }
+
+    let errors = EventBus::from_config(&config).publish(after, &events);
+    Ok(Notification { events: events.len(), errors })
+}
+
+/// 单个钩子配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookConfig {
+    #[serde(flatten)]
+    pub sink: SinkConfig,
+    /// 订阅的事件类型，为空表示订阅全部
+    #[serde(default)]
+    pub events: Vec<EventKind>,
+}
+
+/// 内置事件输出配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkConfig {
+    Command { command: String },
+    Jsonl { path: PathBuf },
+    /// HTTP POST，限制见 [`WebhookSink`]
+    Webhook {
+        url: String,
+        #[serde(default = "default_webhook_timeout_secs")]
+        timeout_secs: u64,
+    },
+}
+
+fn default_webhook_timeout_secs() -> u64 {
+    5
+}
+
+impl HookConfig {
+    /// 构建对应的订阅者
+    pub fn build(&self) -> Box<dyn EventSubscriber> {
+        let sink: Box<dyn EventSubscriber> = match &self.sink {
+            SinkConfig::Command { command } => Box::new(CommandSink::new(command.clone())),
+            SinkConfig::Jsonl { path } => Box::new(JsonlSink::new(path.clone())),
+            SinkConfig::Webhook { url, timeout_secs } => {
+                Box::new(WebhookSink::new(url.clone()).with_timeout(Duration::from_secs(*timeout_secs)))
+            }
+        };
+
+        if self.events.is_empty() {
+            sink
+        } else {
+            Box::new(Filtered { kinds: self.events.clone(), inner: sink })
+        }
+    }
+}
+
+/// 按事件类型过滤的订阅者
+struct Filtered {
+    kinds: Vec<EventKind>,
+    inner: Box<dyn EventSubscriber>,
+}
+
+impl EventSubscriber for Filtered {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn accepts(&self, kind: EventKind) -> bool {
+        self.kinds.contains(&kind) && self.inner.accepts(kind)
+    }
+
+    fn handle(&self, envelope: &EventEnvelope) -> Result<(), Box<dyn std::error::Error>> {
+        self.inner.handle(envelope)
+    }
+}
+
+/// 执行 Shell 命令的订阅者
+///
+/// 事件 JSON 通过标准输入传入，同时设置环境变量
+/// `PROGRESS_EVENT`、`PROGRESS_LEARNER_ID`、`PROGRESS_LEARNER_NAME` 和 `PROGRESS_MESSAGE`。
+pub struct CommandSink {
+    command: String,
+}
+
+impl CommandSink {
+    pub fn new(command: String) -> Self {
+        Self { command }
+    }
+}
+
+impl EventSubscriber for CommandSink {
+    fn name(&self) -> String {
+        format!("command: {}", self.command)
+    }
+
+    fn handle(&self, envelope: &EventEnvelope) -> Result<(), Box<dyn std::error::Error>> {
+        let payload = serde_json::to_string(envelope)?;
+
+        let mut command = if cfg!(windows) {
+            let mut c = Command::new("cmd");
+            c.arg("/C");
+            c
+        } else {
+            let mut c = Command::new("sh");
+            c.arg("-c");
+            c
+        };
+
+        let mut child = command
+            .arg(&self.command)
+            .env("PROGRESS_EVENT", envelope.event.kind().name())
+            .env("PROGRESS_LEARNER_ID", &envelope.learner_id)
+            .env("PROGRESS_LEARNER_NAME", &envelope.learner_name)
+            .env("PROGRESS_MESSAGE", &envelope.message)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            // 命令可能不读取标准输入，忽略管道关闭错误
+            let _ = stdin.write_all(payload.as_bytes());
+        }
+
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(format!("命令退出状态异常: {}", status).into());
+        }
+
+        Ok(())
+    }
+}
+
+/// 追加写入 JSONL 文件的订阅者
+pub struct JsonlSink {
+    path: PathBuf,
+}
+
+impl JsonlSink {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl EventSubscriber for JsonlSink {
+    fn name(&self) -> String {
+        format!("jsonl: {}", self.path.display())
+    }
+
+    fn handle(&self, envelope: &EventEnvelope) -> Result<(), Box<dyn std::error::Error>> {
+        let mut line = serde_json::to_string(envelope)?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(line.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+/// 通过 HTTP POST 发送事件的订阅者
+///
+/// 内置的最小 HTTP/1.1 客户端，有以下限制：
+/// - 仅支持 `http://` 地址，HTTPS 请通过本地代理或命令钩子转发；
+/// - 不跟随重定向，只有 2xx 状态码视为成功；
+/// - IPv6 地址需写成 `http://[::1]:8080/path` 的形式；
+/// - 只读取响应状态行，忽略响应头和响应体。
+pub struct WebhookSink {
+    url: String,
+    timeout: Duration,
+}
+
+impl WebhookSink {
+    pub fn new(url: String) -> Self {
+        Self {
+            url,
+            timeout: Duration::from_secs(default_webhook_timeout_secs()),
+        }
+    }
+
+    /// 设置连接和读写超时
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 解析 URL，返回 (host, port, path)，IPv6 地址不带方括号
+    fn parse_url(&self) -> Result<(String, u16, String), Box<dyn std::error::Error>> {
+        let rest = self.url.strip_prefix("http://")
+            .ok_or_else(|| format!("不支持的 Webhook 地址（仅支持 http://）: {}", self.url))?;
+
+        let (authority, path) = match rest.find('/') {
+            Some(index) => (&rest[..index], &rest[index..]),
+            None => (rest, "/"),
+        };
+
+        let (host, port) = if let Some(bracketed) = authority.strip_prefix('[') {
+            let (host, after) = bracketed.split_once(']')
+                .ok_or_else(|| format!("Webhook 地址中的 IPv6 地址缺少 ']': {}", self.url))?;
+            match after {
+                "" => (host, 80),
+                _ => match after.strip_prefix(':') {
+                    Some(port) => (host, port.parse::<u16>()?),
+                    None => return Err(format!("Webhook 地址格式无效: {}", self.url).into()),
+                },
+            }
+        } else {
+            match authority.rsplit_once(':') {
+                Some((host, port)) => (host, port.parse::<u16>()?),
+                None => (authority, 80),
+            }
+        };
+
+        if host.is_empty() {
+            return Err(format!("Webhook 地址缺少主机名: {}", self.url).into());
+        }
+
+        Ok((host.to_string(), port, path.to_string()))
+    }
+}
+
+impl EventSubscriber for WebhookSink {
+    fn name(&self) -> String {
+        format!("webhook: {}", self.url)
+    }
+
+    fn handle(&self, envelope: &EventEnvelope) -> Result<(), Box<dyn std::error::Error>> {
+        let (host, port, path) = self.parse_url()?;
+        let body = serde_json::to_string(envelope)?;
+
+        let address = (host.as_str(), port).to_socket_addrs()?
+            .next()
+            .ok_or_else(|| format!("无法解析主机: {}", host))?;
+
+        let mut stream = TcpStream::connect_timeout(&address, self.timeout)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+
+        // IPv6 地址在 Host 头中同样需要方括号
+        let host_header = if host.contains(':') { format!("[{}]", host) } else { host.clone() };
+        let request = format!(
+            "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nUser-Agent: progress-tracker\r\nConnection: close\r\n\r\n{}",
+            path,
+            host_header,
+            port,
+            body.len(),
+            body
+        );
+        stream.write_all(request.as_bytes())?;
+
+        // 只需要状态行，响应体可能不是 UTF-8，也可能迟迟不结束
+        let mut status_line = Vec::new();
+        BufReader::new(stream).read_until(b'\n', &mut status_line)?;
+
+        let status = String::from_utf8_lossy(&status_line)
+            .split_whitespace()
+            .nth(1)
+            .and_then(|code| code.parse::<u16>().ok())
+            .ok_or("Webhook 响应格式无效")?;
+
+        if !(200..300).contains(&status) {
+            return Err(format!("Webhook 返回状态码 {}", status).into());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use tempfile::TempDir;
+
+    fn tracker_with_completed_unit() -> (ProgressTracker, ProgressTracker) {
+        let before = ProgressTracker::new("test-learner".to_string(), "测试学习者".to_string());
+        let mut after = before.clone();
+        if let Some(unit) = after.get_unit_mut("stage1-environment") {
+            unit.start();
+            unit.complete(Some(95.0));
+        }
+        after.check_achievements();
+        (before, after)
+    }
+
+    struct Recorder {
+        seen: Arc<Mutex<Vec<EventKind>>>,
+    }
+
+    impl EventSubscriber for Recorder {
+        fn name(&self) -> String {
+            "recorder".to_string()
+        }
+
+        fn handle(&self, envelope: &EventEnvelope) -> Result<(), Box<dyn std::error::Error>> {
+            self.seen.lock().unwrap().push(envelope.event.kind());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_collect_events() {
+        let (before, after) = tracker_with_completed_unit();
+        let events = collect_events(&before, &after);
+
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], ProgressEvent::UnitStatusChanged { unit_id, to: LearningUnitStatus::Completed, .. } if unit_id == "stage1-environment"));
+        assert!(matches!(&events[1], ProgressEvent::AchievementUnlocked { achievement_id, .. } if achievement_id == "first_steps"));
+
+        // 无变化时不产生事件
+        assert!(collect_events(&after, &after).is_empty());
+    }
+
+    #[test]
+    fn test_stage_completed_event() {
+        let before = ProgressTracker::new("test-learner".to_string(), "测试学习者".to_string());
+        let mut after = before.clone();
+        for unit in &mut after.learning_units {
+            unit.complete(None);
+        }
+        after.check_achievements();
+
+        let events = collect_events(&before, &after);
+        assert!(events.iter().any(|e| matches!(e, ProgressEvent::StageCompleted { stage: LearningStage::Stage1Basics, .. })));
+        assert!(events.iter().any(|e| matches!(e, ProgressEvent::AchievementUnlocked { achievement_id, .. } if achievement_id == "stage1_master")));
+    }
+
+    #[test]
+    fn test_event_bus_filtering() {
+        let (before, after) = tracker_with_completed_unit();
+        let events = collect_events(&before, &after);
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut bus = EventBus::new();
+        bus.subscribe(Box::new(Filtered {
+            kinds: vec![EventKind::AchievementUnlocked],
+            inner: Box::new(Recorder { seen: Arc::clone(&seen) }),
+        }));
+
+        let errors = bus.publish(&after, &events);
+        assert!(errors.is_empty());
+        assert_eq!(*seen.lock().unwrap(), vec![EventKind::AchievementUnlocked]);
+    }
+
+    #[test]
+    fn test_jsonl_sink() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("events.jsonl");
+        let (before, after) = tracker_with_completed_unit();
+
+        let mut bus = EventBus::new();
+        bus.subscribe(Box::new(JsonlSink::new(path.clone())));
+        let errors = bus.publish(&after, &collect_events(&before, &after));
+        assert!(errors.is_empty());
+
+        let content = fs::read_to_string(&path).unwrap();
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+
+        let envelope: EventEnvelope = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(envelope.learner_id, "test-learner");
+        assert_eq!(envelope.event.kind(), EventKind::AchievementUnlocked);
+        assert!(envelope.message.contains("初次尝试"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_sink() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("command.log");
+        let (before, after) = tracker_with_completed_unit();
+
+        let command = format!("echo \"$PROGRESS_EVENT $PROGRESS_LEARNER_ID\" >> '{}'", path.display());
+        let mut bus = EventBus::new();
+        bus.subscribe(Box::new(CommandSink::new(command)));
+        let errors = bus.publish(&after, &collect_events(&before, &after));
+        assert!(errors.is_empty());
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(content, "unit_status_changed test-learner\nachievement_unlocked test-learner\n");
+
+        let failing = CommandSink::new("exit 3".to_string());
+        let envelope = EventEnvelope::new(&after, collect_events(&before, &after).remove(0));
+        assert!(failing.handle(&envelope).is_err());
+    }
+
+    #[test]
+    fn test_webhook_sink() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+
+            // 读取请求头和请求体
+            let mut request = Vec::new();
+            let mut buffer = [0u8; 4096];
+            loop {
+                let n = stream.read(&mut buffer).unwrap();
+                request.extend_from_slice(&buffer[..n]);
+                let text = String::from_utf8_lossy(&request);
+                if let Some(header_end) = text.find("\r\n\r\n") {
+                    let content_length = text[..header_end].lines()
+                        .find_map(|l| l.strip_prefix("Content-Length: "))
+                        .and_then(|v| v.trim().parse::<usize>().ok())
+                        .unwrap_or(0);
+                    if request.len() >= header_end + 4 + content_length {
+                        break;
+                    }
+                }
+                if n == 0 {
+                    break;
+                }
+            }
+
+            // 响应体不是 UTF-8，也不影响 2xx 的结果
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n\xff\xfe").unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let (before, after) = tracker_with_completed_unit();
+        let event = collect_events(&before, &after).pop().unwrap();
+        let sink = WebhookSink::new(format!("http://127.0.0.1:{}/hooks/progress", port));
+        sink.handle(&EventEnvelope::new(&after, event)).unwrap();
+
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /hooks/progress HTTP/1.1\r\n"));
+        assert!(request.contains("Content-Type: application/json"));
+        assert!(request.contains("\"type\":\"achievement_unlocked\""));
+    }
+
+    #[test]
+    fn test_webhook_url_parsing() {
+        let parse = |url: &str| WebhookSink::new(url.to_string()).parse_url().map_err(|e| e.to_string());
+
+        assert_eq!(parse("http://example.com").unwrap(), ("example.com".to_string(), 80, "/".to_string()));
+        assert_eq!(parse("http://127.0.0.1:8080/chat").unwrap(), ("127.0.0.1".to_string(), 8080, "/chat".to_string()));
+        assert_eq!(parse("http://[::1]:8080/chat").unwrap(), ("::1".to_string(), 8080, "/chat".to_string()));
+        assert_eq!(parse("http://[fe80::1]").unwrap(), ("fe80::1".to_string(), 80, "/".to_string()));
+        assert!(parse("http://[::1/chat").is_err());
+        assert!(parse("http://[::1]x/chat").is_err());
+        assert!(parse("http://:8080/chat").is_err());
+    }
+
+    #[test]
+    fn test_webhook_rejects_https() {
+        let (before, after) = tracker_with_completed_unit();
+        let event = collect_events(&before, &after).pop().unwrap();
+        let sink = WebhookSink::new("https://example.com/hook".to_string());
+        assert!(sink.handle(&EventEnvelope::new(&after, event)).is_err());
+    }
+
+    #[test]
+    fn test_notify_hooks() {
+        let temp_dir = TempDir::new().unwrap();
+        let hooks_file = temp_dir.path().join(DEFAULT_HOOKS_FILE);
+        let events_file = temp_dir.path().join("events.jsonl");
+        let (before, after) = tracker_with_completed_unit();
+
+        // 没有配置文件时不分发
+        let notification = notify_hooks(&hooks_file, &before, &after).unwrap();
+        assert_eq!(notification.events, 0);
+
+        let config = HooksConfig {
+            hooks: vec![HookConfig { sink: SinkConfig::Jsonl { path: events_file.clone() }, events: Vec::new() }],
+        };
+        fs::write(&hooks_file, serde_json::to_string(&config).unwrap()).unwrap();
+        let notification = notify_hooks(&hooks_file, &before, &after).unwrap();
+        assert_eq!(notification.events, 2);
+        assert!(notification.errors.is_empty());
+        assert_eq!(fs::read_to_string(&events_file).unwrap().lines().count(), 2);
+
+        fs::write(&hooks_file, "not json").unwrap();
+        assert!(notify_hooks(&hooks_file, &before, &after).is_err());
+    }
+
+    #[test]
+    fn test_hooks_config_parsing() {
+        let json = r#"{
+            "hooks": [
+                { "type": "command", "command": "notify-send \"$PROGRESS_MESSAGE\"", "events": ["achievement_unlocked"] },
+                { "type": "jsonl", "path": "events.jsonl" },
+                { "type": "webhook", "url": "http://127.0.0.1:8080/chat" }
+            ]
+        }"#;
+
+        let config: HooksConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.hooks.len(), 3);
+        assert_eq!(config.hooks[0].events, vec![EventKind::AchievementUnlocked]);
+        assert!(matches!(config.hooks[2].sink, SinkConfig::Webhook { timeout_secs: 5, .. }));
+
+        let bus = EventBus::from_config(&config);
+        assert_eq!(bus.len(), 3);
+    }
+}
@@ -0,0 +1,364 @@
+//! 学习目标与完成日期预测模块
+//!
+//! 根据学习者的历史学习速度（每个活跃日完成的预计学习分钟数）预测阶段完成日期，
+//! 并判断目标是提前、按计划还是存在风险。
+
+use crate::{LearningStage, LearningUnitStatus, ProgressTracker};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// 预测完成日期比截止日期早至少这么多天时视为“提前”
+const AHEAD_THRESHOLD_DAYS: i64 = 7;
+
+/// 学习目标：在截止日期前完成某个阶段
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LearningGoal {
+    pub stage: LearningStage,
+    pub deadline: NaiveDate,
+    pub created_at: DateTime<Utc>,
+}
+
+impl LearningGoal {
+    pub fn new(stage: LearningStage, deadline: NaiveDate) -> Self {
+        Self {
+            stage,
+            deadline,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// 目标状态
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum GoalStatus {
+    Achieved,   // 已达成
+    Ahead,      // 提前
+    OnTrack,    // 按计划
+    AtRisk,     // 存在风险
+}
+
+impl GoalStatus {
+    /// 获取状态名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            GoalStatus::Achieved => "已达成",
+            GoalStatus::Ahead => "提前",
+            GoalStatus::OnTrack => "按计划",
+            GoalStatus::AtRisk => "存在风险",
+        }
+    }
+
+    /// 获取状态图标
+    pub fn icon(&self) -> &'static str {
+        match self {
+            GoalStatus::Achieved => "🏁",
+            GoalStatus::Ahead => "🚀",
+            GoalStatus::OnTrack => "✅",
+            GoalStatus::AtRisk => "⚠️",
+        }
+    }
+}
+
+/// 预测依据
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ForecastBasis {
+    Velocity,       // 基于历史学习速度
+    StageEstimate,  // 暂无学习记录，基于阶段预计周数
+}
+
+impl ForecastBasis {
+    /// 获取预测依据名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            ForecastBasis::Velocity => "每个活跃日的历史学习速度，假设此后每天学习",
+            ForecastBasis::StageEstimate => "阶段预计周数",
+        }
+    }
+}
+
+/// 学习速度统计
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LearningVelocity {
+    pub completed_minutes: u32,
+    pub active_days: u32,
+    pub span_days: u32,
+    pub minutes_per_active_day: f32,
+    pub minutes_per_calendar_day: f32,
+}
+
+/// 目标预测结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoalForecast {
+    pub goal: LearningGoal,
+    pub status: GoalStatus,
+    pub forecast_date: NaiveDate,
+    pub remaining_minutes: u32,
+    pub days_until_deadline: i64,
+    pub slack_days: i64,
+    pub basis: ForecastBasis,
+}
+
+impl GoalForecast {
+    /// 生成一句话说明
+    pub fn summary(&self) -> String {
+        match self.status {
+            GoalStatus::Achieved => format!("{} 目标已达成", self.goal.stage.name()),
+            _ if self.days_until_deadline < 0 => format!(
+                "{} 已超过截止日期 {} 天，预计 {} 完成",
+                self.goal.stage.name(),
+                -self.days_until_deadline,
+                self.forecast_date
+            ),
+            _ => format!(
+                "{} 截止 {}，预计 {} 完成（{}，{}）",
+                self.goal.stage.name(),
+                self.goal.deadline,
+                self.forecast_date,
+                self.status.name(),
+                if self.slack_days >= 0 {
+                    format!("富余 {} 天", self.slack_days)
+                } else {
+                    format!("落后 {} 天", -self.slack_days)
+                }
+            ),
+        }
+    }
+}
+
+impl ProgressTracker {
+    /// 设置阶段目标（同一阶段只保留一个目标）
+    pub fn set_goal(&mut self, stage: LearningStage, deadline: NaiveDate) {
+        self.goals.retain(|g| g.stage != stage);
+        self.goals.push(LearningGoal::new(stage, deadline));
+        self.goals.sort_by_key(|g| g.stage.number());
+        self.last_updated = Utc::now();
+    }
+
+    /// 移除阶段目标，返回是否存在该目标
+    pub fn remove_goal(&mut self, stage: &LearningStage) -> bool {
+        let before = self.goals.len();
+        self.goals.retain(|g| g.stage != *stage);
+        let removed = self.goals.len() != before;
+        if removed {
+            self.last_updated = Utc::now();
+        }
+        removed
+    }
+
+    /// 计算截至指定日期的学习速度，没有完成记录时返回 None
+    pub fn learning_velocity_at(&self, today: NaiveDate) -> Option<LearningVelocity> {
+        let mut active_dates = HashSet::new();
+        let mut completed_minutes = 0;
+
+        for unit in &self.learning_units {
+            if let Some(started_at) = unit.started_at {
+                active_dates.insert(started_at.date_naive());
+            }
+            if let (LearningUnitStatus::Completed, Some(completed_at)) = (&unit.status, unit.completed_at) {
+                active_dates.insert(completed_at.date_naive());
+                completed_minutes += unit.estimated_time_minutes;
+            }
+        }
+
+        if completed_minutes == 0 || active_dates.is_empty() {
+            return None;
+        }
+
+        let first_day = *active_dates.iter().min()?;
+        let span_days = ((today - first_day).num_days() + 1).max(active_dates.len() as i64) as u32;
+        let active_days = active_dates.len() as u32;
+
+        Some(LearningVelocity {
+            completed_minutes,
+            active_days,
+            span_days,
+            minutes_per_active_day: completed_minutes as f32 / active_days as f32,
+            minutes_per_calendar_day: completed_minutes as f32 / span_days as f32,
+        })
+    }
+
+    /// 计算当前的学习速度
+    pub fn learning_velocity(&self) -> Option<LearningVelocity> {
+        self.learning_velocity_at(Utc::now().date_naive())
+    }
+
+    /// 预测截至指定日期所有目标的完成情况
+    pub fn forecast_goals_at(&self, today: NaiveDate) -> Vec<GoalForecast> {
+        let velocity = self.learning_velocity_at(today);
+        self.goals.iter()
+            .map(|goal| self.forecast_goal(goal, velocity.as_ref(), today))
+            .collect()
+    }
+
+    /// 预测当前所有目标的完成情况
+    pub fn forecast_goals(&self) -> Vec<GoalForecast> {
+        self.forecast_goals_at(Utc::now().date_naive())
+    }
+
+    fn forecast_goal(&self, goal: &LearningGoal, velocity: Option<&LearningVelocity>, today: NaiveDate) -> GoalForecast {
+        let days_until_deadline = (goal.deadline - today).num_days();
+
+        // 目标阶段及之前所有阶段中尚未完成的工作
+        let mut remaining_minutes = 0;
+        let mut unplanned_days = 0.0;
+        let mut estimated_days = 0.0;
+        let mut achieved = true;
+
+        for stage in LearningStage::all_stages().into_iter().filter(|s| s.number() <= goal.stage.number()) {
+            let units: Vec<_> = self.learning_units.iter().filter(|u| u.stage == stage).collect();
+            let stage_days = (stage.estimated_weeks() * 7) as f32;
+
+            if units.is_empty() {
+                // 没有登记学习单元的阶段只能按预计周数估算
+                unplanned_days += stage_days;
+                achieved = false;
+                continue;
+            }
+
+            // 跳过的单元与已完成的一样不再需要学习
+            let pending: Vec<_> = units.iter()
+                .filter(|u| !matches!(u.status, LearningUnitStatus::Completed | LearningUnitStatus::Skipped))
+                .collect();
+            let total: u32 = units.iter().map(|u| u.estimated_time_minutes).sum();
+            let remaining: u32 = pending.iter().map(|u| u.estimated_time_minutes).sum();
+
+            if !pending.is_empty() {
+                achieved = false;
+            }
+
+            remaining_minutes += remaining;
+            if total > 0 {
+                estimated_days += stage_days * remaining as f32 / total as f32;
+            }
+        }
+
+        // 按每个活跃日的速度计算还需要的学习日数
+        let (basis, work_days) = match velocity {
+            Some(v) if v.minutes_per_active_day > 0.0 => (
+                ForecastBasis::Velocity,
+                remaining_minutes as f32 / v.minutes_per_active_day,
+            ),
+            _ => (ForecastBasis::StageEstimate, estimated_days),
+        };
+
+        let forecast_days = (work_days + unplanned_days).ceil() as i64;
+        let forecast_date = today + Duration::days(forecast_days);
+        let slack_days = (goal.deadline - forecast_date).num_days();
+
+        let status = if achieved {
+            GoalStatus::Achieved
+        } else if days_until_deadline < 0 || slack_days < 0 {
+            GoalStatus::AtRisk
+        } else if slack_days >= AHEAD_THRESHOLD_DAYS {
+            GoalStatus::Ahead
+        } else {
+            GoalStatus::OnTrack
+        };
+
+        GoalForecast {
+            goal: goal.clone(),
+            status,
+            forecast_date,
+            remaining_minutes,
+            days_until_deadline,
+            slack_days,
+            basis,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn at(day: NaiveDate) -> DateTime<Utc> {
+        Utc.from_utc_datetime(&day.and_hms_opt(12, 0, 0).unwrap())
+    }
+
+    /// 两个活跃日共完成 180 分钟（阶段1剩余 45 分钟）
+    fn tracker_with_history() -> ProgressTracker {
+        let mut tracker = ProgressTracker::new("test-learner".to_string(), "测试学习者".to_string());
+        for (id, day) in [("stage1-environment", date(2026, 10, 1)), ("stage1-syntax", date(2026, 10, 3))] {
+            let unit = tracker.get_unit_mut(id).unwrap();
+            unit.complete(None);
+            unit.started_at = Some(at(day));
+            unit.completed_at = Some(at(day));
+        }
+        tracker
+    }
+
+    #[test]
+    fn test_learning_velocity() {
+        let tracker = tracker_with_history();
+        let velocity = tracker.learning_velocity_at(date(2026, 10, 10)).unwrap();
+
+        assert_eq!(velocity.completed_minutes, 180);
+        assert_eq!(velocity.active_days, 2);
+        assert_eq!(velocity.span_days, 10);
+        assert_eq!(velocity.minutes_per_active_day, 90.0);
+        assert_eq!(velocity.minutes_per_calendar_day, 18.0);
+
+        let fresh = ProgressTracker::new("test-learner".to_string(), "测试学习者".to_string());
+        assert!(fresh.learning_velocity_at(date(2026, 10, 10)).is_none());
+    }
+
+    #[test]
+    fn test_goal_forecast_statuses() {
+        let mut tracker = tracker_with_history();
+        let today = date(2026, 10, 10);
+
+        // 剩余 45 分钟，每个活跃日 90 分钟 → 1 个学习日后完成
+        tracker.set_goal(LearningStage::Stage1Basics, date(2026, 11, 1));
+        let forecast = &tracker.forecast_goals_at(today)[0];
+        assert_eq!(forecast.basis, ForecastBasis::Velocity);
+        assert_eq!(forecast.remaining_minutes, 45);
+        assert_eq!(forecast.forecast_date, date(2026, 10, 11));
+        assert_eq!(forecast.status, GoalStatus::Ahead);
+
+        tracker.set_goal(LearningStage::Stage1Basics, date(2026, 10, 12));
+        assert_eq!(tracker.goals.len(), 1);
+        assert_eq!(tracker.forecast_goals_at(today)[0].status, GoalStatus::OnTrack);
+
+        tracker.set_goal(LearningStage::Stage1Basics, date(2026, 10, 10));
+        assert_eq!(tracker.forecast_goals_at(today)[0].status, GoalStatus::AtRisk);
+
+        tracker.get_unit_mut("stage1-syntax-demo").unwrap().complete(None);
+        assert_eq!(tracker.forecast_goals_at(today)[0].status, GoalStatus::Achieved);
+    }
+
+    #[test]
+    fn test_goal_achieved_with_skipped_units() {
+        let mut tracker = tracker_with_history();
+        let today = date(2026, 10, 10);
+        tracker.set_goal(LearningStage::Stage1Basics, date(2026, 10, 1));
+
+        // 跳过剩余单元后目标即达成，即使已过截止日期
+        tracker.get_unit_mut("stage1-syntax-demo").unwrap().skip();
+        let forecast = &tracker.forecast_goals_at(today)[0];
+        assert_eq!(forecast.remaining_minutes, 0);
+        assert_eq!(forecast.status, GoalStatus::Achieved);
+    }
+
+    #[test]
+    fn test_goal_forecast_uses_stage_estimates() {
+        let mut tracker = ProgressTracker::new("test-learner".to_string(), "测试学习者".to_string());
+        let today = date(2026, 10, 10);
+
+        // 无学习记录：阶段1（3 周）+ 阶段2（2 周，无学习单元）= 35 天
+        tracker.set_goal(LearningStage::Stage2Ownership, date(2026, 12, 1));
+        let forecast = &tracker.forecast_goals_at(today)[0];
+        assert_eq!(forecast.basis, ForecastBasis::StageEstimate);
+        assert_eq!(forecast.forecast_date, date(2026, 11, 14));
+        assert_eq!(forecast.status, GoalStatus::Ahead);
+
+        assert!(tracker.remove_goal(&LearningStage::Stage2Ownership));
+        assert!(!tracker.remove_goal(&LearningStage::Stage2Ownership));
+        assert!(tracker.forecast_goals_at(today).is_empty());
+    }
+}
@@ -3,12 +3,15 @@
 //! 提供学习进度跟踪、可视化、个性化推荐和成就系统功能。
 
 pub mod dashboard;
+pub mod events;
+pub mod goals;
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
 use std::fs;
 use chrono::{DateTime, Utc};
+use goals::{GoalStatus, LearningGoal};
 
 /// 学习阶段定义
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -64,6 +67,27 @@ impl LearningStage {
             LearningStage::Stage5Projects => 3,
         }
     }
+
+    /// 获取阶段序号（1-5）
+    pub fn number(&self) -> u32 {
+        match self {
+            LearningStage::Stage1Basics => 1,
+            LearningStage::Stage2Ownership => 2,
+            LearningStage::Stage3AdvancedConcepts => 3,
+            LearningStage::Stage4Ecosystem => 4,
+            LearningStage::Stage5Projects => 5,
+        }
+    }
+
+    /// 根据阶段序号获取阶段
+    pub fn from_number(number: u32) -> Option<LearningStage> {
+        LearningStage::all_stages().into_iter().find(|s| s.number() == number)
+    }
+
+    /// 该阶段自我评估单元的默认 ID，例如 `stage2-assessment`
+    pub fn assessment_unit_id(&self) -> String {
+        format!("stage{}-assessment", self.number())
+    }
 }
 
 /// 学习单元类型
@@ -275,6 +299,8 @@ pub struct ProgressTracker {
     pub learner_name: String,
     pub learning_units: Vec<LearningUnit>,
     pub achievements: Vec<Achievement>,
+    #[serde(default)]
+    pub goals: Vec<LearningGoal>,
     pub created_at: DateTime<Utc>,
     pub last_updated: DateTime<Utc>,
 }
@@ -287,6 +313,7 @@ impl ProgressTracker {
             learner_name,
             learning_units: Vec::new(),
             achievements: Vec::new(),
+            goals: Vec::new(),
             created_at: Utc::now(),
             last_updated: Utc::now(),
         };
@@ -406,6 +433,46 @@ impl ProgressTracker {
         self.last_updated = Utc::now();
     }
 
+    /// 记录一次自我评估成绩
+    ///
+    /// 以百分制分数完成对应的评估单元，重新测验会覆盖上一次的成绩。只有该阶段默认的评估单元
+    /// （见 [`LearningStage::assessment_unit_id`]）不存在时会自动创建；单元不是评估单元、
+    /// 所属阶段不符或 ID 不存在时返回错误。
+    pub fn record_assessment(
+        &mut self,
+        unit_id: &str,
+        stage: LearningStage,
+        score: f32,
+        started_at: DateTime<Utc>,
+    ) -> Result<&LearningUnit, Box<dyn std::error::Error>> {
+        let index = match self.learning_units.iter().position(|u| u.id == unit_id) {
+            Some(index) => {
+                let unit = &self.learning_units[index];
+                if unit.unit_type != LearningUnitType::Assessment {
+                    return Err(format!("单元 {} 是{}，不是评估单元", unit_id, unit.unit_type.name()).into());
+                }
+                if unit.stage != stage {
+                    return Err(format!("评估单元 {} 属于{}，不是{}", unit_id, unit.stage.name(), stage.name()).into());
+                }
+                index
+            }
+            None if unit_id == stage.assessment_unit_id() => {
+                let name = format!("{} 自我评估", stage.name());
+                let path = "tools/assessment".to_string();
+                self.learning_units.push(LearningUnit::new(unit_id.to_string(), name, LearningUnitType::Assessment, stage, path, 30));
+                self.learning_units.len() - 1
+            }
+            None => return Err(format!("找不到评估单元 {}", unit_id).into()),
+        };
+
+        let unit = &mut self.learning_units[index];
+        unit.started_at = Some(started_at);
+        unit.complete(Some(score.clamp(0.0, 100.0)));
+        self.last_updated = Utc::now();
+
+        Ok(&self.learning_units[index])
+    }
+
     /// 获取进度统计
     pub fn get_progress_stats(&self) -> ProgressStats {
         let total_units = self.learning_units.len();
@@ -557,7 +624,7 @@ impl ProgressTracker {
             0.0
         };
 
-        let reasoning = if next_units.is_empty() {
+        let mut reasoning = if next_units.is_empty() {
             "恭喜！您已完成所有学习单元。建议复习或开始实际项目练习。".to_string()
         } else {
             format!("基于您的学习进度，推荐您接下来完成 {} 的 {} 个学习单元，预计需要 {} 分钟。",
@@ -567,6 +634,22 @@ impl ProgressTracker {
             )
         };
 
+        // 当前阶段的自我评估不及格时，提醒先巩固薄弱内容
+        if let Some(unit) = self.learning_units.iter()
+            .filter(|u| u.unit_type == LearningUnitType::Assessment && u.stage == stats.current_stage)
+            .find(|u| u.score.is_some_and(|s| s < 60.0))
+        {
+            reasoning.push_str(&format!("{} 得分 {:.0} 分，建议先复习本阶段内容后重新测验。", unit.name, unit.score.unwrap_or(0.0)));
+        }
+
+        // 有存在风险的目标时，提醒优先保证截止日期最近的目标
+        if let Some(forecast) = self.forecast_goals().into_iter()
+            .filter(|f| f.status == GoalStatus::AtRisk)
+            .min_by_key(|f| f.goal.deadline)
+        {
+            reasoning.push_str(&format!("目标提醒：{}。", forecast.summary()));
+        }
+
         LearningPathRecommendation {
             next_units,
             recommended_stage: stats.current_stage.clone(),
@@ -679,6 +762,19 @@ impl ProgressTracker {
             }
         }
 
+        // 基于自我评估成绩给出建议
+        for unit in self.learning_units.iter().filter(|u| u.unit_type == LearningUnitType::Assessment) {
+            match unit.score {
+                Some(score) if score < 60.0 => {
+                    suggestions.push(format!("📝 {} 得分 {:.0} 分，建议回顾错题并复习相关章节后重新测验。", unit.name, score));
+                }
+                Some(score) if score >= 90.0 => {
+                    suggestions.push(format!("✅ {} 得分 {:.0} 分，已经很好地掌握了本阶段内容。", unit.name, score));
+                }
+                _ => {}
+            }
+        }
+
         // 基于学习时间给出建议
         let total_hours = stats.completed_time_minutes / 60;
         if total_hours < 10 {
@@ -687,6 +783,17 @@ impl ProgressTracker {
             suggestions.push("💪 您已经投入了大量时间学习，坚持下去一定会取得成功！".to_string());
         }
 
+        // 基于学习目标给出建议
+        for forecast in self.forecast_goals() {
+            let suggestion = match forecast.status {
+                GoalStatus::Achieved => continue,
+                GoalStatus::Ahead => format!("{} {}，可以适当挑战更深入的内容。", forecast.status.icon(), forecast.summary()),
+                GoalStatus::OnTrack => format!("{} {}，保持当前节奏即可。", forecast.status.icon(), forecast.summary()),
+                GoalStatus::AtRisk => format!("{} {}，建议增加每天的学习时间或调整截止日期。", forecast.status.icon(), forecast.summary()),
+            };
+            suggestions.push(suggestion);
+        }
+
         // 基于当前阶段给出具体建议
         match stats.current_stage {
             LearningStage::Stage1Basics => {
@@ -722,6 +829,15 @@ mod tests {
         assert_eq!(LearningStage::Stage1Basics.estimated_weeks(), 3);
     }
 
+    #[test]
+    fn test_stage_numbers() {
+        for stage in LearningStage::all_stages() {
+            assert_eq!(LearningStage::from_number(stage.number()), Some(stage));
+        }
+        assert_eq!(LearningStage::from_number(0), None);
+        assert_eq!(LearningStage::from_number(6), None);
+    }
+
     #[test]
     fn test_learning_unit() {
         let mut unit = LearningUnit::new(
@@ -763,4 +879,32 @@ mod tests {
         assert_eq!(stats.completed_units, 1);
         assert!(stats.overall_progress > 0.0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_record_assessment() {
+        let mut tracker = ProgressTracker::new("test-learner".to_string(), "测试学习者".to_string());
+        let unit_id = LearningStage::Stage2Ownership.assessment_unit_id();
+        assert_eq!(unit_id, "stage2-assessment");
+
+        let unit = tracker.record_assessment(&unit_id, LearningStage::Stage2Ownership, 45.0, Utc::now()).unwrap();
+        assert_eq!(unit.unit_type, LearningUnitType::Assessment);
+        assert_eq!(unit.status, LearningUnitStatus::Completed);
+        assert_eq!(unit.score, Some(45.0));
+        assert_eq!(tracker.learning_units.len(), 4);
+        assert!(tracker.get_personalized_suggestions().iter().any(|s| s.contains("重新测验")));
+
+        // 重新测验覆盖成绩，不会重复创建单元
+        tracker.record_assessment(&unit_id, LearningStage::Stage2Ownership, 120.0, Utc::now()).unwrap();
+        assert_eq!(tracker.learning_units.len(), 4);
+        assert_eq!(tracker.get_unit(&unit_id).unwrap().score, Some(100.0));
+
+        // 不是评估单元、阶段不符或拼错的 ID 都不会被完成或创建
+        let lesson = tracker.record_assessment("stage1-environment", LearningStage::Stage1Basics, 90.0, Utc::now());
+        assert!(lesson.unwrap_err().to_string().contains("不是评估单元"));
+        assert_eq!(tracker.get_unit("stage1-environment").unwrap().status, LearningUnitStatus::NotStarted);
+        assert!(tracker.record_assessment(&unit_id, LearningStage::Stage3AdvancedConcepts, 90.0, Utc::now()).is_err());
+        assert!(tracker.record_assessment("stage2-asessment", LearningStage::Stage2Ownership, 90.0, Utc::now()).is_err());
+        assert_eq!(tracker.learning_units.len(), 4);
+        assert_eq!(tracker.get_unit(&unit_id).unwrap().score, Some(100.0));
+    }
+}
@@ -1,8 +1,11 @@
 //! Rust 学习进度跟踪工具 - 主程序
 
 use progress_tracker::{
-    ProgressTracker, dashboard::{DashboardRenderer, DashboardConfig, generate_html_dashboard}
+    ProgressTracker, LearningStage, dashboard::{DashboardRenderer, DashboardConfig, generate_html_dashboard},
+    events::{self, HooksConfig, DEFAULT_HOOKS_FILE},
+    goals::GoalStatus
 };
+use chrono::NaiveDate;
 use std::path::Path;
 use std::fs;
 use std::io::{self, Write};
@@ -18,6 +21,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         Some("show") => show_progress(&args),
         Some("update") => update_progress(&args),
         Some("recommend") => show_recommendations(&args),
+        Some("goal") => set_goal(&args),
+        Some("goals") => show_goals(&args),
         Some("export") => export_dashboard(&args),
         Some("help") | None => {
             show_help();
@@ -38,11 +43,17 @@ fn show_help() {
     println!("  progress-tracker update <unit_id>       - 更新学习单元状态");
     println!("  progress-tracker recommend [progress.json] - 显示学习推荐");
     println!("  progress-tracker export [progress.json] - 导出 HTML 仪表板");
+    println!("  progress-tracker goal <stage> <YYYY-MM-DD|clear> [progress.json] - 设置或清除阶段目标");
+    println!("  progress-tracker goals [progress.json]  - 显示目标完成预测");
     println!("  progress-tracker help                   - 显示此帮助信息");
+    println!("\n🔔 事件通知:");
+    println!("  在当前目录创建 {} 或设置 PROGRESS_HOOKS 环境变量指定钩子配置文件，", DEFAULT_HOOKS_FILE);
+    println!("  单元状态变化、阶段完成和成就解锁时会执行命令、写入 JSONL 文件或发送 Webhook。");
     println!("\n💡 示例:");
     println!("  progress-tracker init \"张三\"");
     println!("  progress-tracker show");
     println!("  progress-tracker update stage1-environment");
+    println!("  progress-tracker goal 3 2026-12-01");
     println!("  progress-tracker export");
 }
 
@@ -108,6 +119,7 @@ fn update_progress(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
     }
 
     let mut tracker = ProgressTracker::from_file(filename)?;
+    let before = tracker.clone();
     
     let unit = match tracker.get_unit_mut(unit_id) {
         Some(unit) => unit,
@@ -183,10 +195,29 @@ fn update_progress(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
     // 保存更新
     tracker.to_file(filename)?;
     println!("\n💾 进度已保存到: {}", filename);
+
+    // 通知事件订阅者
+    notify_hooks(&before, &tracker);
     
     Ok(())
 }
 
+/// 加载钩子配置并分发本次更新产生的事件
+fn notify_hooks(before: &ProgressTracker, after: &ProgressTracker) {
+    let hooks_file = HooksConfig::default_path();
+    match events::notify_hooks(&hooks_file, before, after) {
+        Ok(notification) => {
+            for error in &notification.errors {
+                eprintln!("⚠️  事件通知失败 [{}] {}: {}", error.event.name(), error.subscriber, error.error);
+            }
+            if notification.events > 0 {
+                println!("🔔 已发送 {} 个事件通知", notification.events);
+            }
+        }
+        Err(e) => eprintln!("⚠️  无法加载钩子配置 {}: {}", hooks_file.display(), e),
+    }
+}
+
 fn show_recommendations(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
     let filename = args.get(2).map(|s| s.as_str()).unwrap_or("progress.json");
     
@@ -220,6 +251,15 @@ fn show_recommendations(args: &[String]) -> Result<(), Box<dyn std::error::Error
         }
     }
     
+    // 显示目标预测
+    let forecasts = tracker.forecast_goals();
+    if !forecasts.is_empty() {
+        println!("\n🗓️ 学习目标:");
+        for forecast in &forecasts {
+            println!("  {} {}", forecast.status.icon(), forecast.summary());
+        }
+    }
+    
     // 显示个性化建议
     let suggestions = tracker.get_personalized_suggestions();
     if !suggestions.is_empty() {
@@ -232,6 +272,104 @@ fn show_recommendations(args: &[String]) -> Result<(), Box<dyn std::error::Error
     Ok(())
 }
 
+/// 解析阶段参数，支持 "3"、"stage3" 等写法
+fn parse_stage(arg: &str) -> Option<LearningStage> {
+    let lower = arg.to_lowercase();
+    let number = lower.strip_prefix("stage").unwrap_or(&lower);
+    number.parse::<u32>().ok().and_then(LearningStage::from_number)
+}
+
+fn set_goal(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    if args.len() < 4 {
+        eprintln!("❌ 请提供阶段和截止日期，例如: progress-tracker goal 3 2026-12-01");
+        return Ok(());
+    }
+
+    let stage = match parse_stage(&args[2]) {
+        Some(stage) => stage,
+        None => {
+            eprintln!("❌ 无效的阶段: {}（可选 1-5）", args[2]);
+            return Ok(());
+        }
+    };
+    let filename = args.get(4).map(|s| s.as_str()).unwrap_or("progress.json");
+    
+    if !Path::new(filename).exists() {
+        eprintln!("❌ 找不到进度文件: {}", filename);
+        return Ok(());
+    }
+
+    let mut tracker = ProgressTracker::from_file(filename)?;
+
+    if args[3] == "clear" {
+        if tracker.remove_goal(&stage) {
+            println!("✅ 已清除 {} 的学习目标", stage.name());
+        } else {
+            println!("💡 {} 没有设置学习目标", stage.name());
+        }
+    } else {
+        let deadline = match NaiveDate::parse_from_str(&args[3], "%Y-%m-%d") {
+            Ok(date) => date,
+            Err(_) => {
+                eprintln!("❌ 无效的日期: {}（格式: YYYY-MM-DD）", args[3]);
+                return Ok(());
+            }
+        };
+
+        tracker.set_goal(stage.clone(), deadline);
+        println!("✅ 已设置目标: {} 截止 {}", stage.name(), deadline);
+
+        if let Some(forecast) = tracker.forecast_goals().into_iter().find(|f| f.goal.stage == stage) {
+            println!("  {} {}", forecast.status.icon(), forecast.summary());
+        }
+    }
+
+    tracker.to_file(filename)?;
+    println!("💾 进度已保存到: {}", filename);
+    
+    Ok(())
+}
+
+fn show_goals(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let filename = args.get(2).map(|s| s.as_str()).unwrap_or("progress.json");
+    
+    if !Path::new(filename).exists() {
+        eprintln!("❌ 找不到进度文件: {}", filename);
+        return Ok(());
+    }
+
+    let tracker = ProgressTracker::from_file(filename)?;
+    
+    println!("🗓️ 学习目标与预测");
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+
+    match tracker.learning_velocity() {
+        Some(velocity) => println!(
+            "⚡ 学习速度: 每个活跃日 {:.0} 分钟（{} 个活跃日，平均每天 {:.0} 分钟）",
+            velocity.minutes_per_active_day, velocity.active_days, velocity.minutes_per_calendar_day
+        ),
+        None => println!("⚡ 学习速度: 暂无完成记录，按阶段预计周数估算"),
+    }
+
+    let forecasts = tracker.forecast_goals();
+    if forecasts.is_empty() {
+        println!("\n💡 尚未设置学习目标，可运行: progress-tracker goal <阶段> <YYYY-MM-DD>");
+        return Ok(());
+    }
+
+    for forecast in &forecasts {
+        println!("\n{} {} - 截止 {} [{}]",
+                 forecast.status.icon(), forecast.goal.stage.name(), forecast.goal.deadline, forecast.status.name());
+        if forecast.status != GoalStatus::Achieved {
+            println!("  预计完成: {}", forecast.forecast_date);
+            println!("  剩余学习时间: {} 分钟", forecast.remaining_minutes);
+            println!("  预测依据: {}", forecast.basis.name());
+        }
+    }
+    
+    Ok(())
+}
+
 fn export_dashboard(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
     let filename = args.get(2).map(|s| s.as_str()).unwrap_or("progress.json");
     let output_file = args.get(3).map(|s| s.as_str()).unwrap_or("dashboard.html");
@@ -276,6 +414,14 @@ mod tests {
         assert_eq!(loaded_tracker.learning_units.len(), 3); // 示例单元
     }
 
+    #[test]
+    fn test_parse_stage() {
+        assert_eq!(parse_stage("3"), Some(LearningStage::Stage3AdvancedConcepts));
+        assert_eq!(parse_stage("Stage1"), Some(LearningStage::Stage1Basics));
+        assert_eq!(parse_stage("6"), None);
+        assert_eq!(parse_stage("basics"), None);
+    }
+
     #[test]
     fn test_update_progress() {
         let mut tracker = ProgressTracker::new("test-learner".to_string(), "测试学习者".to_string());